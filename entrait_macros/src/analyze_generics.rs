@@ -1,20 +1,39 @@
 use crate::generics::{FnDeps, TraitDependencyMode, TraitGenerics};
 use crate::idents::{CrateIdents, GenericIdents};
 use crate::input::FnInputMode;
-use crate::opt::Opts;
-use crate::signature::ImplReceiverKind;
+use crate::opt::{Opts, SpanOpt};
 use crate::signature::{converter::SignatureConverter, EntraitSignature, InputSig};
-use crate::token_util::TokenPair;
+use crate::signature::{ungroup_type, ImplReceiverKind};
+use crate::token_util::{mentions_ident, mentions_lifetime, TokenPair};
 
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
 use syn::spanned::Spanned;
 
 #[derive(Clone)]
 pub struct TraitFn {
     pub deps: FnDeps,
     pub attrs: Vec<syn::Attribute>,
+    /// Attributes forwarded with `forward_attrs` that only apply to the delegating method, like `#[tracing::instrument]`
+    pub impl_attrs: Vec<syn::Attribute>,
     pub entrait_sig: EntraitSignature,
     pub originally_async: bool,
+    /// The name of the entraited fn, when the trait method is renamed
+    pub original_ident: Option<syn::Ident>,
+    /// Braced default body for the method in the trait definition
+    pub default_body: Option<TokenStream>,
+    /// Whether the delegating method memoizes its output
+    pub memo: bool,
+    /// Whether the future of the `async` method is boxed, for a dyn-compatible trait
+    pub boxed_future: bool,
+    /// The boxed `impl Trait` output of the method, for a dyn-compatible trait
+    pub boxed_output: Option<syn::Type>,
+    /// Generic parameters of the entraited fn, which are passed explicitly when the method has generics
+    pub fn_generics: syn::Generics,
+    /// The nested modules containing the entraited fn, relative to the entraited module
+    pub fn_mod_path: Vec<syn::Ident>,
+    /// Whether the entraited fn is a method of an entraited impl block, taking the service as `&self`
+    pub service_method: bool,
 }
 
 impl TraitFn {
@@ -22,6 +41,33 @@ impl TraitFn {
         &self.entrait_sig.sig
     }
 
+    /// Give the trait method another name than the entraited fn
+    pub fn rename(&mut self, method_ident: syn::Ident) {
+        let original_ident = std::mem::replace(&mut self.entrait_sig.sig.ident, method_ident);
+        self.original_ident.get_or_insert(original_ident);
+    }
+
+    /// The `#[cfg]` attributes of the method, which every item generated for it must carry too
+    pub fn cfg_attrs(&self) -> impl Iterator<Item = &syn::Attribute> {
+        self.attrs.iter().filter(|attr| attr.path().is_ident("cfg"))
+    }
+
+    /// The `#[cfg]` and lint attributes of the method, for the delegating method.
+    /// `#[must_use]` is left out, since it only has an effect on the trait method.
+    pub fn delegation_attrs(&self) -> impl Iterator<Item = &syn::Attribute> {
+        self.attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg") || is_lint_attr(attr))
+            .chain(&self.impl_attrs)
+    }
+
+    /// The name of the entraited fn to call
+    pub fn fn_ident(&self) -> &syn::Ident {
+        self.original_ident
+            .as_ref()
+            .unwrap_or(&self.entrait_sig.sig.ident)
+    }
+
     pub fn opt_dot_await(&self, span: Span) -> Option<impl quote::ToTokens> {
         if self.originally_async {
             Some(TokenPair(syn::token::Dot(span), syn::token::Await(span)))
@@ -33,7 +79,6 @@ impl TraitFn {
 
 pub struct TraitFnAnalyzer<'s> {
     pub impl_receiver_kind: ImplReceiverKind,
-    pub trait_span: Span,
     pub crate_idents: &'s CrateIdents,
     pub opts: &'s Opts,
 }
@@ -47,18 +92,26 @@ impl<'s> TraitFnAnalyzer<'s> {
         let deps = analyzer.analyze_fn_deps(input_sig, self.opts)?;
         let entrait_sig = SignatureConverter {
             crate_idents: self.crate_idents,
-            trait_span: self.trait_span,
             opts: self.opts,
             input_sig,
             deps: &deps,
             impl_receiver_kind: self.impl_receiver_kind,
         }
-        .convert_fn_to_trait_fn();
+        .convert_fn_to_trait_fn()?;
         Ok(TraitFn {
             deps,
             attrs: vec![],
+            impl_attrs: vec![],
             entrait_sig,
             originally_async: input_sig.asyncness.is_some(),
+            original_ident: None,
+            default_body: None,
+            memo: false,
+            boxed_future: false,
+            boxed_output: None,
+            fn_generics: input_sig.generics.clone(),
+            fn_mod_path: vec![],
+            service_method: false,
         })
     }
 }
@@ -77,7 +130,7 @@ pub(super) fn detect_trait_dependency_mode<'t, 'c>(
                     ty.span(),
                     "Using concrete dependencies in a module is an anti-pattern. Instead, write a trait manually, use the #[entrait] attribute on it, and implement it for your application type",
                 )),
-                FnInputMode::ImplBlock(_) => Err(syn::Error::new(
+                FnInputMode::ImplBlock(_) | FnInputMode::InherentImpl(_) => Err(syn::Error::new(
                     ty.span(),
                     "Cannot (yet) use concrete dependency in an impl block"
                 )),
@@ -111,7 +164,7 @@ impl GenericsAnalyzer {
     }
 
     pub fn analyze_fn_deps(&mut self, input_sig: InputSig<'_>, opts: &Opts) -> syn::Result<FnDeps> {
-        if opts.no_deps_value() {
+        if takes_no_deps(&input_sig, opts) {
             return self.deps_with_generics(FnDeps::NoDeps, &input_sig.generics);
         }
 
@@ -146,10 +199,11 @@ impl GenericsAnalyzer {
             syn::Type::ImplTrait(type_impl_trait) => {
                 // Simple case, bounds are actually inline, no lookup necessary
                 self.deps_with_generics(
-                    FnDeps::Generic {
-                        generic_param: None,
-                        trait_bounds: extract_trait_bounds(&type_impl_trait.bounds),
-                    },
+                    generic_deps(
+                        None,
+                        extract_trait_bounds(&type_impl_trait.bounds),
+                        &input_sig.generics,
+                    ),
                     &input_sig.generics,
                 )
             }
@@ -185,6 +239,8 @@ impl GenericsAnalyzer {
                 self.extract_deps_from_type(input_sig, type_reference.elem.as_ref())
             }
             syn::Type::Paren(paren) => self.extract_deps_from_type(input_sig, paren.elem.as_ref()),
+            // Invisible group, e.g. from a `$ty:ty` fragment in `macro_rules!`
+            syn::Type::Group(group) => self.extract_deps_from_type(input_sig, group.elem.as_ref()),
             ty => {
                 self.deps_with_generics(FnDeps::Concrete(Box::new(ty.clone())), &input_sig.generics)
             }
@@ -213,40 +269,64 @@ impl GenericsAnalyzer {
                 _ => None,
             })?;
 
+        // Extract "direct" bounds, and bounds on the deps type in the where clause
+        let mut deps_trait_bounds = extract_trait_bounds(&matching_type_param.bounds);
+
+        if let Some(where_clause) = &generics.where_clause {
+            for predicate in &where_clause.predicates {
+                if let syn::WherePredicate::Type(predicate_type) = predicate {
+                    if is_deps_predicate(predicate, generic_param_ident) {
+                        deps_trait_bounds.extend(extract_trait_bounds(&predicate_type.bounds));
+                    }
+                }
+            }
+        }
+
+        let deps = generic_deps(
+            Some(generic_param_ident.clone()),
+            deps_trait_bounds,
+            generics,
+        );
+        let method_params = method_params(generics, &deps);
+        let method_lifetimes = method_lifetimes(generics);
+
         for (index, param) in generic_params.iter().enumerate() {
-            if index != matching_index && !(matches!(param, &syn::GenericParam::Lifetime(_))) {
+            if index != matching_index
+                && !(matches!(param, &syn::GenericParam::Lifetime(_)))
+                && !is_method_param(param, &method_params)
+            {
                 self.trait_generics.params.push(param.clone());
             }
         }
 
-        // Extract "direct" bounds, not from where clause
-        let mut deps_trait_bounds = extract_trait_bounds(&matching_type_param.bounds);
-
         if let Some(where_clause) = &generics.where_clause {
             for predicate in &where_clause.predicates {
+                // Predicates on projections of the deps type, like `<D::Db as Database>::Row: Default`,
+                // stay on the trait method, where `D` is rewritten to `Self`. So do predicates on method generics.
+                if mentions_ident(predicate.to_token_stream(), generic_param_ident)
+                    || mentions_any(predicate, &method_params)
+                    || mentions_any_lifetime(predicate, &method_lifetimes)
+                {
+                    continue;
+                }
+
                 match predicate {
-                    syn::WherePredicate::Type(predicate_type) => match &predicate_type.bounded_ty {
-                        syn::Type::Path(type_path) => {
-                            if type_path.qself.is_some() || type_path.path.leading_colon.is_some() {
-                                self.trait_generics.where_predicates.push(predicate.clone());
-                                continue;
+                    syn::WherePredicate::Type(predicate_type) => {
+                        match ungroup_type(&predicate_type.bounded_ty) {
+                            syn::Type::Path(type_path) => {
+                                // Predicates like `T: Trait` stay on the trait method, along with `T`
+                                if type_path.qself.is_some()
+                                    || type_path.path.leading_colon.is_some()
+                                    || type_path.path.segments.len() != 1
+                                {
+                                    self.trait_generics.where_predicates.push(predicate.clone());
+                                }
                             }
-                            if type_path.path.segments.len() != 1 {
+                            _ => {
                                 self.trait_generics.where_predicates.push(predicate.clone());
-                                continue;
-                            }
-                            let first_segment = type_path.path.segments.first().unwrap();
-
-                            if &first_segment.ident == generic_param_ident {
-                                let where_paths = extract_trait_bounds(&predicate_type.bounds);
-
-                                deps_trait_bounds.extend(where_paths);
                             }
                         }
-                        _ => {
-                            self.trait_generics.where_predicates.push(predicate.clone());
-                        }
-                    },
+                    }
                     _ => {
                         self.trait_generics.where_predicates.push(predicate.clone());
                     }
@@ -254,10 +334,7 @@ impl GenericsAnalyzer {
             }
         };
 
-        Some(FnDeps::Generic {
-            generic_param: Some(generic_param_ident.clone()),
-            trait_bounds: deps_trait_bounds,
-        })
+        Some(deps)
     }
 
     fn deps_with_generics(
@@ -265,12 +342,14 @@ impl GenericsAnalyzer {
         deps: FnDeps,
         generics: &syn::Generics,
     ) -> syn::Result<FnDeps> {
+        let method_params = method_params(generics, &deps);
+        let method_lifetimes = method_lifetimes(generics);
+
         for param in &generics.params {
             match param {
-                syn::GenericParam::Type(_) => {
-                    self.trait_generics.params.push(param.clone());
-                }
-                syn::GenericParam::Const(_) => {
+                syn::GenericParam::Type(_) | syn::GenericParam::Const(_)
+                    if is_method_param(param, &method_params) => {}
+                syn::GenericParam::Type(_) | syn::GenericParam::Const(_) => {
                     self.trait_generics.params.push(param.clone());
                 }
                 syn::GenericParam::Lifetime(_) => {}
@@ -279,7 +358,11 @@ impl GenericsAnalyzer {
 
         if let Some(where_clause) = &generics.where_clause {
             for predicate in &where_clause.predicates {
-                self.trait_generics.where_predicates.push(predicate.clone());
+                if !mentions_any(predicate, &method_params)
+                    && !mentions_any_lifetime(predicate, &method_lifetimes)
+                {
+                    self.trait_generics.where_predicates.push(predicate.clone());
+                }
             }
         }
 
@@ -292,3 +375,137 @@ fn extract_trait_bounds(
 ) -> Vec<syn::TypeParamBound> {
     bounds.iter().cloned().collect()
 }
+
+/// Generic deps, where bounds naming lifetimes of the fn, like `D: 'a`, are separated from the bounds of the `impl` block
+fn generic_deps(
+    generic_param: Option<syn::Ident>,
+    bounds: Vec<syn::TypeParamBound>,
+    generics: &syn::Generics,
+) -> FnDeps {
+    let mut trait_bounds = vec![];
+    let mut outlives = vec![];
+
+    for bound in bounds {
+        match bound {
+            syn::TypeParamBound::Lifetime(lifetime)
+                if generics.lifetimes().any(|param| param.lifetime == lifetime) =>
+            {
+                outlives.push(lifetime);
+            }
+            bound => trait_bounds.push(bound),
+        }
+    }
+
+    FnDeps::Generic {
+        generic_param,
+        trait_bounds,
+        outlives,
+    }
+}
+
+/// Whether the predicate bounds the deps type itself, like `D: Trait`
+fn is_deps_predicate(predicate: &syn::WherePredicate, ident: &syn::Ident) -> bool {
+    match predicate {
+        syn::WherePredicate::Type(predicate_type) => match ungroup_type(&predicate_type.bounded_ty)
+        {
+            syn::Type::Path(type_path) => {
+                type_path.qself.is_none() && type_path.path.is_ident(ident)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Type and const parameters which are not needed by the deps, like closure parameters, stay on the trait method.
+/// So do the predicates mentioning them.
+fn method_params(generics: &syn::Generics, deps: &FnDeps) -> Vec<syn::Ident> {
+    let deps_ident = match deps {
+        FnDeps::Generic { generic_param, .. } => generic_param.as_ref(),
+        _ => None,
+    };
+
+    generics
+        .type_params()
+        .map(|type_param| &type_param.ident)
+        .chain(
+            generics
+                .const_params()
+                .map(|const_param| &const_param.ident),
+        )
+        .filter(|ident| Some(*ident) != deps_ident && !deps.mentions_param(ident))
+        .cloned()
+        .collect()
+}
+
+fn is_method_param(param: &syn::GenericParam, method_params: &[syn::Ident]) -> bool {
+    match param {
+        syn::GenericParam::Type(type_param) => method_params.contains(&type_param.ident),
+        syn::GenericParam::Const(const_param) => method_params.contains(&const_param.ident),
+        syn::GenericParam::Lifetime(_) => false,
+    }
+}
+
+/// Lifetimes of the fn are lifetimes of the trait method, so predicates mentioning them stay on the method
+fn method_lifetimes(generics: &syn::Generics) -> Vec<syn::Lifetime> {
+    generics
+        .lifetimes()
+        .map(|param| param.lifetime.clone())
+        .collect()
+}
+
+fn mentions_any_lifetime(predicate: &syn::WherePredicate, lifetimes: &[syn::Lifetime]) -> bool {
+    lifetimes
+        .iter()
+        .any(|lifetime| mentions_lifetime(predicate.to_token_stream(), lifetime))
+}
+
+fn mentions_any(predicate: &syn::WherePredicate, idents: &[syn::Ident]) -> bool {
+    idents
+        .iter()
+        .any(|ident| mentions_ident(predicate.to_token_stream(), ident))
+}
+
+/// Whether the fn takes no deps parameter, as given with `no_deps` or `deps`.
+///
+/// Otherwise, a fn without parameters takes no deps, and neither does a fn whose first parameter clearly isn't deps:
+/// one with attributes, like the `#[path] param: String` of `feignhttp`, or an owned primitive or `String`.
+/// Borrowed ones like `&str` are still deps, for traits implemented by them.
+pub fn takes_no_deps(sig: &syn::Signature, opts: &Opts) -> bool {
+    if let Some(SpanOpt(no_deps, _)) = opts.no_deps {
+        return no_deps;
+    }
+
+    match sig.inputs.first() {
+        None => true,
+        Some(syn::FnArg::Typed(pat_type)) => {
+            !pat_type.attrs.is_empty() || is_owned_plain_value(&pat_type.ty)
+        }
+        Some(syn::FnArg::Receiver(_)) => false,
+    }
+}
+
+fn is_owned_plain_value(ty: &syn::Type) -> bool {
+    const VALUE_TYPES: &[&str] = &[
+        "bool", "char", "String", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32",
+        "i64", "i128", "isize", "f32", "f64",
+    ];
+
+    match ungroup_type(ty) {
+        syn::Type::Paren(paren) => is_owned_plain_value(&paren.elem),
+        syn::Type::Path(type_path) => {
+            type_path.qself.is_none()
+                && VALUE_TYPES
+                    .iter()
+                    .any(|value_type| type_path.path.is_ident(value_type))
+        }
+        _ => false,
+    }
+}
+
+/// Attributes like `#[allow(..)]` that configure lints
+pub fn is_lint_attr(attr: &syn::Attribute) -> bool {
+    ["allow", "warn", "deny", "forbid", "expect"]
+        .iter()
+        .any(|lint_level| attr.path().is_ident(lint_level))
+}
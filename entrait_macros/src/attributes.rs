@@ -2,27 +2,16 @@ use crate::analyze_generics::TraitFn;
 use crate::generics::{self, TraitIndirection};
 use crate::idents::CrateIdents;
 use crate::input::FnInputMode;
-use crate::opt::{MockApiIdent, Opts};
+use crate::opt::{MockApiIdent, Opts, UnimockVersion};
 use crate::token_util::{comma_sep, push_tokens};
 
 use proc_macro2::{Span, TokenStream};
-use quote::ToTokens;
-
-pub struct Attr<P>(pub P);
+use quote::{quote, ToTokens};
 
 pub trait IsEmpty {
     fn is_empty(&self) -> bool;
 }
 
-impl<P: ToTokens> ToTokens for Attr<P> {
-    fn to_tokens(&self, stream: &mut TokenStream) {
-        push_tokens!(stream, syn::token::Pound::default());
-        syn::token::Bracket::default().surround(stream, |stream| {
-            push_tokens!(stream, self.0);
-        });
-    }
-}
-
 pub struct ExportGatedAttr<'a, P: ToTokens + IsEmpty> {
     pub params: P,
     pub opts: &'a Opts,
@@ -35,57 +24,22 @@ impl<'a, P: ToTokens + IsEmpty> ToTokens for ExportGatedAttr<'a, P> {
         }
         push_tokens!(stream, syn::token::Pound::default());
         syn::token::Bracket::default().surround(stream, |stream| {
-            if self.opts.export_value() {
-                push_tokens!(stream, self.params);
-            } else {
-                push_tokens!(stream, syn::Ident::new("cfg_attr", Span::call_site()));
-                syn::token::Paren::default().surround(stream, |stream| {
-                    push_tokens!(
-                        stream,
-                        syn::Ident::new("test", Span::call_site()),
-                        syn::token::Comma::default(),
-                        self.params
-                    );
-                });
+            match self.opts.mock_cfg_predicate() {
+                None => push_tokens!(stream, self.params),
+                Some(predicate) => {
+                    push_tokens!(stream, syn::Ident::new("cfg_attr", Span::call_site()));
+                    syn::token::Paren::default().surround(stream, |stream| {
+                        push_tokens!(stream, predicate, syn::token::Comma::default(), self.params);
+                    });
+                }
             }
         });
     }
 }
 
-pub struct EntraitForTraitParams<'a> {
-    pub crate_idents: &'a CrateIdents,
-}
-
-impl<'a> ToTokens for EntraitForTraitParams<'a> {
-    fn to_tokens(&self, stream: &mut TokenStream) {
-        use syn::token::*;
-        use syn::Ident;
-
-        push_tokens!(
-            stream,
-            PathSep::default(),
-            self.crate_idents.entrait,
-            PathSep::default(),
-            self.crate_idents.entrait
-        );
-        Paren::default().surround(stream, |stream| {
-            push_tokens!(
-                stream,
-                Ident::new("unimock", Span::call_site()),
-                Eq::default(),
-                syn::LitBool::new(false, Span::call_site()),
-                Comma::default(),
-                Ident::new("mockall", Span::call_site()),
-                Eq::default(),
-                syn::LitBool::new(false, Span::call_site())
-            );
-        });
-    }
-}
-
 pub struct UnimockAttrParams<'s> {
-    pub trait_ident: &'s syn::Ident,
     pub mock_api: Option<&'s MockApiIdent>,
+    pub version: UnimockVersion,
     pub trait_indirection: TraitIndirection,
     pub crate_idents: &'s CrateIdents,
     pub trait_fns: &'s [TraitFn],
@@ -109,13 +63,17 @@ impl<'s> ToTokens for UnimockAttrParams<'s> {
         use syn::Ident;
 
         let span = self.span;
+        let reexport = match self.version {
+            UnimockVersion::V0_5 => &self.crate_idents.__unimock_0_5,
+            UnimockVersion::V0_6 => &self.crate_idents.__unimock,
+        };
 
         push_tokens!(
             stream,
             PathSep(span),
             self.crate_idents.entrait,
             PathSep(span),
-            self.crate_idents.__unimock,
+            reexport,
             PathSep(span),
             self.crate_idents.unimock
         );
@@ -132,7 +90,7 @@ impl<'s> ToTokens for UnimockAttrParams<'s> {
                     PathSep(span),
                     self.crate_idents.entrait,
                     PathSep(span),
-                    self.crate_idents.__unimock
+                    reexport
                 );
             });
 
@@ -174,19 +132,29 @@ impl<'s> UnimockAttrParams<'s> {
             let mut punctuator = comma_sep(stream, span);
 
             for trait_fn in self.trait_fns {
-                let fn_ident = &trait_fn.sig().ident;
+                // The entraited fn, which may be renamed or in a nested module
+                let fn_mod_path = &trait_fn.fn_mod_path;
+                let fn_ident = trait_fn.fn_ident();
+                let fn_path = match self.fn_input_mode {
+                    FnInputMode::InherentImpl(self_ty) => quote! { <#self_ty>::#fn_ident },
+                    _ => quote! { #(#fn_mod_path::)* #fn_ident },
+                };
 
                 match &trait_fn.deps {
+                    // There is no service to call the method with
+                    _ if trait_fn.service_method => {
+                        punctuator.push(Underscore(span));
+                    }
                     generics::FnDeps::Generic { .. } => {
-                        punctuator.push(fn_ident);
+                        punctuator.push(&fn_path);
                     }
                     generics::FnDeps::Concrete(_) => {
                         punctuator.push(Underscore(span));
                     }
-                    generics::FnDeps::NoDeps { .. } => {
+                    generics::FnDeps::NoDeps => {
                         // fn_ident(a, b, c)
                         punctuator.push_fn(|stream| {
-                            push_tokens!(stream, fn_ident);
+                            push_tokens!(stream, fn_path);
 
                             Paren(span).surround(stream, |stream| {
                                 let mut punctuator = comma_sep(stream, span);
@@ -229,22 +197,25 @@ impl ToTokens for MockallAutomockParams {
     }
 }
 
-pub struct AsyncTraitParams<'a> {
-    pub crate_idents: &'a CrateIdents,
+pub struct MryParams {
     pub span: Span,
 }
 
-impl<'a> ToTokens for AsyncTraitParams<'a> {
+impl IsEmpty for MryParams {
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+impl ToTokens for MryParams {
     fn to_tokens(&self, stream: &mut TokenStream) {
         let span = self.span;
         push_tokens!(
             stream,
             syn::token::PathSep(span),
-            self.crate_idents.entrait,
-            syn::token::PathSep(span),
-            syn::Ident::new("__async_trait", span),
+            syn::Ident::new("mry", span),
             syn::token::PathSep(span),
-            syn::Ident::new("async_trait", span)
+            syn::Ident::new("mry", span)
         );
     }
 }
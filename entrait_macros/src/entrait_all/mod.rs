@@ -0,0 +1,161 @@
+//! Implementation for invoking entrait_all on a module!
+
+use crate::input::{InputFn, InputMod, ModItem};
+use crate::opt::EntraitOpt;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, ToTokens};
+use syn::parse::{Parse, ParseStream};
+
+/// Options forwarded verbatim to every generated `#[entrait]` attribute
+pub struct EntraitAllAttr {
+    pub opts: TokenStream,
+    /// Functions without parameters are only eligible with `no_deps`
+    pub no_deps: bool,
+}
+
+impl Parse for EntraitAllAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let opts: TokenStream = input.fork().parse()?;
+
+        let parsed =
+            syn::punctuated::Punctuated::<EntraitOpt, syn::token::Comma>::parse_terminated(input)?;
+
+        let mut no_deps = false;
+
+        for entrait_opt in parsed {
+            match entrait_opt {
+                EntraitOpt::NoDeps(opt) => no_deps = opt.0,
+                EntraitOpt::Debug(_)
+                | EntraitOpt::Export(_)
+                | EntraitOpt::ExportCfg(_)
+                | EntraitOpt::MockCfg(_)
+                | EntraitOpt::MaybeSend(_)
+                | EntraitOpt::SendCfg(_)
+                | EntraitOpt::MaybeSync(_)
+                | EntraitOpt::Unimock(_)
+                | EntraitOpt::UnimockVersion(_)
+                | EntraitOpt::Mockall(_)
+                | EntraitOpt::Mry(_)
+                | EntraitOpt::Faux(_)
+                | EntraitOpt::Stub(_)
+                | EntraitOpt::Record(_)
+                | EntraitOpt::ForwardImpls(_)
+                | EntraitOpt::ConstTrait(_)
+                | EntraitOpt::Log(_)
+                | EntraitOpt::Instrument(_)
+                | EntraitOpt::OtelMetrics(_)
+                | EntraitOpt::Intercept(_)
+                | EntraitOpt::Local(_)
+                | EntraitOpt::Fingerprint(_)
+                | EntraitOpt::Dependencies(_)
+                | EntraitOpt::NoStd(_)
+                | EntraitOpt::MinimalCodegen(_)
+                | EntraitOpt::SelfBounds(_)
+                | EntraitOpt::Threadsafe(_)
+                | EntraitOpt::WhereClause(_)
+                | EntraitOpt::Group(_)
+                | EntraitOpt::Strict(_)
+                | EntraitOpt::HideFn(_)
+                | EntraitOpt::ForwardAttrs(_)
+                | EntraitOpt::Dyn(_) => {}
+                entrait_opt => {
+                    return Err(syn::Error::new(entrait_opt.span(), "Unsupported option"))
+                }
+            }
+        }
+
+        Ok(Self { opts, no_deps })
+    }
+}
+
+pub fn output_tokens(attr: EntraitAllAttr, mut input_mod: InputMod) -> TokenStream {
+    for item in input_mod.items.iter_mut() {
+        if let ModItem::PubFn(input_fn) = item {
+            if take_skip_attr(input_fn) || !is_eligible(input_fn, attr.no_deps) {
+                continue;
+            }
+
+            let vis = &input_fn.fn_vis;
+            let trait_ident = trait_ident_from_fn(&input_fn.fn_sig.ident);
+            let opts = &attr.opts;
+            let opt_comma = if opts.is_empty() {
+                None
+            } else {
+                Some(syn::token::Comma::default())
+            };
+
+            input_fn.fn_attrs.insert(
+                0,
+                syn::parse_quote! {
+                    #[::entrait::entrait(#vis #trait_ident #opt_comma #opts)]
+                },
+            );
+        }
+    }
+
+    input_mod.to_token_stream()
+}
+
+/// A public fn with a parameter to take dependencies from (any fn with `no_deps`),
+/// not already annotated with its own `#[entrait]` (which overrides the module-wide one).
+fn is_eligible(input_fn: &InputFn, no_deps: bool) -> bool {
+    let has_entrait_attr = input_fn.fn_attrs.iter().any(|attr| {
+        attr.path().segments.last().map_or(false, |segment| {
+            segment.ident.to_string().starts_with("entrait")
+        })
+    });
+
+    !has_entrait_attr && (no_deps || !input_fn.fn_sig.inputs.is_empty())
+}
+
+/// Remove an `#[entrait(skip)]` attribute, which leaves the fn without a trait
+fn take_skip_attr(input_fn: &mut InputFn) -> bool {
+    let len = input_fn.fn_attrs.len();
+    input_fn.fn_attrs.retain(|attr| {
+        let is_skip = attr.path().is_ident("entrait")
+            && attr
+                .parse_args::<syn::Ident>()
+                .map_or(false, |ident| ident == "skip");
+        !is_skip
+    });
+
+    input_fn.fn_attrs.len() < len
+}
+
+/// `fetch_user` -> `FetchUser`
+fn trait_ident_from_fn(fn_ident: &syn::Ident) -> syn::Ident {
+    let fn_name = fn_ident.to_string();
+    let fn_name = fn_name.trim_start_matches("r#");
+
+    let trait_name: String = fn_name
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    format_ident!("{}", trait_name, span = fn_ident.span())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_derive_trait_ident_from_fn() {
+        let derive = |ident: &str| {
+            trait_ident_from_fn(&syn::Ident::new(ident, proc_macro2::Span::call_site())).to_string()
+        };
+
+        assert_eq!("FetchUser", derive("fetch_user"));
+        assert_eq!("Foo", derive("foo"));
+        assert_eq!("Foo2Bar", derive("foo2_bar"));
+        assert_eq!("PrivateFoo", derive("_private__foo"));
+    }
+}
@@ -0,0 +1,272 @@
+//! `#[derive(EntraitApp)]`, wiring the delegations of an application struct from annotations on its fields.
+//!
+//! Every field can have an `#[app(..)]` attribute with one or more of these options:
+//!
+//! * `as_ref`: `impl AsRef<FieldType> for App`, e.g. for `delegate_by = Arc` or services of impl blocks.
+//! * `as_ref = dyn Trait`: `impl AsRef<dyn Trait> for App`, for `delegate_by = ref`.
+//! * `borrow`: `impl Borrow<FieldType> for App`, for functions with the field type as concrete dependency.
+//! * `boxed`: `impl BoxedDelegate<D> for App` for a `Box<D>` field, for `delegate_by = Box`.
+//! * `delegate = DelegateTrait`: `impl DelegateTrait<Self> for App` with the field type as `Target`, for custom delegation traits.
+//! * `leaf = Trait`: `impl Trait for App` forwarding to the field, for leaf traits, with the hidden macro generated next to them.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+
+use crate::idents::CrateIdents;
+
+pub fn derive(input: syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        syn::Data::Struct(data_struct) => &data_struct.fields,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`EntraitApp` can only be derived for structs",
+            ))
+        }
+    };
+
+    let app = App {
+        crate_idents: CrateIdents::new(Span::call_site()),
+        input: &input,
+    };
+
+    let mut impls = vec![];
+    for (index, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index {
+                index: index as u32,
+                span: field.ty.span(),
+            }),
+        };
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("app"))
+        {
+            let app_opts = attr.parse_args_with(
+                syn::punctuated::Punctuated::<AppOpt, syn::token::Comma>::parse_terminated,
+            )?;
+            for app_opt in app_opts {
+                impls.push(app.gen_impl(app_opt, &member, &field.ty)?);
+            }
+        }
+    }
+
+    Ok(quote! { #(#impls)* })
+}
+
+enum AppOpt {
+    AsRef(Span, Option<syn::Type>),
+    Borrow(Span),
+    Boxed(Span),
+    Delegate(syn::Path),
+    Leaf(syn::Path),
+}
+
+impl Parse for AppOpt {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: syn::Ident = input.parse()?;
+        let span = ident.span();
+
+        match ident.to_string().as_str() {
+            "as_ref" => {
+                if input.peek(syn::token::Eq) {
+                    input.parse::<syn::token::Eq>()?;
+                    let target_ty: syn::Type = input.parse()?;
+                    Ok(Self::AsRef(span, Some(ungroup(&target_ty).clone())))
+                } else {
+                    Ok(Self::AsRef(span, None))
+                }
+            }
+            "borrow" => Ok(Self::Borrow(span)),
+            "boxed" => Ok(Self::Boxed(span)),
+            "delegate" => {
+                input.parse::<syn::token::Eq>()?;
+                Ok(Self::Delegate(input.parse()?))
+            }
+            "leaf" => {
+                input.parse::<syn::token::Eq>()?;
+                Ok(Self::Leaf(input.parse()?))
+            }
+            other => Err(syn::Error::new(
+                span,
+                format!("Unknown app option \"{other}\", expected `as_ref`, `borrow`, `boxed`, `delegate` or `leaf`"),
+            )),
+        }
+    }
+}
+
+struct App<'i> {
+    crate_idents: CrateIdents,
+    input: &'i syn::DeriveInput,
+}
+
+impl<'i> App<'i> {
+    fn gen_impl(
+        &self,
+        app_opt: AppOpt,
+        member: &syn::Member,
+        field_ty: &syn::Type,
+    ) -> syn::Result<TokenStream> {
+        let entrait = &self.crate_idents.entrait;
+        let core = &self.crate_idents.core;
+        let app_ident = &self.input.ident;
+        let (impl_generics, ty_generics, where_clause) = self.input.generics.split_for_impl();
+
+        match app_opt {
+            AppOpt::AsRef(span, None) => Ok(quote_spanned! { span=>
+                impl #impl_generics ::#core::convert::AsRef<#field_ty> for #app_ident #ty_generics #where_clause {
+                    fn as_ref(&self) -> &#field_ty {
+                        &self.#member
+                    }
+                }
+            }),
+            AppOpt::Borrow(span) => Ok(quote_spanned! { span=>
+                impl #impl_generics ::#core::borrow::Borrow<#field_ty> for #app_ident #ty_generics #where_clause {
+                    fn borrow(&self) -> &#field_ty {
+                        &self.#member
+                    }
+                }
+            }),
+            AppOpt::AsRef(span, Some(target_ty)) => {
+                let target_ty = with_static_bound(target_ty);
+                let return_ty = match &target_ty {
+                    syn::Type::TraitObject(_) => quote_spanned! { span=> &(#target_ty) },
+                    _ => quote_spanned! { span=> &#target_ty },
+                };
+                // Trait objects are usually owned through a smart pointer
+                let borrow = if matches!(target_ty, syn::Type::TraitObject(_))
+                    && is_smart_pointer(field_ty)
+                {
+                    quote_spanned! { span=> &*self.#member }
+                } else {
+                    quote_spanned! { span=> &self.#member }
+                };
+
+                Ok(quote_spanned! { span=>
+                    impl #impl_generics ::#core::convert::AsRef<#target_ty> for #app_ident #ty_generics #where_clause {
+                        fn as_ref(&self) -> #return_ty {
+                            #borrow
+                        }
+                    }
+                })
+            }
+            AppOpt::Boxed(span) => {
+                let boxed_ty = match boxed_type(field_ty) {
+                    Some(boxed_ty) => boxed_ty,
+                    None => {
+                        return Err(syn::Error::new(
+                            field_ty.span(),
+                            "`boxed` requires a field of type `Box<dyn Trait>`",
+                        ))
+                    }
+                };
+
+                Ok(quote_spanned! { span=>
+                    impl #impl_generics ::#entrait::BoxedDelegate<#boxed_ty> for #app_ident #ty_generics #where_clause {
+                        fn boxed_delegate(&self) -> &#field_ty {
+                            &self.#member
+                        }
+                    }
+                })
+            }
+            AppOpt::Delegate(mut delegate_path) => {
+                let span = delegate_path.span();
+                // The application is the first argument of the delegation trait
+                let last_segment = delegate_path.segments.last_mut().unwrap();
+                let mut arguments = match std::mem::replace(
+                    &mut last_segment.arguments,
+                    syn::PathArguments::None,
+                ) {
+                    syn::PathArguments::AngleBracketed(arguments) => arguments,
+                    syn::PathArguments::None => syn::parse_quote! { <> },
+                    syn::PathArguments::Parenthesized(arguments) => {
+                        return Err(syn::Error::new(
+                            arguments.span(),
+                            "Expected angle-bracketed trait arguments",
+                        ))
+                    }
+                };
+                arguments.args.insert(0, syn::parse_quote! { Self });
+                last_segment.arguments = syn::PathArguments::AngleBracketed(arguments);
+
+                Ok(quote_spanned! { span=>
+                    impl #impl_generics #delegate_path for #app_ident #ty_generics #where_clause {
+                        type Target = #field_ty;
+                    }
+                })
+            }
+            AppOpt::Leaf(trait_path) => {
+                let span = trait_path.span();
+                let mut macro_path = trait_path.clone();
+                let last_segment = macro_path.segments.last_mut().unwrap();
+                if !last_segment.arguments.is_empty() {
+                    return Err(syn::Error::new(
+                        last_segment.arguments.span(),
+                        "`leaf` is not supported for generic traits",
+                    ));
+                }
+                last_segment.ident = format_ident!("__entrait_leaf_{}", last_segment.ident);
+
+                Ok(quote_spanned! { span=>
+                    #macro_path! {
+                        [#trait_path] [#impl_generics] [#where_clause] #app_ident #ty_generics, #member: #field_ty
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// Types passed through `macro_rules` fragments arrive in invisible groups
+fn ungroup(ty: &syn::Type) -> &syn::Type {
+    match ty {
+        syn::Type::Group(group) => ungroup(&group.elem),
+        ty => ty,
+    }
+}
+
+/// `AsRef<dyn Trait>` is `AsRef<dyn Trait + 'static>`, which must be explicit in the return type of `as_ref`
+fn with_static_bound(mut ty: syn::Type) -> syn::Type {
+    if let syn::Type::TraitObject(trait_object) = &mut ty {
+        if !trait_object
+            .bounds
+            .iter()
+            .any(|bound| matches!(bound, syn::TypeParamBound::Lifetime(_)))
+        {
+            trait_object.bounds.push(syn::parse_quote! { 'static });
+        }
+    }
+    ty
+}
+
+fn is_smart_pointer(ty: &syn::Type) -> bool {
+    match ungroup(ty) {
+        syn::Type::Path(type_path) => type_path.path.segments.last().map_or(false, |segment| {
+            segment.ident == "Box" || segment.ident == "Arc" || segment.ident == "Rc"
+        }),
+        _ => false,
+    }
+}
+
+/// The `D` of a `Box<D>`
+fn boxed_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let segment = match ungroup(ty) {
+        syn::Type::Path(type_path) => type_path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Box" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(arguments) => match arguments.args.first()? {
+            syn::GenericArgument::Type(boxed_ty) => Some(boxed_ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,261 @@
+//! Dyn-compatible traits for entraited functions, enabled with the `dyn` option.
+//!
+//! * The futures of `async` methods are boxed, like `-> Pin<Box<dyn Future<Output = T> + Send + 'entrait_fut>>`.
+//! * `impl Trait` outputs are boxed, like `-> Box<dyn Iterator<Item = T> + 'entrait_fut>`.
+//! * Closure parameters become trait objects: `&dyn Fn(..)`, `&mut dyn FnMut(..)` or `Box<dyn FnOnce(..)>`.
+//!   The entraited function still receives them as its generic closure type, since these all implement the closure trait.
+//!
+//! Any other generic method is rejected, and the trait gets a compile-time assertion:
+//!
+//! ```text
+//! const _: () = {
+//!     fn assert_dyn_compatible(_: &dyn Trait) {}
+//! };
+//! ```
+
+use crate::analyze_generics::TraitFn;
+use crate::generics;
+use crate::idents::CrateIdents;
+use crate::opt::{check_alloc, Opts};
+use crate::signature::boxed_future::{box_future, box_impl_output};
+use crate::signature::{is_closure_bounds, is_closure_param, ungroup_type};
+use crate::token_util::mentions_ident;
+
+use proc_macro2::TokenStream;
+use quote::{quote_spanned, ToTokens};
+
+type Bounds = syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Plus>;
+
+/// Rewrite the trait method so that it doesn't make the trait dyn-incompatible.
+///
+/// Futures are not boxed when `#[async_trait]` already takes care of that.
+pub fn make_dyn_compatible(
+    trait_fn: &mut TraitFn,
+    box_futures: bool,
+    crate_idents: &CrateIdents,
+    opts: &Opts,
+) -> syn::Result<()> {
+    let sig = &mut trait_fn.entrait_sig.sig;
+
+    box_closure_params(sig, crate_idents)?;
+    reject_generics(sig)?;
+
+    // The boxed output of an `async` method borrows for as long as the boxed future
+    if !trait_fn.originally_async || box_futures {
+        trait_fn.boxed_output = box_impl_output(sig, &crate_idents.entrait)?;
+    }
+    reject_impl_output(sig)?;
+
+    if trait_fn.originally_async && box_futures {
+        box_future(sig, &crate_idents.core, &crate_idents.entrait, opts);
+        trait_fn.boxed_future = true;
+    }
+
+    if trait_fn.boxed_output.is_some() || trait_fn.boxed_future {
+        check_alloc(
+            "Boxing the futures and `impl Trait` outputs of `dyn` traits",
+            trait_fn.sig().ident.span(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Fails to compile when the generated trait is not dyn-compatible, for example because of its supertraits
+pub fn gen_assertion(
+    trait_ident: &syn::Ident,
+    trait_generics: &generics::TraitGenerics,
+) -> TokenStream {
+    let span = trait_ident.span();
+    let params = trait_generics.trait_params();
+    let args = trait_generics.arguments(&generics::ImplIndirection::None);
+    let where_clause = trait_generics.trait_where_clause();
+
+    quote_spanned! { span=>
+        const _: () = {
+            #[allow(dead_code)]
+            fn assert_dyn_compatible #params (_: &dyn #trait_ident #args) #where_clause {}
+        };
+    }
+}
+
+fn box_closure_params(sig: &mut syn::Signature, crate_idents: &CrateIdents) -> syn::Result<()> {
+    let closure_params: Vec<(syn::Ident, Bounds)> = sig
+        .generics
+        .type_params()
+        .filter(|type_param| is_closure_param(&sig.generics, &type_param.ident))
+        .map(|type_param| {
+            let ident = type_param.ident.clone();
+            let bounds = closure_param_bounds(&sig.generics, &ident);
+            (ident, bounds)
+        })
+        .collect();
+
+    for fn_arg in sig.inputs.iter_mut() {
+        let pat_type = match fn_arg {
+            syn::FnArg::Typed(pat_type) => pat_type,
+            syn::FnArg::Receiver(_) => continue,
+        };
+
+        if let syn::Type::ImplTrait(impl_trait) = ungroup_type(&pat_type.ty) {
+            if is_closure_bounds(&impl_trait.bounds) {
+                let bounds = impl_trait.bounds.clone();
+                *pat_type.ty = closure_object(bounds, crate_idents);
+            }
+            continue;
+        }
+
+        for (ident, bounds) in &closure_params {
+            if is_path_of(&pat_type.ty, ident) {
+                *pat_type.ty = closure_object(bounds.clone(), crate_idents);
+            } else if mentions_ident(pat_type.ty.to_token_stream(), ident) {
+                return Err(syn::Error::new_spanned(
+                    &pat_type.ty,
+                    format!("The closure parameter `{ident}` must be the type of a parameter to be passed as a trait object with `dyn`"),
+                ));
+            }
+        }
+    }
+
+    for (ident, _) in &closure_params {
+        if mentions_ident(sig.output.to_token_stream(), ident) {
+            return Err(syn::Error::new_spanned(
+                &sig.output,
+                format!("The closure parameter `{ident}` can't be part of the output with `dyn`"),
+            ));
+        }
+    }
+
+    // The closure parameters are now trait objects:
+    let params = std::mem::take(&mut sig.generics.params);
+    sig.generics.params = params
+        .into_iter()
+        .filter(|param| match param {
+            syn::GenericParam::Type(type_param) => !closure_params
+                .iter()
+                .any(|(ident, _)| type_param.ident == *ident),
+            _ => true,
+        })
+        .collect();
+    if let Some(where_clause) = &mut sig.generics.where_clause {
+        let predicates = std::mem::take(&mut where_clause.predicates);
+        where_clause.predicates = predicates
+            .into_iter()
+            .filter(|predicate| match predicate {
+                syn::WherePredicate::Type(predicate_type) => !closure_params
+                    .iter()
+                    .any(|(ident, _)| is_path_of(&predicate_type.bounded_ty, ident)),
+                _ => true,
+            })
+            .collect();
+        if where_clause.predicates.is_empty() {
+            sig.generics.where_clause = None;
+        }
+    }
+    if sig.generics.params.is_empty() {
+        sig.generics.lt_token = None;
+        sig.generics.gt_token = None;
+    }
+
+    Ok(())
+}
+
+fn reject_generics(sig: &syn::Signature) -> syn::Result<()> {
+    if let Some(param) = sig
+        .generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, syn::GenericParam::Lifetime(_)))
+    {
+        return Err(syn::Error::new_spanned(
+            param,
+            "Generic methods are not dyn-compatible. Only closure parameters can be used with `dyn`",
+        ));
+    }
+
+    for fn_arg in &sig.inputs {
+        if let syn::FnArg::Typed(pat_type) = fn_arg {
+            if let syn::Type::ImplTrait(impl_trait) = ungroup_type(&pat_type.ty) {
+                return Err(syn::Error::new_spanned(
+                    impl_trait,
+                    "`impl Trait` parameters are not dyn-compatible. Only closure parameters can be used with `dyn`",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// An `impl Trait` output that could not be boxed
+fn reject_impl_output(sig: &syn::Signature) -> syn::Result<()> {
+    if let syn::ReturnType::Type(_, ty) = &sig.output {
+        if let syn::Type::ImplTrait(impl_trait) = ungroup_type(ty) {
+            return Err(syn::Error::new_spanned(
+                impl_trait,
+                "`impl Trait` outputs are not dyn-compatible",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The inline bounds and where clause bounds of a closure parameter
+fn closure_param_bounds(generics: &syn::Generics, ident: &syn::Ident) -> Bounds {
+    let mut bounds = Bounds::new();
+
+    for type_param in generics.type_params() {
+        if type_param.ident == *ident {
+            bounds.extend(type_param.bounds.iter().cloned());
+        }
+    }
+    if let Some(where_clause) = &generics.where_clause {
+        for predicate in &where_clause.predicates {
+            if let syn::WherePredicate::Type(predicate_type) = predicate {
+                if is_path_of(&predicate_type.bounded_ty, ident) {
+                    bounds.extend(predicate_type.bounds.iter().cloned());
+                }
+            }
+        }
+    }
+
+    bounds
+}
+
+/// `Fn` closures are borrowed, `FnMut` closures are borrowed mutably and `FnOnce` closures are boxed.
+///
+/// A borrowed `Send` closure must also be `Sync` for the reference to be `Send`.
+fn closure_object(mut bounds: Bounds, crate_idents: &CrateIdents) -> syn::Type {
+    let entrait = &crate_idents.entrait;
+    let core = &crate_idents.core;
+
+    let has_bound = |bounds: &Bounds, name: &str| {
+        bounds.iter().any(|bound| match bound {
+            syn::TypeParamBound::Trait(trait_bound) => trait_bound
+                .path
+                .segments
+                .last()
+                .map_or(false, |segment| segment.ident == name),
+            _ => false,
+        })
+    };
+
+    if has_bound(&bounds, "FnOnce") {
+        syn::parse_quote! { ::#entrait::__alloc::Box<dyn #bounds> }
+    } else if has_bound(&bounds, "FnMut") {
+        syn::parse_quote! { &mut (dyn #bounds) }
+    } else {
+        if has_bound(&bounds, "Send") && !has_bound(&bounds, "Sync") {
+            bounds.push(syn::parse_quote! { ::#core::marker::Sync });
+        }
+        syn::parse_quote! { &(dyn #bounds) }
+    }
+}
+
+fn is_path_of(ty: &syn::Type, ident: &syn::Ident) -> bool {
+    match ungroup_type(ty) {
+        syn::Type::Path(type_path) => type_path.qself.is_none() && type_path.path.is_ident(ident),
+        _ => false,
+    }
+}
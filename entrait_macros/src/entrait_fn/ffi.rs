@@ -0,0 +1,188 @@
+//! C bindings for entraited modules.
+//!
+//! With `ffi = App`, the module gets `extern "C"` functions operating on an opaque `*mut Impl<App>` handle,
+//! prefixed with the module name:
+//!
+//! ```text
+//! pub extern "C" fn users_create() -> *mut Impl<App>;
+//! pub unsafe extern "C" fn users_destroy(handle: *mut Impl<App>);
+//! pub unsafe extern "C" fn users_string_free(string: *mut c_char);
+//!
+//! pub unsafe extern "C" fn users_get_user(handle: *const Impl<App>, id: <u32 as FromFfi>::Ffi) -> <User as IntoFfi>::Ffi {
+//!     let handle = unsafe { &*handle };
+//!     let id = <u32 as FromFfi>::from_ffi(id);
+//!     IntoFfi::into_ffi(handle.get_user(id))
+//! }
+//! ```
+
+use super::{is_unsized, reject_unexportable};
+use crate::analyze_generics::TraitFn;
+use crate::idents::CrateIdents;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+
+pub fn gen_ffi_fns(
+    app_ident: &syn::Ident,
+    mod_ident: &syn::Ident,
+    trait_fns: &[TraitFn],
+    crate_idents: &CrateIdents,
+) -> syn::Result<TokenStream> {
+    let entrait = &crate_idents.entrait;
+    let core = &crate_idents.core;
+    let span = app_ident.span();
+
+    let impl_app = quote_spanned! {span=> ::#entrait::Impl<super::#app_ident> };
+    let c_char = quote_spanned! {span=> ::#core::ffi::c_char };
+    let box_ = quote_spanned! {span=> ::#entrait::__alloc::Box };
+
+    // Local variables should not collide with parameter names
+    let handle = syn::Ident::new("handle", Span::mixed_site());
+    let string = syn::Ident::new("string", Span::mixed_site());
+
+    let create_ident = format_ident!("{}_create", mod_ident, span = span);
+    let destroy_ident = format_ident!("{}_destroy", mod_ident, span = span);
+    let string_free_ident = format_ident!("{}_string_free", mod_ident, span = span);
+
+    let mut fn_items = vec![];
+
+    for trait_fn in trait_fns {
+        let sig = trait_fn.sig();
+        let fn_ident = &sig.ident;
+        let cfg_attrs = trait_fn.cfg_attrs();
+
+        reject_unexportable(sig, "ffi")?;
+        if trait_fn.originally_async {
+            return Err(syn::Error::new(
+                fn_ident.span(),
+                "`async` functions can't be exported with `ffi`",
+            ));
+        }
+
+        let mut params = vec![];
+        let mut conversions = vec![];
+        let mut arguments = vec![];
+
+        for arg in &sig.inputs {
+            let pat_type = match arg {
+                syn::FnArg::Receiver(_) => continue,
+                syn::FnArg::Typed(pat_type) => pat_type,
+            };
+            let ident = match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+                _ => panic!("Found a non-ident pattern, this should be handled in signature.rs"),
+            };
+
+            let (owned_ty, reference) = match pat_type.ty.as_ref() {
+                syn::Type::Reference(type_reference) => (
+                    type_reference.elem.as_ref(),
+                    Some(&type_reference.mutability),
+                ),
+                ty => (ty, None),
+            };
+
+            if is_string(owned_ty) {
+                params.push(quote! { #ident: *const #c_char });
+                conversions.push(quote! {
+                    let #ident = unsafe { ::#core::ffi::CStr::from_ptr(#ident) }.to_string_lossy();
+                });
+                arguments.push(match reference {
+                    Some(_) => quote! { &#ident },
+                    None => quote! { #ident.into_owned() },
+                });
+                continue;
+            }
+
+            if is_unsized(owned_ty) {
+                return Err(syn::Error::new_spanned(
+                    owned_ty,
+                    "Unsized parameters other than `&str` can't be exported with `ffi`",
+                ));
+            }
+
+            let from_ffi = quote_spanned! {span=> <#owned_ty as ::#entrait::ffi::FromFfi> };
+            params.push(quote! { #ident: #from_ffi::Ffi });
+            match reference {
+                Some(mutability) => {
+                    conversions.push(quote! {
+                        let #mutability #ident = #from_ffi::from_ffi(#ident);
+                    });
+                    arguments.push(quote! { & #mutability #ident });
+                }
+                None => {
+                    conversions.push(quote! {
+                        let #ident = #from_ffi::from_ffi(#ident);
+                    });
+                    arguments.push(quote! { #ident });
+                }
+            }
+        }
+
+        let output_ty = match &sig.output {
+            syn::ReturnType::Default => quote! { () },
+            syn::ReturnType::Type(_, ty) => {
+                if let syn::Type::Reference(_) = ty.as_ref() {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        "Functions returning references can't be exported with `ffi`",
+                    ));
+                }
+                quote! { #ty }
+            }
+        };
+        let into_ffi = quote_spanned! {span=> <#output_ty as ::#entrait::ffi::IntoFfi> };
+        let extern_ident = format_ident!("{}_{}", mod_ident, fn_ident, span = span);
+
+        fn_items.push(quote_spanned! {span=>
+            #(#cfg_attrs)*
+            /// # Safety
+            /// The handle must come from the `create` function, and string parameters must be nul-terminated.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #extern_ident(#handle: *const #impl_app, #(#params),*) -> #into_ffi::Ffi {
+                let #handle = unsafe { &*#handle };
+                #(#conversions)*
+                #into_ffi::into_ffi(#handle.#fn_ident(#(#arguments),*))
+            }
+        });
+    }
+
+    Ok(quote_spanned! {span=>
+        /// Create a handle to the application, to be released with the `destroy` function
+        #[unsafe(no_mangle)]
+        pub extern "C" fn #create_ident() -> *mut #impl_app {
+            #box_::into_raw(#box_::new(::#entrait::Impl::new(
+                <super::#app_ident as ::#core::default::Default>::default(),
+            )))
+        }
+
+        /// # Safety
+        /// The handle must come from the `create` function, and not be used afterwards.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #destroy_ident(#handle: *mut #impl_app) {
+            if !#handle.is_null() {
+                ::#core::mem::drop(unsafe { #box_::from_raw(#handle) });
+            }
+        }
+
+        /// # Safety
+        /// The string must be returned from one of the functions of this module, and not be used afterwards.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #string_free_ident(#string: *mut #c_char) {
+            if !#string.is_null() {
+                ::#core::mem::drop(unsafe { ::#entrait::__alloc::CString::from_raw(#string) });
+            }
+        }
+
+        #(#fn_items)*
+    })
+}
+
+fn is_string(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => {
+            type_path.qself.is_none()
+                && (type_path.path.is_ident("str") || type_path.path.is_ident("String"))
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,302 @@
+//! Entraiting an inherent impl block of a service type, like `impl MyService { .. }`.
+//!
+//! The trait gets the methods of the impl block without their `&self` receiver,
+//! and is implemented for `Impl<T>` where `T: AsRef<MyService>`, which lends the service to the methods.
+
+use super::*;
+use crate::input::InputInherentImpl;
+
+use syn::spanned::Spanned;
+
+pub fn entrait_for_inherent_impl(
+    attr: &EntraitFnAttr,
+    mut input_impl: InputInherentImpl,
+) -> syn::Result<TokenStream> {
+    check_options(attr)?;
+
+    let include_private = matches!(attr.include_private, Some(SpanOpt(true, _)));
+    let impl_fn_attrs = input_impl
+        .trait_fns_mut(include_private)
+        .into_iter()
+        .map(take_mod_fn_attr)
+        .collect::<syn::Result<Vec<_>>>()?;
+    if let Some(doc_hidden) = hide_fn_attr(attr) {
+        for (input_fn, impl_fn_attr) in input_impl
+            .trait_fns_mut(include_private)
+            .into_iter()
+            .zip(&impl_fn_attrs)
+        {
+            if !impl_fn_attr.skip {
+                input_fn.fn_attrs.push(doc_hidden.clone());
+            }
+        }
+    }
+    let input_fns: Vec<_> = input_impl
+        .trait_fns(include_private)
+        .into_iter()
+        .zip(impl_fn_attrs)
+        .filter(|(_, impl_fn_attr)| !impl_fn_attr.skip)
+        .map(|(input_fn, impl_fn_attr)| Ok((ServiceFn::new(input_fn)?, impl_fn_attr)))
+        .collect::<syn::Result<_>>()?;
+
+    let contains_async = input_fns
+        .iter()
+        .any(|(service_fn, _)| service_fn.input_fn.fn_sig.asyncness.is_some());
+    let trait_items = with_non_send_variant(
+        attr,
+        contains_async,
+        gen_impl_trait_items(attr, &input_impl, &input_fns)?,
+        |non_send_attr| gen_impl_trait_items(non_send_attr, &input_impl, &input_fns),
+    )?;
+    let local_attr = local_variant(
+        attr,
+        &input_fns
+            .iter()
+            .map(|(service_fn, _)| service_fn.input_fn)
+            .collect::<Vec<_>>(),
+    )?;
+    let opt_local_trait_items = match &local_attr {
+        Some(local_attr) => Some(gen_impl_trait_items(local_attr, &input_impl, &input_fns)?),
+        None => None,
+    };
+    let also_trait_items = also_traits(attr, &input_fns)?
+        .iter()
+        .map(|(also_attr, also_fns)| {
+            with_non_send_variant(
+                also_attr,
+                contains_async,
+                gen_impl_trait_items(also_attr, &input_impl, also_fns)?,
+                |non_send_attr| gen_impl_trait_items(non_send_attr, &input_impl, also_fns),
+            )
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #input_impl
+        #trait_items
+        #opt_local_trait_items
+        #(#also_trait_items)*
+    })
+}
+
+fn check_options(attr: &EntraitFnAttr) -> syn::Result<()> {
+    let unsupported = [
+        attr.default_body
+            .as_ref()
+            .map(|opt| ("default_body", opt.1)),
+        attr.method.as_ref().map(|ident| ("method", ident.span())),
+        attr.wasm_bindgen
+            .as_ref()
+            .map(|ident| ("wasm_bindgen", ident.span())),
+        attr.pyo3.as_ref().map(|ident| ("pyo3", ident.span())),
+        attr.ffi.as_ref().map(|ident| ("ffi", ident.span())),
+        attr.tower_service
+            .as_ref()
+            .map(|ident| ("tower_service", ident.span())),
+        attr.tonic
+            .as_ref()
+            .map(|path| ("tonic", syn::spanned::Spanned::span(path))),
+        attr.memo.as_ref().map(|opt| ("memo", opt.1)),
+        attr.deferred.as_ref().map(|opt| ("deferred", opt.1)),
+        attr.opts
+            .const_trait
+            .as_ref()
+            .map(|opt| ("const_trait", opt.1)),
+    ];
+
+    match unsupported.into_iter().flatten().next() {
+        Some(("method", span)) => Err(syn::Error::new(
+            span,
+            "`method` is only supported for single functions. In an impl block, use `#[entrait(method = ..)]` on the method",
+        )),
+        Some((option, span)) => Err(syn::Error::new(
+            span,
+            format!("`{option}` is not supported for impl blocks"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A method of the impl block, as a standalone fn taking the dependencies first
+#[derive(Clone)]
+struct ServiceFn<'i> {
+    input_fn: &'i InputFn,
+    sig: syn::Signature,
+    service_method: bool,
+}
+
+impl<'i> ServiceFn<'i> {
+    fn new(input_fn: &'i InputFn) -> syn::Result<Self> {
+        let mut sig = input_fn.fn_sig.clone();
+        let service_method = match sig.inputs.first() {
+            Some(syn::FnArg::Receiver(receiver)) => {
+                if receiver.reference.is_none()
+                    || receiver.mutability.is_some()
+                    || receiver.colon_token.is_some()
+                {
+                    return Err(syn::Error::new(
+                        receiver.span(),
+                        "Methods of an entraited impl block must take the service as `&self`",
+                    ));
+                }
+                true
+            }
+            _ => false,
+        };
+        if service_method {
+            sig.inputs = sig.inputs.into_iter().skip(1).collect();
+        }
+
+        Ok(Self {
+            input_fn,
+            sig,
+            service_method,
+        })
+    }
+}
+
+/// The trait of an impl block, and its implementation
+fn gen_impl_trait_items(
+    attr: &EntraitFnAttr,
+    input_impl: &InputInherentImpl,
+    input_fns: &[(ServiceFn, ModFnAttr)],
+) -> syn::Result<TokenStream> {
+    let fn_input_mode = FnInputMode::InherentImpl(&input_impl.self_ty);
+    strict::check_fns(
+        attr,
+        &input_fns
+            .iter()
+            .map(|(service_fn, _)| service_fn.input_fn)
+            .collect::<Vec<_>>(),
+    )?;
+    let sub_attributes = analyze_sub_attributes(&input_impl.attrs);
+    let mut generics_analyzer = analyze_generics::GenericsAnalyzer::new();
+    let trait_fns = input_fns
+        .iter()
+        .map(|(service_fn, impl_fn_attr)| {
+            let mut trait_fn = TraitFnAnalyzer {
+                impl_receiver_kind: signature::ImplReceiverKind::SelfRef,
+                crate_idents: &attr.crate_idents,
+                opts: &attr.opts,
+            }
+            .analyze(
+                signature::InputSig::new(&service_fn.sig),
+                &mut generics_analyzer,
+            )?;
+            trait_fn.service_method = service_fn.service_method;
+            if let (true, Some(syn::FnArg::Receiver(receiver))) =
+                (trait_fn.service_method, trait_fn.sig().inputs.first())
+            {
+                // The service is borrowed from the same `Impl<T>` that is passed as deps
+                if receiver.reference.is_none() {
+                    return Err(syn::Error::new(
+                        receiver.self_token.span,
+                        "Methods taking `&self` can't take their dependencies by value",
+                    ));
+                }
+            }
+            mirror_attrs(attr, &mut trait_fn, &service_fn.input_fn.fn_attrs);
+            if let Some(method) = &impl_fn_attr.method {
+                trait_fn.rename(method.clone());
+            }
+            if let Some(SpanOpt(true, _)) = &attr.dyn_compatible {
+                dyn_compatible::make_dyn_compatible(
+                    &mut trait_fn,
+                    !contains_async_trait(&sub_attributes),
+                    &attr.crate_idents,
+                    &attr.opts,
+                )?;
+            }
+            Ok(trait_fn)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let trait_dependency_mode = detect_trait_dependency_mode(
+        &fn_input_mode,
+        &trait_fns,
+        &attr.crate_idents,
+        attr.trait_ident.span(),
+    )?;
+    attr.opts
+        .check_impl_sync(trait_fns.iter().any(|trait_fn| trait_fn.originally_async))?;
+    check_instrument_fields(attr, &trait_fns)?;
+
+    let trait_generics = generics_analyzer.into_trait_generics();
+    let (supertraits, mut impl_generics) = apply_self_bounds(attr, &trait_generics);
+    apply_where_clause(attr, &trait_dependency_mode, &mut impl_generics)?;
+    apply_intercept(attr, &trait_dependency_mode, &trait_fns, &mut impl_generics)?;
+    apply_service(
+        &input_impl.self_ty,
+        &attr.crate_idents,
+        &trait_dependency_mode,
+        &trait_fns,
+        &mut impl_generics,
+    );
+    let trait_def = TraitCodegen {
+        opts: &attr.opts,
+        crate_idents: &attr.crate_idents,
+        trait_indirection: generics::TraitIndirection::Plain,
+        trait_dependency_mode: &trait_dependency_mode,
+        sub_attributes: &sub_attributes,
+    }
+    .gen_trait_def(
+        &attr.trait_visibility,
+        &attr.trait_ident,
+        &trait_generics,
+        &supertraits,
+        &[],
+        &[],
+        &trait_fns,
+        &fn_input_mode,
+    )?;
+    let impl_block = fn_delegation_codegen::FnDelegationCodegen {
+        opts: &attr.opts,
+        crate_idents: &attr.crate_idents,
+        trait_ref: &attr.trait_ident,
+        trait_span: attr.trait_ident.span(),
+        impl_indirection: generics::ImplIndirection::None,
+        trait_generics: &impl_generics,
+        fn_input_mode: &fn_input_mode,
+        trait_dependency_mode: &trait_dependency_mode,
+        sub_attributes: &sub_attributes,
+    }
+    .gen_impl_block(&[], &[], &trait_fns);
+    let opt_dyn_assertion = gen_opt_dyn_assertion(attr, &trait_generics);
+
+    graph::record_dependencies(&attr.trait_ident, &trait_fns)?;
+    let opt_self_dependency_error =
+        graph::check_self_dependency(&attr.trait_ident, &trait_fns, &attr.opts)
+            .err()
+            .map(|error| error.to_compile_error());
+
+    Ok(quote! {
+        #trait_def
+        #impl_block
+        #opt_dyn_assertion
+        #opt_self_dependency_error
+    })
+}
+
+/// The methods taking `&self` borrow the service from the `T` of `Impl<T>`
+fn apply_service(
+    self_ty: &syn::Type,
+    crate_idents: &CrateIdents,
+    trait_dependency_mode: &TraitDependencyMode,
+    trait_fns: &[analyze_generics::TraitFn],
+    impl_generics: &mut generics::TraitGenerics,
+) {
+    let impl_t = match trait_dependency_mode {
+        TraitDependencyMode::Generic(idents) => &idents.impl_t,
+        TraitDependencyMode::Concrete(_) => return,
+    };
+    if !trait_fns.iter().any(|trait_fn| trait_fn.service_method) {
+        return;
+    }
+
+    let core = &crate_idents.core;
+    impl_generics
+        .where_predicates
+        .push(syn::parse_quote_spanned! { self_ty.span()=>
+            #impl_t: ::#core::convert::AsRef<#self_ty>
+        });
+}
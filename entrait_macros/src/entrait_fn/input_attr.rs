@@ -4,10 +4,28 @@ use crate::opt::*;
 use syn::parse::{Parse, ParseStream};
 
 /// The `entrait` invocation for functions
+#[derive(Clone)]
 pub struct EntraitFnAttr {
     pub trait_visibility: syn::Visibility,
     pub trait_ident: syn::Ident,
     pub opts: Opts,
+    pub default_body: Option<SpanOpt<DefaultBody>>,
+    pub method: Option<syn::Ident>,
+    pub wasm_bindgen: Option<syn::Ident>,
+    pub pyo3: Option<syn::Ident>,
+    pub ffi: Option<syn::Ident>,
+    pub tower_service: Option<syn::Ident>,
+    pub tonic: Option<syn::Path>,
+    pub memo: Option<SpanOpt<bool>>,
+    pub deferred: Option<SpanOpt<bool>>,
+    pub self_bounds: Option<SpanOpt<SelfBounds>>,
+    pub where_clause: Option<SpanOpt<WhereClause>>,
+    pub group: Option<syn::LitStr>,
+    pub strict: Option<SpanOpt<bool>>,
+    pub dyn_compatible: Option<SpanOpt<bool>>,
+    pub include_private: Option<SpanOpt<bool>>,
+    pub hide_fn: Option<SpanOpt<bool>>,
+    pub forward_attrs: Option<SpanOpt<Vec<syn::Path>>>,
 
     pub crate_idents: CrateIdents,
 }
@@ -18,14 +36,57 @@ impl Parse for EntraitFnAttr {
         let trait_visibility: syn::Visibility = input.parse()?;
 
         let trait_ident: syn::Ident = input.parse()?;
+        if input.peek(syn::token::PathSep) {
+            return Err(syn::Error::new(input.span(), OTHER_MODULE_MSG));
+        }
 
         let mut no_deps = None;
         let mut debug = None;
         let mut export = None;
+        let mut export_cfg = None;
         let mut future_send = None;
+        let mut send_cfg = None;
+        let mut impl_sync = None;
         let mut mock_api = None;
+        let mut mock_cfg = None;
         let mut unimock = None;
+        let mut unimock_version = None;
         let mut mockall = None;
+        let mut mry = None;
+        let mut faux = None;
+        let mut stub = None;
+        let mut record = None;
+        let mut forward_impls = None;
+        let mut double = None;
+        let mut const_trait = None;
+        let mut log = None;
+        let mut instrument = None;
+        let mut otel_metrics = None;
+        let mut intercept = None;
+        let mut fingerprint = None;
+        let mut dependencies = None;
+        let mut trait_doc = None;
+        let mut no_std = None;
+        let mut minimal_codegen = None;
+        let mut default_body = None;
+        let mut method = None;
+        let mut wasm_bindgen = None;
+        let mut pyo3 = None;
+        let mut ffi = None;
+        let mut tower_service = None;
+        let mut tonic = None;
+        let mut memo = None;
+        let mut deferred = None;
+        let mut self_bounds = None;
+        let mut threadsafe = None;
+        let mut where_clause = None;
+        let mut group = None;
+        let mut strict = None;
+        let mut dyn_compatible = None;
+        let mut include_private = None;
+        let mut hide_fn = None;
+        let mut forward_attrs = None;
+        let mut local = None;
 
         while input.peek(syn::token::Comma) {
             input.parse::<syn::token::Comma>()?;
@@ -34,14 +95,68 @@ impl Parse for EntraitFnAttr {
                 EntraitOpt::NoDeps(opt) => no_deps = Some(opt),
                 EntraitOpt::Debug(opt) => debug = Some(opt),
                 EntraitOpt::Export(opt) => export = Some(opt),
+                EntraitOpt::ExportCfg(opt) => export_cfg = Some(opt),
                 EntraitOpt::MaybeSend(send) => future_send = Some(send),
+                EntraitOpt::SendCfg(opt) => send_cfg = Some(opt),
+                EntraitOpt::MaybeSync(sync) => impl_sync = Some(sync),
                 EntraitOpt::MockApi(ident) => mock_api = Some(ident),
+                EntraitOpt::MockCfg(opt) => mock_cfg = Some(opt),
                 EntraitOpt::Unimock(opt) => unimock = Some(opt),
+                EntraitOpt::UnimockVersion(opt) => unimock_version = Some(opt),
                 EntraitOpt::Mockall(opt) => mockall = Some(opt),
+                EntraitOpt::Mry(opt) => mry = Some(opt),
+                EntraitOpt::Faux(opt) => faux = Some(opt),
+                EntraitOpt::Stub(opt) => stub = Some(opt),
+                EntraitOpt::Record(opt) => record = Some(opt),
+                EntraitOpt::ForwardImpls(opt) => forward_impls = Some(opt),
+                EntraitOpt::Double(ty) => double = Some(ty),
+                EntraitOpt::ConstTrait(opt) => const_trait = Some(opt),
+                EntraitOpt::Log(opt) => log = Some(opt),
+                EntraitOpt::Instrument(opt) => instrument = Some(opt),
+                EntraitOpt::OtelMetrics(opt) => otel_metrics = Some(opt),
+                EntraitOpt::Intercept(opt) => intercept = Some(opt),
+                EntraitOpt::Fingerprint(opt) => fingerprint = Some(opt),
+                EntraitOpt::Dependencies(opt) => dependencies = Some(opt),
+                EntraitOpt::TraitDoc(lit_str) => trait_doc = Some(lit_str),
+                EntraitOpt::NoStd(opt) => no_std = Some(opt),
+                EntraitOpt::MinimalCodegen(opt) => minimal_codegen = Some(opt),
+                EntraitOpt::DefaultBody(opt) => default_body = Some(opt),
+                EntraitOpt::Method(ident) => method = Some(ident),
+                EntraitOpt::WasmBindgen(ident) => wasm_bindgen = Some(ident),
+                EntraitOpt::Pyo3(ident) => pyo3 = Some(ident),
+                EntraitOpt::Ffi(ident) => ffi = Some(ident),
+                EntraitOpt::TowerService(ident) => tower_service = Some(ident),
+                EntraitOpt::Tonic(path) => tonic = Some(path),
+                EntraitOpt::Memo(opt) => memo = Some(opt),
+                EntraitOpt::Deferred(opt) => deferred = Some(opt),
+                EntraitOpt::SelfBounds(opt) => self_bounds = Some(opt),
+                EntraitOpt::Threadsafe(opt) => threadsafe = Some(opt),
+                EntraitOpt::WhereClause(opt) => where_clause = Some(opt),
+                EntraitOpt::Group(lit_str) => group = Some(lit_str),
+                EntraitOpt::Strict(opt) => strict = Some(opt),
+                EntraitOpt::Dyn(opt) => dyn_compatible = Some(opt),
+                EntraitOpt::IncludePrivate(opt) => include_private = Some(opt),
+                EntraitOpt::HideFn(opt) => hide_fn = Some(opt),
+                EntraitOpt::ForwardAttrs(opt) => forward_attrs = Some(opt),
+                EntraitOpt::Local(opt) => local = Some(opt),
                 opt => return Err(syn::Error::new(opt.span(), "Unsupported option")),
             };
         }
 
+        // `threadsafe` is a shorthand for `Send + Sync` self bounds
+        if let Some(SpanOpt(true, span)) = threadsafe {
+            if let Some(SpanOpt(ImplSync(false), sync_span)) = impl_sync {
+                return Err(syn::Error::new(
+                    sync_span,
+                    "`?Sync` can't be combined with `threadsafe`, which requires `Impl<T>` to be `Sync`",
+                ));
+            }
+            let SpanOpt(bounds, _) =
+                self_bounds.get_or_insert_with(|| SpanOpt(SelfBounds::new(), span));
+            bounds.push(syn::parse_quote_spanned! { span=> Send });
+            bounds.push(syn::parse_quote_spanned! { span=> Sync });
+        }
+
         let default_span = trait_ident.span();
 
         Ok(EntraitFnAttr {
@@ -52,11 +167,50 @@ impl Parse for EntraitFnAttr {
                 no_deps,
                 debug,
                 export,
+                export_cfg,
+                mock_cfg,
                 future_send,
+                send_cfg,
+                impl_sync,
                 mock_api,
                 unimock,
+                unimock_version,
                 mockall,
+                mry,
+                faux,
+                stub,
+                record,
+                forward_impls,
+                double,
+                const_trait,
+                log,
+                instrument,
+                otel_metrics,
+                intercept,
+                fingerprint,
+                dependencies,
+                trait_doc,
+                local,
+                no_std,
+                minimal_codegen,
             },
+            default_body,
+            method,
+            wasm_bindgen,
+            pyo3,
+            ffi,
+            tower_service,
+            tonic,
+            memo,
+            deferred,
+            self_bounds,
+            where_clause,
+            group,
+            strict,
+            dyn_compatible,
+            include_private,
+            hide_fn,
+            forward_attrs,
             crate_idents: CrateIdents::new(span),
         })
     }
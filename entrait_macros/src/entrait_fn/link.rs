@@ -0,0 +1,217 @@
+//! Deferred delegation, linked by the final crate.
+//!
+//! With `deferred`, no implementation for `Impl<T>` is generated next to the trait.
+//! Instead, a hidden macro implements the trait for an application type, calling the functions like the delegation would.
+//! `link_entrait!` invokes it through its alias `__entrait_link_{Trait}` next to the trait:
+//!
+//! ```text
+//! entrait::link_entrait!(App => crate_a::Foo);
+//!
+//! // expands to
+//! crate_a::__entrait_link_Foo! { [crate_a::Foo] [crate_a::] App }
+//!
+//! // expands to
+//! const _: () = {
+//!     use crate_a::*;
+//!
+//!     impl crate_a::Foo for App {
+//!         fn foo(&self, arg: i32) -> i32 {
+//!             crate_a::foo(self, arg)
+//!         }
+//!     }
+//! };
+//! ```
+//!
+//! The application type is local to the final crate, which makes the implementation coherent there,
+//! unlike an implementation for `Impl<T>`.
+//! The glob import resolves the types of the signatures like in the module of the functions.
+//! For a module, the alias is re-exported next to the trait, and the functions are called through the module.
+
+use super::input_attr::EntraitFnAttr;
+use crate::analyze_generics::TraitFn;
+use crate::fn_delegation_codegen::gen_opt_turbofish;
+use crate::generics::{FnDeps, TraitGenerics};
+use crate::opt::SpanOpt;
+use crate::signature::boxed_future::gen_boxed_output;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The span of `deferred`, after checking the options that only make sense with an implementation for `Impl<T>`
+pub fn deferred_span(attr: &EntraitFnAttr) -> syn::Result<Option<Span>> {
+    let span = match &attr.deferred {
+        Some(SpanOpt(true, span)) => *span,
+        _ => return Ok(None),
+    };
+
+    let unsupported = [
+        attr.default_body
+            .as_ref()
+            .map(|opt| ("default_body", opt.1)),
+        attr.memo.as_ref().map(|opt| ("memo", opt.1)),
+        attr.opts.log.as_ref().map(|opt| ("log", opt.1)),
+        attr.opts
+            .instrument
+            .as_ref()
+            .map(|opt| ("instrument", opt.1)),
+        attr.opts
+            .otel_metrics
+            .as_ref()
+            .map(|opt| ("otel_metrics", opt.1)),
+        attr.opts.intercept.as_ref().map(|opt| ("intercept", opt.1)),
+        attr.wasm_bindgen
+            .as_ref()
+            .map(|ident| ("wasm_bindgen", ident.span())),
+        attr.pyo3.as_ref().map(|ident| ("pyo3", ident.span())),
+        attr.ffi.as_ref().map(|ident| ("ffi", ident.span())),
+        attr.tonic
+            .as_ref()
+            .map(|path| ("tonic", syn::spanned::Spanned::span(path))),
+    ];
+
+    match unsupported.into_iter().flatten().next() {
+        Some((option, option_span)) => {
+            let mut error = syn::Error::new(
+                option_span,
+                format!("`{option}` requires the implementation for `Impl<T>`, which is not generated with `deferred`"),
+            );
+            error.combine(syn::Error::new(span, "`deferred` given here"));
+            Err(error)
+        }
+        None => Ok(Some(span)),
+    }
+}
+
+/// The hidden macro implementing the trait for an application type, and its alias.
+///
+/// Exported macros live at the crate root, so its own name is made unique with a counter.
+/// In a module, the alias is `__entrait_link_mod_{Trait}`, re-exported next to the trait with [gen_mod_alias_reexport].
+pub fn gen_link_macro(
+    attr: &EntraitFnAttr,
+    span: Span,
+    mod_ident: Option<&syn::Ident>,
+    trait_generics: &TraitGenerics,
+    trait_fns: &[TraitFn],
+) -> syn::Result<TokenStream> {
+    static LINK_MACRO_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let entrait = &attr.crate_idents.entrait;
+    let trait_ident = &attr.trait_ident;
+
+    if !trait_generics.params.is_empty() {
+        return Err(syn::Error::new(
+            span,
+            "`deferred` is not supported for generic traits",
+        ));
+    }
+
+    let opt_mod_path = mod_ident.map(|mod_ident| quote! { #mod_ident:: });
+    let mut fn_items = vec![];
+
+    for trait_fn in trait_fns {
+        let trait_fn_sig = trait_fn.sig();
+        let fn_ident = trait_fn.fn_ident();
+        let fn_mod_path = &trait_fn.fn_mod_path;
+
+        let opt_self_comma = match (&trait_fn.deps, trait_fn_sig.inputs.first()) {
+            (FnDeps::Generic { .. }, Some(syn::FnArg::Receiver(receiver))) => {
+                let self_token = receiver.self_token;
+                Some(quote! { #self_token, })
+            }
+            (FnDeps::Concrete(ty), _) => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "`deferred` requires generic dependencies, concrete ones are borrowed from the `T` of `Impl<T>`",
+                ))
+            }
+            _ => None,
+        };
+        let arguments = trait_fn_sig
+            .inputs
+            .iter()
+            .filter_map(|fn_arg| match fn_arg {
+                syn::FnArg::Receiver(_) => None,
+                syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                    syn::Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+                    _ => {
+                        panic!("Found a non-ident pattern, this should be handled in signature.rs")
+                    }
+                },
+            });
+        let opt_turbofish = gen_opt_turbofish(trait_fn, span);
+        let opt_dot_await = trait_fn.opt_dot_await(span);
+
+        let body = quote_spanned! { span=>
+            $($module)* #opt_mod_path #(#fn_mod_path::)* #fn_ident #opt_turbofish(#opt_self_comma #(#arguments),*) #opt_dot_await
+        };
+        let body = match &trait_fn_sig.unsafety {
+            Some(unsafety) => quote_spanned! { span=> #unsafety { #body } },
+            None => body,
+        };
+        let body = match &trait_fn.boxed_output {
+            Some(boxed_output) => gen_boxed_output(boxed_output, body, entrait, span),
+            None => body,
+        };
+        let body = if trait_fn.boxed_future {
+            quote_spanned! { span=>
+                ::#entrait::__alloc::Box::pin(async move { #body })
+            }
+        } else {
+            body
+        };
+
+        let delegation_attrs = trait_fn.delegation_attrs();
+        fn_items.push(quote_spanned! { span=>
+            #(#delegation_attrs)*
+            #trait_fn_sig {
+                #body
+            }
+        });
+    }
+
+    let alias_ident = match mod_ident {
+        Some(_) => format_ident!("__entrait_link_mod_{}", trait_ident),
+        None => format_ident!("__entrait_link_{}", trait_ident),
+    };
+    let macro_ident = format_ident!(
+        "{}_{}",
+        alias_ident,
+        LINK_MACRO_COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! #macro_ident {
+            ([$($trait_path:tt)*] [$($module:tt)*] $app:ty) => {
+                const _: () = {
+                    #[allow(unused_imports)]
+                    use $($module)* #opt_mod_path *;
+
+                    impl $($trait_path)* for $app {
+                        #(#fn_items)*
+                    }
+                };
+            };
+        }
+
+        #[doc(hidden)]
+        pub use #macro_ident as #alias_ident;
+    })
+}
+
+/// The alias of the link macro of a module trait, next to the re-exported trait
+pub fn gen_mod_alias_reexport(
+    mod_ident: &syn::Ident,
+    trait_vis: &syn::Visibility,
+    trait_ident: &syn::Ident,
+) -> TokenStream {
+    let mod_alias_ident = format_ident!("__entrait_link_mod_{}", trait_ident);
+    let alias_ident = format_ident!("__entrait_link_{}", trait_ident);
+
+    quote! {
+        #[doc(hidden)]
+        #trait_vis use #mod_ident::#mod_alias_ident as #alias_ident;
+    }
+}
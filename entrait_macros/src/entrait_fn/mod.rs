@@ -3,38 +3,154 @@
 //! Procedural macros used by entrait.
 //!
 
+mod dyn_compatible;
+mod ffi;
+mod inherent_impl;
 pub mod input_attr;
+mod link;
+mod pyo3;
+mod strict;
+mod tonic;
+mod tower_service;
+mod wasm_bindgen;
 
 use crate::analyze_generics;
 use crate::analyze_generics::GenericsAnalyzer;
 use crate::analyze_generics::TraitFnAnalyzer;
 use crate::fn_delegation_codegen;
 use crate::generics;
+use crate::generics::TraitDependencyMode;
+use crate::graph;
+use crate::idents::{CrateIdents, GenericIdents};
 use crate::input::FnInputMode;
-use crate::input::{InputFn, InputMod, ModItem};
+use crate::input::{InputFn, InputMod};
+use crate::opt::{
+    impl_where_predicates, DefaultBody, EntraitOpt, FutureSend, ImplSync, MockApiIdent, SpanOpt,
+};
+use crate::send_cfg;
 use crate::signature;
+use crate::signature::boxed_future::gen_boxed_output;
+use crate::signature::fn_params::fn_param_idents;
 use crate::sub_attributes::analyze_sub_attributes;
+use crate::sub_attributes::contains_async_trait;
+use crate::sub_attributes::SubAttribute;
 use crate::trait_codegen::Supertraits;
 use crate::trait_codegen::TraitCodegen;
+pub use inherent_impl::entrait_for_inherent_impl;
 use input_attr::*;
 
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::punctuated::Punctuated;
 
 use crate::analyze_generics::detect_trait_dependency_mode;
 
 pub fn entrait_for_single_fn(attr: &EntraitFnAttr, input_fn: InputFn) -> syn::Result<TokenStream> {
+    let trait_items = with_non_send_variant(
+        attr,
+        input_fn.fn_sig.asyncness.is_some(),
+        gen_single_fn_trait_items(attr, &input_fn)?,
+        |non_send_attr| gen_single_fn_trait_items(non_send_attr, &input_fn),
+    )?;
+    let opt_local_trait_items = match local_variant(attr, &[&input_fn])? {
+        Some(local_attr) => Some(gen_single_fn_trait_items(&local_attr, &input_fn)?),
+        None => None,
+    };
+
+    let InputFn {
+        fn_attrs,
+        fn_vis,
+        fn_sig,
+        fn_body,
+        ..
+    } = input_fn;
+    let opt_doc_hidden = hide_fn_attr(attr);
+
+    let out = quote! {
+        #(#fn_attrs)* #opt_doc_hidden #fn_vis #fn_sig #fn_body
+        #trait_items
+        #opt_local_trait_items
+    };
+
+    // println!("\n\nfn output: {out}");
+
+    Ok(out)
+}
+
+/// The trait of a single fn, and its implementation
+fn gen_single_fn_trait_items(attr: &EntraitFnAttr, input_fn: &InputFn) -> syn::Result<TokenStream> {
     let fn_input_mode = FnInputMode::SingleFn(&input_fn.fn_sig.ident);
     let mut generics_analyzer = GenericsAnalyzer::new();
+    check_const_trait(attr, &input_fn.fn_sig)?;
+    strict::check_fns(attr, &[input_fn])?;
+    let sub_attributes = analyze_sub_attributes(&input_fn.fn_attrs);
 
-    let trait_fns = [TraitFnAnalyzer {
+    let mut trait_fn = TraitFnAnalyzer {
         impl_receiver_kind: signature::ImplReceiverKind::SelfRef,
-        trait_span: attr.trait_ident.span(),
         crate_idents: &attr.crate_idents,
         opts: &attr.opts,
     }
-    .analyze(input_fn.input_sig(), &mut generics_analyzer)?];
-    let sub_attributes = analyze_sub_attributes(&input_fn.fn_attrs);
+    .analyze(input_fn.input_sig(), &mut generics_analyzer)?;
+    mirror_attrs(attr, &mut trait_fn, &input_fn.fn_attrs);
+    if let Some(method) = &attr.method {
+        trait_fn.rename(method.clone());
+    }
+    if let Some(memo) = &attr.memo {
+        attr.opts.check_std("`memo`", memo.1)?;
+        set_memo(&mut trait_fn, memo)?;
+    }
+    if let Some(SpanOpt(true, _)) = &attr.dyn_compatible {
+        dyn_compatible::make_dyn_compatible(
+            &mut trait_fn,
+            !contains_async_trait(&sub_attributes),
+            &attr.crate_idents,
+            &attr.opts,
+        )?;
+    }
+    if let Some(default_body) = &attr.default_body {
+        if matches!(default_body.0, DefaultBody::Value(_)) {
+            // The parameters are unused by a constant body
+            trait_fn
+                .attrs
+                .push(syn::parse_quote! { #[allow(unused_variables)] });
+        }
+        trait_fn.default_body = Some(gen_default_body(
+            default_body,
+            &trait_fn,
+            &attr.crate_idents,
+        ));
+    }
+    if let Some(wrapper_ident) = &attr.wasm_bindgen {
+        return Err(syn::Error::new(
+            wrapper_ident.span(),
+            "`wasm_bindgen` is only supported for modules",
+        ));
+    }
+    if let Some(wrapper_ident) = &attr.pyo3 {
+        return Err(syn::Error::new(
+            wrapper_ident.span(),
+            "`pyo3` is only supported for modules",
+        ));
+    }
+    if let Some(app_ident) = &attr.ffi {
+        return Err(syn::Error::new(
+            app_ident.span(),
+            "`ffi` is only supported for modules",
+        ));
+    }
+    if let Some(service_trait) = &attr.tonic {
+        return Err(syn::Error::new_spanned(
+            service_trait,
+            "`tonic` is only supported for modules",
+        ));
+    }
+    if let Some(SpanOpt(_, span)) = &attr.include_private {
+        return Err(syn::Error::new(
+            *span,
+            "`include_private` is only supported for modules",
+        ));
+    }
+    let trait_fns = [trait_fn];
 
     let trait_dependency_mode = detect_trait_dependency_mode(
         &fn_input_mode,
@@ -42,7 +158,13 @@ pub fn entrait_for_single_fn(attr: &EntraitFnAttr, input_fn: InputFn) -> syn::Re
         &attr.crate_idents,
         attr.trait_ident.span(),
     )?;
+    attr.opts
+        .check_impl_sync(trait_fns.iter().any(|trait_fn| trait_fn.originally_async))?;
+    check_instrument_fields(attr, &trait_fns)?;
     let trait_generics = generics_analyzer.into_trait_generics();
+    let (supertraits, mut impl_generics) = apply_self_bounds(attr, &trait_generics);
+    apply_where_clause(attr, &trait_dependency_mode, &mut impl_generics)?;
+    apply_intercept(attr, &trait_dependency_mode, &trait_fns, &mut impl_generics)?;
     let trait_def = TraitCodegen {
         opts: &attr.opts,
         crate_idents: &attr.crate_idents,
@@ -54,61 +176,295 @@ pub fn entrait_for_single_fn(attr: &EntraitFnAttr, input_fn: InputFn) -> syn::Re
         &attr.trait_visibility,
         &attr.trait_ident,
         &trait_generics,
-        &Supertraits::None,
+        &supertraits,
+        &[],
+        &[],
         &trait_fns,
         &fn_input_mode,
     )?;
 
-    let impl_block = fn_delegation_codegen::FnDelegationCodegen {
+    let impl_block = match link::deferred_span(attr)? {
+        Some(deferred_span) => {
+            link::gen_link_macro(attr, deferred_span, None, &trait_generics, &trait_fns)?
+        }
+        None => fn_delegation_codegen::FnDelegationCodegen {
+            opts: &attr.opts,
+            crate_idents: &attr.crate_idents,
+            trait_ref: &attr.trait_ident,
+            trait_span: attr.trait_ident.span(),
+            impl_indirection: generics::ImplIndirection::None,
+            trait_generics: &impl_generics,
+            fn_input_mode: &fn_input_mode,
+            trait_dependency_mode: &trait_dependency_mode,
+            sub_attributes: &sub_attributes,
+        }
+        .gen_impl_block(&[], &[], &trait_fns),
+    };
+    let opt_borrowed_deps_impl_block = match &trait_dependency_mode {
+        // Owned concrete deps can't be moved out of the application, so only the concrete type implements the trait
+        TraitDependencyMode::Concrete(_)
+            if generics::has_any_self_by_value(trait_fns.iter().map(|trait_fn| trait_fn.sig()))
+                .0 =>
+        {
+            None
+        }
+        TraitDependencyMode::Concrete(ty) => Some(gen_borrowed_deps_impl_block(
+            attr,
+            &fn_input_mode,
+            &impl_generics,
+            &sub_attributes,
+            &trait_fns,
+            ty,
+        )),
+        TraitDependencyMode::Generic(_) => None,
+    };
+    let opt_dyn_assertion = gen_opt_dyn_assertion(attr, &trait_generics);
+    let opt_tower_service = match &attr.tower_service {
+        Some(_) if !trait_generics.params.is_empty() => {
+            return Err(syn::Error::new(
+                attr.trait_ident.span(),
+                "`tower_service` is not supported for generic traits",
+            ))
+        }
+        Some(service_ident) => Some(tower_service::gen_tower_service(
+            service_ident,
+            &attr.trait_visibility,
+            &attr.trait_ident,
+            &trait_fns[0],
+            &attr.opts,
+            &attr.crate_idents,
+        )?),
+        None => None,
+    };
+    graph::record_dependencies(&attr.trait_ident, &trait_fns)?;
+    // The trait is still generated, so that the self dependency is the only error reported by the macro
+    let opt_self_dependency_error =
+        graph::check_self_dependency(&attr.trait_ident, &trait_fns, &attr.opts)
+            .err()
+            .map(|error| error.to_compile_error());
+
+    Ok(quote! {
+        #trait_def
+        #impl_block
+        #opt_borrowed_deps_impl_block
+        #opt_dyn_assertion
+        #opt_tower_service
+        #opt_self_dependency_error
+    })
+}
+
+/// The impl for `Impl<T>` of a trait with concrete deps, which borrows them from the `T`.
+///
+/// With the blanket `Borrow<T> for T`, an `Impl<Config>` keeps working,
+/// while an application type can provide several concrete deps by implementing `Borrow` for each of them.
+/// Trait objects like `&dyn Plugin` are borrowed with `AsRef<dyn Plugin>` instead.
+fn gen_borrowed_deps_impl_block(
+    attr: &EntraitFnAttr,
+    fn_input_mode: &FnInputMode,
+    impl_generics: &generics::TraitGenerics,
+    sub_attributes: &[SubAttribute],
+    trait_fns: &[analyze_generics::TraitFn],
+    concrete_ty: &syn::Type,
+) -> TokenStream {
+    let span = attr.trait_ident.span();
+    let core = &attr.crate_idents.core;
+    let generic_idents = GenericIdents::new(&attr.crate_idents, span);
+    let impl_t = &generic_idents.impl_t;
+
+    let (borrow_trait, _) = fn_delegation_codegen::concrete_deps_borrow(concrete_ty, core, span);
+
+    let mut impl_generics = impl_generics.clone();
+    impl_generics
+        .where_predicates
+        .push(syn::parse_quote_spanned! { span=>
+            #impl_t: #borrow_trait<#concrete_ty>
+        });
+
+    fn_delegation_codegen::FnDelegationCodegen {
         opts: &attr.opts,
         crate_idents: &attr.crate_idents,
         trait_ref: &attr.trait_ident,
-        trait_span: attr.trait_ident.span(),
+        trait_span: span,
         impl_indirection: generics::ImplIndirection::None,
-        trait_generics: &trait_generics,
-        fn_input_mode: &fn_input_mode,
-        trait_dependency_mode: &trait_dependency_mode,
-        sub_attributes: &sub_attributes,
+        trait_generics: &impl_generics,
+        fn_input_mode,
+        trait_dependency_mode: &TraitDependencyMode::Generic(generic_idents),
+        sub_attributes,
     }
-    .gen_impl_block(&trait_fns);
+    .gen_impl_block(&[], &[], trait_fns)
+}
 
-    let InputFn {
-        fn_attrs,
-        fn_vis,
-        fn_sig,
-        fn_body,
+pub fn entrait_for_mod(attr: &EntraitFnAttr, mut input_mod: InputMod) -> syn::Result<TokenStream> {
+    if let Some(SpanOpt(_, span)) = &attr.default_body {
+        return Err(syn::Error::new(
+            *span,
+            "`default_body` is only supported for single functions",
+        ));
+    }
+    if let Some(method) = &attr.method {
+        return Err(syn::Error::new(
+            method.span(),
+            "`method` is only supported for single functions. In a module, use `#[entrait(method = ..)]` on the function",
+        ));
+    }
+
+    let include_private = matches!(attr.include_private, Some(SpanOpt(true, _)));
+    let mod_fn_attrs = input_mod
+        .trait_fns_mut(include_private)
+        .into_iter()
+        .map(take_mod_fn_attr)
+        .collect::<syn::Result<Vec<_>>>()?;
+    if let Some(doc_hidden) = hide_fn_attr(attr) {
+        for (input_fn, mod_fn_attr) in input_mod
+            .trait_fns_mut(include_private)
+            .into_iter()
+            .zip(&mod_fn_attrs)
+        {
+            if !mod_fn_attr.skip {
+                input_fn.fn_attrs.push(doc_hidden.clone());
+            }
+        }
+    }
+    let input_fns: Vec<_> = input_mod
+        .trait_fns(include_private)
+        .into_iter()
+        .zip(mod_fn_attrs)
+        .filter(|(_, mod_fn_attr)| !mod_fn_attr.skip)
+        .collect();
+
+    let contains_async = input_fns
+        .iter()
+        .any(|((_, input_fn), _)| input_fn.fn_sig.asyncness.is_some());
+    let trait_items = with_non_send_variant(
+        attr,
+        contains_async,
+        gen_mod_trait_items(attr, &input_mod, &input_fns)?,
+        |non_send_attr| gen_mod_trait_items(non_send_attr, &input_mod, &input_fns),
+    )?;
+    let local_attr = local_variant(
+        attr,
+        &input_fns
+            .iter()
+            .map(|((_, input_fn), _)| *input_fn)
+            .collect::<Vec<_>>(),
+    )?;
+    let opt_local_trait_items = match &local_attr {
+        Some(local_attr) => Some(gen_mod_trait_items(local_attr, &input_mod, &input_fns)?),
+        None => None,
+    };
+    let also_traits = also_traits(attr, &input_fns)?;
+    let also_trait_items = also_traits
+        .iter()
+        .map(|(also_attr, also_fns)| {
+            with_non_send_variant(
+                also_attr,
+                contains_async,
+                gen_mod_trait_items(also_attr, &input_mod, also_fns)?,
+                |non_send_attr| gen_mod_trait_items(non_send_attr, &input_mod, also_fns),
+            )
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let also_trait_idents: Vec<_> = also_traits
+        .into_iter()
+        .map(|(also_attr, _)| also_attr.trait_ident)
+        .collect();
+
+    let InputMod {
+        attrs,
+        vis,
+        mod_token,
+        ident: mod_ident,
+        items,
         ..
-    } = input_fn;
+    } = input_mod;
 
-    let out = quote! {
-        #(#fn_attrs)* #fn_vis #fn_sig #fn_body
-        #trait_def
-        #impl_block
+    let trait_vis = &attr.trait_visibility;
+    let trait_ident = &attr.trait_ident;
+    let opt_local_trait_ident = local_attr.map(|local_attr| local_attr.trait_ident);
+    let opt_local_use = opt_local_trait_ident.as_ref().map(|local_trait_ident| {
+        quote! {
+            #trait_vis use #mod_ident::#local_trait_ident;
+        }
+    });
+    // The link macros are called through the module
+    let link_alias_reexports = match &attr.deferred {
+        Some(SpanOpt(true, _)) => std::iter::once(trait_ident)
+            .chain(&opt_local_trait_ident)
+            .chain(&also_trait_idents)
+            .map(|trait_ident| link::gen_mod_alias_reexport(&mod_ident, trait_vis, trait_ident))
+            .collect(),
+        _ => vec![],
     };
 
-    // println!("\n\nfn output: {out}");
+    Ok(quote! {
+        #(#attrs)*
+        #vis #mod_token #mod_ident {
+            #(#items)*
 
-    Ok(out)
+            #trait_items
+            #opt_local_trait_items
+            #(#also_trait_items)*
+        }
+
+        #trait_vis use #mod_ident::#trait_ident;
+        #opt_local_use
+        #(#trait_vis use #mod_ident::#also_trait_idents;)*
+        #(#link_alias_reexports)*
+    })
 }
 
-pub fn entrait_for_mod(attr: &EntraitFnAttr, input_mod: InputMod) -> syn::Result<TokenStream> {
+/// The trait of a module, its implementation and the bindings generated inside the module
+fn gen_mod_trait_items(
+    attr: &EntraitFnAttr,
+    input_mod: &InputMod,
+    input_fns: &[((Vec<syn::Ident>, &InputFn), ModFnAttr)],
+) -> syn::Result<TokenStream> {
     let fn_input_mode = FnInputMode::Module(&input_mod.ident);
+    strict::check_fns(
+        attr,
+        &input_fns
+            .iter()
+            .map(|((_, input_fn), _)| *input_fn)
+            .collect::<Vec<_>>(),
+    )?;
+    if let Some(service_ident) = &attr.tower_service {
+        return Err(syn::Error::new(
+            service_ident.span(),
+            "`tower_service` is only supported for single functions",
+        ));
+    }
+    let sub_attributes = analyze_sub_attributes(&input_mod.attrs);
     let mut generics_analyzer = analyze_generics::GenericsAnalyzer::new();
-    let trait_fns = input_mod
-        .items
+    let trait_fns = input_fns
         .iter()
-        .filter_map(ModItem::filter_pub_fn)
-        .map(|input_fn| {
-            TraitFnAnalyzer {
+        .map(|((fn_mod_path, input_fn), mod_fn_attr)| {
+            check_const_trait(attr, &input_fn.fn_sig)?;
+            let mut trait_fn = TraitFnAnalyzer {
                 impl_receiver_kind: signature::ImplReceiverKind::SelfRef,
-                trait_span: attr.trait_ident.span(),
                 crate_idents: &attr.crate_idents,
                 opts: &attr.opts,
             }
-            .analyze(input_fn.input_sig(), &mut generics_analyzer)
+            .analyze(input_fn.input_sig(), &mut generics_analyzer)?;
+            trait_fn.fn_mod_path = fn_mod_path.clone();
+            mirror_attrs(attr, &mut trait_fn, &input_fn.fn_attrs);
+            if let Some(method) = &mod_fn_attr.method {
+                trait_fn.rename(method.clone());
+            }
+            if let Some(memo) = &attr.memo {
+                attr.opts.check_std("`memo`", memo.1)?;
+                set_memo(&mut trait_fn, memo)?;
+            }
+            if let Some(SpanOpt(true, _)) = &attr.dyn_compatible {
+                dyn_compatible::make_dyn_compatible(
+                    &mut trait_fn,
+                    !contains_async_trait(&sub_attributes),
+                    &attr.crate_idents,
+                    &attr.opts,
+                )?;
+            }
+            Ok(trait_fn)
         })
         .collect::<syn::Result<Vec<_>>>()?;
-    let sub_attributes = analyze_sub_attributes(&input_mod.attrs);
 
     let trait_dependency_mode = detect_trait_dependency_mode(
         &fn_input_mode,
@@ -116,8 +472,14 @@ pub fn entrait_for_mod(attr: &EntraitFnAttr, input_mod: InputMod) -> syn::Result
         &attr.crate_idents,
         attr.trait_ident.span(),
     )?;
+    attr.opts
+        .check_impl_sync(trait_fns.iter().any(|trait_fn| trait_fn.originally_async))?;
+    check_instrument_fields(attr, &trait_fns)?;
 
     let trait_generics = generics_analyzer.into_trait_generics();
+    let (supertraits, mut impl_generics) = apply_self_bounds(attr, &trait_generics);
+    apply_where_clause(attr, &trait_dependency_mode, &mut impl_generics)?;
+    apply_intercept(attr, &trait_dependency_mode, &trait_fns, &mut impl_generics)?;
     let trait_def = TraitCodegen {
         opts: &attr.opts,
         crate_idents: &attr.crate_idents,
@@ -129,44 +491,665 @@ pub fn entrait_for_mod(attr: &EntraitFnAttr, input_mod: InputMod) -> syn::Result
         &attr.trait_visibility,
         &attr.trait_ident,
         &trait_generics,
-        &Supertraits::None,
+        &supertraits,
+        &[],
+        &[],
         &trait_fns,
         &fn_input_mode,
     )?;
-    let impl_block = fn_delegation_codegen::FnDelegationCodegen {
-        opts: &attr.opts,
-        crate_idents: &attr.crate_idents,
-        trait_ref: &attr.trait_ident,
-        trait_span: attr.trait_ident.span(),
-        impl_indirection: generics::ImplIndirection::None,
-        trait_generics: &trait_generics,
-        fn_input_mode: &fn_input_mode,
-        trait_dependency_mode: &trait_dependency_mode,
-        sub_attributes: &sub_attributes,
+    let impl_block = match link::deferred_span(attr)? {
+        Some(deferred_span) => link::gen_link_macro(
+            attr,
+            deferred_span,
+            Some(&input_mod.ident),
+            &trait_generics,
+            &trait_fns,
+        )?,
+        None => fn_delegation_codegen::FnDelegationCodegen {
+            opts: &attr.opts,
+            crate_idents: &attr.crate_idents,
+            trait_ref: &attr.trait_ident,
+            trait_span: attr.trait_ident.span(),
+            impl_indirection: generics::ImplIndirection::None,
+            trait_generics: &impl_generics,
+            fn_input_mode: &fn_input_mode,
+            trait_dependency_mode: &trait_dependency_mode,
+            sub_attributes: &sub_attributes,
+        }
+        .gen_impl_block(&[], &[], &trait_fns),
+    };
+    let opt_dyn_assertion = gen_opt_dyn_assertion(attr, &trait_generics);
+    let opt_wasm_bindgen_impl = attr
+        .wasm_bindgen
+        .as_ref()
+        .map(|wrapper_ident| {
+            wasm_bindgen::gen_wasm_bindgen_impl(wrapper_ident, &trait_fns, &attr.crate_idents)
+        })
+        .transpose()?;
+    let opt_pyo3_impl = attr
+        .pyo3
+        .as_ref()
+        .map(|wrapper_ident| pyo3::gen_pyo3_impl(wrapper_ident, &trait_fns, &attr.crate_idents))
+        .transpose()?;
+    let opt_ffi_fns = attr
+        .ffi
+        .as_ref()
+        .map(|app_ident| {
+            ffi::gen_ffi_fns(app_ident, &input_mod.ident, &trait_fns, &attr.crate_idents)
+        })
+        .transpose()?;
+    let opt_tonic_impl = match &attr.tonic {
+        Some(service_trait) if !trait_generics.params.is_empty() => {
+            return Err(syn::Error::new_spanned(
+                service_trait,
+                "`tonic` is not supported for generic traits",
+            ))
+        }
+        Some(service_trait) => Some(tonic::gen_tonic_impl(
+            service_trait,
+            &attr.trait_ident,
+            &trait_fns,
+            &attr.opts,
+            &attr.crate_idents,
+        )?),
+        None => None,
+    };
+
+    graph::record_dependencies(&attr.trait_ident, &trait_fns)?;
+    let opt_self_dependency_error =
+        graph::check_self_dependency(&attr.trait_ident, &trait_fns, &attr.opts)
+            .err()
+            .map(|error| error.to_compile_error());
+
+    Ok(quote! {
+        #trait_def
+        #impl_block
+        #opt_dyn_assertion
+        #opt_wasm_bindgen_impl
+        #opt_pyo3_impl
+        #opt_ffi_fns
+        #opt_tonic_impl
+        #opt_self_dependency_error
+    })
+}
+
+/// The trait items with `Send` futures for the targets of `send_cfg`,
+/// next to a `?Send` variant of them for the other targets, like `wasm32`.
+///
+/// The variant has no `tonic` service, which requires `Send` futures, and no `strict` checks.
+fn with_non_send_variant(
+    attr: &EntraitFnAttr,
+    contains_async: bool,
+    trait_items: TokenStream,
+    gen_trait_items: impl FnOnce(&EntraitFnAttr) -> syn::Result<TokenStream>,
+) -> syn::Result<TokenStream> {
+    let non_send_opts = match attr.opts.non_send_variant(contains_async) {
+        Some(non_send_opts) => non_send_opts,
+        None => return Ok(trait_items),
+    };
+
+    let mut non_send_attr = attr.clone();
+    non_send_attr.opts = non_send_opts;
+    non_send_attr.tonic = None;
+    non_send_attr.strict = None;
+
+    send_cfg::gate(trait_items, gen_trait_items(&non_send_attr)?, &attr.opts)
+}
+
+/// The attribute of the `Local{Trait}` variant generated with `local`,
+/// which has `?Send` futures and no `Sync` bound on the `T` of `Impl<T>`.
+///
+/// Mocks, bindings and `strict` checks are only generated for the `Send` trait.
+/// When `local` is given with `ENTRAIT_DEFAULTS`, traits without `async` functions or with `?Send` get no variant.
+fn local_variant(
+    attr: &EntraitFnAttr,
+    input_fns: &[&InputFn],
+) -> syn::Result<Option<EntraitFnAttr>> {
+    let (local, span) = match &attr.opts.local {
+        Some(SpanOpt(local, span)) if local.enabled => (local, *span),
+        _ => return Ok(None),
+    };
+    if let Some(SpanOpt(FutureSend(false), send_span)) = attr.opts.future_send {
+        if local.by_default {
+            return Ok(None);
+        }
+        return Err(syn::Error::new(
+            send_span,
+            "`?Send` can't be combined with `local`, which generates a `?Send` variant next to the `Send` trait",
+        ));
+    }
+    if input_fns
+        .iter()
+        .all(|input_fn| input_fn.fn_sig.asyncness.is_none())
+    {
+        if local.by_default {
+            return Ok(None);
+        }
+        return Err(syn::Error::new(
+            span,
+            "`local` has no effect without `async` functions",
+        ));
     }
-    .gen_impl_block(&trait_fns);
 
-    let InputMod {
-        attrs,
-        vis,
-        mod_token,
-        ident: mod_ident,
-        items,
-        ..
-    } = input_mod;
+    let mut local_attr = attr.clone();
+    local_attr.trait_ident = match &local.ident {
+        Some(ident) => ident.clone(),
+        None => quote::format_ident!("Local{}", attr.trait_ident, span = attr.trait_ident.span()),
+    };
+    local_attr.opts.future_send = Some(SpanOpt(FutureSend(false), span));
+    local_attr.opts.impl_sync = Some(SpanOpt(ImplSync(false), span));
+    local_attr.opts.mock_api = None;
+    local_attr.opts.unimock = None;
+    local_attr.opts.mockall = None;
+    local_attr.opts.mry = None;
+    local_attr.opts.faux = None;
+    local_attr.opts.stub = None;
+    local_attr.opts.record = None;
+    local_attr.opts.double = None;
+    local_attr.opts.local = None;
+    local_attr.wasm_bindgen = None;
+    local_attr.pyo3 = None;
+    local_attr.ffi = None;
+    local_attr.tower_service = None;
+    local_attr.tonic = None;
+    local_attr.strict = None;
 
-    let trait_vis = &attr.trait_visibility;
-    let trait_ident = &attr.trait_ident;
+    Ok(Some(local_attr))
+}
 
-    Ok(quote! {
-        #(#attrs)*
-        #vis #mod_token #mod_ident {
-            #(#items)*
+/// The attribute of a trait named by `also`, and the functions of the module in it
+type AlsoTrait<F> = (EntraitFnAttr, Vec<(F, ModFnAttr)>);
+
+/// The traits named by `#[entrait(also = Trait)]` on the functions of a module or impl block,
+/// each with the attribute of the trait and the functions naming it.
+///
+/// They are generated like the trait of the module, but without the bindings and the `local` variant.
+/// Their mock API is named like the one of the module trait.
+fn also_traits<F: Clone>(
+    attr: &EntraitFnAttr,
+    input_fns: &[(F, ModFnAttr)],
+) -> syn::Result<Vec<AlsoTrait<F>>> {
+    let mut also_traits: Vec<AlsoTrait<F>> = vec![];
 
-            #trait_def
-            #impl_block
+    for (input_fn, mod_fn_attr) in input_fns {
+        for also_ident in &mod_fn_attr.also {
+            if *also_ident == attr.trait_ident {
+                return Err(syn::Error::new(
+                    also_ident.span(),
+                    format!("`also` must name another trait than `{also_ident}`"),
+                ));
+            }
+
+            let also_fn = (input_fn.clone(), mod_fn_attr.clone());
+            match also_traits
+                .iter_mut()
+                .find(|(also_attr, _)| also_attr.trait_ident == *also_ident)
+            {
+                Some((_, also_fns)) => also_fns.push(also_fn),
+                None => {
+                    let mut also_attr = attr.clone();
+                    also_attr.trait_ident = also_ident.clone();
+                    also_attr.opts.local = None;
+                    also_attr.opts.double = None;
+                    also_attr.opts.trait_doc = None;
+                    also_attr.wasm_bindgen = None;
+                    also_attr.pyo3 = None;
+                    also_attr.ffi = None;
+                    also_attr.tower_service = None;
+                    also_attr.tonic = None;
+                    if let Some(MockApiIdent(mock_api)) = &attr.opts.mock_api {
+                        // Named like the mock API of the module trait, e.g. `FooMock` and `BarMock`
+                        let trait_name = attr.trait_ident.to_string();
+                        let mock_api_name = mock_api.to_string();
+                        if !mock_api_name.contains(&trait_name) {
+                            return Err(syn::Error::new(
+                                mock_api.span(),
+                                format!("`mock_api` must contain the trait name `{trait_name}`, so that the mock APIs of the `also` traits can be named after theirs"),
+                            ));
+                        }
+                        also_attr.opts.mock_api = Some(MockApiIdent(syn::Ident::new(
+                            &mock_api_name.replacen(&trait_name, &also_ident.to_string(), 1),
+                            also_ident.span(),
+                        )));
+                    }
+
+                    also_traits.push((also_attr, vec![also_fn]));
+                }
+            }
         }
+    }
 
-        #trait_vis use #mod_ident::#trait_ident;
-    })
+    Ok(also_traits)
+}
+
+/// `#[doc(hidden)]` for the entraited functions, with `hide_fn`
+fn hide_fn_attr(attr: &EntraitFnAttr) -> Option<syn::Attribute> {
+    match &attr.hide_fn {
+        Some(SpanOpt(true, span)) => Some(syn::parse_quote_spanned! { *span=> #[doc(hidden)] }),
+        _ => None,
+    }
+}
+
+/// Mirror the attributes of an entraited fn on its trait method.
+///
+/// By default, these are the docs, `#[cfg]`, `#[must_use]` and lint attributes. `forward_attrs` lists them explicitly instead.
+/// Attributes other than these are only forwarded to the delegating method, since they may need a body, like `#[tracing::instrument]`.
+///
+/// Only doc comments are mirrored, not `#[doc(..)]` attributes like the `#[doc(hidden)]` of `hide_fn`,
+/// and with `minimal_codegen`, only when `forward_attrs` lists them.
+/// `#[expect]` becomes `#[allow]`, since the expected lint may not fire for the generated items.
+fn mirror_attrs(
+    attr: &EntraitFnAttr,
+    trait_fn: &mut analyze_generics::TraitFn,
+    fn_attrs: &[syn::Attribute],
+) {
+    let is_trait_attr = |fn_attr: &syn::Attribute| {
+        (fn_attr.path().is_ident("doc") && matches!(fn_attr.meta, syn::Meta::NameValue(_)))
+            || fn_attr.path().is_ident("cfg")
+            || fn_attr.path().is_ident("must_use")
+            || analyze_generics::is_lint_attr(fn_attr)
+    };
+
+    for fn_attr in fn_attrs {
+        let forwarded = match &attr.forward_attrs {
+            Some(SpanOpt(paths, _)) => {
+                let fn_attr_path = fn_attr.path().to_token_stream().to_string();
+                paths
+                    .iter()
+                    .any(|path| path.to_token_stream().to_string() == fn_attr_path)
+                    && (!fn_attr.path().is_ident("doc") || is_trait_attr(fn_attr))
+            }
+            None => {
+                is_trait_attr(fn_attr)
+                    && !(attr.opts.minimal_codegen_value() && fn_attr.path().is_ident("doc"))
+            }
+        };
+        if !forwarded {
+            continue;
+        }
+
+        let mut fn_attr = fn_attr.clone();
+        if let syn::Meta::List(list) = &mut fn_attr.meta {
+            if let Some(expect) = list.path.get_ident().filter(|ident| *ident == "expect") {
+                list.path = syn::Ident::new("allow", expect.span()).into();
+            }
+        }
+        if is_trait_attr(&fn_attr) {
+            trait_fn.attrs.push(fn_attr);
+        } else {
+            trait_fn.impl_attrs.push(fn_attr);
+        }
+    }
+}
+
+/// Options of a function inside an entraited module
+#[derive(Clone, Default)]
+struct ModFnAttr {
+    /// The name of the trait method
+    method: Option<syn::Ident>,
+    /// Whether the function is left out of the trait
+    skip: bool,
+    /// Other traits the function is a method of, next to the trait of the module
+    also: Vec<syn::Ident>,
+}
+
+/// Remove an `#[entrait(..)]` attribute from a function inside an entraited module,
+/// returning its options.
+fn take_mod_fn_attr(input_fn: &mut InputFn) -> syn::Result<ModFnAttr> {
+    let mut mod_fn_attr = ModFnAttr::default();
+    let mut result = Ok(());
+
+    input_fn.fn_attrs.retain(|attr| {
+        let is_entrait = attr
+            .path()
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "entrait");
+        if !is_entrait || result.is_err() {
+            return true;
+        }
+
+        result = attr
+            .parse_args_with(Punctuated::<EntraitOpt, syn::token::Comma>::parse_terminated)
+            .and_then(|entrait_opts| {
+                for entrait_opt in entrait_opts {
+                    match entrait_opt {
+                        EntraitOpt::Method(ident) => mod_fn_attr.method = Some(ident),
+                        EntraitOpt::Skip(SpanOpt(skip, _)) => mod_fn_attr.skip = skip,
+                        EntraitOpt::Also(ident) => mod_fn_attr.also.push(ident),
+                        entrait_opt => return Err(syn::Error::new(
+                            entrait_opt.span(),
+                            "Only `method`, `skip` and `also` are supported for functions inside an entraited module or impl block",
+                        )),
+                    }
+                }
+                Ok(())
+            });
+
+        false
+    });
+
+    result.map(|_| mod_fn_attr)
+}
+
+/// `supertraits` (or `self_bounds`) become supertraits of the generated trait,
+/// and the impl block needs `Self` to satisfy them.
+fn apply_self_bounds(
+    attr: &EntraitFnAttr,
+    trait_generics: &generics::TraitGenerics,
+) -> (Supertraits, generics::TraitGenerics) {
+    let mut impl_generics = trait_generics.clone();
+
+    match &attr.self_bounds {
+        Some(SpanOpt(bounds, span)) => {
+            impl_generics
+                .where_predicates
+                .push(syn::parse_quote_spanned! { *span=> Self: #bounds });
+
+            (
+                Supertraits::Some {
+                    colon_token: syn::token::Colon(*span),
+                    bounds: bounds.clone(),
+                },
+                impl_generics,
+            )
+        }
+        None => (Supertraits::None, impl_generics),
+    }
+}
+
+/// `where_clause` predicates only go on the impl block, the trait itself is unaffected.
+fn apply_where_clause(
+    attr: &EntraitFnAttr,
+    trait_dependency_mode: &TraitDependencyMode,
+    impl_generics: &mut generics::TraitGenerics,
+) -> syn::Result<()> {
+    if let Some(SpanOpt(where_clause, _)) = &attr.where_clause {
+        let impl_t = match trait_dependency_mode {
+            TraitDependencyMode::Generic(idents) => Some(&idents.impl_t),
+            TraitDependencyMode::Concrete(_) => None,
+        };
+        impl_generics
+            .where_predicates
+            .extend(impl_where_predicates(where_clause, impl_t)?);
+    }
+    Ok(())
+}
+
+/// The hooks of `intercept` are called on the `T` of `Impl<T>`, borrowed from the `&self` receiver.
+fn apply_intercept(
+    attr: &EntraitFnAttr,
+    trait_dependency_mode: &TraitDependencyMode,
+    trait_fns: &[analyze_generics::TraitFn],
+    impl_generics: &mut generics::TraitGenerics,
+) -> syn::Result<()> {
+    let span = match attr.opts.intercept {
+        Some(SpanOpt(true, span)) => span,
+        _ => return Ok(()),
+    };
+    let impl_t = match trait_dependency_mode {
+        TraitDependencyMode::Generic(idents) => &idents.impl_t,
+        TraitDependencyMode::Concrete(_) => {
+            return Err(syn::Error::new(
+                span,
+                "`intercept` requires generic dependencies, there is no `Impl<T>` to intercept",
+            ))
+        }
+    };
+    if let generics::TakesSelfByValue(true) =
+        generics::has_any_self_by_value(trait_fns.iter().map(|trait_fn| trait_fn.sig()))
+    {
+        return Err(syn::Error::new(
+            span,
+            "`intercept` can't be used with dependencies taken by value",
+        ));
+    }
+
+    let entrait = &attr.crate_idents.entrait;
+    impl_generics
+        .where_predicates
+        .push(syn::parse_quote_spanned! { span=>
+            #impl_t: ::#entrait::intercept::CallInterceptor
+        });
+    Ok(())
+}
+
+fn gen_opt_dyn_assertion(
+    attr: &EntraitFnAttr,
+    trait_generics: &generics::TraitGenerics,
+) -> Option<TokenStream> {
+    match &attr.dyn_compatible {
+        Some(SpanOpt(true, _)) => Some(dyn_compatible::gen_assertion(
+            &attr.trait_ident,
+            trait_generics,
+        )),
+        _ => None,
+    }
+}
+
+/// The trait method's default body.
+///
+/// A fallback function gets called with the receiver and all the arguments.
+/// A value is returned (or the future's output, for `async` functions).
+fn gen_default_body(
+    SpanOpt(default_body, span): &SpanOpt<DefaultBody>,
+    trait_fn: &analyze_generics::TraitFn,
+    crate_idents: &CrateIdents,
+) -> TokenStream {
+    let span = *span;
+
+    let expr = match default_body {
+        DefaultBody::Fallback(fallback_fn) => {
+            let arguments = trait_fn.sig().inputs.iter().map(|arg| match arg {
+                syn::FnArg::Receiver(receiver) => receiver.self_token.to_token_stream(),
+                syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                    syn::Pat::Ident(pat_ident) => pat_ident.ident.to_token_stream(),
+                    _ => {
+                        panic!("Found a non-ident pattern, this should be handled in signature.rs")
+                    }
+                },
+            });
+
+            return quote_spanned! {span=>
+                {
+                    #fallback_fn(#(#arguments),*)
+                }
+            };
+        }
+        DefaultBody::Value(expr) => expr,
+    };
+
+    let expr = match &trait_fn.boxed_output {
+        Some(boxed_output) => gen_boxed_output(boxed_output, expr, &crate_idents.entrait, span),
+        None => expr.to_token_stream(),
+    };
+
+    if trait_fn.boxed_future {
+        let entrait = &crate_idents.entrait;
+        quote_spanned! {span=>
+            {
+                ::#entrait::__alloc::Box::pin(async { #expr })
+            }
+        }
+    } else if trait_fn.originally_async {
+        quote_spanned! {span=>
+            {
+                async { #expr }
+            }
+        }
+    } else {
+        quote_spanned! {span=>
+            {
+                #expr
+            }
+        }
+    }
+}
+
+/// Every span field of `instrument` must be a parameter of some function
+fn check_instrument_fields(
+    attr: &EntraitFnAttr,
+    trait_fns: &[analyze_generics::TraitFn],
+) -> syn::Result<()> {
+    if let Some(SpanOpt(fields, _)) = &attr.opts.instrument {
+        for field in fields {
+            if !trait_fns
+                .iter()
+                .any(|trait_fn| fn_param_idents(trait_fn.sig()).any(|ident| ident == field))
+            {
+                return Err(syn::Error::new(
+                    field.span(),
+                    format!("`{field}` is not a parameter of any entraited function"),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The delegating methods of a `const` impl can only call `const fn`s
+fn check_const_trait(attr: &EntraitFnAttr, fn_sig: &syn::Signature) -> syn::Result<()> {
+    match attr.opts.const_trait {
+        Some(SpanOpt(true, span)) if fn_sig.constness.is_none() => Err(syn::Error::new(
+            span,
+            format!(
+                "`const_trait` requires `{}` to be a `const fn`",
+                fn_sig.ident
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Memoized outputs are keyed by owned copies of the arguments
+fn set_memo(
+    trait_fn: &mut analyze_generics::TraitFn,
+    SpanOpt(memo, span): &SpanOpt<bool>,
+) -> syn::Result<()> {
+    if !memo {
+        return Ok(());
+    }
+    if trait_fn.originally_async {
+        return Err(syn::Error::new(
+            *span,
+            "`memo` is not supported for async functions",
+        ));
+    }
+    if let Some(unsafety) = &trait_fn.sig().unsafety {
+        return Err(syn::Error::new(
+            unsafety.span,
+            "`memo` is not supported for unsafe functions",
+        ));
+    }
+
+    let sig = trait_fn.sig();
+    if let Some(syn::FnArg::Receiver(receiver)) = sig.inputs.first() {
+        if receiver.reference.is_none() {
+            return Err(syn::Error::new(
+                receiver.self_token.span,
+                "`memo` is not supported for dependencies taken by value",
+            ));
+        }
+    }
+    if let Some(param) = sig
+        .generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, syn::GenericParam::Lifetime(_)))
+    {
+        return Err(syn::Error::new_spanned(
+            param,
+            "`memo` is not supported for generic functions",
+        ));
+    }
+
+    for arg in &sig.inputs {
+        if let syn::FnArg::Typed(pat_type) = arg {
+            match pat_type.ty.as_ref() {
+                syn::Type::ImplTrait(_) => {
+                    return Err(syn::Error::new_spanned(
+                        &pat_type.ty,
+                        "`impl Trait` parameters are not supported by `memo`",
+                    ));
+                }
+                syn::Type::Reference(syn::TypeReference {
+                    mutability: Some(_),
+                    ..
+                }) => {
+                    return Err(syn::Error::new_spanned(
+                        &pat_type.ty,
+                        "`&mut` parameters are not supported by `memo`",
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    trait_fn.memo = true;
+    Ok(())
+}
+
+/// Language bindings can't export generic or unsafe functions
+fn reject_unexportable(sig: &syn::Signature, option: &str) -> syn::Result<()> {
+    if let Some(unsafety) = &sig.unsafety {
+        return Err(syn::Error::new(
+            unsafety.span,
+            format!("Unsafe functions can't be exported with `{option}`"),
+        ));
+    }
+
+    if let Some(param) = sig
+        .generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, syn::GenericParam::Lifetime(_)))
+    {
+        return Err(syn::Error::new_spanned(
+            param,
+            format!("Generic functions can't be exported with `{option}`"),
+        ));
+    }
+
+    for arg in &sig.inputs {
+        if let syn::FnArg::Typed(pat_type) = arg {
+            if let syn::Type::ImplTrait(impl_trait) = pat_type.ty.as_ref() {
+                return Err(syn::Error::new_spanned(
+                    impl_trait,
+                    format!("`impl Trait` parameters can't be exported with `{option}`"),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The `T` and `E` of a `Result<T, E>` output
+fn result_args(ty: &syn::Type) -> Option<(&syn::Type, &syn::Type)> {
+    let segment = match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.last()?,
+        _ => return None,
+    };
+    let args = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if segment.ident == "Result" => args,
+        _ => return None,
+    };
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+    match (types.next(), types.next(), types.next()) {
+        (Some(ok), Some(err), None) => Some((ok, err)),
+        _ => None,
+    }
+}
+
+/// `str` and slices, which language bindings have to convert into their owned counterparts
+fn is_unsized(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Slice(_) => true,
+        syn::Type::Path(type_path) => type_path.qself.is_none() && type_path.path.is_ident("str"),
+        _ => false,
+    }
 }
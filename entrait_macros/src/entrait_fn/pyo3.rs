@@ -0,0 +1,130 @@
+//! Python bindings for entraited modules.
+//!
+//! With `pyo3 = PyApp`, each trait method becomes a method in a `#[pymethods] impl PyApp` block.
+//! `PyApp` is a user-defined `#[pyclass]` tuple struct (declared next to the module) whose field implements the trait,
+//! e.g. `Arc<Impl<App>>`. Arguments and return values are converted by pyo3 itself:
+//!
+//! ```text
+//! fn get_user(&self, id: u32) -> User {
+//!     self.0.get_user(id)
+//! }
+//! ```
+//!
+//! `async` methods return a Python awaitable, driven on the tokio runtime of `pyo3-async-runtimes`
+//! by a clone of the field:
+//!
+//! ```text
+//! fn fetch_user<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+//!     let this = self.0.clone();
+//!     pyo3_async_runtimes::tokio::future_into_py(py, async move {
+//!         Ok(this.fetch_user(&name).await)
+//!     })
+//! }
+//! ```
+
+use super::{is_unsized, reject_unexportable};
+use crate::analyze_generics::TraitFn;
+use crate::idents::CrateIdents;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+
+pub fn gen_pyo3_impl(
+    wrapper_ident: &syn::Ident,
+    trait_fns: &[TraitFn],
+    crate_idents: &CrateIdents,
+) -> syn::Result<TokenStream> {
+    let entrait = &crate_idents.entrait;
+    let core = &crate_idents.core;
+    let span = wrapper_ident.span();
+
+    let pyo3 = quote_spanned! {span=> ::#entrait::__pyo3 };
+    let crate_path = syn::LitStr::new(&format!("::{entrait}::__pyo3"), span);
+
+    // Local variables should not collide with parameter names
+    let this = syn::Ident::new("this", Span::mixed_site());
+    let py = syn::Ident::new("py", Span::mixed_site());
+
+    let mut fn_items = vec![];
+
+    for trait_fn in trait_fns {
+        let sig = trait_fn.sig();
+        let fn_ident = &sig.ident;
+        let cfg_attrs: Vec<_> = trait_fn.cfg_attrs().collect();
+
+        reject_unexportable(sig, "pyo3")?;
+
+        let mut params = vec![];
+        let mut arguments = vec![];
+
+        for arg in &sig.inputs {
+            let pat_type = match arg {
+                syn::FnArg::Receiver(_) => continue,
+                syn::FnArg::Typed(pat_type) => pat_type,
+            };
+            let ident = match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+                _ => panic!("Found a non-ident pattern, this should be handled in signature.rs"),
+            };
+
+            match pat_type.ty.as_ref() {
+                // The future must be 'static, so it can't borrow from the arguments
+                syn::Type::Reference(type_reference) if trait_fn.originally_async => {
+                    let elem = &type_reference.elem;
+                    let mutability = &type_reference.mutability;
+                    if is_unsized(elem) {
+                        params.push(quote! {
+                            #mutability #ident: <#elem as ::#entrait::__alloc::ToOwned>::Owned
+                        });
+                    } else {
+                        params.push(quote! { #mutability #ident: #elem });
+                    }
+                    arguments.push(quote! { & #mutability #ident });
+                }
+                ty => {
+                    params.push(quote! { #ident: #ty });
+                    arguments.push(quote! { #ident });
+                }
+            }
+        }
+
+        if trait_fn.originally_async {
+            fn_items.push(quote! {
+                #(#cfg_attrs)*
+                fn #fn_ident<'py>(
+                    &self,
+                    #py: #pyo3::Python<'py>,
+                    #(#params),*
+                ) -> #pyo3::PyResult<#pyo3::Bound<'py, #pyo3::PyAny>> {
+                    let #this = ::#core::clone::Clone::clone(&self.0);
+                    ::#entrait::__pyo3_async_runtimes::tokio::future_into_py(#py, async move {
+                        #pyo3::PyResult::Ok(#this.#fn_ident(#(#arguments),*).await)
+                    })
+                }
+            });
+        } else {
+            let output = &sig.output;
+
+            fn_items.push(quote! {
+                #(#cfg_attrs)*
+                fn #fn_ident(&self, #(#params),*) #output {
+                    self.0.#fn_ident(#(#arguments),*)
+                }
+            });
+        }
+    }
+
+    // pymethods requires a plain identifier as the self type,
+    // so the wrapper is imported from outside the module in an anonymous scope.
+    Ok(quote_spanned! {span=>
+        const _: () = {
+            use super::#wrapper_ident;
+
+            #[#pyo3::pymethods]
+            #[pyo3(crate = #crate_path)]
+            impl #wrapper_ident {
+                #(#fn_items)*
+            }
+        };
+    })
+}
@@ -0,0 +1,196 @@
+//! Checks for suspicious, but valid, entrait functions, enabled with the `strict` option.
+//!
+//! * A deps parameter which is never used by the function body. Such a function should use `no_deps`.
+//! * Generic parameters not used by any parameter or the output, which callers can't infer.
+//! * `Impl<T>` as a concrete deps type. Such a function is not a leaf, as it depends on traits implemented for `Impl<T>`.
+//! * `?Send` without any `async` functions, or combined with `Send` bounds on the deps or `Self`.
+
+use super::input_attr::EntraitFnAttr;
+use crate::analyze_generics::takes_no_deps;
+use crate::input::InputFn;
+use crate::opt::{FutureSend, SpanOpt};
+use crate::signature::ungroup_type;
+use crate::token_util::mentions_ident;
+
+use quote::ToTokens;
+
+pub fn check_fns(attr: &EntraitFnAttr, input_fns: &[&InputFn]) -> syn::Result<()> {
+    match attr.strict {
+        Some(SpanOpt(true, _)) => {}
+        _ => return Ok(()),
+    }
+
+    for input_fn in input_fns {
+        let no_deps = takes_no_deps(&input_fn.fn_sig, &attr.opts);
+        if !no_deps {
+            check_deps(input_fn)?;
+        }
+        check_generic_params(input_fn, no_deps)?;
+    }
+
+    check_future_send(attr, input_fns)
+}
+
+fn check_deps(input_fn: &InputFn) -> syn::Result<()> {
+    let pat_type = match input_fn.fn_sig.inputs.first() {
+        Some(syn::FnArg::Typed(pat_type)) => pat_type,
+        _ => return Ok(()),
+    };
+
+    let unused = match pat_type.pat.as_ref() {
+        syn::Pat::Wild(_) => true,
+        syn::Pat::Ident(pat_ident) => !mentions_ident(input_fn.fn_body.clone(), &pat_ident.ident),
+        _ => false,
+    };
+    if unused {
+        return Err(syn::Error::new_spanned(
+            &pat_type.pat,
+            "The deps parameter is never used. Pass `no_deps` to entrait to remove it from the trait",
+        ));
+    }
+
+    if let syn::Type::Path(type_path) = deref_type(&pat_type.ty) {
+        if type_path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "Impl")
+        {
+            return Err(syn::Error::new_spanned(
+                type_path,
+                "`Impl<T>` is not a leaf dependency, as it depends on the traits implemented for it. Use generic deps like `&impl Trait` instead",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_generic_params(input_fn: &InputFn, no_deps: bool) -> syn::Result<()> {
+    let sig = &input_fn.fn_sig;
+    let mut typed_inputs = sig.inputs.iter().filter_map(|arg| match arg {
+        syn::FnArg::Typed(pat_type) => Some(pat_type.ty.as_ref()),
+        syn::FnArg::Receiver(_) => None,
+    });
+    let deps_ty = if no_deps { None } else { typed_inputs.next() };
+
+    for type_param in sig.generics.type_params() {
+        let ident = &type_param.ident;
+        let is_deps = deps_ty.map_or(false, |ty| mentions_ident(ty.to_token_stream(), ident));
+        let is_used = typed_inputs
+            .clone()
+            .any(|ty| mentions_ident(ty.to_token_stream(), ident))
+            || mentions_ident(sig.output.to_token_stream(), ident);
+
+        if !is_deps && !is_used {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "The generic parameter `{ident}` is not used by any parameter or the output"
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_future_send(attr: &EntraitFnAttr, input_fns: &[&InputFn]) -> syn::Result<()> {
+    let span = match attr.opts.future_send {
+        Some(SpanOpt(FutureSend(false), span)) => span,
+        _ => return Ok(()),
+    };
+
+    if input_fns
+        .iter()
+        .all(|input_fn| input_fn.fn_sig.asyncness.is_none())
+    {
+        return Err(syn::Error::new(
+            span,
+            "`?Send` has no effect without `async` functions",
+        ));
+    }
+
+    let send = syn::Ident::new("Send", span);
+    let self_send = attr
+        .self_bounds
+        .as_ref()
+        .map_or(false, |SpanOpt(bounds, _)| {
+            mentions_ident(bounds.to_token_stream(), &send)
+        });
+    let deps_send = input_fns.iter().any(|input_fn| {
+        !takes_no_deps(&input_fn.fn_sig, &attr.opts) && deps_bounds_mention(input_fn, &send)
+    });
+
+    if self_send || deps_send {
+        return Err(syn::Error::new(
+            span,
+            "`?Send` is combined with `Send` bounds on the dependencies, which are usually there for spawning the futures",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether the bounds of the deps parameter, inline or on its generic parameter, mention the ident
+fn deps_bounds_mention(input_fn: &InputFn, ident: &syn::Ident) -> bool {
+    let deps_ty = match input_fn.fn_sig.inputs.first() {
+        Some(syn::FnArg::Typed(pat_type)) => deref_type(&pat_type.ty),
+        _ => return false,
+    };
+
+    match deps_ty {
+        syn::Type::ImplTrait(impl_trait) => {
+            mentions_ident(impl_trait.bounds.to_token_stream(), ident)
+        }
+        syn::Type::Path(type_path) if type_path.qself.is_none() => {
+            let generics = &input_fn.fn_sig.generics;
+            let deps_param = generics
+                .type_params()
+                .find(|type_param| type_path.path.is_ident(&type_param.ident));
+
+            match deps_param {
+                Some(deps_param) => {
+                    mentions_ident(deps_param.bounds.to_token_stream(), ident)
+                        || generics
+                            .where_clause
+                            .as_ref()
+                            .map_or(false, |where_clause| {
+                                where_clause
+                                    .predicates
+                                    .iter()
+                                    .any(|predicate| match predicate {
+                                        syn::WherePredicate::Type(predicate_type) => {
+                                            is_path_of(
+                                                &predicate_type.bounded_ty,
+                                                &deps_param.ident,
+                                            ) && mentions_ident(
+                                                predicate_type.bounds.to_token_stream(),
+                                                ident,
+                                            )
+                                        }
+                                        _ => false,
+                                    })
+                            })
+                }
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn deref_type(ty: &syn::Type) -> &syn::Type {
+    match ungroup_type(ty) {
+        syn::Type::Reference(type_reference) => deref_type(&type_reference.elem),
+        syn::Type::Paren(paren) => deref_type(&paren.elem),
+        ty => ty,
+    }
+}
+
+fn is_path_of(ty: &syn::Type, ident: &syn::Ident) -> bool {
+    match ungroup_type(ty) {
+        syn::Type::Path(type_path) => type_path.qself.is_none() && type_path.path.is_ident(ident),
+        _ => false,
+    }
+}
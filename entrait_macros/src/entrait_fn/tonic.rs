@@ -0,0 +1,191 @@
+//! gRPC services for entraited modules.
+//!
+//! With `tonic = greeter_server::Greeter`, the tonic-generated service trait is implemented for `Impl<T>`,
+//! with each RPC delegating to the module trait method of the same name:
+//!
+//! ```text
+//! #[tonic::async_trait]
+//! impl<T: Send + Sync + 'static> super::greeter_server::Greeter for Impl<T>
+//! where
+//!     Self: Greeting,
+//! {
+//!     async fn say_hello(
+//!         &self,
+//!         request: tonic::Request<HelloRequest>,
+//!     ) -> Result<tonic::Response<HelloReply>, tonic::Status> {
+//!         match <Self as Greeting>::say_hello(self, request.into_inner()).await {
+//!             Ok(output) => Ok(tonic::Response::new(output)),
+//!             Err(error) => Err(Into::into(error)),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Functions can take the whole `tonic::Request<M>` or return a `tonic::Response<M>`, to work with the metadata.
+//! Their error type only has to convert into `tonic::Status`, and outputs that aren't a `Result` always succeed.
+
+use super::{reject_unexportable, result_args};
+use crate::analyze_generics::TraitFn;
+use crate::idents::CrateIdents;
+use crate::opt::{FutureSend, Opts};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+
+pub fn gen_tonic_impl(
+    service_trait: &syn::Path,
+    trait_ident: &syn::Ident,
+    trait_fns: &[TraitFn],
+    opts: &Opts,
+    crate_idents: &CrateIdents,
+) -> syn::Result<TokenStream> {
+    let entrait = &crate_idents.entrait;
+    let core = &crate_idents.core;
+    let span = syn::spanned::Spanned::span(service_trait);
+
+    let tonic = quote_spanned! {span=> ::#entrait::__tonic };
+    let impl_t = syn::Ident::new("EntraitT", span);
+
+    if let FutureSend(false) = opts.future_send() {
+        return Err(syn::Error::new(
+            span,
+            "`tonic` requires `Send` futures, so it can't be combined with `?Send`",
+        ));
+    }
+
+    // Local variables should not collide with parameter names
+    let request = syn::Ident::new("request", Span::mixed_site());
+    let output = syn::Ident::new("output", Span::mixed_site());
+    let error = syn::Ident::new("error", Span::mixed_site());
+
+    let mut fn_items = vec![];
+
+    for trait_fn in trait_fns {
+        let sig = trait_fn.sig();
+        let fn_ident = &sig.ident;
+        let cfg_attrs = trait_fn.cfg_attrs();
+
+        reject_unexportable(sig, "tonic")?;
+        if sig.asyncness.is_none() {
+            return Err(syn::Error::new(
+                fn_ident.span(),
+                "`tonic` requires `async` functions, without `dyn`",
+            ));
+        }
+
+        let mut request_types = sig.inputs.iter().filter_map(|arg| match arg {
+            syn::FnArg::Receiver(_) => None,
+            syn::FnArg::Typed(pat_type) => Some(pat_type.ty.as_ref()),
+        });
+        let request_ty = match (request_types.next(), request_types.next()) {
+            (Some(request_ty), None) => request_ty,
+            _ => {
+                return Err(syn::Error::new(
+                    fn_ident.span(),
+                    "`tonic` requires the request as the only parameter besides the deps",
+                ))
+            }
+        };
+        let (tonic_request_ty, argument) = match last_segment_arg(request_ty, "Request") {
+            Some(_) => (quote! { #request_ty }, quote! { #request }),
+            None => (
+                quote_spanned! {span=> #tonic::Request<#request_ty> },
+                quote! { #request.into_inner() },
+            ),
+        };
+
+        let call = quote_spanned! {span=>
+            <Self as #trait_ident>::#fn_ident(self, #argument).await
+        };
+        let (response_ty, body) = match &sig.output {
+            syn::ReturnType::Default => (
+                quote_spanned! {span=> () },
+                quote_spanned! {span=>
+                    #call;
+                    ::#core::result::Result::Ok(#tonic::Response::new(()))
+                },
+            ),
+            syn::ReturnType::Type(_, ty) => {
+                let (ok_ty, is_result) = match result_args(ty) {
+                    Some((ok_ty, _)) => (ok_ty, true),
+                    None => (ty.as_ref(), false),
+                };
+                let (response_ty, response) = match last_segment_arg(ok_ty, "Response") {
+                    Some(message_ty) => (quote! { #message_ty }, quote! { #output }),
+                    None => (
+                        quote! { #ok_ty },
+                        quote_spanned! {span=> #tonic::Response::new(#output) },
+                    ),
+                };
+                let body = if is_result {
+                    quote_spanned! {span=>
+                        match #call {
+                            ::#core::result::Result::Ok(#output) => ::#core::result::Result::Ok(#response),
+                            ::#core::result::Result::Err(#error) => ::#core::result::Result::Err(::#core::convert::Into::into(#error)),
+                        }
+                    }
+                } else {
+                    quote_spanned! {span=>
+                        let #output = #call;
+                        ::#core::result::Result::Ok(#response)
+                    }
+                };
+                (response_ty, body)
+            }
+        };
+
+        fn_items.push(quote_spanned! {span=>
+            #(#cfg_attrs)*
+            async fn #fn_ident(
+                &self,
+                #request: #tonic_request_ty,
+            ) -> ::#core::result::Result<#tonic::Response<#response_ty>, #tonic::Status> {
+                #body
+            }
+        });
+    }
+
+    let service_trait = relative_to_parent(service_trait);
+
+    Ok(quote_spanned! {span=>
+        #[#tonic::async_trait]
+        impl<#impl_t> #service_trait for ::#entrait::Impl<#impl_t>
+        where
+            #impl_t: ::#core::marker::Send + ::#core::marker::Sync + 'static,
+            Self: #trait_ident,
+        {
+            #(#fn_items)*
+        }
+    })
+}
+
+/// The `M` of a `Request<M>` or `Response<M>`
+fn last_segment_arg<'t>(ty: &'t syn::Type, ident: &str) -> Option<&'t syn::Type> {
+    let segment = match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if segment.ident == ident => {
+            match args.args.first()? {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The path to the service trait is written outside the module, but the impl is generated inside it
+fn relative_to_parent(path: &syn::Path) -> syn::Path {
+    let mut path = path.clone();
+    match path.segments.first() {
+        _ if path.leading_colon.is_some() => {}
+        Some(segment) if segment.ident == "crate" => {}
+        Some(segment) if segment.ident == "self" => {
+            path.segments[0].ident = syn::Ident::new("super", segment.ident.span());
+        }
+        _ => path.segments.insert(0, syn::parse_quote! { super }),
+    }
+    path
+}
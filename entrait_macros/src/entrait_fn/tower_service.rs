@@ -0,0 +1,158 @@
+//! tower services for entraited functions.
+//!
+//! With `tower_service = CreateUserService`, an `async fn` taking the request as its only parameter besides the deps
+//! gets a service struct, generic over the deps, implementing `tower::Service` for the request type:
+//!
+//! ```text
+//! #[derive(Clone, Debug, Default)]
+//! pub struct CreateUserService<D>(pub D);
+//!
+//! impl<D> tower_service::Service<NewUser> for CreateUserService<D>
+//! where
+//!     D: CreateUser + Clone + Send + 'static,
+//! {
+//!     type Response = User;
+//!     type Error = Error;
+//!     type Future = Pin<Box<dyn Future<Output = Result<User, Error>> + Send>>;
+//!
+//!     fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+//!         Poll::Ready(Ok(()))
+//!     }
+//!
+//!     fn call(&mut self, request: NewUser) -> Self::Future {
+//!         let deps = Clone::clone(&self.0);
+//!         Box::pin(async move { deps.create_user(request).await })
+//!     }
+//! }
+//! ```
+//!
+//! The service is a struct of its own rather than an implementation for `Impl<T>`,
+//! since neither the `Service` trait nor `Impl` are local to the crate using the option.
+//! Outputs that aren't a `Result` are always `Ok`, with `Infallible` as the error.
+
+use super::{reject_unexportable, result_args};
+use crate::analyze_generics::TraitFn;
+use crate::idents::CrateIdents;
+use crate::opt::{check_alloc, FutureSend, Opts};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote_spanned;
+
+pub fn gen_tower_service(
+    service_ident: &syn::Ident,
+    visibility: &syn::Visibility,
+    trait_ident: &syn::Ident,
+    trait_fn: &TraitFn,
+    opts: &Opts,
+    crate_idents: &CrateIdents,
+) -> syn::Result<TokenStream> {
+    let entrait = &crate_idents.entrait;
+    let core = &crate_idents.core;
+    let span = service_ident.span();
+    let sig = trait_fn.sig();
+    let fn_ident = &sig.ident;
+
+    check_alloc("`tower_service`", span)?;
+    reject_unexportable(sig, "tower_service")?;
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new(
+            span,
+            "`tower_service` requires an `async` function, without `dyn`",
+        ));
+    }
+
+    let mut request_types = sig.inputs.iter().filter_map(|arg| match arg {
+        syn::FnArg::Receiver(_) => None,
+        syn::FnArg::Typed(pat_type) => Some(pat_type.ty.as_ref()),
+    });
+    let request_ty = match (request_types.next(), request_types.next()) {
+        (Some(request_ty), None) => request_ty,
+        _ => {
+            return Err(syn::Error::new(
+                fn_ident.span(),
+                "`tower_service` requires the request as the only parameter besides the deps",
+            ))
+        }
+    };
+    if let syn::Type::Reference(type_reference) = request_ty {
+        return Err(syn::Error::new_spanned(
+            type_reference,
+            "The request of `tower_service` must be an owned type",
+        ));
+    }
+
+    let (response_ty, error_ty, is_result) = match &sig.output {
+        syn::ReturnType::Default => (quote_spanned! {span=> () }, infallible(core, span), false),
+        syn::ReturnType::Type(_, output) => match result_args(output) {
+            Some((ok, err)) => (
+                quote_spanned! {span=> #ok },
+                quote_spanned! {span=> #err },
+                true,
+            ),
+            None => (
+                quote_spanned! {span=> #output },
+                infallible(core, span),
+                false,
+            ),
+        },
+    };
+
+    let send = match opts.future_send() {
+        FutureSend(true) => Some(quote_spanned! {span=> + ::#core::marker::Send }),
+        FutureSend(false) => None,
+    };
+
+    // Local variables should not collide with parameter names
+    let deps_t = syn::Ident::new("EntraitD", span);
+    let deps = syn::Ident::new("deps", Span::mixed_site());
+    let request = syn::Ident::new("request", Span::mixed_site());
+
+    let call =
+        quote_spanned! {span=> <#deps_t as #trait_ident>::#fn_ident(&#deps, #request).await };
+    let output = if is_result {
+        call
+    } else {
+        quote_spanned! {span=> ::#core::result::Result::Ok(#call) }
+    };
+    let result = quote_spanned! {span=> ::#core::result::Result<#response_ty, #error_ty> };
+    let opt_doc = if opts.minimal_codegen_value() {
+        None
+    } else {
+        let doc =
+            format!(" A tower service calling [`{trait_ident}::{fn_ident}`] for each request.");
+        Some(quote_spanned! {span=> #[doc = #doc] })
+    };
+
+    Ok(quote_spanned! {span=>
+        #opt_doc
+        #[derive(Clone, Debug, Default)]
+        #visibility struct #service_ident<#deps_t>(pub #deps_t);
+
+        impl<#deps_t> ::#entrait::__tower_service::Service<#request_ty> for #service_ident<#deps_t>
+        where
+            #deps_t: #trait_ident + ::#core::clone::Clone #send + 'static,
+        {
+            type Response = #response_ty;
+            type Error = #error_ty;
+            type Future = ::#core::pin::Pin<
+                ::#entrait::__alloc::Box<dyn ::#core::future::Future<Output = #result> #send>
+            >;
+
+            fn poll_ready(
+                &mut self,
+                _: &mut ::#core::task::Context<'_>,
+            ) -> ::#core::task::Poll<::#core::result::Result<(), #error_ty>> {
+                ::#core::task::Poll::Ready(::#core::result::Result::Ok(()))
+            }
+
+            fn call(&mut self, #request: #request_ty) -> Self::Future {
+                let #deps = ::#core::clone::Clone::clone(&self.0);
+                ::#entrait::__alloc::Box::pin(async move { #output })
+            }
+        }
+    })
+}
+
+fn infallible(core: &syn::Ident, span: Span) -> TokenStream {
+    quote_spanned! {span=> ::#core::convert::Infallible }
+}
@@ -0,0 +1,129 @@
+//! JavaScript bindings for entraited modules.
+//!
+//! With `wasm_bindgen = JsApp`, each trait method becomes a method in a `#[wasm_bindgen] impl JsApp` block.
+//! `JsApp` is a user-defined tuple struct (declared next to the module) whose field implements the trait,
+//! e.g. `Impl<App>` or `Rc<Impl<App>>`:
+//!
+//! ```text
+//! pub fn get_user(&self, id: JsValue) -> Result<JsValue, JsValue> {
+//!     let id: u32 = serde_wasm_bindgen::from_value(id)?;
+//!     let output = self.0.get_user(id);
+//!     Ok(serde_wasm_bindgen::to_value(&output)?)
+//! }
+//! ```
+//!
+//! `async` methods return a `Promise` instead, driven by a clone of the field.
+
+use super::{is_unsized, reject_unexportable};
+use crate::analyze_generics::TraitFn;
+use crate::idents::CrateIdents;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+
+pub fn gen_wasm_bindgen_impl(
+    wrapper_ident: &syn::Ident,
+    trait_fns: &[TraitFn],
+    crate_idents: &CrateIdents,
+) -> syn::Result<TokenStream> {
+    let entrait = &crate_idents.entrait;
+    let core = &crate_idents.core;
+    let span = wrapper_ident.span();
+
+    let js_value = quote_spanned! {span=> ::#entrait::__wasm_bindgen::JsValue };
+    let serde_wasm_bindgen = quote_spanned! {span=> ::#entrait::__serde_wasm_bindgen };
+
+    // Local variables should not collide with parameter names
+    let this = syn::Ident::new("this", Span::mixed_site());
+    let output = syn::Ident::new("output", Span::mixed_site());
+
+    let mut fn_items = vec![];
+
+    for trait_fn in trait_fns {
+        let sig = trait_fn.sig();
+        let fn_ident = &sig.ident;
+        let cfg_attrs: Vec<_> = trait_fn.cfg_attrs().collect();
+
+        reject_unexportable(sig, "wasm_bindgen")?;
+
+        let mut js_params = vec![];
+        let mut conversions = vec![];
+        let mut arguments = vec![];
+
+        for arg in &sig.inputs {
+            let pat_type = match arg {
+                syn::FnArg::Receiver(_) => continue,
+                syn::FnArg::Typed(pat_type) => pat_type,
+            };
+            let ident = match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+                _ => panic!("Found a non-ident pattern, this should be handled in signature.rs"),
+            };
+
+            js_params.push(quote! { #ident: #js_value });
+
+            match pat_type.ty.as_ref() {
+                syn::Type::Reference(type_reference) => {
+                    // Deserialize into an owned value, and lend it out
+                    let elem = &type_reference.elem;
+                    let mutability = &type_reference.mutability;
+                    let owned_ty = if is_unsized(elem) {
+                        quote! { <#elem as ::#entrait::__alloc::ToOwned>::Owned }
+                    } else {
+                        quote! { #elem }
+                    };
+                    conversions.push(quote! {
+                        let #mutability #ident: #owned_ty = #serde_wasm_bindgen::from_value(#ident)?;
+                    });
+                    arguments.push(quote! { & #mutability #ident });
+                }
+                ty => {
+                    conversions.push(quote! {
+                        let #ident: #ty = #serde_wasm_bindgen::from_value(#ident)?;
+                    });
+                    arguments.push(quote! { #ident });
+                }
+            }
+        }
+
+        if trait_fn.originally_async {
+            let promise = quote_spanned! {span=>
+                ::#entrait::__wasm_bindgen_futures::js_sys::Promise
+            };
+
+            fn_items.push(quote! {
+                #(#cfg_attrs)*
+                pub fn #fn_ident(&self, #(#js_params),*) -> #promise {
+                    let #this = ::#core::clone::Clone::clone(&self.0);
+                    ::#entrait::__wasm_bindgen_futures::future_to_promise(async move {
+                        #(#conversions)*
+                        let #output = #this.#fn_ident(#(#arguments),*).await;
+                        ::#core::result::Result::Ok(#serde_wasm_bindgen::to_value(&#output)?)
+                    })
+                }
+            });
+        } else {
+            fn_items.push(quote! {
+                #(#cfg_attrs)*
+                pub fn #fn_ident(&self, #(#js_params),*) -> ::#core::result::Result<#js_value, #js_value> {
+                    #(#conversions)*
+                    let #output = self.0.#fn_ident(#(#arguments),*);
+                    ::#core::result::Result::Ok(#serde_wasm_bindgen::to_value(&#output)?)
+                }
+            });
+        }
+    }
+
+    // wasm_bindgen requires a plain identifier as the self type,
+    // so the wrapper is imported from outside the module in an anonymous scope.
+    Ok(quote_spanned! {span=>
+        const _: () = {
+            use super::#wrapper_ident;
+
+            #[::#entrait::__wasm_bindgen::prelude::wasm_bindgen(wasm_bindgen = ::#entrait::__wasm_bindgen)]
+            impl #wrapper_ident {
+                #(#fn_items)*
+            }
+        };
+    })
+}
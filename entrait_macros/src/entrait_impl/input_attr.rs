@@ -7,6 +7,7 @@ use syn::parse::{Parse, ParseStream};
 pub struct EntraitSimpleImplAttr {
     pub impl_kind: ImplKind,
     pub opts: Opts,
+    pub group: Option<syn::LitStr>,
     pub crate_idents: CrateIdents,
 }
 
@@ -24,11 +25,13 @@ impl Parse for EntraitSimpleImplAttr {
         let dyn_token: Option<syn::token::Dyn> = input.parse()?;
 
         let mut debug = None;
+        let mut group = None;
 
         if !input.is_empty() {
             loop {
                 match input.parse::<EntraitOpt>()? {
                     EntraitOpt::Debug(opt) => debug = Some(opt),
+                    EntraitOpt::Group(lit_str) => group = Some(lit_str),
                     entrait_opt => {
                         return Err(syn::Error::new(entrait_opt.span(), "Unsupported option"))
                     }
@@ -53,55 +56,34 @@ impl Parse for EntraitSimpleImplAttr {
                 no_deps: None,
                 debug,
                 export: None,
+                export_cfg: None,
+                mock_cfg: None,
                 future_send: None,
+                send_cfg: None,
+                impl_sync: None,
                 mock_api: None,
                 unimock: None,
+                unimock_version: None,
                 mockall: None,
+                mry: None,
+                faux: None,
+                stub: None,
+                record: None,
+                forward_impls: None,
+                double: None,
+                const_trait: None,
+                log: None,
+                instrument: None,
+                otel_metrics: None,
+                intercept: None,
+                fingerprint: None,
+                dependencies: None,
+                trait_doc: None,
+                local: None,
+                no_std: None,
+                minimal_codegen: None,
             },
-            crate_idents: CrateIdents::new(span),
-        })
-    }
-}
-
-pub struct EntraitImplAttr {
-    pub opts: Opts,
-    pub crate_idents: CrateIdents,
-}
-
-impl Parse for EntraitImplAttr {
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        let span = input.span();
-
-        let mut debug = None;
-
-        if !input.is_empty() {
-            loop {
-                match input.parse::<EntraitOpt>()? {
-                    EntraitOpt::Debug(opt) => debug = Some(opt),
-                    entrait_opt => {
-                        return Err(syn::Error::new(entrait_opt.span(), "Unsupported option"))
-                    }
-                };
-
-                if input.peek(syn::token::Comma) {
-                    input.parse::<syn::token::Comma>()?;
-                } else {
-                    break;
-                }
-            }
-        }
-
-        Ok(Self {
-            opts: Opts {
-                default_span: span,
-                no_deps: None,
-                debug,
-                export: None,
-                future_send: None,
-                mock_api: None,
-                unimock: None,
-                mockall: None,
-            },
+            group,
             crate_idents: CrateIdents::new(span),
         })
     }
@@ -23,10 +23,8 @@ pub fn output_tokens_for_impl(
         attrs,
         unsafety,
         impl_token,
-        trait_path,
-        for_token: _,
+        mut trait_path,
         self_ty,
-        brace_token: _,
         items,
     }: InputImpl,
 ) -> syn::Result<proc_macro2::TokenStream> {
@@ -36,6 +34,22 @@ pub fn output_tokens_for_impl(
         .map(|segment| segment.span())
         .unwrap_or_else(proc_macro2::Span::call_site);
 
+    // The generic arguments of the trait are generated along with `EntraitT`
+    let trait_args = match trait_path
+        .segments
+        .last_mut()
+        .map(|segment| std::mem::replace(&mut segment.arguments, syn::PathArguments::None))
+    {
+        Some(syn::PathArguments::AngleBracketed(arguments)) => arguments.args.into_iter().collect(),
+        Some(syn::PathArguments::Parenthesized(arguments)) => {
+            return Err(syn::Error::new(
+                arguments.span(),
+                "Expected angle-bracketed trait arguments",
+            ));
+        }
+        Some(syn::PathArguments::None) | None => vec![],
+    };
+
     let mut generics_analyzer = analyze_generics::GenericsAnalyzer::new();
     let trait_fns = items
         .iter()
@@ -46,13 +60,14 @@ pub fn output_tokens_for_impl(
                     ImplKind::Static => signature::ImplReceiverKind::StaticImpl,
                     ImplKind::DynRef => signature::ImplReceiverKind::DynamicImpl,
                 },
-                trait_span,
                 crate_idents: &attr.crate_idents,
                 opts: &attr.opts,
             }
             .analyze(input_fn.input_sig(), &mut generics_analyzer)
         })
         .collect::<syn::Result<Vec<_>>>()?;
+    let impl_types: Vec<_> = items.iter().filter_map(ImplItem::filter_type).collect();
+    let impl_consts: Vec<_> = items.iter().filter_map(ImplItem::filter_const).collect();
     let sub_attributes = analyze_sub_attributes(&attrs);
 
     let trait_generics = generics_analyzer.into_trait_generics();
@@ -62,8 +77,14 @@ pub fn output_tokens_for_impl(
         detect_trait_dependency_mode(&fn_input_mode, &trait_fns, &attr.crate_idents, trait_span)?;
 
     let impl_indirection = match attr.impl_kind {
-        ImplKind::Static => generics::ImplIndirection::Static { ty: &self_ty },
-        ImplKind::DynRef => generics::ImplIndirection::Dynamic { ty: &self_ty },
+        ImplKind::Static => generics::ImplIndirection::Static {
+            ty: &self_ty,
+            trait_args: &trait_args,
+        },
+        ImplKind::DynRef => generics::ImplIndirection::Dynamic {
+            ty: &self_ty,
+            trait_args: &trait_args,
+        },
     };
 
     let impl_block = fn_delegation_codegen::FnDelegationCodegen {
@@ -77,7 +98,9 @@ pub fn output_tokens_for_impl(
         trait_dependency_mode: &trait_dependency_mode,
         sub_attributes: &sub_attributes,
     }
-    .gen_impl_block(&trait_fns);
+    .gen_impl_block(&impl_types, &impl_consts, &trait_fns);
+
+    let inherent_items = items.iter().filter(|item| item.filter_type().is_none());
 
     let inherent_sub_attrs = sub_attributes
         .iter()
@@ -86,7 +109,7 @@ pub fn output_tokens_for_impl(
     Ok(quote! {
         #(#inherent_sub_attrs)*
         #unsafety #impl_token #self_ty {
-            #(#items)*
+            #(#inherent_items)*
         }
         #impl_block
     })
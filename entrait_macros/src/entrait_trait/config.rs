@@ -0,0 +1,205 @@
+//! Implement a leaf trait of getters for a configuration struct.
+//!
+//! Each method `fn foo(&self) -> T` reads the field `foo`, optionally adjusted with a `#[config(..)]` attribute:
+//!
+//! ```text
+//! #[entrait(config = AppConfig)]
+//! trait Settings {
+//!     #[config(field = db_url)]
+//!     fn database_url(&self) -> &str;
+//!
+//!     #[config(default = 30, env = "APP_TIMEOUT")]
+//!     fn timeout(&self) -> u64;
+//! }
+//! ```
+
+use super::out_trait::OutTrait;
+use crate::idents::CrateIdents;
+use crate::opt::Opts;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+
+/// A `#[config(..)]` argument
+enum ConfigArg {
+    /// Read another field than the one named like the method
+    Field(syn::Ident),
+    /// The field is an `Option`, with this default value
+    Default(syn::Expr),
+    /// An environment variable that overrides the field
+    Env(syn::LitStr),
+}
+
+impl Parse for ConfigArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: syn::Ident = input.parse()?;
+        let _: syn::token::Eq = input.parse()?;
+
+        match ident.to_string().as_str() {
+            "field" => Ok(Self::Field(input.parse()?)),
+            "default" => Ok(Self::Default(input.parse()?)),
+            "env" => Ok(Self::Env(input.parse()?)),
+            other => Err(syn::Error::new(
+                ident.span(),
+                format!("Unknown config argument \"{other}\""),
+            )),
+        }
+    }
+}
+
+/// Generate `impl Trait for ConfigType`, stripping the `#[config(..)]` attributes off the trait methods
+pub fn gen_config_impl(
+    config_ty: &syn::Type,
+    out_trait: &mut OutTrait,
+    crate_idents: &CrateIdents,
+    opts: &Opts,
+) -> syn::Result<TokenStream> {
+    let entrait = &crate_idents.entrait;
+    let core = &crate_idents.core;
+
+    if !out_trait.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            config_ty.span(),
+            "`config` is not supported for generic traits",
+        ));
+    }
+
+    if let Some(trait_type) = out_trait.types.first() {
+        return Err(syn::Error::new(
+            trait_type.ident.span(),
+            "`config` is not supported for traits with associated types",
+        ));
+    }
+
+    if let Some(trait_const) = out_trait
+        .consts
+        .iter()
+        .find(|trait_const| trait_const.default.is_none())
+    {
+        return Err(syn::Error::new(
+            trait_const.ident.span(),
+            "`config` requires associated constants to have a default value",
+        ));
+    }
+
+    let mut method_items = vec![];
+
+    for trait_fn in out_trait.fns.iter_mut() {
+        let mut field = trait_fn.sig().ident.clone();
+        let mut default = None;
+        let mut env = None;
+
+        let mut config_args = vec![];
+        trait_fn.attrs.retain(|attr| {
+            if attr.path().is_ident("config") {
+                config_args.push(attr.parse_args_with(
+                    syn::punctuated::Punctuated::<ConfigArg, syn::token::Comma>::parse_terminated,
+                ));
+                false
+            } else {
+                true
+            }
+        });
+        for config_arg in config_args {
+            for config_arg in config_arg? {
+                match config_arg {
+                    ConfigArg::Field(ident) => field = ident,
+                    ConfigArg::Default(expr) => default = Some(expr),
+                    ConfigArg::Env(lit_str) => env = Some(lit_str),
+                }
+            }
+        }
+
+        let sig = trait_fn.sig();
+        let self_token = match sig.inputs.first() {
+            Some(syn::FnArg::Receiver(syn::Receiver {
+                reference: Some(_),
+                mutability: None,
+                self_token,
+                ..
+            })) if sig.inputs.len() == 1 && !trait_fn.originally_async => self_token,
+            _ => {
+                return Err(syn::Error::new(
+                    sig.ident.span(),
+                    "A config getter must be a non-async method taking only `&self`",
+                ))
+            }
+        };
+
+        let returns_ref = match &sig.output {
+            syn::ReturnType::Type(_, ty) => matches!(ty.as_ref(), syn::Type::Reference(_)),
+            syn::ReturnType::Default => {
+                return Err(syn::Error::new(
+                    sig.ident.span(),
+                    "A config getter must return a value",
+                ))
+            }
+        };
+
+        let value = match (&default, returns_ref) {
+            (None, false) => quote! { ::#core::clone::Clone::clone(&#self_token.#field) },
+            (None, true) => quote! { &#self_token.#field },
+            (Some(default), false) => {
+                // `default = "text"` for `String`s
+                let default = match default {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(_),
+                        ..
+                    }) => quote! { ::#core::convert::Into::into(#default) },
+                    _ => quote! { #default },
+                };
+
+                quote! {
+                    match &#self_token.#field {
+                        ::#core::option::Option::Some(value) => ::#core::clone::Clone::clone(value),
+                        ::#core::option::Option::None => #default,
+                    }
+                }
+            }
+            (Some(default), true) => quote! {
+                match &#self_token.#field {
+                    ::#core::option::Option::Some(value) => value,
+                    ::#core::option::Option::None => #default,
+                }
+            },
+        };
+
+        let opt_env_override = match env {
+            Some(env) if returns_ref => {
+                return Err(syn::Error::new(
+                    env.span(),
+                    "`env` overrides require an owned return type",
+                ))
+            }
+            Some(env) => {
+                opts.check_std("An `env` override", env.span())?;
+                Some(quote! {
+                if let ::#core::option::Option::Some(value) = ::#entrait::__std::env::var(#env)
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+                {
+                    return value;
+                }
+                })
+            }
+            None => None,
+        };
+
+        method_items.push(quote! {
+            #sig {
+                #opt_env_override
+                #value
+            }
+        });
+    }
+
+    let trait_ident = &out_trait.ident;
+
+    Ok(quote! {
+        impl #trait_ident for #config_ty {
+            #(#method_items)*
+        }
+    })
+}
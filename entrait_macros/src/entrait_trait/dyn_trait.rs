@@ -0,0 +1,324 @@
+//! Dyn-compatible companion trait for `delegate_by=ref` leaf traits with `async` methods or `impl Trait` outputs,
+//! and for leaf traits given the `dyn` option.
+//!
+//! Native `async fn`s in traits are not dyn-compatible, so a trait like
+//!
+//! ```text
+//! trait Foo { async fn foo(&self, arg: &str) -> i32; }
+//! ```
+//!
+//! gets a companion trait where each future is boxed:
+//!
+//! ```text
+//! trait DynFoo {
+//!     fn dyn_foo<'entrait0, 'entrait1, 'entrait_fut>(&'entrait0 self, arg: &'entrait1 str)
+//!         -> Pin<Box<dyn Future<Output = i32> + Send + 'entrait_fut>>
+//!     where 'entrait0: 'entrait_fut, 'entrait1: 'entrait_fut, Self: 'entrait_fut;
+//! }
+//!
+//! impl<T: Foo> DynFoo for T { .. }
+//! ```
+//!
+//! Likewise, `impl Trait` outputs become boxed trait objects, like `Box<dyn Iterator<Item = i32> + 'entrait_fut>`.
+//!
+//! The methods are prefixed with `dyn_`, so that they never compete with the original ones in method resolution
+//! (`Impl<T>` implements both traits).
+//!
+//! With `dyn`, the trait is also implemented the other way around, for the trait object and a `Box` of it:
+//!
+//! ```text
+//! impl Foo for dyn DynFoo + Send + Sync { .. }
+//! impl Foo for Box<dyn DynFoo + Send + Sync> { .. }
+//! ```
+
+use super::input_attr::EntraitTraitAttr;
+use super::out_trait::OutTrait;
+use super::{gen_forwarding_items, has_only_shared_receivers, ContainsAsync, DelegatingMethod};
+use crate::generics;
+use crate::idents::GenericIdents;
+use crate::opt::Opts;
+use crate::signature::boxed_future::{box_future, box_impl_output};
+use crate::sub_attributes::{contains_async_trait, SubAttribute};
+use crate::trait_codegen::Supertraits;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+pub struct DynTrait {
+    pub ident: syn::Ident,
+    pub tokens: TokenStream,
+}
+
+pub fn gen_dyn_trait(
+    out_trait: &OutTrait,
+    generic_idents: &GenericIdents,
+    opts: &Opts,
+) -> syn::Result<DynTrait> {
+    let entrait = &generic_idents.crate_idents.entrait;
+    let core = &generic_idents.crate_idents.core;
+    let impl_t = &generic_idents.impl_t;
+    let trait_ident = &out_trait.ident;
+    let dyn_ident = format_ident!("Dyn{}", trait_ident);
+    let vis = &out_trait.vis;
+    let trait_params = out_trait.generics.trait_params();
+    let trait_where_clause = out_trait.generics.trait_where_clause();
+    let impl_params = out_trait.generics.impl_params_from_idents(
+        generic_idents,
+        generics::TakesSelfByValue(false),
+        opts.required_impl_sync(
+            out_trait
+                .fns
+                .iter()
+                .any(|trait_fn| trait_fn.originally_async),
+        ),
+    );
+    let args = out_trait
+        .generics
+        .arguments(&generics::ImplIndirection::None);
+    let where_predicates = out_trait.generics.where_predicates.iter();
+    let supertraits = dyn_supertraits(&out_trait.supertraits);
+
+    let mut fn_defs = vec![];
+    let mut fn_impls = vec![];
+
+    for trait_fn in &out_trait.fns {
+        let mut sig = trait_fn.sig().clone();
+        let fn_ident = sig.ident.clone();
+        sig.ident = dyn_fn_ident(&fn_ident);
+        let self_token = match sig.inputs.first() {
+            Some(syn::FnArg::Receiver(receiver)) => receiver.self_token,
+            _ => {
+                return Err(syn::Error::new(
+                    fn_ident.span(),
+                    "A dyn-compatible method requires a `self` receiver",
+                ))
+            }
+        };
+        let arguments: Vec<_> = sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Receiver(_) => None,
+                syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                    syn::Pat::Ident(pat_ident) => Some(pat_ident.ident.clone()),
+                    _ => {
+                        panic!("Found a non-ident pattern, this should be handled in signature.rs")
+                    }
+                },
+            })
+            .collect();
+
+        let call = quote! {
+            <#impl_t as #trait_ident #args>::#fn_ident(#self_token, #(#arguments),*)
+        };
+
+        let boxed_output = box_impl_output(&mut sig, entrait)?;
+        let cfg_attrs: Vec<_> = trait_fn.cfg_attrs().collect();
+
+        if trait_fn.originally_async {
+            box_future(&mut sig, core, entrait, opts);
+
+            let fut = match &boxed_output {
+                Some(boxed_output) => quote! {
+                    async move {
+                        let output: #boxed_output = ::#entrait::__alloc::Box::new(#call.await);
+                        output
+                    }
+                },
+                None => call,
+            };
+
+            fn_impls.push(quote! {
+                #(#cfg_attrs)*
+                #sig {
+                    ::#entrait::__alloc::Box::pin(#fut)
+                }
+            });
+        } else if boxed_output.is_some() {
+            fn_impls.push(quote! {
+                #(#cfg_attrs)*
+                #sig {
+                    ::#entrait::__alloc::Box::new(#call)
+                }
+            });
+        } else {
+            fn_impls.push(quote! {
+                #(#cfg_attrs)*
+                #sig {
+                    #call
+                }
+            });
+        }
+
+        fn_defs.push(quote! {
+            #(#cfg_attrs)*
+            #sig;
+        });
+    }
+
+    let tokens = quote! {
+        #vis trait #dyn_ident #trait_params #supertraits #trait_where_clause {
+            #(#fn_defs)*
+        }
+
+        impl #impl_params #dyn_ident #args for #impl_t
+        where
+            #impl_t: #trait_ident #args,
+            #(#where_predicates,)*
+        {
+            #(#fn_impls)*
+        }
+    };
+
+    Ok(DynTrait {
+        ident: dyn_ident,
+        tokens,
+    })
+}
+
+pub fn check_dyn_bridge(
+    out_trait: &OutTrait,
+    attr: &EntraitTraitAttr,
+    sub_attributes: &[SubAttribute],
+    dyn_incompatible: bool,
+    span: proc_macro2::Span,
+) -> syn::Result<()> {
+    let message = if attr.impl_trait.is_some() {
+        "`dyn` is only supported for leaf traits"
+    } else if contains_async_trait(sub_attributes) {
+        "`dyn` has no effect with `#[async_trait]`, which makes the trait dyn-compatible by itself"
+    } else if !dyn_incompatible {
+        "`dyn` has no effect without `async` methods or `impl Trait` outputs, the trait is already dyn-compatible"
+    } else if !out_trait.types.is_empty() || !out_trait.consts.is_empty() {
+        "`dyn` is not supported for traits with associated types or constants"
+    } else {
+        return Ok(());
+    };
+
+    Err(syn::Error::new(span, message))
+}
+
+/// Implement the trait for the companion trait object and a `Box` of it, with `dyn`.
+///
+/// The trait object is `Send + Sync` when the futures are `Send`, since they borrow it.
+pub fn gen_dyn_bridge(
+    out_trait: &OutTrait,
+    dyn_ident: &syn::Ident,
+    generic_idents: &GenericIdents,
+    opts: &Opts,
+    contains_async: ContainsAsync,
+) -> TokenStream {
+    let entrait = &generic_idents.crate_idents.entrait;
+    let core = &generic_idents.crate_idents.core;
+    let trait_ident = &out_trait.ident;
+    let params = out_trait.generics.params.iter();
+    let impl_params = quote! { <'entrait_dyn #(, #params)*> };
+    let trait_where_clause = out_trait.generics.trait_where_clause();
+    let args = out_trait
+        .generics
+        .arguments(&generics::ImplIndirection::None);
+
+    let has_supertrait = |name: &str| {
+        match &out_trait.supertraits {
+        Supertraits::Some { bounds, .. } => bounds.iter().any(|bound| {
+            matches!(bound, syn::TypeParamBound::Trait(trait_bound) if trait_bound.path.is_ident(name))
+        }),
+        Supertraits::None => false,
+    }
+    };
+    let auto_traits: Vec<_> = ["Send", "Sync"]
+        .into_iter()
+        .filter(|name| contains_async.0 && opts.future_send().0 && !has_supertrait(name))
+        .map(|name| format_ident!("{}", name))
+        .collect();
+    let dyn_ty = quote! { dyn #dyn_ident #args #(+ ::#core::marker::#auto_traits)* + 'entrait_dyn };
+
+    let method_items = out_trait.fns.iter().map(|trait_fn| {
+        let fn_sig = trait_fn.sig();
+        let dyn_fn_ident = dyn_fn_ident(&fn_sig.ident);
+        let self_token = match fn_sig.inputs.first() {
+            Some(syn::FnArg::Receiver(receiver)) => receiver.self_token,
+            _ => syn::token::SelfValue::default(),
+        };
+        let arguments = fn_sig.inputs.iter().filter_map(|arg| match arg {
+            syn::FnArg::Receiver(_) => None,
+            syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+                _ => panic!("Found a non-ident pattern, this should be handled in signature.rs"),
+            },
+        });
+
+        DelegatingMethod {
+            trait_fn,
+            call: quote! {
+                #dyn_ident::#dyn_fn_ident(#self_token, #(#arguments),*)
+            },
+        }
+    });
+
+    // Like the implementation for `Arc<T>`, the `Box` only lends out shared references.
+    // With `forward_impls`, the trait is implemented for any `Box<T>` already
+    let box_impl = if has_only_shared_receivers(out_trait) && !opts.forward_impls_value() {
+        let (_, box_method_items) = gen_forwarding_items(
+            out_trait,
+            quote! { #dyn_ty as #trait_ident #args },
+            |self_token| quote! { &**#self_token },
+        );
+        Some(quote! {
+            impl #impl_params #trait_ident #args for ::#entrait::__alloc::Box<#dyn_ty> #trait_where_clause {
+                #(#box_method_items)*
+            }
+        })
+    } else {
+        None
+    };
+
+    quote! {
+        impl #impl_params #trait_ident #args for #dyn_ty #trait_where_clause {
+            #(#method_items)*
+        }
+
+        #box_impl
+    }
+}
+
+/// The name of a method in the companion trait
+pub fn dyn_fn_ident(fn_ident: &syn::Ident) -> syn::Ident {
+    format_ident!("dyn_{}", fn_ident)
+}
+
+/// Only lifetimes and `Send`/`Sync` carry over to the companion trait,
+/// other supertraits may not be dyn-compatible.
+fn dyn_supertraits(supertraits: &Supertraits) -> Supertraits {
+    match supertraits {
+        Supertraits::None => Supertraits::None,
+        Supertraits::Some {
+            colon_token,
+            bounds,
+        } => {
+            let bounds: syn::punctuated::Punctuated<_, _> = bounds
+                .iter()
+                .filter(|bound| match bound {
+                    syn::TypeParamBound::Lifetime(_) => true,
+                    syn::TypeParamBound::Trait(trait_bound) => {
+                        trait_bound.path.segments.last().map_or(false, |segment| {
+                            segment.arguments.is_none()
+                                && (segment.ident == "Send" || segment.ident == "Sync")
+                        })
+                    }
+                    _ => false,
+                })
+                .cloned()
+                .collect();
+
+            if bounds.is_empty() {
+                Supertraits::None
+            } else {
+                Supertraits::Some {
+                    colon_token: *colon_token,
+                    bounds,
+                }
+            }
+        }
+    }
+}
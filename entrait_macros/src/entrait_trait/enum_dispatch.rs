@@ -0,0 +1,166 @@
+//! Static dispatch of a leaf trait to one of a list of delegation targets.
+//!
+//! ```text
+//! #[entrait(delegate_by = enum(Postgres, Sqlite))]
+//! pub trait Repository {
+//!     fn fetch(&self, id: u32) -> String;
+//! }
+//!
+//! // generates, next to the implementation for `Impl<T>` where `T: AsRef<RepositoryEnum>`
+//! pub enum RepositoryEnum {
+//!     Postgres(Postgres),
+//!     Sqlite(Sqlite),
+//! }
+//!
+//! impl Repository for RepositoryEnum {
+//!     fn fetch(&self, id: u32) -> String {
+//!         match self {
+//!             Self::Postgres(target) => target.fetch(id),
+//!             Self::Sqlite(target) => target.fetch(id),
+//!         }
+//!     }
+//! }
+//! ```
+
+use super::out_trait::OutTrait;
+use crate::idents::CrateIdents;
+use crate::signature::returns_impl_trait;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+
+/// The name of the generated enum
+pub fn enum_ident(trait_ident: &syn::Ident) -> syn::Ident {
+    format_ident!("{}Enum", trait_ident)
+}
+
+/// Every method must be implementable by a `match` over the variants, with the same signature for all of them
+pub fn check_enum_dispatch(out_trait: &OutTrait, is_leaf: bool, span: Span) -> syn::Result<()> {
+    if !is_leaf {
+        return Err(syn::Error::new(
+            span,
+            "`delegate_by = enum(..)` is only supported for leaf traits",
+        ));
+    }
+    if !out_trait.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            span,
+            "`delegate_by = enum(..)` is not supported for generic traits",
+        ));
+    }
+    if let Some(trait_type) = out_trait.types.first() {
+        return Err(syn::Error::new_spanned(
+            &trait_type.ident,
+            "Associated types may differ between the variants, so they are not supported with `delegate_by = enum(..)`",
+        ));
+    }
+    if let Some(trait_const) = out_trait.consts.first() {
+        return Err(syn::Error::new_spanned(
+            &trait_const.ident,
+            "Associated constants may differ between the variants, so they are not supported with `delegate_by = enum(..)`",
+        ));
+    }
+
+    for trait_fn in &out_trait.fns {
+        let sig = trait_fn.sig();
+        if !matches!(sig.inputs.first(), Some(syn::FnArg::Receiver(_))) {
+            return Err(syn::Error::new_spanned(
+                &sig.ident,
+                "`delegate_by = enum(..)` dispatches on the receiver, so methods without one are not supported",
+            ));
+        }
+        if returns_impl_trait(sig) {
+            return Err(syn::Error::new_spanned(
+                &sig.output,
+                "The variants return different `impl Trait` types, so these outputs are not supported with `delegate_by = enum(..)`",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The enum with a variant for each delegation target, and its implementation of the trait
+pub fn gen_enum_dispatch(
+    out_trait: &OutTrait,
+    targets: &[syn::Type],
+    crate_idents: &CrateIdents,
+) -> syn::Result<TokenStream> {
+    let core = &crate_idents.core;
+    let vis = &out_trait.vis;
+    let trait_ident = &out_trait.ident;
+    let enum_ident = enum_ident(trait_ident);
+    let enum_doc = format!(
+        " Static dispatch of [{trait_ident}] to one of its delegation targets, selected at runtime."
+    );
+
+    let variant_idents = targets
+        .iter()
+        .map(|target| match target {
+            syn::Type::Path(type_path) if type_path.qself.is_none() => {
+                Ok(type_path.path.segments.last().unwrap().ident.clone())
+            }
+            _ => Err(syn::Error::new_spanned(
+                target,
+                "Expected a type path, which the variant is named after",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let methods = out_trait.fns.iter().map(|trait_fn| {
+        let sig = trait_fn.sig();
+        let fn_ident = &sig.ident;
+        let self_token = match sig.inputs.first() {
+            Some(syn::FnArg::Receiver(receiver)) => receiver.self_token,
+            _ => syn::token::SelfValue::default(),
+        };
+        let arguments: Vec<_> = sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Receiver(_) => None,
+                syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                    syn::Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+                    _ => {
+                        panic!("Found a non-ident pattern, this should be handled in signature.rs")
+                    }
+                },
+            })
+            .collect();
+        let opt_dot_await = trait_fn.opt_dot_await(Span::call_site());
+        let call = quote! { #fn_ident(#(#arguments),*) #opt_dot_await };
+        let delegation_attrs = trait_fn.delegation_attrs();
+
+        quote! {
+            #(#delegation_attrs)*
+            #sig {
+                match #self_token {
+                    #(
+                        Self::#variant_idents(target) => target.#call,
+                    )*
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[doc = #enum_doc]
+        #vis enum #enum_ident {
+            #(
+                #variant_idents(#targets),
+            )*
+        }
+
+        #(
+            impl ::#core::convert::From<#targets> for #enum_ident {
+                fn from(target: #targets) -> Self {
+                    Self::#variant_idents(target)
+                }
+            }
+        )*
+
+        impl #trait_ident for #enum_ident {
+            #(#methods)*
+        }
+    })
+}
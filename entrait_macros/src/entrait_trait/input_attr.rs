@@ -1,16 +1,34 @@
+use crate::analyze_generics::TraitFn;
 use crate::idents::CrateIdents;
 use crate::opt::*;
 
 use syn::parse::{Parse, ParseStream};
 
+#[derive(Clone)]
 pub struct EntraitTraitAttr {
     pub impl_trait: Option<ImplTrait>,
     pub opts: Opts,
     pub delegation_kind: Option<SpanOpt<Delegate>>,
+    pub watch: Option<SpanOpt<bool>>,
+    pub registry: Option<SpanOpt<Registry>>,
+    pub delegate_defaults: Option<SpanOpt<bool>>,
+    pub dyn_compatible: Option<SpanOpt<bool>>,
+    pub config: Option<syn::Type>,
+    pub group: Option<syn::LitStr>,
+    pub where_clause: Option<SpanOpt<WhereClause>>,
     pub crate_idents: CrateIdents,
 }
 
-pub struct ImplTrait(pub syn::Visibility, pub syn::Ident);
+#[derive(Clone)]
+pub struct ImplTrait(pub syn::Ident);
+
+impl EntraitTraitAttr {
+    /// Whether the implementation for `Impl<T>` delegates the method.
+    /// Methods with default bodies are only delegated with `delegate_defaults`.
+    pub fn delegates(&self, trait_fn: &TraitFn) -> bool {
+        trait_fn.default_body.is_none() || matches!(self.delegate_defaults, Some(SpanOpt(true, _)))
+    }
+}
 
 impl Parse for EntraitTraitAttr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
@@ -18,11 +36,14 @@ impl Parse for EntraitTraitAttr {
 
         let mut impl_trait = None;
 
-        if !input.is_empty() && input.fork().parse::<EntraitOpt>().is_err() {
-            let vis: syn::Visibility = input.parse()?;
+        // `key = value` is always an option, even if it fails to parse
+        let is_key_value = input.peek(syn::Ident) && input.peek2(syn::token::Eq);
+
+        if !input.is_empty() && !is_key_value && input.fork().parse::<EntraitOpt>().is_err() {
+            input.parse::<syn::Visibility>()?;
             let ident: syn::Ident = input.parse()?;
 
-            impl_trait = Some(ImplTrait(vis, ident));
+            impl_trait = Some(ImplTrait(ident));
 
             if input.peek(syn::token::Comma) {
                 input.parse::<syn::token::Comma>()?;
@@ -31,20 +52,58 @@ impl Parse for EntraitTraitAttr {
 
         let mut debug = None;
         let mut mock_api = None;
+        let mut mock_cfg = None;
         let mut future_send = None;
+        let mut send_cfg = None;
+        let mut impl_sync = None;
         let mut unimock = None;
+        let mut unimock_version = None;
         let mut mockall = None;
+        let mut mry = None;
+        let mut faux = None;
+        let mut stub = None;
+        let mut record = None;
+        let mut forward_impls = None;
         let mut delegation_kind = None;
+        let mut watch = None;
+        let mut registry = None;
+        let mut delegate_defaults = None;
+        let mut dyn_compatible = None;
+        let mut config = None;
+        let mut fingerprint = None;
+        let mut no_std = None;
+        let mut minimal_codegen = None;
+        let mut group = None;
+        let mut where_clause = None;
 
         if !input.is_empty() {
             loop {
                 match input.parse::<EntraitOpt>()? {
                     EntraitOpt::Debug(opt) => debug = Some(opt),
                     EntraitOpt::MockApi(ident) => mock_api = Some(ident),
+                    EntraitOpt::MockCfg(opt) => mock_cfg = Some(opt),
                     EntraitOpt::MaybeSend(send) => future_send = Some(send),
+                    EntraitOpt::SendCfg(opt) => send_cfg = Some(opt),
+                    EntraitOpt::MaybeSync(sync) => impl_sync = Some(sync),
                     EntraitOpt::Unimock(opt) => unimock = Some(opt),
+                    EntraitOpt::UnimockVersion(opt) => unimock_version = Some(opt),
                     EntraitOpt::Mockall(opt) => mockall = Some(opt),
+                    EntraitOpt::Mry(opt) => mry = Some(opt),
+                    EntraitOpt::Faux(opt) => faux = Some(opt),
+                    EntraitOpt::Stub(opt) => stub = Some(opt),
+                    EntraitOpt::Record(opt) => record = Some(opt),
+                    EntraitOpt::ForwardImpls(opt) => forward_impls = Some(opt),
                     EntraitOpt::DelegateBy(kind) => delegation_kind = Some(kind),
+                    EntraitOpt::Watch(opt) => watch = Some(opt),
+                    EntraitOpt::Registry(opt) => registry = Some(opt),
+                    EntraitOpt::DelegateDefaults(opt) => delegate_defaults = Some(opt),
+                    EntraitOpt::Dyn(opt) => dyn_compatible = Some(opt),
+                    EntraitOpt::Config(ty) => config = Some(ty),
+                    EntraitOpt::Fingerprint(opt) => fingerprint = Some(opt),
+                    EntraitOpt::NoStd(opt) => no_std = Some(opt),
+                    EntraitOpt::MinimalCodegen(opt) => minimal_codegen = Some(opt),
+                    EntraitOpt::Group(lit_str) => group = Some(lit_str),
+                    EntraitOpt::WhereClause(opt) => where_clause = Some(opt),
                     entrait_opt => {
                         return Err(syn::Error::new(entrait_opt.span(), "Unsupported option"))
                     }
@@ -65,12 +124,41 @@ impl Parse for EntraitTraitAttr {
                 no_deps: None,
                 debug,
                 export: None,
+                export_cfg: None,
+                mock_cfg,
                 future_send,
+                send_cfg,
+                impl_sync,
                 mock_api,
                 unimock,
+                unimock_version,
                 mockall,
+                mry,
+                faux,
+                stub,
+                record,
+                forward_impls,
+                double: None,
+                const_trait: None,
+                log: None,
+                instrument: None,
+                otel_metrics: None,
+                intercept: None,
+                fingerprint,
+                dependencies: None,
+                trait_doc: None,
+                local: None,
+                no_std,
+                minimal_codegen,
             },
             delegation_kind,
+            watch,
+            registry,
+            delegate_defaults,
+            dyn_compatible,
+            config,
+            group,
+            where_clause,
             crate_idents: CrateIdents::new(span),
         })
     }
@@ -1,7 +1,11 @@
 //! Implementation for invoking entrait on a trait!
 
+mod config;
+mod dyn_trait;
+mod enum_dispatch;
 pub mod input_attr;
 mod out_trait;
+mod registry;
 
 use input_attr::EntraitTraitAttr;
 use proc_macro2::Span;
@@ -14,7 +18,11 @@ use crate::idents::GenericIdents;
 use crate::input::FnInputMode;
 use crate::input::LiteralAttrs;
 use crate::opt::*;
+use crate::send_cfg;
+use crate::signature::returns_impl_trait;
 use crate::sub_attributes::analyze_sub_attributes;
+use crate::sub_attributes::contains_async_trait;
+use crate::sub_attributes::contains_send_async_trait;
 use crate::sub_attributes::SubAttribute;
 use crate::token_util::*;
 use crate::trait_codegen::Supertraits;
@@ -26,12 +34,47 @@ use quote::ToTokens;
 
 use self::out_trait::OutTrait;
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 #[derive(Clone, Copy)]
 struct ContainsAsync(bool);
 
 pub fn output_tokens(
     attr: EntraitTraitAttr,
     item_trait: syn::ItemTrait,
+) -> syn::Result<TokenStream> {
+    let contains_async = item_trait.items.iter().any(|item| match item {
+        syn::TraitItem::Fn(method) => method.sig.asyncness.is_some(),
+        _ => false,
+    });
+
+    // A `Send` `#[async_trait]` decides by itself that the futures are `Send` on every target
+    let send_async_trait = contains_send_async_trait(&analyze_sub_attributes(&item_trait.attrs));
+
+    // The trait itself differs between the `Send` output and the `?Send` one for the other targets
+    match attr
+        .opts
+        .non_send_variant(contains_async && !send_async_trait)
+    {
+        Some(non_send_opts) => {
+            let send_opts = attr.opts.clone();
+            let non_send_attr = EntraitTraitAttr {
+                opts: non_send_opts,
+                ..attr.clone()
+            };
+            send_cfg::gate(
+                gen_output_tokens(attr, item_trait.clone())?,
+                gen_output_tokens(non_send_attr, item_trait)?,
+                &send_opts,
+            )
+        }
+        None => gen_output_tokens(attr, item_trait),
+    }
+}
+
+fn gen_output_tokens(
+    attr: EntraitTraitAttr,
+    item_trait: syn::ItemTrait,
 ) -> syn::Result<TokenStream> {
     if let (None, Some(SpanOpt(Delegate::ByTrait(_), span))) =
         (&attr.impl_trait, &attr.delegation_kind)
@@ -48,7 +91,39 @@ pub fn output_tokens(
         _ => false,
     }));
 
-    let out_trait = out_trait::analyze_trait(item_trait)?;
+    if let Some(SpanOpt(true, span)) = &attr.watch {
+        if attr.impl_trait.is_some()
+            || !matches!(
+                attr.delegation_kind,
+                None | Some(SpanOpt(Delegate::BySelf, _))
+            )
+        {
+            return Err(syn::Error::new(
+                *span,
+                "`watch` is only supported for leaf traits delegating by `Self`",
+            ));
+        }
+        if contains_async.0 {
+            return Err(syn::Error::new(
+                *span,
+                "`watch` does not support async methods, the watched value cannot be borrowed across an `.await`",
+            ));
+        }
+        attr.opts.check_std("`watch`", *span)?;
+    }
+
+    attr.opts.check_impl_sync(contains_async.0)?;
+
+    let mut out_trait = out_trait::analyze_trait(item_trait, attr.opts.future_send().0)?;
+    let config_impl = match &attr.config {
+        Some(config_ty) => Some(config::gen_config_impl(
+            config_ty,
+            &mut out_trait,
+            &attr.crate_idents,
+            &attr.opts,
+        )?),
+        None => None,
+    };
     let sub_attributes = analyze_sub_attributes(&out_trait.attrs);
     let impl_sub_attributes: Vec<_> = sub_attributes
         .iter()
@@ -65,6 +140,92 @@ pub fn output_tokens(
         _ => panic!(),
     };
 
+    // Native async methods and `impl Trait` outputs are not dyn-compatible,
+    // so dynamic dispatch goes through a boxing companion trait:
+    let contains_impl_output = out_trait
+        .fns
+        .iter()
+        .any(|trait_fn| returns_impl_trait(trait_fn.sig()));
+    let delegates_by_dyn_trait = matches!(
+        (&attr.impl_trait, &attr.delegation_kind),
+        (None, Some(SpanOpt(Delegate::ByRef(_), _)))
+    ) && (contains_async.0 || contains_impl_output)
+        && !contains_async_trait(&sub_attributes);
+    let dyn_bridge = match &attr.dyn_compatible {
+        Some(SpanOpt(true, span)) => {
+            dyn_trait::check_dyn_bridge(
+                &out_trait,
+                &attr,
+                &sub_attributes,
+                contains_async.0 || contains_impl_output,
+                *span,
+            )?;
+            true
+        }
+        _ => false,
+    };
+    let dyn_trait = if delegates_by_dyn_trait || dyn_bridge {
+        check_alloc(
+            "Dynamic dispatch of `async` methods and `impl Trait` outputs",
+            out_trait.ident.span(),
+        )?;
+        Some(dyn_trait::gen_dyn_trait(
+            &out_trait,
+            generic_idents,
+            &attr.opts,
+        )?)
+    } else {
+        None
+    };
+    let dyn_bridge_def = match &dyn_trait {
+        Some(dyn_trait) if dyn_bridge => Some(dyn_trait::gen_dyn_bridge(
+            &out_trait,
+            &dyn_trait.ident,
+            generic_idents,
+            &attr.opts,
+            contains_async,
+        )),
+        _ => None,
+    };
+    // The implementation for `Impl<T>` only goes through the companion trait when delegating by `ref`
+    let dyn_trait_ident = dyn_trait
+        .as_ref()
+        .filter(|_| delegates_by_dyn_trait)
+        .map(|dyn_trait| &dyn_trait.ident);
+    check_arc_receivers(&out_trait, &attr, dyn_trait_ident)?;
+    check_swap_outputs(&out_trait, &attr)?;
+    let dyn_trait_def = dyn_trait.as_ref().map(|dyn_trait| &dyn_trait.tokens);
+    let enum_def = match &attr.delegation_kind {
+        Some(SpanOpt(Delegate::ByEnum(targets), span)) => {
+            enum_dispatch::check_enum_dispatch(&out_trait, attr.impl_trait.is_none(), *span)?;
+            Some(enum_dispatch::gen_enum_dispatch(
+                &out_trait,
+                targets,
+                &attr.crate_idents,
+            )?)
+        }
+        _ => None,
+    };
+    let registry_def = match &attr.registry {
+        Some(registry) => {
+            registry::check_registry(
+                &out_trait,
+                registry.1,
+                matches!(
+                    (&attr.impl_trait, &attr.delegation_kind),
+                    (None, Some(SpanOpt(Delegate::ByRef(_), _)))
+                ),
+            )?;
+            Some(registry::gen_registry(
+                &out_trait,
+                dyn_trait_ident.unwrap_or(&out_trait.ident),
+                registry,
+                &attr.crate_idents,
+            ))
+        }
+        None => None,
+    };
+
     let delegation_trait_def = gen_impl_delegation_trait_defs(
         &out_trait,
         &trait_dependency_mode,
@@ -85,6 +246,8 @@ pub fn output_tokens(
         &out_trait.ident,
         &out_trait.generics,
         &out_trait.supertraits,
+        &out_trait.types,
+        &out_trait.consts,
         &out_trait.fns,
         &FnInputMode::RawTrait(LiteralAttrs(&out_trait.attrs)),
     )?;
@@ -93,39 +256,359 @@ pub fn output_tokens(
     let params = out_trait.generics.impl_params_from_idents(
         generic_idents,
         generics::TakesSelfByValue(false), // BUG?
+        attr.opts.required_impl_sync(contains_async.0),
     );
     let args = out_trait
         .generics
         .arguments(&generics::ImplIndirection::None);
     let self_ty = generic_idents.impl_path(trait_ident_span);
+    let mut extra_predicates = match &attr.where_clause {
+        Some(SpanOpt(where_clause, _)) => {
+            impl_where_predicates(where_clause, Some(&generic_idents.impl_t))?
+        }
+        None => vec![],
+    };
+    if let (Some(_), Some(SpanOpt(Delegate::ByRef(_), _))) =
+        (&attr.impl_trait, &attr.delegation_kind)
+    {
+        // The `dyn` implementation trait is `'static`, and so must be its type arguments
+        for param in out_trait.generics.params.iter() {
+            if let syn::GenericParam::Type(type_param) = param {
+                let ident = &type_param.ident;
+                extra_predicates.push(syn::parse_quote! { #ident: 'static });
+            }
+        }
+    }
     let where_clause = ImplWhereClause {
         out_trait: &out_trait,
         contains_async,
         trait_generics: &out_trait.generics,
         generic_idents,
         attr: &attr,
+        dyn_trait_ident,
+        extra_predicates: &extra_predicates,
         span: trait_ident_span,
     };
 
+    let type_items = out_trait
+        .types
+        .iter()
+        .map(|trait_type| gen_delegation_type(trait_type, &out_trait, generic_idents, &attr))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let const_items = out_trait
+        .consts
+        .iter()
+        .map(|trait_const| gen_delegation_const(trait_const, &out_trait, generic_idents, &attr))
+        .collect::<syn::Result<Vec<_>>>()?;
     let method_items = out_trait
         .fns
         .iter()
-        .map(|trait_fn| gen_delegation_method(trait_fn, generic_idents, &attr, contains_async));
+        .filter(|trait_fn| attr.delegates(trait_fn))
+        .map(|trait_fn| {
+            gen_delegation_method(
+                trait_fn,
+                &out_trait,
+                generic_idents,
+                &attr,
+                contains_async,
+                dyn_trait_ident,
+            )
+        });
+
+    let watch_impl = match &attr.watch {
+        Some(SpanOpt(true, span)) => Some(gen_watch_impl(
+            &out_trait,
+            generic_idents,
+            &attr.opts,
+            *span,
+        )),
+        _ => None,
+    };
+    // With `forward_impls`, the trait is implemented for any `Arc<T>` already
+    let arc_impl = match (&attr.impl_trait, &attr.delegation_kind) {
+        (None, None | Some(SpanOpt(Delegate::BySelf, _)))
+            if cfg!(feature = "alloc") && !attr.opts.forward_impls_value() =>
+        {
+            gen_arc_impl(
+                &out_trait,
+                generic_idents,
+                &impl_sub_attributes,
+                &attr.opts,
+                contains_async,
+            )
+        }
+        _ => None,
+    };
+    let leaf_macro = match (&attr.impl_trait, &attr.delegation_kind) {
+        (None, None | Some(SpanOpt(Delegate::BySelf, _))) => {
+            gen_leaf_macro(&out_trait, &impl_sub_attributes)
+        }
+        _ => None,
+    };
 
     let out = quote! {
         #trait_def
 
         #delegation_trait_def
 
+        #dyn_trait_def
+
+        #dyn_bridge_def
+
+        #enum_def
+
         #(#impl_sub_attributes)*
         impl #params #trait_ident #args for #self_ty #where_clause {
+            #(#type_items)*
+            #(#const_items)*
             #(#method_items)*
         }
+
+        #watch_impl
+
+        #registry_def
+
+        #arc_impl
+
+        #leaf_macro
+
+        #config_impl
     };
 
     Ok(out)
 }
 
+/// Implement a leaf trait for `tokio::sync::watch::Receiver<T>`,
+/// where each call is delegated to the latest value seen by the receiver.
+fn gen_watch_impl(
+    out_trait: &OutTrait,
+    generic_idents: &GenericIdents,
+    opts: &Opts,
+    span: proc_macro2::Span,
+) -> TokenStream {
+    let entrait = &generic_idents.crate_idents.entrait;
+    let tokio = &generic_idents.crate_idents.__tokio;
+    let impl_t = &generic_idents.impl_t;
+    let trait_ident = &out_trait.ident;
+    let params = out_trait.generics.impl_params_from_idents(
+        generic_idents,
+        generics::TakesSelfByValue(false),
+        // Watched traits have no async methods
+        opts.required_impl_sync(false),
+    );
+    let args = out_trait
+        .generics
+        .arguments(&generics::ImplIndirection::None);
+    let where_predicates = out_trait.generics.where_predicates.iter();
+    let receiver = quote::quote_spanned! {span=>
+        ::#entrait::#tokio::sync::watch::Receiver
+    };
+
+    let (assoc_items, method_items) = gen_forwarding_items(
+        out_trait,
+        quote! { #impl_t as #trait_ident #args },
+        |self_token| quote! { &*#receiver::borrow(#self_token) },
+    );
+
+    quote! {
+        impl #params #trait_ident #args for #receiver<#impl_t>
+        where
+            #impl_t: #trait_ident #args,
+            #(#where_predicates,)*
+        {
+            #(#assoc_items)*
+            #(#method_items)*
+        }
+    }
+}
+
+/// Implement a leaf trait for `Arc<T>`, so that it is also implemented by the `T` in a shared `Impl<Arc<T>>`.
+///
+/// Not possible when a method needs to own or mutably borrow `self`, or for supertraits other than `Send` and `Sync`.
+fn gen_arc_impl(
+    out_trait: &OutTrait,
+    generic_idents: &GenericIdents,
+    impl_sub_attributes: &[SubAttribute],
+    opts: &Opts,
+    contains_async: ContainsAsync,
+) -> Option<TokenStream> {
+    if !has_only_shared_receivers(out_trait) {
+        return None;
+    }
+
+    let mut thread_safe = contains_async.0 && opts.future_send().0;
+    if let Supertraits::Some { bounds, .. } = &out_trait.supertraits {
+        for bound in bounds {
+            match bound {
+                syn::TypeParamBound::Lifetime(_) => {}
+                syn::TypeParamBound::Trait(trait_bound)
+                    if trait_bound.path.is_ident("Send") || trait_bound.path.is_ident("Sync") =>
+                {
+                    thread_safe = true;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    let entrait = &generic_idents.crate_idents.entrait;
+    let impl_t = &generic_idents.impl_t;
+    let trait_ident = &out_trait.ident;
+    let params = out_trait.generics.impl_params_from_idents(
+        generic_idents,
+        generics::TakesSelfByValue(false),
+        opts.required_impl_sync(contains_async.0),
+    );
+    let args = out_trait
+        .generics
+        .arguments(&generics::ImplIndirection::None);
+    let where_predicates = out_trait.generics.where_predicates.iter();
+    let opt_send_sync = if thread_safe {
+        Some(quote! { + Send + Sync })
+    } else {
+        None
+    };
+
+    let (assoc_items, method_items) = gen_forwarding_items(
+        out_trait,
+        quote! { #impl_t as #trait_ident #args },
+        |self_token| quote! { &**#self_token },
+    );
+
+    Some(quote! {
+        #(#impl_sub_attributes)*
+        impl #params #trait_ident #args for ::#entrait::__alloc::Arc<#impl_t>
+        where
+            #impl_t: #trait_ident #args + ?Sized #opt_send_sync,
+            #(#where_predicates,)*
+        {
+            #(#assoc_items)*
+            #(#method_items)*
+        }
+    })
+}
+
+/// A hidden macro implementing a leaf trait for an application type, by forwarding to one of its fields.
+///
+/// `#[derive(EntraitApp)]` invokes it through its alias `__entrait_leaf_{Trait}` next to the trait, for fields with `#[app(leaf = Trait)]`:
+///
+/// ```text
+/// __entrait_leaf_Foo! { [path::Foo] [impl generics] [where clause] App, field: FieldType }
+/// ```
+///
+/// The macro is exported, so that the alias can be used from other crates too.
+/// Exported macros live at the crate root, so its own name is made unique with a counter.
+fn gen_leaf_macro(
+    out_trait: &OutTrait,
+    impl_sub_attributes: &[SubAttribute],
+) -> Option<TokenStream> {
+    static LEAF_MACRO_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    if !out_trait.generics.params.is_empty() || !has_only_shared_receivers(out_trait) {
+        return None;
+    }
+
+    let trait_ident = &out_trait.ident;
+    let (assoc_items, method_items) = gen_forwarding_items(
+        out_trait,
+        quote! { $field_ty as $($trait_path)* },
+        |self_token| quote! { &#self_token.$field },
+    );
+
+    let alias_ident = quote::format_ident!("__entrait_leaf_{}", trait_ident);
+    let macro_ident = quote::format_ident!(
+        "{}_{}",
+        alias_ident,
+        LEAF_MACRO_COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+
+    Some(quote! {
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! #macro_ident {
+            ([$($trait_path:tt)*] [$($impl_generics:tt)*] [$($where_clause:tt)*] $app:ty, $field:tt: $field_ty:ty) => {
+                #(#impl_sub_attributes)*
+                impl $($impl_generics)* $($trait_path)* for $app $($where_clause)* {
+                    #(#assoc_items)*
+                    #(#method_items)*
+                }
+            };
+        }
+
+        // The alias is only used by crates with an `EntraitApp` forwarding to the trait
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub use #macro_ident as #alias_ident;
+    })
+}
+
+fn has_only_shared_receivers(out_trait: &OutTrait) -> bool {
+    out_trait.fns.iter().all(|trait_fn| {
+        matches!(
+            trait_fn.sig().inputs.first(),
+            Some(syn::FnArg::Receiver(syn::Receiver {
+                reference: Some(_),
+                mutability: None,
+                ..
+            }))
+        )
+    })
+}
+
+/// Items of an impl forwarding every associated item and method to the `target` implementation, like `T as Trait`,
+/// whose receiver is borrowed from `self` with the given expression.
+fn gen_forwarding_items(
+    out_trait: &OutTrait,
+    target: TokenStream,
+    borrow_target: impl Fn(syn::token::SelfValue) -> TokenStream,
+) -> (Vec<TokenStream>, Vec<DelegatingMethod<'_>>) {
+    let type_items = out_trait
+        .types
+        .iter()
+        .map(|trait_type| gen_associated_type(trait_type, target.clone()));
+    let const_items = out_trait.consts.iter().map(|trait_const| {
+        let syn::TraitItemConst {
+            attrs, ident, ty, ..
+        } = trait_const;
+        quote! {
+            #(#attrs)*
+            const #ident: #ty = <#target>::#ident;
+        }
+    });
+    let assoc_items = type_items.chain(const_items).collect();
+    let method_items = out_trait
+        .fns
+        .iter()
+        .map(|trait_fn| {
+            let fn_sig = trait_fn.sig();
+            let fn_ident = &fn_sig.ident;
+            let self_token = match fn_sig.inputs.first() {
+                Some(syn::FnArg::Receiver(receiver)) => receiver.self_token,
+                _ => syn::token::SelfValue::default(),
+            };
+            let arguments = fn_sig.inputs.iter().filter_map(|arg| match arg {
+                syn::FnArg::Receiver(_) => None,
+                syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                    syn::Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+                    _ => {
+                        panic!("Found a non-ident pattern, this should be handled in signature.rs")
+                    }
+                },
+            });
+            let borrowed_target = borrow_target(self_token);
+
+            DelegatingMethod {
+                trait_fn,
+                call: quote! {
+                    <#target>::#fn_ident(#borrowed_target, #(#arguments),*)
+                },
+            }
+        })
+        .collect();
+
+    (assoc_items, method_items)
+}
+
 fn gen_impl_delegation_trait_defs(
     out_trait: &OutTrait,
     trait_dependency_mode: &TraitDependencyMode,
@@ -135,29 +618,49 @@ fn gen_impl_delegation_trait_defs(
 ) -> syn::Result<Option<TokenStream>> {
     let entrait = &generic_idents.crate_idents.entrait;
 
-    let ImplTrait(_, impl_trait_ident) = match &attr.impl_trait {
+    let ImplTrait(impl_trait_ident) = match &attr.impl_trait {
         Some(impl_trait) => impl_trait,
         None => return Ok(None),
     };
 
     let mut trait_copy = out_trait.clone();
     trait_copy.ident = impl_trait_ident.clone();
+    let lifetime_count = lifetime_params(out_trait).count();
+    // Default bodies are left on the trait, they can't be written in terms of the implementation trait
+    trait_copy.fns.retain(|trait_fn| attr.delegates(trait_fn));
+    for trait_fn in trait_copy.fns.iter_mut() {
+        trait_fn.default_body = None;
+    }
+    for trait_type in trait_copy.types.iter_mut() {
+        add_impl_t_outlives_predicates(trait_type);
+    }
 
     let no_mock_opts = Opts {
         mock_api: None,
         unimock: None,
+        unimock_version: None,
         mockall: None,
+        mry: None,
+        faux: None,
+        stub: None,
+        record: None,
+        forward_impls: None,
+        double: None,
+        export_cfg: None,
+        mock_cfg: None,
+        send_cfg: None,
+        instrument: None,
+        trait_doc: None,
+        local: None,
         ..attr.opts
     };
 
     match &attr.delegation_kind {
         Some(SpanOpt(Delegate::ByTrait(delegation_ident), _)) => {
-            trait_copy.generics.params.insert(
-                0,
-                syn::parse_quote! {
-                    EntraitT
-                },
-            );
+            trait_copy
+                .generics
+                .params
+                .insert(lifetime_count, syn::parse_quote! { EntraitT });
             for trait_fn in trait_copy.fns.iter_mut() {
                 if !matches!(trait_fn.sig().inputs.first(), Some(syn::FnArg::Receiver(_))) {
                     continue;
@@ -193,26 +696,36 @@ fn gen_impl_delegation_trait_defs(
                     colon_token: syn::token::Colon::default(),
                     bounds: syn::parse_quote! { 'static },
                 },
+                &trait_copy.types,
+                &trait_copy.consts,
                 &trait_copy.fns,
                 &FnInputMode::RawTrait(LiteralAttrs(&[])),
             )?;
 
+            let delegation_params = trait_copy.generics.params.iter();
+            let delegation_params = quote! { <#(#delegation_params),*> };
+            let where_predicates = &out_trait.generics.where_predicates;
+            let where_clause = if where_predicates.is_empty() {
+                None
+            } else {
+                Some(quote! { where #where_predicates })
+            };
+            let target_args = impl_trait_arguments(out_trait, &quote! { EntraitT });
+
             Ok(Some(quote! {
                 #(#impl_sub_attributes)*
                 #trait_def
 
-                pub trait #delegation_ident<T> {
-                    type Target: #impl_trait_ident<T>;
+                pub trait #delegation_ident #delegation_params #where_clause {
+                    type Target: #impl_trait_ident #target_args;
                 }
             }))
         }
         Some(SpanOpt(Delegate::ByRef(_), _)) => {
-            trait_copy.generics.params.insert(
-                0,
-                syn::parse_quote! {
-                    EntraitT
-                },
-            );
+            trait_copy
+                .generics
+                .params
+                .insert(lifetime_count, syn::parse_quote! { EntraitT });
             for trait_fn in trait_copy.fns.iter_mut() {
                 if !matches!(trait_fn.sig().inputs.first(), Some(syn::FnArg::Receiver(_))) {
                     continue;
@@ -229,7 +742,20 @@ fn gen_impl_delegation_trait_defs(
             let no_mock_opts = Opts {
                 mock_api: None,
                 unimock: None,
+                unimock_version: None,
                 mockall: None,
+                mry: None,
+                faux: None,
+                stub: None,
+                record: None,
+                forward_impls: None,
+                double: None,
+                export_cfg: None,
+                mock_cfg: None,
+                send_cfg: None,
+                instrument: None,
+                trait_doc: None,
+                local: None,
                 ..attr.opts
             };
 
@@ -248,6 +774,8 @@ fn gen_impl_delegation_trait_defs(
                     colon_token: syn::token::Colon::default(),
                     bounds: syn::parse_quote! { 'static },
                 },
+                &trait_copy.types,
+                &trait_copy.consts,
                 &trait_copy.fns,
                 &FnInputMode::RawTrait(LiteralAttrs(&[])),
             )?;
@@ -264,15 +792,167 @@ fn gen_impl_delegation_trait_defs(
     }
 }
 
+/// Associated constants are read from the type that the methods delegate to
+/// Associated types are taken from the type that the methods delegate to
+fn gen_delegation_type(
+    trait_type: &syn::TraitItemType,
+    out_trait: &OutTrait,
+    generic_idents: &GenericIdents,
+    attr: &EntraitTraitAttr,
+) -> syn::Result<TokenStream> {
+    let impl_t = &generic_idents.impl_t;
+
+    let source = match (&attr.impl_trait, &attr.delegation_kind) {
+        (Some(ImplTrait(impl_trait_ident)), Some(SpanOpt(Delegate::ByTrait(_), _))) => {
+            let impl_trait_args = impl_trait_arguments(out_trait, impl_t);
+            quote! { #impl_t::Target as #impl_trait_ident #impl_trait_args }
+        }
+        (_, Some(SpanOpt(Delegate::ByRef(_), span))) => {
+            return Err(syn::Error::new(
+                *span,
+                format!(
+                    "Associated types like `{}` would have to be specified in the `dyn` trait, so they are not supported with `delegate_by = ref`",
+                    trait_type.ident
+                ),
+            ));
+        }
+        _ => {
+            let trait_ident = &out_trait.ident;
+            let args = out_trait
+                .generics
+                .arguments(&generics::ImplIndirection::None);
+            quote! { #impl_t as #trait_ident #args }
+        }
+    };
+
+    Ok(gen_associated_type(trait_type, source))
+}
+
+/// The methods of the implementation trait take `&'a Impl<EntraitT>` instead of `&'a self`,
+/// so a `Self: 'a` bound of a generic associated type also needs `EntraitT: 'a`.
+fn add_impl_t_outlives_predicates(trait_type: &mut syn::TraitItemType) {
+    let lifetimes = trait_type
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| where_clause.predicates.iter())
+        .filter_map(|predicate| match predicate {
+            syn::WherePredicate::Type(predicate_type) => Some(predicate_type),
+            _ => None,
+        })
+        .filter(|predicate_type| {
+            matches!(&predicate_type.bounded_ty, syn::Type::Path(type_path) if type_path.path.is_ident("Self"))
+        })
+        .flat_map(|predicate_type| predicate_type.bounds.iter())
+        .filter_map(|bound| match bound {
+            syn::TypeParamBound::Lifetime(lifetime) => Some(lifetime.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for lifetime in lifetimes {
+        trait_type
+            .generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote! { EntraitT: #lifetime });
+    }
+}
+
+/// `type Item<'a> = <Source>::Item<'a> where ..;`
+fn gen_associated_type(trait_type: &syn::TraitItemType, source: TokenStream) -> TokenStream {
+    let syn::TraitItemType {
+        attrs,
+        ident,
+        generics,
+        ..
+    } = trait_type;
+    let (_, type_args, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #(#attrs)*
+        type #ident #generics = <#source>::#ident #type_args #where_clause;
+    }
+}
+
+fn lifetime_params(out_trait: &OutTrait) -> impl Iterator<Item = &syn::LifetimeParam> {
+    out_trait
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Lifetime(lifetime_param) => Some(lifetime_param),
+            _ => None,
+        })
+}
+
+/// The arguments of the implementation and delegation traits: the trait's own, with `impl_t` after the lifetimes.
+fn impl_trait_arguments(out_trait: &OutTrait, impl_t: &impl ToTokens) -> TokenStream {
+    let lifetimes = lifetime_params(out_trait).map(|param| &param.lifetime);
+    let others = out_trait
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Lifetime(_) => None,
+            syn::GenericParam::Type(type_param) => Some(&type_param.ident),
+            syn::GenericParam::Const(const_param) => Some(&const_param.ident),
+        });
+
+    quote! { <#(#lifetimes,)* #impl_t #(, #others)*> }
+}
+
+fn gen_delegation_const(
+    trait_const: &syn::TraitItemConst,
+    out_trait: &OutTrait,
+    generic_idents: &GenericIdents,
+    attr: &EntraitTraitAttr,
+) -> syn::Result<TokenStream> {
+    let syn::TraitItemConst {
+        attrs, ident, ty, ..
+    } = trait_const;
+    let impl_t = &generic_idents.impl_t;
+    let impl_trait_args = impl_trait_arguments(out_trait, impl_t);
+
+    let value = match (&attr.impl_trait, &attr.delegation_kind) {
+        (Some(ImplTrait(impl_trait_ident)), Some(SpanOpt(Delegate::ByTrait(_), _))) => quote! {
+            <#impl_t::Target as #impl_trait_ident #impl_trait_args>::#ident
+        },
+        (_, Some(SpanOpt(Delegate::ByRef(_), span))) => {
+            return Err(syn::Error::new(
+                *span,
+                format!("Associated constants like `{ident}` make the trait dyn-incompatible, so they are not supported with `delegate_by = ref`, `Arc` or `Box`"),
+            ));
+        }
+        _ => {
+            let trait_ident = &out_trait.ident;
+            let args = out_trait
+                .generics
+                .arguments(&generics::ImplIndirection::None);
+            quote! {
+                <#impl_t as #trait_ident #args>::#ident
+            }
+        }
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        const #ident: #ty = #value;
+    })
+}
+
 fn gen_delegation_method<'s>(
     trait_fn: &'s TraitFn,
+    out_trait: &OutTrait,
     generic_idents: &'s GenericIdents,
     attr: &'s EntraitTraitAttr,
     contains_async: ContainsAsync,
+    dyn_trait_ident: Option<&syn::Ident>,
 ) -> DelegatingMethod<'s> {
     let fn_sig = &trait_fn.sig();
     let fn_ident = &fn_sig.ident;
     let impl_t = &generic_idents.impl_t;
+    let impl_trait_args = impl_trait_arguments(out_trait, impl_t);
 
     let arguments = fn_sig.inputs.iter().filter_map(|arg| match arg {
         syn::FnArg::Receiver(_) => None,
@@ -283,17 +963,87 @@ fn gen_delegation_method<'s>(
     });
     let core = &generic_idents.crate_idents.core;
 
+    // `self` is hygienic in `macro_rules!`, so reuse the receiver's own token
+    let self_token = match fn_sig.inputs.first() {
+        Some(syn::FnArg::Receiver(receiver)) => receiver.self_token,
+        _ => syn::token::SelfValue::default(),
+    };
+
+    if arc_receiver(fn_sig).is_some() {
+        // Checked to delegate by `Arc`, so a clone of the delegate takes the receiver
+        let entrait = &generic_idents.crate_idents.entrait;
+        return DelegatingMethod {
+            trait_fn,
+            call: quote! {
+                ::#entrait::__alloc::Arc::clone((**#self_token).as_ref()).#fn_ident(#(#arguments),*)
+            },
+        };
+    }
+
+    if let Some(dyn_trait_ident) = dyn_trait_ident {
+        let dyn_fn_ident = dyn_trait::dyn_fn_ident(fn_ident);
+        let dyn_ref = match &attr.delegation_kind {
+            Some(SpanOpt(Delegate::ByRef(RefDelegate::Borrow), _)) => {
+                quote! { #self_token.as_ref().borrow() }
+            }
+            Some(SpanOpt(Delegate::ByRef(RefDelegate::Arc), _)) => {
+                quote! { &**#self_token.as_ref().as_ref() }
+            }
+            Some(SpanOpt(Delegate::ByRef(RefDelegate::Box), _)) => {
+                let entrait = &generic_idents.crate_idents.entrait;
+                quote! { &**::#entrait::BoxedDelegate::boxed_delegate(#self_token.as_ref()) }
+            }
+            Some(SpanOpt(Delegate::ByRef(RefDelegate::Swap), _)) => {
+                let entrait = &generic_idents.crate_idents.entrait;
+                let call = if trait_fn.originally_async {
+                    // The loaded target is kept alive by the future, which borrows it
+                    quote! {
+                        async move {
+                            let target = ::#entrait::__arc_swap::ArcSwapAny::load_full(#self_token.as_ref().as_ref());
+                            #dyn_trait_ident::#dyn_fn_ident(&**target, #(#arguments),*).await
+                        }
+                    }
+                } else {
+                    quote! {
+                        #dyn_trait_ident::#dyn_fn_ident(
+                            &***::#entrait::__arc_swap::ArcSwapAny::load(#self_token.as_ref().as_ref()),
+                            #(#arguments),*
+                        )
+                    }
+                };
+                return DelegatingMethod { trait_fn, call };
+            }
+            _ => quote! { #self_token.as_ref().as_ref() },
+        };
+
+        let call = if trait_fn.originally_async {
+            // The `&dyn` borrow must not live across the `.await`, only the (`Send`) boxed future
+            quote! {
+                {
+                    let fut = #dyn_trait_ident::#dyn_fn_ident(#dyn_ref, #(#arguments),*);
+                    fut
+                }
+            }
+        } else {
+            quote! {
+                #dyn_trait_ident::#dyn_fn_ident(#dyn_ref, #(#arguments),*)
+            }
+        };
+
+        return DelegatingMethod { trait_fn, call };
+    }
+
     match (&attr.impl_trait, &attr.delegation_kind) {
-        (Some(ImplTrait(_, impl_trait_ident)), Some(SpanOpt(Delegate::ByTrait(_), _))) => {
+        (Some(ImplTrait(impl_trait_ident)), Some(SpanOpt(Delegate::ByTrait(_), _))) => {
             DelegatingMethod {
                 trait_fn,
                 call: quote! {
                     // TODO: pass additional generic arguments(?)
-                    <#impl_t::Target as #impl_trait_ident<#impl_t>>::#fn_ident(self, #(#arguments),*)
+                    <#impl_t::Target as #impl_trait_ident #impl_trait_args>::#fn_ident(#self_token, #(#arguments),*)
                 },
             }
         }
-        (Some(ImplTrait(_, impl_trait_ident)), Some(SpanOpt(Delegate::ByRef(ref_delegate), _))) => {
+        (Some(ImplTrait(impl_trait_ident)), Some(SpanOpt(Delegate::ByRef(ref_delegate), _))) => {
             let plus_sync = if contains_async.0 {
                 Some(TokenPair(
                     syn::token::Plus::default(),
@@ -305,41 +1055,210 @@ fn gen_delegation_method<'s>(
             let call = match ref_delegate {
                 RefDelegate::AsRef => {
                     quote! {
-                        <#impl_t as ::#core::convert::AsRef<dyn #impl_trait_ident<#impl_t> #plus_sync>>::as_ref(&*self)
-                            .#fn_ident(self, #(#arguments),*)
+                        <#impl_t as ::#core::convert::AsRef<dyn #impl_trait_ident #impl_trait_args #plus_sync>>::as_ref(&*#self_token)
+                            .#fn_ident(#self_token, #(#arguments),*)
                     }
                 }
                 RefDelegate::Borrow => {
                     quote! {
-                        <#impl_t as ::#core::borrow::Borrow<dyn #impl_trait_ident<#impl_t> #plus_sync>>::borrow(&*self)
-                            .#fn_ident(self, #(#arguments),*)
+                        <#impl_t as ::#core::borrow::Borrow<dyn #impl_trait_ident #impl_trait_args #plus_sync>>::borrow(&*#self_token)
+                            .#fn_ident(#self_token, #(#arguments),*)
+                    }
+                }
+                RefDelegate::Arc => {
+                    let entrait = &generic_idents.crate_idents.entrait;
+                    quote! {
+                        <#impl_t as ::#core::convert::AsRef<::#entrait::__alloc::Arc<dyn #impl_trait_ident #impl_trait_args + Send + Sync>>>::as_ref(&*#self_token)
+                            .#fn_ident(#self_token, #(#arguments),*)
+                    }
+                }
+                RefDelegate::Box => {
+                    let entrait = &generic_idents.crate_idents.entrait;
+                    quote! {
+                        <#impl_t as ::#entrait::BoxedDelegate<dyn #impl_trait_ident #impl_trait_args + Send + Sync>>::boxed_delegate(&*#self_token)
+                            .#fn_ident(#self_token, #(#arguments),*)
+                    }
+                }
+                RefDelegate::Swap => {
+                    let entrait = &generic_idents.crate_idents.entrait;
+                    let swap = quote! {
+                        <#impl_t as ::#core::convert::AsRef<::#entrait::__arc_swap::ArcSwap<::#entrait::__alloc::Box<dyn #impl_trait_ident #impl_trait_args + Send + Sync>>>>::as_ref(&*#self_token)
+                    };
+                    if trait_fn.originally_async {
+                        quote! {
+                            async move {
+                                let target = ::#entrait::__arc_swap::ArcSwapAny::load_full(#swap);
+                                target.#fn_ident(#self_token, #(#arguments),*).await
+                            }
+                        }
+                    } else {
+                        quote! {
+                            ::#entrait::__arc_swap::ArcSwapAny::load(#swap)
+                                .#fn_ident(#self_token, #(#arguments),*)
+                        }
                     }
                 }
             };
 
             DelegatingMethod { trait_fn, call }
         }
-        (None, Some(SpanOpt(Delegate::ByRef(RefDelegate::AsRef), _))) => DelegatingMethod {
-            trait_fn,
-            call: quote! {
-                self.as_ref().as_ref().#fn_ident(#(#arguments),*)
-            },
-        },
+        (None, Some(SpanOpt(Delegate::ByRef(RefDelegate::AsRef | RefDelegate::Arc), _))) => {
+            DelegatingMethod {
+                trait_fn,
+                call: quote! {
+                    #self_token.as_ref().as_ref().#fn_ident(#(#arguments),*)
+                },
+            }
+        }
         (None, Some(SpanOpt(Delegate::ByRef(RefDelegate::Borrow), _))) => DelegatingMethod {
             trait_fn,
             call: quote! {
-                self.as_ref().borrow().#fn_ident(#(#arguments),*)
+                #self_token.as_ref().borrow().#fn_ident(#(#arguments),*)
             },
         },
+        (None, Some(SpanOpt(Delegate::ByRef(RefDelegate::Box), _))) => {
+            let entrait = &generic_idents.crate_idents.entrait;
+            DelegatingMethod {
+                trait_fn,
+                call: quote! {
+                    ::#entrait::BoxedDelegate::boxed_delegate(#self_token.as_ref()).#fn_ident(#(#arguments),*)
+                },
+            }
+        }
+        (None, Some(SpanOpt(Delegate::ByEnum(_), _))) => {
+            let enum_ident = enum_dispatch::enum_ident(&out_trait.ident);
+            DelegatingMethod {
+                trait_fn,
+                call: quote! {
+                    <#impl_t as ::#core::convert::AsRef<#enum_ident>>::as_ref(&*#self_token).#fn_ident(#(#arguments),*)
+                },
+            }
+        }
+        (None, Some(SpanOpt(Delegate::ByRef(RefDelegate::Swap), _))) => {
+            let entrait = &generic_idents.crate_idents.entrait;
+            let call = if trait_fn.originally_async {
+                quote! {
+                    async move {
+                        let target = ::#entrait::__arc_swap::ArcSwapAny::load_full(#self_token.as_ref().as_ref());
+                        target.#fn_ident(#(#arguments),*).await
+                    }
+                }
+            } else {
+                quote! {
+                    ::#entrait::__arc_swap::ArcSwapAny::load(#self_token.as_ref().as_ref()).#fn_ident(#(#arguments),*)
+                }
+            };
+            DelegatingMethod { trait_fn, call }
+        }
         _ => DelegatingMethod {
             trait_fn,
             call: quote! {
-                self.as_ref().#fn_ident(#(#arguments),*)
+                #self_token.as_ref().#fn_ident(#(#arguments),*)
+            },
+        },
+    }
+}
+
+/// The receiver of a method taking `self: Arc<Self>`
+fn arc_receiver(sig: &syn::Signature) -> Option<&syn::Receiver> {
+    match sig.inputs.first() {
+        Some(syn::FnArg::Receiver(
+            receiver @ syn::Receiver {
+                reference: None,
+                colon_token: Some(_),
+                ..
             },
+        )) => match receiver.ty.as_ref() {
+            syn::Type::Path(type_path)
+                if type_path
+                    .path
+                    .segments
+                    .last()
+                    .map_or(false, |segment| segment.ident == "Arc") =>
+            {
+                Some(receiver)
+            }
+            _ => None,
         },
+        _ => None,
     }
 }
 
+/// An `Arc<T>` can't be obtained from the `Arc<Impl<T>>` receiver,
+/// so `self: Arc<Self>` methods are only delegated to an `Arc<dyn Trait>` of `T`
+fn check_arc_receivers(
+    out_trait: &OutTrait,
+    attr: &EntraitTraitAttr,
+    dyn_trait_ident: Option<&syn::Ident>,
+) -> syn::Result<()> {
+    let delegates_by_arc = matches!(
+        (&attr.impl_trait, &attr.delegation_kind, dyn_trait_ident),
+        (
+            None,
+            Some(SpanOpt(Delegate::ByRef(RefDelegate::Arc), _)),
+            None
+        )
+    );
+
+    for trait_fn in &out_trait.fns {
+        match arc_receiver(trait_fn.sig()) {
+            Some(receiver) if !delegates_by_arc => return Err(syn::Error::new(
+                syn::spanned::Spanned::span(receiver),
+                "`self: Arc<Self>` receivers are only supported with `delegate_by = Arc`, in traits without `async` methods or `impl Trait` outputs, since the implementation for `Impl<T>` can't get an `Arc` of `T` otherwise",
+            )),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// The target of `delegate_by = swap` is only borrowed from a guard within the delegating method,
+/// so the outputs of synchronous methods can't borrow from it
+fn check_swap_outputs(out_trait: &OutTrait, attr: &EntraitTraitAttr) -> syn::Result<()> {
+    struct BorrowDetector(bool);
+
+    impl syn::visit_mut::VisitMut for BorrowDetector {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+            if lifetime.ident != "static" {
+                self.0 = true;
+            }
+        }
+
+        fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+            if reference.lifetime.is_none() {
+                self.0 = true;
+            }
+            syn::visit_mut::visit_type_reference_mut(self, reference);
+        }
+    }
+
+    if !matches!(
+        &attr.delegation_kind,
+        Some(SpanOpt(Delegate::ByRef(RefDelegate::Swap), _))
+    ) {
+        return Ok(());
+    }
+
+    for trait_fn in &out_trait.fns {
+        if trait_fn.originally_async {
+            continue;
+        }
+        if let syn::ReturnType::Type(_, ty) = &trait_fn.sig().output {
+            let mut detector = BorrowDetector(false);
+            syn::visit_mut::VisitMut::visit_type_mut(&mut detector, &mut ty.as_ref().clone());
+            if detector.0 {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "`delegate_by = swap` can't return borrowed data, since the delegation target may be swapped out at any time",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 struct DelegatingMethod<'s> {
     trait_fn: &'s TraitFn,
     call: TokenStream,
@@ -392,6 +1311,8 @@ struct ImplWhereClause<'g, 'c> {
     trait_generics: &'g generics::TraitGenerics,
     generic_idents: &'g GenericIdents<'c>,
     attr: &'g EntraitTraitAttr,
+    dyn_trait_ident: Option<&'g syn::Ident>,
+    extra_predicates: &'g [syn::WherePredicate],
     span: proc_macro2::Span,
 }
 
@@ -406,34 +1327,36 @@ impl<'g, 'c> ImplWhereClause<'g, 'c> {
                 push_tokens!(
                     stream,
                     delegate_ident,
-                    Lt(self.span),
-                    self.generic_idents.impl_t,
-                    Gt(self.span),
-                    self.plus_sync(),
+                    impl_trait_arguments(self.out_trait, &self.generic_idents.impl_t),
+                    self.opt_plus_impl_sync(),
                     self.plus_static()
                 );
             }
             (
-                Some(ImplTrait(_, impl_trait_ident)),
+                Some(ImplTrait(impl_trait_ident)),
                 Some(SpanOpt(Delegate::ByRef(ref_delegate), _)),
             ) => {
                 self.push_core_delegation_trait(stream, ref_delegate);
-                push_tokens!(
-                    stream,
-                    // Generic arguments:
-                    Lt(self.span),
-                    Dyn(self.span),
-                    impl_trait_ident,
-                    Lt(self.span),
-                    self.generic_idents.impl_t,
-                    Gt(self.span),
-                    if self.contains_async.0 {
-                        Some(self.plus_sync())
-                    } else {
-                        None
-                    },
-                    Gt(self.span)
-                );
+                push_tokens!(stream, Lt(self.span));
+                self.push_dyn_target(stream, ref_delegate, |stream| {
+                    push_tokens!(
+                        stream,
+                        Dyn(self.span),
+                        impl_trait_ident,
+                        impl_trait_arguments(self.out_trait, &self.generic_idents.impl_t),
+                        if self.contains_async.0
+                            && !matches!(
+                                ref_delegate,
+                                RefDelegate::Arc | RefDelegate::Box | RefDelegate::Swap
+                            )
+                        {
+                            Some(self.plus_sync())
+                        } else {
+                            None
+                        }
+                    );
+                });
+                push_tokens!(stream, Gt(self.span));
 
                 if self.contains_async.0 {
                     push_tokens!(stream, self.plus_send(), self.plus_sync());
@@ -442,11 +1365,36 @@ impl<'g, 'c> ImplWhereClause<'g, 'c> {
             }
             (None, Some(SpanOpt(Delegate::ByRef(ref_delegate), _))) => {
                 self.push_core_delegation_trait(stream, ref_delegate);
+                push_tokens!(stream, Lt(self.span));
+                self.push_dyn_target(stream, ref_delegate, |stream| {
+                    push_tokens!(stream, Dyn(self.span));
+                    match self.dyn_trait_ident {
+                        Some(dyn_trait_ident) => {
+                            push_tokens!(
+                                stream,
+                                dyn_trait_ident,
+                                self.trait_generics
+                                    .arguments(&generics::ImplIndirection::None)
+                            );
+                        }
+                        None => {
+                            push_tokens!(stream, self.trait_with_arguments());
+                        }
+                    }
+                });
+                push_tokens!(stream, Gt(self.span));
+
+                if self.contains_async.0 {
+                    push_tokens!(stream, self.plus_send(), self.plus_sync());
+                }
+                push_tokens!(stream, self.plus_static());
+            }
+            (_, Some(SpanOpt(Delegate::ByEnum(_), _))) => {
+                self.push_core_delegation_trait(stream, &RefDelegate::AsRef);
                 push_tokens!(
                     stream,
                     Lt(self.span),
-                    Dyn(self.span),
-                    self.trait_with_arguments(),
+                    enum_dispatch::enum_ident(&self.out_trait.ident),
                     Gt(self.span)
                 );
 
@@ -456,7 +1404,11 @@ impl<'g, 'c> ImplWhereClause<'g, 'c> {
                 push_tokens!(stream, self.plus_static());
             }
             _delegate_to_impl_t => {
-                push_tokens!(stream, self.trait_with_arguments(), self.plus_sync());
+                push_tokens!(
+                    stream,
+                    self.trait_with_arguments(),
+                    self.opt_plus_impl_sync()
+                );
                 if self.contains_async.0 {
                     // There will be a `self.as_ref().fn().await`,
                     // that borrow will need to be 'static for the future to be Send
@@ -469,7 +1421,7 @@ impl<'g, 'c> ImplWhereClause<'g, 'c> {
     fn push_core_delegation_trait(&self, stream: &mut TokenStream, ref_delegate: &RefDelegate) {
         use syn::token::*;
         match ref_delegate {
-            RefDelegate::AsRef => {
+            RefDelegate::AsRef | RefDelegate::Arc | RefDelegate::Swap => {
                 push_tokens!(
                     stream,
                     PathSep(self.span),
@@ -491,6 +1443,75 @@ impl<'g, 'c> ImplWhereClause<'g, 'c> {
                     syn::Ident::new("Borrow", self.span)
                 );
             }
+            RefDelegate::Box => {
+                push_tokens!(
+                    stream,
+                    PathSep(self.span),
+                    self.generic_idents.crate_idents.entrait,
+                    PathSep(self.span),
+                    syn::Ident::new("BoxedDelegate", self.span)
+                );
+            }
+        }
+    }
+
+    /// The type argument of the core delegation trait, which is the `dyn` trait itself,
+    /// or a `Send + Sync` one, possibly in an `Arc` or the `Box` of an `ArcSwap`
+    fn push_dyn_target(
+        &self,
+        stream: &mut TokenStream,
+        ref_delegate: &RefDelegate,
+        push_dyn: impl FnOnce(&mut TokenStream),
+    ) {
+        use syn::token::*;
+        match ref_delegate {
+            RefDelegate::Arc => {
+                push_tokens!(
+                    stream,
+                    PathSep(self.span),
+                    self.generic_idents.crate_idents.entrait,
+                    PathSep(self.span),
+                    syn::Ident::new("__alloc", self.span),
+                    PathSep(self.span),
+                    syn::Ident::new("Arc", self.span),
+                    Lt(self.span)
+                );
+                push_dyn(stream);
+                push_tokens!(stream, self.plus_send(), self.plus_sync(), Gt(self.span));
+            }
+            RefDelegate::Box => {
+                push_dyn(stream);
+                push_tokens!(stream, self.plus_send(), self.plus_sync());
+            }
+            RefDelegate::Swap => {
+                let entrait = &self.generic_idents.crate_idents.entrait;
+                push_tokens!(
+                    stream,
+                    PathSep(self.span),
+                    entrait,
+                    PathSep(self.span),
+                    syn::Ident::new("__arc_swap", self.span),
+                    PathSep(self.span),
+                    syn::Ident::new("ArcSwap", self.span),
+                    Lt(self.span),
+                    PathSep(self.span),
+                    entrait,
+                    PathSep(self.span),
+                    syn::Ident::new("__alloc", self.span),
+                    PathSep(self.span),
+                    syn::Ident::new("Box", self.span),
+                    Lt(self.span)
+                );
+                push_dyn(stream);
+                push_tokens!(
+                    stream,
+                    self.plus_send(),
+                    self.plus_sync(),
+                    Gt(self.span),
+                    Gt(self.span)
+                );
+            }
+            RefDelegate::AsRef | RefDelegate::Borrow => push_dyn(stream),
         }
     }
 
@@ -516,6 +1537,15 @@ impl<'g, 'c> ImplWhereClause<'g, 'c> {
         )
     }
 
+    /// The `Sync` bound on the `T` in `Impl<T>`, for `Send` futures of async methods unless opted out of with `?Sync`
+    fn opt_plus_impl_sync(&self) -> Option<TokenPair<impl ToTokens, impl ToTokens>> {
+        if self.attr.opts.required_impl_sync(self.contains_async.0).0 {
+            Some(self.plus_sync())
+        } else {
+            None
+        }
+    }
+
     fn plus_sync(&self) -> TokenPair<impl ToTokens, impl ToTokens> {
         TokenPair(
             syn::token::Plus(self.span),
@@ -541,5 +1571,9 @@ impl<'g, 'c> quote::ToTokens for ImplWhereClause<'g, 'c> {
         for predicate in &self.trait_generics.where_predicates {
             punctuator.push(predicate);
         }
+
+        for predicate in self.extra_predicates {
+            punctuator.push(predicate);
+        }
     }
 }
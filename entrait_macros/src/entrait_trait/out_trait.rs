@@ -2,42 +2,82 @@ use crate::{
     analyze_generics::TraitFn,
     generics::{FnDeps, TraitGenerics},
     signature::EntraitSignature,
+    sub_attributes::{analyze_sub_attributes, contains_async_trait},
     trait_codegen::{self, Supertraits},
 };
 
+use quote::{quote_spanned, ToTokens};
 use syn::spanned::Spanned;
 
 #[derive(Clone)]
 pub struct OutTrait {
     pub attrs: Vec<syn::Attribute>,
     pub vis: syn::Visibility,
-    pub trait_token: syn::token::Trait,
     pub generics: TraitGenerics,
     pub ident: syn::Ident,
     pub supertraits: trait_codegen::Supertraits,
+    pub types: Vec<syn::TraitItemType>,
+    pub consts: Vec<syn::TraitItemConst>,
     pub fns: Vec<TraitFn>,
 }
 
-pub fn analyze_trait(item_trait: syn::ItemTrait) -> syn::Result<OutTrait> {
-    let mut associated_types = vec![];
+pub fn analyze_trait(item_trait: syn::ItemTrait, future_send: bool) -> syn::Result<OutTrait> {
+    let mut types = vec![];
+    let mut consts = vec![];
     let mut fns = vec![];
+    let async_trait = contains_async_trait(&analyze_sub_attributes(&item_trait.attrs));
 
     for item in item_trait.items.into_iter() {
         match item {
-            syn::TraitItem::Fn(method) => {
+            syn::TraitItem::Fn(mut method) => {
                 let originally_async = method.sig.asyncness.is_some();
+                // Native `async fn`s become `fn`s returning `impl Future`
+                let native_async_default = originally_async && !async_trait;
+                if native_async_default && future_send && method.default.is_some() {
+                    if let Some(predicate) = send_receiver_predicate(&method.sig) {
+                        method
+                            .sig
+                            .generics
+                            .make_where_clause()
+                            .predicates
+                            .push(predicate);
+                    }
+                }
+                let default_body = method.default.map(|block| {
+                    if native_async_default {
+                        quote_spanned! { block.span()=>
+                            {
+                                async move #block
+                            }
+                        }
+                    } else {
+                        block.to_token_stream()
+                    }
+                });
 
                 let entrait_sig = EntraitSignature::new(method.sig);
 
                 fns.push(TraitFn {
                     deps: FnDeps::NoDeps,
                     attrs: method.attrs,
+                    impl_attrs: vec![],
                     entrait_sig,
                     originally_async,
+                    original_ident: None,
+                    default_body,
+                    memo: false,
+                    boxed_future: false,
+                    boxed_output: None,
+                    fn_generics: Default::default(),
+                    fn_mod_path: vec![],
+                    service_method: false,
                 });
             }
             syn::TraitItem::Type(ty) => {
-                associated_types.push(ty);
+                types.push(ty);
+            }
+            syn::TraitItem::Const(item_const) => {
+                consts.push(item_const);
             }
             item => {
                 return Err(syn::Error::new(
@@ -60,7 +100,6 @@ pub fn analyze_trait(item_trait: syn::ItemTrait) -> syn::Result<OutTrait> {
     Ok(OutTrait {
         attrs: item_trait.attrs,
         vis: item_trait.vis,
-        trait_token: item_trait.trait_token,
         ident: item_trait.ident,
         generics: TraitGenerics {
             params: item_trait.generics.params,
@@ -71,6 +110,22 @@ pub fn analyze_trait(item_trait: syn::ItemTrait) -> syn::Result<OutTrait> {
                 .unwrap_or_default(),
         },
         supertraits,
+        types,
+        consts,
         fns,
     })
 }
+
+/// The `Send` future of a default body holds the receiver, like with `async_trait`:
+/// `&self` requires `Self: Sync`, while `&mut self` and `self` require `Self: Send`.
+fn send_receiver_predicate(sig: &syn::Signature) -> Option<syn::WherePredicate> {
+    match sig.inputs.first() {
+        Some(syn::FnArg::Receiver(syn::Receiver {
+            reference: Some(_),
+            mutability: None,
+            ..
+        })) => Some(syn::parse_quote! { Self: ::core::marker::Sync }),
+        Some(syn::FnArg::Receiver(_)) => Some(syn::parse_quote! { Self: ::core::marker::Send }),
+        _ => None,
+    }
+}
@@ -0,0 +1,169 @@
+//! A distributed registry of the delegation targets of a leaf trait.
+//!
+//! ```text
+//! #[entrait(delegate_by = ref, registry = inventory)]
+//! pub trait Repository {
+//!     fn fetch(&self) -> i32;
+//! }
+//!
+//! // In any crate linked into the executable
+//! entrait::register_entrait!(repository::Repository, "postgres", || Box::new(Postgres));
+//!
+//! // In the executable
+//! let repository = RepositoryProvider::find("postgres").unwrap().create();
+//! ```
+//!
+//! The registered `{Trait}Provider`s are collected by `inventory` or by a `linkme` distributed slice.
+//! `register_entrait!` invokes the hidden macro `__entrait_register_{Trait}` next to the trait,
+//! which knows how the provider is submitted to the registry.
+
+use super::out_trait::OutTrait;
+use crate::idents::CrateIdents;
+use crate::opt::{Registry, SpanOpt};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The registry only holds boxed `dyn` targets that the app can own in its delegation target,
+/// so the trait must not be generic over the app
+pub fn check_registry(
+    out_trait: &OutTrait,
+    registry_span: Span,
+    delegates_by_ref: bool,
+) -> syn::Result<()> {
+    if !delegates_by_ref {
+        return Err(syn::Error::new(
+            registry_span,
+            "`registry` is only supported for leaf traits delegating by `ref`, `Arc`, `Box` or `swap`",
+        ));
+    }
+    if !out_trait.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            registry_span,
+            "`registry` is not supported for generic traits, a static registry can't hold a provider for every type argument",
+        ));
+    }
+
+    Ok(())
+}
+
+/// The `{Trait}Provider` type with its registry, and the hidden macro registering a provider
+pub fn gen_registry(
+    out_trait: &OutTrait,
+    target_ident: &syn::Ident,
+    registry: &SpanOpt<Registry>,
+    crate_idents: &CrateIdents,
+) -> TokenStream {
+    static REGISTER_MACRO_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let entrait = &crate_idents.entrait;
+    let vis = &out_trait.vis;
+    let trait_ident = &out_trait.ident;
+    let span = registry.1;
+    let provider_ident = format_ident!("{}Provider", trait_ident, span = span);
+    let provider_doc = format!(
+        " A provider of a [{trait_ident}] implementation, registered with `entrait::register_entrait!`."
+    );
+    let target = quote! { ::#entrait::__alloc::Box<dyn #target_ident + Send + Sync> };
+
+    let (registry_def, all_providers, submit) = match registry.0 {
+        Registry::Inventory => (
+            quote! {
+                ::#entrait::__inventory::collect!(#provider_ident);
+            },
+            quote! {
+                ::#entrait::__inventory::iter::<Self>.into_iter()
+            },
+            quote! {
+                ::#entrait::__inventory::submit! {
+                    $($module)* #provider_ident::__new($name, $create)
+                }
+            },
+        ),
+        Registry::Linkme => {
+            // Linker sections are named after the slice, which must be unique in the executable
+            let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_default();
+            let slice_ident = format_ident!(
+                "__ENTRAIT_PROVIDERS_{}_{}",
+                crate_name,
+                trait_ident,
+                span = span
+            );
+            (
+                quote! {
+                    #[doc(hidden)]
+                    #[::#entrait::__linkme::distributed_slice]
+                    #[linkme(crate = ::#entrait::__linkme)]
+                    #[allow(non_upper_case_globals)]
+                    #vis static #slice_ident: [#provider_ident];
+                },
+                quote! {
+                    #slice_ident.iter()
+                },
+                quote! {
+                    const _: () = {
+                        #[::#entrait::__linkme::distributed_slice($($module)* #slice_ident)]
+                        #[linkme(crate = ::#entrait::__linkme)]
+                        static PROVIDER: $($module)* #provider_ident = $($module)* #provider_ident::__new($name, $create);
+                    };
+                },
+            )
+        }
+    };
+
+    let alias_ident = format_ident!("__entrait_register_{}", trait_ident);
+    let macro_ident = format_ident!(
+        "{}_{}",
+        alias_ident,
+        REGISTER_MACRO_COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+
+    quote! {
+        #[doc = #provider_doc]
+        #vis struct #provider_ident {
+            name: &'static str,
+            create: fn() -> #target,
+        }
+
+        impl #provider_ident {
+            #[doc(hidden)]
+            pub const fn __new(name: &'static str, create: fn() -> #target) -> Self {
+                Self { name, create }
+            }
+
+            /// The name the provider was registered with.
+            pub fn name(&self) -> &'static str {
+                self.name
+            }
+
+            /// Create a new instance of the provided implementation.
+            pub fn create(&self) -> #target {
+                (self.create)()
+            }
+
+            /// All the registered providers, in no particular order.
+            pub fn all() -> impl Iterator<Item = &'static Self> {
+                #all_providers
+            }
+
+            /// The provider registered with the given name.
+            pub fn find(name: &str) -> Option<&'static Self> {
+                Self::all().find(|provider| provider.name == name)
+            }
+        }
+
+        #registry_def
+
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! #macro_ident {
+            ([$($module:tt)*] $name:expr, $create:expr $(,)?) => {
+                #submit
+            };
+        }
+
+        #[doc(hidden)]
+        pub use #macro_ident as #alias_ident;
+    }
+}
@@ -0,0 +1,48 @@
+//! Writing of expansions to files, with `debug = file`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use proc_macro2::TokenStream;
+
+/// Write the pretty-printed `output` of the invocation generating `trait_name`
+/// to `target/entrait-expansions/<crate>/<trait_name>.rs`.
+pub fn write_to_file(trait_name: &str, output: &TokenStream) -> io::Result<PathBuf> {
+    let mut dir = target_dir().join("entrait-expansions");
+    if let Ok(crate_name) = std::env::var("CARGO_CRATE_NAME") {
+        dir.push(crate_name);
+    }
+    fs::create_dir_all(&dir)?;
+
+    // Error output (like a lone `compile_error!`) may not parse as a file
+    let source = match syn::parse2::<syn::File>(output.clone()) {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(_) => output.to_string(),
+    };
+
+    let path = dir.join(format!("{trait_name}.rs"));
+    fs::write(&path, source)?;
+    Ok(path)
+}
+
+/// The target directory of the build.
+///
+/// Unless it's given by `CARGO_TARGET_DIR`, it's the closest `target` directory tagged by cargo,
+/// which is at the workspace root for workspace members.
+fn target_dir() -> PathBuf {
+    if let Some(target_dir) = std::env::var_os("CARGO_TARGET_DIR") {
+        return PathBuf::from(target_dir);
+    }
+
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default());
+    manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("target"))
+        .find(|target_dir| is_cargo_target_dir(target_dir))
+        .unwrap_or_else(|| manifest_dir.join("target"))
+}
+
+fn is_cargo_target_dir(dir: &Path) -> bool {
+    dir.join("CACHEDIR.TAG").is_file()
+}
@@ -0,0 +1,21 @@
+//! Gating everything generated by an invocation with `group = "feature"`.
+
+use proc_macro2::TokenStream;
+use quote::quote_spanned;
+
+/// Put every output item behind `#[cfg(feature = "group")]`
+pub fn gate(output: TokenStream, group: &syn::LitStr) -> syn::Result<TokenStream> {
+    let file: syn::File = syn::parse2(output)?;
+    let span = group.span();
+
+    Ok(file
+        .items
+        .into_iter()
+        .map(|item| {
+            quote_spanned! {span=>
+                #[cfg(feature = #group)]
+                #item
+            }
+        })
+        .collect())
+}
@@ -12,6 +12,9 @@ use crate::idents::CrateIdents;
 use crate::input::FnInputMode;
 use crate::opt::Mockable;
 use crate::opt::Opts;
+use crate::opt::SpanOpt;
+use crate::signature::boxed_future::gen_boxed_output;
+use crate::signature::fn_params::fn_param_idents;
 use crate::sub_attributes::SubAttribute;
 use crate::token_util::push_tokens;
 use crate::token_util::TokenPair;
@@ -41,34 +44,102 @@ impl<'s, TR: ToTokens> FnDelegationCodegen<'s, TR> {
     /// }
     /// ```
     ///
-    pub fn gen_impl_block(&self, trait_fns: &[TraitFn]) -> TokenStream {
-        let params = self.trait_generics.impl_params(
+    pub fn gen_impl_block(
+        &self,
+        impl_types: &[syn::ImplItemType],
+        impl_consts: &[syn::ImplItemConst],
+        trait_fns: &[TraitFn],
+    ) -> TokenStream {
+        // Memoization needs the `Db` of the `Impl<T>`
+        let has_memo = trait_fns.iter().any(|trait_fn| trait_fn.memo);
+        let memo_generics;
+        let trait_generics = match self.trait_dependency_mode {
+            TraitDependencyMode::Generic(idents) if has_memo => {
+                let entrait = &self.crate_idents.entrait;
+                let core = &self.crate_idents.core;
+                let impl_t = &idents.impl_t;
+                let mut generics = self.trait_generics.clone();
+                generics
+                    .where_predicates
+                    .push(syn::parse_quote_spanned! { self.trait_span=>
+                        #impl_t: ::#core::convert::AsRef<::#entrait::memo::Db>
+                    });
+                memo_generics = generics;
+                &memo_generics
+            }
+            _ => self.trait_generics,
+        };
+
+        // Only the futures of async methods hold the deps across `.await`s, which need `T: Sync` when borrowed
+        // and `T: Send` when taken by value, for the futures to be `Send`
+        let (by_value_async_fns, by_ref_async_fns): (Vec<_>, Vec<_>) = trait_fns
+            .iter()
+            .filter(|trait_fn| trait_fn.originally_async)
+            .partition(|trait_fn| {
+                generics::has_any_self_by_value(std::iter::once(trait_fn.sig())).0
+            });
+        let params = trait_generics.impl_params(
             self.trait_dependency_mode,
-            generics::has_any_self_by_value(trait_fns.iter().map(|trait_fn| trait_fn.sig())),
+            generics::TakesSelfByValue(!by_value_async_fns.is_empty() && self.opts.future_send().0),
+            self.opts.required_impl_sync(!by_ref_async_fns.is_empty()),
         );
-        let args = self.trait_generics.arguments(&self.impl_indirection);
+        let args = trait_generics.arguments(&self.impl_indirection);
+        // Default bodies are for hand-written implementations, which are not possible with a blanket impl
+        let has_default_body = trait_fns
+            .iter()
+            .any(|trait_fn| trait_fn.default_body.is_some());
+        // Memoization, interception, services and borrowed concrete deps need the `T` of `Impl<T>`, so they can't use a blanket impl either
+        let has_service = matches!(self.fn_input_mode, FnInputMode::InherentImpl(_));
+        let has_borrowed_deps =
+            matches!(self.trait_dependency_mode, TraitDependencyMode::Generic(_))
+                && trait_fns
+                    .iter()
+                    .any(|trait_fn| matches!(trait_fn.deps, generics::FnDeps::Concrete(_)));
         let self_ty = SelfTy {
             trait_dependency_mode: self.trait_dependency_mode,
             impl_indirection: &self.impl_indirection,
-            mockable: self.opts.mockable(),
+            mockable: if has_default_body
+                || has_memo
+                || has_service
+                || has_borrowed_deps
+                || self.opts.intercept_value()
+            {
+                Mockable::Yes
+            } else {
+                self.opts.mockable()
+            },
             span: self.trait_span,
         };
-        let where_clause = self.trait_generics.impl_where_clause(
+        let where_clause = trait_generics.impl_where_clause(
             trait_fns,
             self.trait_dependency_mode,
             &self.impl_indirection,
             self.trait_span,
         );
 
-        let opt_self_scoping = if let FnInputMode::ImplBlock(ty) = self.fn_input_mode {
-            Some(TokenPair(
-                syn::token::SelfType(ty.span()),
+        let opt_self_scoping = match self.fn_input_mode {
+            FnInputMode::ImplBlock(ty) => Some(TokenPair(
+                syn::token::SelfType(ty.span()).into_token_stream(),
                 syn::token::PathSep(ty.span()),
-            ))
-        } else {
-            None
+            )),
+            FnInputMode::InherentImpl(ty) => Some(TokenPair(
+                quote_spanned! { ty.span()=> <#ty> },
+                syn::token::PathSep(ty.span()),
+            )),
+            _ => None,
         };
 
+        // Associated types can't be declared in an inherent impl, so they are moved to the trait impl
+        let type_items = impl_types.iter();
+
+        // The constants of an impl block are forwarded to the trait
+        let const_items = impl_consts.iter().map(|impl_const| {
+            let syn::ImplItemConst { ident, ty, .. } = impl_const;
+            quote! {
+                const #ident: #ty = #opt_self_scoping #ident;
+            }
+        });
+
         let items = trait_fns.iter().map(|trait_fn| {
             let fn_item = self.gen_delegating_fn_item(trait_fn, self.trait_span, &opt_self_scoping);
 
@@ -85,10 +156,17 @@ impl<'s, TR: ToTokens> FnDelegationCodegen<'s, TR> {
 
         let trait_span = self.trait_span;
         let trait_ref = &self.trait_ref;
+        let opt_const = if self.opts.const_trait_value() {
+            Some(syn::token::Const(trait_span))
+        } else {
+            None
+        };
 
         quote_spanned! { trait_span=>
             #(#trait_impl_sub_attributes)*
-            impl #params #trait_ref #args for #self_ty #where_clause {
+            impl #params #opt_const #trait_ref #args for #self_ty #where_clause {
+                #(#type_items)*
+                #(#const_items)*
                 #(#items)*
             }
         }
@@ -105,13 +183,39 @@ impl<'s, TR: ToTokens> FnDelegationCodegen<'s, TR> {
         let trait_fn_sig = &trait_fn.sig();
         let deps = &trait_fn.deps;
 
-        let mut fn_ident = trait_fn.sig().ident.clone();
+        let mut fn_ident = trait_fn.fn_ident().clone();
         fn_ident.set_span(span);
+        let fn_mod_path = &trait_fn.fn_mod_path;
 
         let opt_self_comma = match (deps, entrait_sig.sig.inputs.first(), &self.impl_indirection) {
-            (generics::FnDeps::NoDeps { .. }, _, _) | (_, None, _) => None,
+            (generics::FnDeps::NoDeps, _, _) | (_, None, _) => None,
             (_, _, ImplIndirection::Static { .. } | ImplIndirection::Dynamic { .. }) => None,
-            (_, Some(_), _) => Some(SelfArgComma(&self.impl_indirection, span)),
+            // Concrete deps in an impl for `Impl<T>` are borrowed from the `T`
+            (generics::FnDeps::Concrete(ty), Some(_), ImplIndirection::None)
+                if matches!(self.trait_dependency_mode, TraitDependencyMode::Generic(_)) =>
+            {
+                Some(SelfArgComma::Borrowed(
+                    ty,
+                    &self.crate_idents.core,
+                    receiver_span(trait_fn_sig).unwrap_or(span),
+                ))
+            }
+            (_, Some(_), _) => Some(SelfArgComma::Deps(
+                &self.impl_indirection,
+                receiver_span(trait_fn_sig).unwrap_or(span),
+            )),
+        };
+
+        // The service of an impl block is borrowed from the `T` of `Impl<T>`
+        let opt_service_comma = match self.fn_input_mode {
+            FnInputMode::InherentImpl(ty) if trait_fn.service_method => {
+                let core = &self.crate_idents.core;
+                let self_token = syn::token::SelfValue(receiver_span(trait_fn_sig).unwrap_or(span));
+                Some(quote_spanned! { span=>
+                    ::#core::convert::AsRef::<#ty>::as_ref(&**#self_token),
+                })
+            }
+            _ => None,
         };
 
         let arguments = entrait_sig
@@ -128,14 +232,288 @@ impl<'s, TR: ToTokens> FnDelegationCodegen<'s, TR> {
                 },
             });
 
-        let opt_dot_await = trait_fn.opt_dot_await(span);
+        let opt_turbofish = gen_opt_turbofish(trait_fn, span);
+
+        let body = if trait_fn.memo {
+            self.gen_memo_body(
+                trait_fn,
+                span,
+                quote! { #opt_self_scoping #(#fn_mod_path::)* #fn_ident },
+                opt_self_comma,
+            )
+        } else {
+            let opt_dot_await = trait_fn.opt_dot_await(span);
+            let call = quote_spanned! { span=>
+                #opt_self_scoping #(#fn_mod_path::)* #fn_ident #opt_turbofish(#opt_service_comma #opt_self_comma #(#arguments),*) #opt_dot_await
+            };
+
+            match &trait_fn_sig.unsafety {
+                // The caller of the unsafe trait method upholds the contract of the entraited fn
+                Some(unsafety) => quote_spanned! { span=> #unsafety { #call } },
+                None => call,
+            }
+        };
+
+        let body = match &trait_fn.boxed_output {
+            Some(boxed_output) => {
+                gen_boxed_output(boxed_output, body, &self.crate_idents.entrait, span)
+            }
+            None => body,
+        };
+
+        let body = if self.opts.log_value() {
+            self.gen_log_body(trait_fn, span, body)
+        } else {
+            body
+        };
+
+        let body = if self.opts.intercept_value() {
+            self.gen_intercept_body(trait_fn, span, body)
+        } else {
+            body
+        };
+
+        let body = if self.opts.otel_metrics_value() {
+            self.gen_otel_metrics_body(trait_fn, span, body)
+        } else {
+            body
+        };
+
+        let body = match &self.opts.instrument {
+            Some(SpanOpt(fields, _)) => self.gen_instrument_body(trait_fn, span, fields, body),
+            None => body,
+        };
+
+        let body = if trait_fn.boxed_future {
+            let entrait = &self.crate_idents.entrait;
+            quote_spanned! { span=>
+                ::#entrait::__alloc::Box::pin(async move { #body })
+            }
+        } else {
+            body
+        };
+
+        let delegation_attrs = trait_fn.delegation_attrs();
 
         quote_spanned! { span=>
+            #(#delegation_attrs)*
             #trait_fn_sig {
-                #opt_self_scoping #fn_ident(#opt_self_comma #(#arguments),*) #opt_dot_await
+                #body
             }
         }
     }
+
+    /// Wrap the delegation in `log` records: a debug record for every call,
+    /// and an error record with the `Debug`-formatted error when a `Result` output is an `Err`.
+    fn gen_log_body(&self, trait_fn: &TraitFn, span: Span, body: TokenStream) -> TokenStream {
+        let entrait = &self.crate_idents.entrait;
+        let core = &self.crate_idents.core;
+        let log = quote_spanned! { span=> ::#entrait::__log };
+
+        let trait_name = self.trait_name();
+        let method_name = trait_fn.sig().ident.to_string();
+        let call_name = format!("{trait_name}::{method_name}");
+
+        let output = syn::Ident::new("output", Span::mixed_site());
+        let error = syn::Ident::new("error", Span::mixed_site());
+
+        let opt_error_record = if returns_result(trait_fn.sig()) {
+            let message = format!("{call_name} failed: {{:?}}");
+            Some(quote_spanned! { span=>
+                if let ::#core::result::Result::Err(#error) = &#output {
+                    #log::error!(trait_name = #trait_name, method = #method_name; #message, #error);
+                }
+            })
+        } else {
+            None
+        };
+
+        quote_spanned! { span=>
+            #log::debug!(trait_name = #trait_name, method = #method_name; #call_name);
+            let #output = { #body };
+            #opt_error_record
+            #output
+        }
+    }
+
+    /// Call the hooks of the application's `CallInterceptor` around the delegation:
+    /// `before` the call, `error` when a `Result` output is an `Err`, and `after` the call.
+    fn gen_intercept_body(&self, trait_fn: &TraitFn, span: Span, body: TokenStream) -> TokenStream {
+        let entrait = &self.crate_idents.entrait;
+        let core = &self.crate_idents.core;
+        let intercept = quote_spanned! { span=> ::#entrait::intercept };
+
+        let trait_name = self.trait_name();
+        let method_name = trait_fn.sig().ident.to_string();
+        let self_value = syn::token::SelfValue(receiver_span(trait_fn.sig()).unwrap_or(span));
+
+        let call = syn::Ident::new("call", Span::mixed_site());
+        let output = syn::Ident::new("output", Span::mixed_site());
+        let error = syn::Ident::new("error", Span::mixed_site());
+
+        let opt_error_hook = if returns_result(trait_fn.sig()) {
+            Some(quote_spanned! { span=>
+                if let ::#core::result::Result::Err(#error) = &#output {
+                    #intercept::CallInterceptor::error(&**#self_value, &#call, #error);
+                }
+            })
+        } else {
+            None
+        };
+
+        quote_spanned! { span=>
+            let #call = #intercept::Call {
+                trait_name: #trait_name,
+                method: #method_name,
+            };
+            #intercept::CallInterceptor::before(&**#self_value, &#call);
+            let #output = { #body };
+            #opt_error_hook
+            #intercept::CallInterceptor::after(&**#self_value, &#call);
+            #output
+        }
+    }
+
+    /// Time the delegation, and record it in the OpenTelemetry metrics of `entrait::otel_metrics`.
+    fn gen_otel_metrics_body(
+        &self,
+        trait_fn: &TraitFn,
+        span: Span,
+        body: TokenStream,
+    ) -> TokenStream {
+        let entrait = &self.crate_idents.entrait;
+        let core = &self.crate_idents.core;
+
+        let trait_name = self.trait_name();
+        let method_name = trait_fn.sig().ident.to_string();
+
+        let timer = syn::Ident::new("timer", Span::mixed_site());
+        let output = syn::Ident::new("output", Span::mixed_site());
+
+        let failed = if returns_result(trait_fn.sig()) {
+            quote_spanned! { span=>
+                ::#core::matches!(&#output, ::#core::result::Result::Err(_))
+            }
+        } else {
+            quote_spanned! { span=> false }
+        };
+
+        quote_spanned! { span=>
+            let #timer = ::#entrait::otel_metrics::CallTimer::start(#trait_name, #method_name);
+            let #output = { #body };
+            #timer.finish(#failed);
+            #output
+        }
+    }
+
+    /// Run the delegation inside a `tracing` span named like `Trait::method`,
+    /// with the selected arguments of the method as `Debug`-formatted fields.
+    ///
+    /// The span is entered for the duration of a sync call, and attached to the future of an `async` call.
+    fn gen_instrument_body(
+        &self,
+        trait_fn: &TraitFn,
+        span: Span,
+        fields: &[syn::Ident],
+        body: TokenStream,
+    ) -> TokenStream {
+        let entrait = &self.crate_idents.entrait;
+        let tracing = quote_spanned! { span=> ::#entrait::__tracing };
+
+        let span_name = format!("{}::{}", self.trait_name(), trait_fn.sig().ident);
+        let fields = fields
+            .iter()
+            .filter(|field| fn_param_idents(trait_fn.sig()).any(|ident| ident == *field));
+
+        let tracing_span = syn::Ident::new("span", Span::mixed_site());
+        let new_span = quote_spanned! { span=>
+            let #tracing_span = #tracing::info_span!(#span_name, #(#fields = ?#fields),*);
+        };
+
+        if trait_fn.originally_async {
+            quote_spanned! { span=>
+                #new_span
+                #tracing::Instrument::instrument(async move { #body }, #tracing_span).await
+            }
+        } else {
+            let entered = syn::Ident::new("entered", Span::mixed_site());
+            quote_spanned! { span=>
+                #new_span
+                let #entered = #tracing_span.enter();
+                #body
+            }
+        }
+    }
+
+    fn trait_name(&self) -> String {
+        self.trait_ref
+            .to_token_stream()
+            .to_string()
+            .replace(' ', "")
+    }
+
+    /// Generate a delegating body that looks up the output in the `memo::Db` before calling the entraited fn.
+    /// The arguments are copied into the key, and lent to the entraited fn from there.
+    fn gen_memo_body(
+        &self,
+        trait_fn: &TraitFn,
+        span: Span,
+        fn_path: TokenStream,
+        opt_self_comma: Option<SelfArgComma>,
+    ) -> TokenStream {
+        let entrait = &self.crate_idents.entrait;
+        let core = &self.crate_idents.core;
+        let trait_fn_sig = trait_fn.sig();
+        let self_token = syn::token::SelfValue(receiver_span(trait_fn_sig).unwrap_or(span));
+
+        let db = match self.trait_dependency_mode {
+            // `self` is an `Impl<T>`
+            TraitDependencyMode::Generic(_) => quote_spanned! { span=>
+                ::#core::convert::AsRef::<::#entrait::memo::Db>::as_ref(&**#self_token)
+            },
+            TraitDependencyMode::Concrete(_) => quote_spanned! { span=>
+                ::#core::convert::AsRef::<::#entrait::memo::Db>::as_ref(#self_token)
+            },
+        };
+
+        let query = syn::Ident::new("EntraitQuery", Span::mixed_site());
+        let key = syn::Ident::new("key", Span::mixed_site());
+
+        let mut key_elems = vec![];
+        let mut arguments = vec![];
+
+        for (index, pat_type) in trait_fn_sig
+            .inputs
+            .iter()
+            .filter_map(|fn_arg| match fn_arg {
+                syn::FnArg::Receiver(_) => None,
+                syn::FnArg::Typed(pat_type) => Some(pat_type),
+            })
+            .enumerate()
+        {
+            let ident = match pat_type.pat.as_ref() {
+                syn::Pat::Ident(pat_ident) => &pat_ident.ident,
+                _ => panic!("Found a non-ident pattern, this should be handled in signature.rs"),
+            };
+            let index = syn::Index::from(index);
+
+            if let syn::Type::Reference(_) = pat_type.ty.as_ref() {
+                key_elems.push(quote! { ::#entrait::__alloc::ToOwned::to_owned(#ident) });
+                arguments.push(quote! { &#key.#index });
+            } else {
+                key_elems.push(quote! { #ident });
+                arguments.push(quote! { ::#core::clone::Clone::clone(&#key.#index) });
+            }
+        }
+
+        quote_spanned! { span=>
+            struct #query;
+
+            ::#entrait::memo::Db::memoize::<#query, _, _>(#db, (#(#key_elems,)*), |#key| {
+                #fn_path(#opt_self_comma #(#arguments),*)
+            })
+        }
+    }
 }
 
 struct SelfTy<'g, 'c> {
@@ -156,10 +534,10 @@ impl<'g, 'c> quote::ToTokens for SelfTy<'g, 'c> {
                         push_tokens!(stream, idents.impl_t)
                     }
                 }
-                ImplIndirection::Static { ty } => {
+                ImplIndirection::Static { ty, .. } => {
                     push_tokens!(stream, ty);
                 }
-                ImplIndirection::Dynamic { ty } => {
+                ImplIndirection::Dynamic { ty, .. } => {
                     push_tokens!(stream, ty);
                 }
             },
@@ -170,13 +548,114 @@ impl<'g, 'c> quote::ToTokens for SelfTy<'g, 'c> {
     }
 }
 
-// i.e. `self,`
-struct SelfArgComma<'g>(&'g ImplIndirection<'g>, Span);
+/// Explicit generic arguments for calling the entraited fn from a generic method, like `::<_, T>`,
+/// since method generics only used by the output can't be inferred.
+///
+/// The deps parameter, and closure parameters passed as trait objects, are left to inference.
+pub fn gen_opt_turbofish(trait_fn: &TraitFn, span: Span) -> Option<TokenStream> {
+    let method_generics = &trait_fn.sig().generics;
+    let is_method_param = |ident: &syn::Ident| {
+        method_generics
+            .type_params()
+            .any(|param| param.ident == *ident)
+            || method_generics
+                .const_params()
+                .any(|param| param.ident == *ident)
+    };
+
+    let fn_params = &trait_fn.fn_generics.params;
+    if !fn_params.iter().any(|param| match param {
+        syn::GenericParam::Type(type_param) => is_method_param(&type_param.ident),
+        syn::GenericParam::Const(const_param) => is_method_param(&const_param.ident),
+        syn::GenericParam::Lifetime(_) => false,
+    }) {
+        return None;
+    }
+
+    let args = fn_params.iter().filter_map(|param| match param {
+        syn::GenericParam::Lifetime(_) => None,
+        syn::GenericParam::Type(type_param) => {
+            let ident = &type_param.ident;
+            if is_method_param(ident) || trait_fn.deps.mentions_param(ident) {
+                Some(quote_spanned! { span=> #ident })
+            } else {
+                Some(quote_spanned! { span=> _ })
+            }
+        }
+        syn::GenericParam::Const(const_param) => {
+            let ident = &const_param.ident;
+            Some(quote_spanned! { span=> #ident })
+        }
+    });
+
+    Some(quote_spanned! { span=> ::<#(#args),*> })
+}
+
+/// Whether the output type is named `Result`, like `core::result::Result` and its aliases
+fn returns_result(sig: &syn::Signature) -> bool {
+    match &sig.output {
+        syn::ReturnType::Type(_, ty) => match ty.as_ref() {
+            syn::Type::Path(type_path) => matches!(
+                type_path.path.segments.last(),
+                Some(segment) if segment.ident == "Result"
+            ),
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
+
+/// The span of the `self` receiver.
+///
+/// `self` is hygienic in `macro_rules!`, so the token passed along in the delegating call
+/// must resolve in the same context as the receiver of the signature.
+fn receiver_span(sig: &syn::Signature) -> Option<Span> {
+    match sig.inputs.first() {
+        Some(syn::FnArg::Receiver(receiver)) => Some(receiver.self_token.span),
+        _ => None,
+    }
+}
+
+/// The trait that borrows concrete deps from the `T` of `Impl<T>`, and its method:
+/// `AsRef` for trait objects, since `Borrow` is only implemented for sized types, and `Borrow` for other types.
+pub fn concrete_deps_borrow(
+    ty: &syn::Type,
+    core: &syn::Ident,
+    span: Span,
+) -> (TokenStream, syn::Ident) {
+    match ty {
+        syn::Type::TraitObject(_) => (
+            quote_spanned! { span=> ::#core::convert::AsRef },
+            syn::Ident::new("as_ref", span),
+        ),
+        _ => (
+            quote_spanned! { span=> ::#core::borrow::Borrow },
+            syn::Ident::new("borrow", span),
+        ),
+    }
+}
+
+enum SelfArgComma<'g> {
+    /// i.e. `self,`
+    Deps(&'g ImplIndirection<'g>, Span),
+    /// i.e. `::core::borrow::Borrow::<Config>::borrow(&**self),`, see [concrete_deps_borrow]
+    Borrowed(&'g syn::Type, &'g syn::Ident, Span),
+}
 
 impl<'g> quote::ToTokens for SelfArgComma<'g> {
     fn to_tokens(&self, stream: &mut TokenStream) {
-        let span = self.1;
-        match &self.0 {
+        let (impl_indirection, span) = match self {
+            Self::Deps(impl_indirection, span) => (impl_indirection, *span),
+            Self::Borrowed(ty, core, span) => {
+                let self_token = syn::token::SelfValue(*span);
+                let (borrow_trait, borrow_fn) = concrete_deps_borrow(ty, core, *span);
+                stream.extend(quote_spanned! { *span=>
+                    #borrow_trait::<#ty>::#borrow_fn(&**#self_token),
+                });
+                return;
+            }
+        };
+        match impl_indirection {
             ImplIndirection::None => {
                 push_tokens!(stream, syn::token::SelfValue(span), syn::token::Comma(span));
             }
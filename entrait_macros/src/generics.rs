@@ -3,14 +3,23 @@ use proc_macro2::TokenStream;
 use crate::{
     analyze_generics::TraitFn,
     idents::GenericIdents,
-    token_util::{push_tokens, EmptyToken, Punctuator, TokenPair},
+    opt::ImplSync,
+    token_util::{mentions_ident, push_tokens, EmptyToken, Punctuator, TokenPair},
 };
+use quote::ToTokens;
+use std::collections::BTreeSet;
 
 #[derive(Clone)]
 pub enum ImplIndirection<'s> {
     None,
-    Static { ty: &'s syn::Type },
-    Dynamic { ty: &'s syn::Type },
+    Static {
+        ty: &'s syn::Type,
+        trait_args: &'s [syn::GenericArgument],
+    },
+    Dynamic {
+        ty: &'s syn::Type,
+        trait_args: &'s [syn::GenericArgument],
+    },
 }
 
 #[derive(Clone, Copy)]
@@ -25,9 +34,6 @@ pub enum TraitIndirection {
     DynamicImpl,
 }
 
-#[derive(Clone, Copy)]
-pub struct UseAssociatedFuture(pub bool);
-
 #[derive(Clone, Copy)]
 pub struct TakesSelfByValue(pub bool);
 
@@ -45,11 +51,28 @@ pub enum FnDeps {
     Generic {
         generic_param: Option<syn::Ident>,
         trait_bounds: Vec<syn::TypeParamBound>,
+        /// Bounds like `D: 'a` naming lifetimes of the fn, which become `Self: 'a` on the trait method
+        outlives: Vec<syn::Lifetime>,
     },
     Concrete(Box<syn::Type>),
     NoDeps,
 }
 
+impl FnDeps {
+    /// Whether a generic parameter of the entraited fn is part of the deps type or its bounds.
+    /// Such parameters are needed by the `impl` block, so they become generic parameters of the trait.
+    /// Other type parameters stay on the trait method.
+    pub fn mentions_param(&self, ident: &syn::Ident) -> bool {
+        match self {
+            Self::Generic { trait_bounds, .. } => trait_bounds
+                .iter()
+                .any(|bound| mentions_ident(bound.to_token_stream(), ident)),
+            Self::Concrete(ty) => mentions_ident(ty.to_token_stream(), ident),
+            Self::NoDeps => false,
+        }
+    }
+}
+
 pub enum TraitDependencyMode<'t, 'c> {
     Generic(GenericIdents<'c>),
     Concrete(&'t syn::Type),
@@ -67,6 +90,7 @@ impl TraitGenerics {
             params: &self.params,
             impl_t: None,
             takes_self_by_value: TakesSelfByValue(false),
+            impl_sync: ImplSync(true),
         }
     }
 
@@ -80,7 +104,8 @@ impl TraitGenerics {
         &'i self,
         trait_dependency_mode: &'i TraitDependencyMode<'i, '_>,
         takes_self_by_value: TakesSelfByValue,
-    ) -> ParamsGenerator<'_> {
+        impl_sync: ImplSync,
+    ) -> ParamsGenerator<'i> {
         ParamsGenerator {
             params: &self.params,
             impl_t: match trait_dependency_mode {
@@ -88,6 +113,7 @@ impl TraitGenerics {
                 TraitDependencyMode::Concrete(_) => None,
             },
             takes_self_by_value,
+            impl_sync,
         }
     }
 
@@ -95,11 +121,13 @@ impl TraitGenerics {
         &'i self,
         idents: &'i GenericIdents,
         takes_self_by_value: TakesSelfByValue,
-    ) -> ParamsGenerator<'_> {
+        impl_sync: ImplSync,
+    ) -> ParamsGenerator<'i> {
         ParamsGenerator {
             params: &self.params,
             impl_t: Some(&idents.impl_t),
             takes_self_by_value,
+            impl_sync,
         }
     }
 
@@ -162,6 +190,7 @@ pub struct ParamsGenerator<'g> {
     params: &'g syn::punctuated::Punctuated<syn::GenericParam, syn::token::Comma>,
     impl_t: Option<&'g syn::Ident>,
     takes_self_by_value: TakesSelfByValue,
+    impl_sync: ImplSync,
 }
 
 impl<'g> quote::ToTokens for ParamsGenerator<'g> {
@@ -173,37 +202,47 @@ impl<'g> quote::ToTokens for ParamsGenerator<'g> {
             syn::token::Gt::default(),
         );
 
+        // Lifetimes must be declared before the impl T
+        let (lifetimes, others): (Vec<_>, Vec<_>) = self
+            .params
+            .iter()
+            .partition(|param| matches!(param, syn::GenericParam::Lifetime(_)));
+
+        for param in lifetimes {
+            punctuator.push(param);
+        }
+
         if let Some(impl_t) = &self.impl_t {
             punctuator.push_fn(|stream| {
-                push_tokens!(
-                    stream,
-                    impl_t,
-                    syn::token::Colon::default(),
-                    syn::Ident::new("Sync", proc_macro2::Span::call_site())
-                );
+                push_tokens!(stream, impl_t, syn::token::Colon::default());
 
-                if self.takes_self_by_value.0 {
+                // Can be opted out of with `?Sync`
+                if self.impl_sync.0 {
                     push_tokens!(
                         stream,
-                        syn::token::Plus::default(),
-                        // In case T is not a reference, it has to be Send
-                        syn::Ident::new("Send", proc_macro2::Span::call_site())
+                        syn::Ident::new("Sync", proc_macro2::Span::call_site()),
+                        syn::token::Plus::default()
                     );
                 }
 
-                // if self.use_associated_future.0 {
-                if true {
+                if self.takes_self_by_value.0 {
                     push_tokens!(
                         stream,
-                        syn::token::Plus::default(),
-                        // Deps must be 'static for zero-cost futures to work
-                        syn::Lifetime::new("'static", proc_macro2::Span::call_site())
+                        // In case T is not a reference, it has to be Send
+                        syn::Ident::new("Send", proc_macro2::Span::call_site()),
+                        syn::token::Plus::default()
                     );
                 }
+
+                push_tokens!(
+                    stream,
+                    // Deps must be 'static for zero-cost futures to work
+                    syn::Lifetime::new("'static", proc_macro2::Span::call_site())
+                );
             });
         }
 
-        for param in self.params {
+        for param in others {
             punctuator.push(param);
         }
     }
@@ -224,11 +263,21 @@ impl<'g> quote::ToTokens for ArgumentsGenerator<'g> {
             syn::token::Gt::default(),
         );
 
-        if matches!(
-            &self.impl_indirection,
-            ImplIndirection::Static { .. } | ImplIndirection::Dynamic { .. }
-        ) {
+        if let ImplIndirection::Static { trait_args, .. }
+        | ImplIndirection::Dynamic { trait_args, .. } = &self.impl_indirection
+        {
+            // The arguments of a generic implementation trait, with `EntraitT` after the lifetimes
+            let (lifetimes, others): (Vec<_>, Vec<_>) = trait_args
+                .iter()
+                .partition(|arg| matches!(arg, syn::GenericArgument::Lifetime(_)));
+
+            for arg in lifetimes {
+                punctuator.push(arg);
+            }
             punctuator.push(syn::Ident::new("EntraitT", proc_macro2::Span::call_site()));
+            for arg in others {
+                punctuator.push(arg);
+            }
         }
 
         for pair in self.params.pairs() {
@@ -325,6 +374,9 @@ impl<'g, 's, 'c> quote::ToTokens for ImplWhereClauseGenerator<'g, 's, 'c> {
     }
 }
 
+/// The bounds of the deps of all the functions, like `Self: Foo + Bar`.
+///
+/// Functions of a module often share dependencies, so every bound is only stated once, where it first appears.
 fn push_impl_t_bounds(
     stream: &mut TokenStream,
     bound_param: impl quote::ToTokens,
@@ -337,11 +389,14 @@ fn push_impl_t_bounds(
         syn::token::Plus(span),
         EmptyToken,
     );
+    let mut pushed_bounds = BTreeSet::new();
 
     for trait_fn in trait_fns {
         if let FnDeps::Generic { trait_bounds, .. } = &trait_fn.deps {
             for bound in trait_bounds {
-                bound_punctuator.push(bound);
+                if pushed_bounds.insert(bound.to_token_stream().to_string()) {
+                    bound_punctuator.push(bound);
+                }
             }
         }
     }
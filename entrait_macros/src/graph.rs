@@ -0,0 +1,256 @@
+//! Export of the dependency graph between entraited traits, as a Graphviz DOT file.
+//!
+//! When the `ENTRAIT_GRAPH_OUT` environment variable is set during compilation,
+//! every entraited function or module updates the file at that path with the edges from its trait
+//! to the traits (or concrete types) its functions depend on.
+//! Each update replaces the edges previously recorded for the same trait, and the file stays a valid `digraph`.
+
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use quote::ToTokens;
+
+use crate::analyze_generics::TraitFn;
+use crate::generics::FnDeps;
+use crate::opt::Opts;
+
+const GRAPH_OUT_VAR: &str = "ENTRAIT_GRAPH_OUT";
+
+/// Bounds that don't name a dependency, like the `Any` of deps that are not used
+pub const MARKER_TRAITS: &[&str] = &["Any", "Send", "Sync", "Sized", "Unpin"];
+
+/// How long to wait for another compilation to finish its update,
+/// before assuming that its lock file was left behind
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Record the dependencies of the trait, when `ENTRAIT_GRAPH_OUT` is set.
+pub fn record_dependencies(trait_ident: &syn::Ident, trait_fns: &[TraitFn]) -> syn::Result<()> {
+    let path = match std::env::var_os(GRAPH_OUT_VAR) {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => return Ok(()),
+    };
+
+    let node = dot_id(&trait_ident.to_string());
+    let dependencies = trait_fns
+        .iter()
+        .flat_map(|trait_fn| dependencies(&trait_fn.deps))
+        .collect::<BTreeSet<_>>();
+
+    let statements = std::iter::once(format!("{node};"))
+        .chain(
+            dependencies
+                .iter()
+                .map(|dependency| format!("{node} -> {};", dot_id(dependency))),
+        )
+        .collect();
+
+    update_graph(&path, &node, statements).map_err(|err| {
+        syn::Error::new(
+            trait_ident.span(),
+            format!(
+                "Failed to write the dependency graph to `{}`: {err}",
+                path.display()
+            ),
+        )
+    })
+}
+
+/// Reject a trait that depends on itself,
+/// since its implementation for `Impl<T>` would require itself, which the trait solver can't prove.
+///
+/// Only bounds naming the trait unqualified, or through `self::`, are known to be the trait itself:
+/// a macro invocation can't resolve other paths, so cycles through other traits are left to the trait solver.
+/// Mocked traits are let through, as their mocks can implement the dependencies.
+pub fn check_self_dependency(
+    trait_ident: &syn::Ident,
+    trait_fns: &[TraitFn],
+    opts: &Opts,
+) -> syn::Result<()> {
+    // A unimock mock is only generated with a `mock_api`
+    if (opts.default_option(opts.unimock, false).0 && opts.mock_api.is_some())
+        || opts.mockall.is_some()
+        || opts.mry.is_some()
+        || opts.faux.is_some()
+    {
+        return Ok(());
+    }
+
+    let depends_on_itself = trait_fns.iter().any(|trait_fn| match &trait_fn.deps {
+        FnDeps::Generic { trait_bounds, .. } => trait_bounds.iter().any(|bound| match bound {
+            syn::TypeParamBound::Trait(syn::TraitBound {
+                modifier: syn::TraitBoundModifier::None,
+                path,
+                ..
+            }) => names_trait(path, trait_ident),
+            _ => false,
+        }),
+        _ => false,
+    });
+    if depends_on_itself {
+        return Err(syn::Error::new(
+            trait_ident.span(),
+            format!("`{trait_ident}` depends on itself, so it can't be implemented for `Impl<T>`: the trait solver can't prove an implementation that requires itself. Consider a plain recursive fn inside the entraited fn instead"),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `path` is `Trait` or `self::Trait`
+fn names_trait(path: &syn::Path, trait_ident: &syn::Ident) -> bool {
+    if path.leading_colon.is_some() {
+        return false;
+    }
+    let mut segments = path.segments.iter();
+    let first = match segments.next() {
+        Some(first) => first,
+        None => return false,
+    };
+    match segments.next() {
+        None => first.ident == *trait_ident,
+        Some(second) => {
+            first.ident == "self" && second.ident == *trait_ident && segments.next().is_none()
+        }
+    }
+}
+
+/// The names of the traits (or the concrete type) in the dependencies of a fn
+pub fn dependencies(deps: &FnDeps) -> Vec<String> {
+    match deps {
+        FnDeps::Generic { trait_bounds, .. } => trait_bounds
+            .iter()
+            .filter_map(|bound| match bound {
+                syn::TypeParamBound::Trait(syn::TraitBound {
+                    modifier: syn::TraitBoundModifier::None,
+                    path,
+                    ..
+                }) => path.segments.last(),
+                _ => None,
+            })
+            .map(|segment| segment.ident.to_string())
+            .filter(|ident| !MARKER_TRAITS.contains(&ident.as_str()))
+            .collect(),
+        FnDeps::Concrete(ty) => vec![ty.to_token_stream().to_string().replace(' ', "")],
+        FnDeps::NoDeps => vec![],
+    }
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\\\""))
+}
+
+/// Replace the statements of `node` in the graph file with `statements`.
+fn update_graph(path: &Path, node: &str, statements: Vec<String>) -> io::Result<()> {
+    let _lock = GraphLock::acquire(path)?;
+
+    let existing = match fs::read_to_string(path) {
+        Ok(existing) => existing,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+
+    let edge_prefix = format!("{node} -> ");
+    let mut lines = existing
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.ends_with(';'))
+        .filter(|line| !line.starts_with(&edge_prefix) && *line != format!("{node};"))
+        .map(String::from)
+        .collect::<BTreeSet<_>>();
+    lines.extend(statements);
+
+    let mut graph = String::from("digraph entrait {\n");
+    for line in lines {
+        graph.push_str("    ");
+        graph.push_str(&line);
+        graph.push('\n');
+    }
+    graph.push_str("}\n");
+
+    fs::write(path, graph)
+}
+
+/// Crates are compiled in parallel, so updates of the graph file are serialized with a lock file next to it.
+struct GraphLock(PathBuf);
+
+impl GraphLock {
+    fn acquire(path: &Path) -> io::Result<Self> {
+        let mut lock_path = OsString::from(path);
+        lock_path.push(".lock");
+        let lock_path = PathBuf::from(lock_path);
+
+        let mut start = Instant::now();
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock_path)
+            {
+                Ok(_) => return Ok(Self(lock_path)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    if start.elapsed() > LOCK_TIMEOUT {
+                        let _ = fs::remove_file(&lock_path);
+                        start = Instant::now();
+                    } else {
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl Drop for GraphLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_replace_the_statements_of_a_node() {
+        let path = std::env::temp_dir().join(format!("entrait_graph_{}.dot", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let statements = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+
+        update_graph(
+            &path,
+            "\"Foo\"",
+            statements(&["\"Foo\";", "\"Foo\" -> \"Bar\";"]),
+        )
+        .unwrap();
+        update_graph(&path, "\"Bar\"", statements(&["\"Bar\";"])).unwrap();
+        update_graph(
+            &path,
+            "\"Foo\"",
+            statements(&["\"Foo\";", "\"Foo\" -> \"Baz\";"]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            "digraph entrait {\n    \"Bar\";\n    \"Foo\" -> \"Baz\";\n    \"Foo\";\n}\n",
+            fs::read_to_string(&path).unwrap()
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_only_name_the_trait_unqualified_or_through_self() {
+        let foo = syn::Ident::new("Foo", proc_macro2::Span::call_site());
+        let names_foo = |path: syn::Path| names_trait(&path, &foo);
+
+        assert!(names_foo(syn::parse_quote!(Foo)));
+        assert!(names_foo(syn::parse_quote!(self::Foo)));
+        assert!(!names_foo(syn::parse_quote!(super::b::Foo)));
+        assert!(!names_foo(syn::parse_quote!(::other::Foo)));
+        assert!(!names_foo(syn::parse_quote!(Bar)));
+    }
+}
@@ -1,8 +1,11 @@
+#[derive(Clone)]
 pub struct CrateIdents {
     pub entrait: syn::Ident,
     pub core: syn::Ident,
     pub __unimock: syn::Ident,
+    pub __unimock_0_5: syn::Ident,
     pub unimock: syn::Ident,
+    pub __tokio: syn::Ident,
 }
 
 impl CrateIdents {
@@ -11,7 +14,9 @@ impl CrateIdents {
             entrait: syn::Ident::new("entrait", span),
             core: syn::Ident::new("core", span),
             __unimock: syn::Ident::new("__unimock", span),
+            __unimock_0_5: syn::Ident::new("__unimock_0_5", span),
             unimock: syn::Ident::new("unimock", span),
+            __tokio: syn::Ident::new("__tokio", span),
         }
     }
 }
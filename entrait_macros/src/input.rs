@@ -17,6 +17,8 @@ pub enum FnInputMode<'a> {
     SingleFn(&'a syn::Ident),
     Module(&'a syn::Ident),
     ImplBlock(&'a syn::Type),
+    /// An inherent impl block of a service type
+    InherentImpl(&'a syn::Type),
     RawTrait(LiteralAttrs<'a>),
 }
 
@@ -35,6 +37,7 @@ pub enum Input {
     Trait(syn::ItemTrait),
     Mod(InputMod),
     Impl(InputImpl),
+    InherentImpl(InputInherentImpl),
 }
 
 impl Parse for Input {
@@ -58,13 +61,22 @@ impl Parse for Input {
             }))
         } else if input.peek(syn::token::Impl) {
             disallow_token(auto_token)?;
-            Ok(Input::Impl(parse_impl(attrs, unsafety, input)?))
+            if peek_inherent_impl(input) {
+                disallow_token(unsafety)?;
+                Ok(Input::InherentImpl(parse_inherent_impl(attrs, input)?))
+            } else {
+                Ok(Input::Impl(parse_impl(attrs, unsafety, input)?))
+            }
         } else if input.peek(syn::token::Mod) {
             disallow_token(unsafety)?;
             disallow_token(auto_token)?;
             Ok(Input::Mod(parse_mod(attrs, vis, input)?))
         } else {
-            let fn_sig: syn::Signature = input.parse()?;
+            let mut fn_sig: syn::Signature = input.parse()?;
+            // `unsafe` was already consumed above, for `unsafe impl` and `unsafe trait`
+            if unsafety.is_some() {
+                fn_sig.unsafety = unsafety;
+            }
             let fn_body = input.parse()?;
 
             Ok(Input::Fn(InputFn {
@@ -100,6 +112,64 @@ pub struct InputMod {
     pub items: Vec<ModItem>,
 }
 
+impl InputMod {
+    /// The functions to include in the trait, along with the nested modules containing them
+    pub fn trait_fns(&self, include_private: bool) -> Vec<(Vec<syn::Ident>, &InputFn)> {
+        let mut fns = vec![];
+        collect_fns(&self.items, include_private, &mut vec![], &mut fns);
+        fns
+    }
+
+    pub fn trait_fns_mut(&mut self, include_private: bool) -> Vec<&mut InputFn> {
+        let mut fns = vec![];
+        collect_fns_mut(&mut self.items, include_private, &mut fns);
+        fns
+    }
+}
+
+fn collect_fns<'i>(
+    items: &'i [ModItem],
+    include_private: bool,
+    mod_path: &mut Vec<syn::Ident>,
+    fns: &mut Vec<(Vec<syn::Ident>, &'i InputFn)>,
+) {
+    for item in items {
+        match item {
+            ModItem::Mod(input_mod) if is_included(&input_mod.vis, include_private) => {
+                mod_path.push(input_mod.ident.clone());
+                collect_fns(&input_mod.items, include_private, mod_path, fns);
+                mod_path.pop();
+            }
+            item => {
+                if let Some(input_fn) = item.filter_fn(include_private) {
+                    fns.push((mod_path.clone(), input_fn));
+                }
+            }
+        }
+    }
+}
+
+fn collect_fns_mut<'i>(
+    items: &'i mut [ModItem],
+    include_private: bool,
+    fns: &mut Vec<&'i mut InputFn>,
+) {
+    for item in items {
+        match item {
+            ModItem::Mod(input_mod) if is_included(&input_mod.vis, include_private) => {
+                collect_fns_mut(&mut input_mod.items, include_private, fns);
+            }
+            ModItem::PubFn(input_fn) => fns.push(input_fn),
+            ModItem::PrivateFn(input_fn) if include_private => fns.push(input_fn),
+            _ => {}
+        }
+    }
+}
+
+fn is_included(vis: &syn::Visibility, include_private: bool) -> bool {
+    include_private || !matches!(vis, syn::Visibility::Inherited)
+}
+
 impl Parse for InputMod {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let attrs = input.call(syn::Attribute::parse_outer)?;
@@ -124,14 +194,19 @@ impl ToTokens for InputMod {
 
 pub enum ModItem {
     PubFn(Box<InputFn>),
+    PrivateFn(Box<InputFn>),
+    /// A nested module, whose functions are included like the ones of the entraited module
+    Mod(Box<InputMod>),
     Unknown(ItemUnknown),
 }
 
 impl ModItem {
-    // We include all functions that have a visibility keyword into the trait
-    pub fn filter_pub_fn(&self) -> Option<&InputFn> {
+    // We include all functions that have a visibility keyword into the trait,
+    // and private functions with `include_private`
+    pub fn filter_fn(&self, include_private: bool) -> Option<&InputFn> {
         match self {
             Self::PubFn(input_fn) => Some(input_fn),
+            Self::PrivateFn(input_fn) if include_private => Some(input_fn),
             _ => None,
         }
     }
@@ -140,7 +215,7 @@ impl ModItem {
 impl ToTokens for ModItem {
     fn to_tokens(&self, stream: &mut TokenStream) {
         match self {
-            ModItem::PubFn(input_fn) => {
+            ModItem::PubFn(input_fn) | ModItem::PrivateFn(input_fn) => {
                 let InputFn {
                     fn_attrs,
                     fn_vis,
@@ -152,6 +227,9 @@ impl ToTokens for ModItem {
                 }
                 push_tokens!(stream, fn_vis, fn_sig, fn_body);
             }
+            ModItem::Mod(input_mod) => {
+                input_mod.to_tokens(stream);
+            }
             ModItem::Unknown(unknown) => {
                 unknown.to_tokens(stream);
             }
@@ -159,8 +237,6 @@ impl ToTokens for ModItem {
     }
 }
 
-pub struct DeriveImplTraitPath(pub syn::Path);
-
 /// An impl block
 /// Note: No support for generics
 pub struct InputImpl {
@@ -168,12 +244,57 @@ pub struct InputImpl {
     pub unsafety: Option<syn::token::Unsafe>,
     pub impl_token: syn::token::Impl,
     pub trait_path: syn::Path,
-    pub for_token: syn::token::For,
+    pub self_ty: syn::Type,
+    pub items: Vec<ImplItem>,
+}
+
+/// An inherent impl block, like `impl MyService { .. }`
+/// Note: No support for generics
+pub struct InputInherentImpl {
+    pub attrs: Vec<syn::Attribute>,
+    pub impl_token: syn::token::Impl,
     pub self_ty: syn::Type,
     pub brace_token: syn::token::Brace,
     pub items: Vec<ImplItem>,
 }
 
+impl InputInherentImpl {
+    /// The methods to include in the trait
+    pub fn trait_fns(&self, include_private: bool) -> Vec<&InputFn> {
+        self.items
+            .iter()
+            .filter_map(ImplItem::filter_fn)
+            .filter(|input_fn| is_included(&input_fn.fn_vis, include_private))
+            .collect()
+    }
+
+    pub fn trait_fns_mut(&mut self, include_private: bool) -> Vec<&mut InputFn> {
+        self.items
+            .iter_mut()
+            .filter_map(|item| match item {
+                ImplItem::Fn(input_fn) if is_included(&input_fn.fn_vis, include_private) => {
+                    Some(input_fn.as_mut())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl ToTokens for InputInherentImpl {
+    fn to_tokens(&self, stream: &mut TokenStream) {
+        for attr in &self.attrs {
+            push_tokens!(stream, attr);
+        }
+        push_tokens!(stream, self.impl_token, self.self_ty);
+        self.brace_token.surround(stream, |stream| {
+            for item in &self.items {
+                item.to_tokens(stream);
+            }
+        });
+    }
+}
+
 pub enum ImplItem {
     Fn(Box<InputFn>),
     Unknown(ItemUnknown),
@@ -187,6 +308,22 @@ impl ImplItem {
             _ => None,
         }
     }
+
+    /// Associated constants, like `const IDENT: Type = value;`
+    pub fn filter_const(&self) -> Option<syn::ImplItemConst> {
+        match self {
+            Self::Unknown(unknown) => syn::parse2(unknown.tokens.clone()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Associated types, like `type Ident = Type;`
+    pub fn filter_type(&self) -> Option<syn::ImplItemType> {
+        match self {
+            Self::Unknown(unknown) => syn::parse2(unknown.tokens.clone()).ok(),
+            _ => None,
+        }
+    }
 }
 
 impl ToTokens for ImplItem {
@@ -265,7 +402,9 @@ impl Parse for ModItem {
         let vis: syn::Visibility = input.parse()?;
         let unknown = input.fork();
 
-        if peek_pub_fn(input, &vis) {
+        if input.peek(syn::token::Mod) && input.peek3(syn::token::Brace) {
+            Ok(ModItem::Mod(Box::new(parse_mod(attrs, vis, input)?)))
+        } else if peek_fn(input) {
             let sig: syn::Signature = input.parse()?;
             if input.peek(syn::token::Semi) {
                 let _ = input.parse::<syn::token::Semi>()?;
@@ -276,12 +415,17 @@ impl Parse for ModItem {
                 }))
             } else {
                 let fn_body = parse_matched_braces_or_ending_semi(input)?;
-                Ok(ModItem::PubFn(Box::new(InputFn {
+                let input_fn = Box::new(InputFn {
                     fn_attrs: attrs,
                     fn_vis: vis,
                     fn_sig: sig,
                     fn_body,
-                })))
+                });
+                if let syn::Visibility::Inherited = &input_fn.fn_vis {
+                    Ok(ModItem::PrivateFn(input_fn))
+                } else {
+                    Ok(ModItem::PubFn(input_fn))
+                }
             }
         } else {
             let tokens = parse_matched_braces_or_ending_semi(input)?;
@@ -297,13 +441,13 @@ fn parse_impl(
 ) -> syn::Result<InputImpl> {
     let impl_token = input.parse()?;
     let trait_path = input.parse()?;
-    let for_token = input.parse()?;
+    input.parse::<syn::token::For>()?;
     let self_ty = input.parse()?;
 
     let lookahead = input.lookahead1();
     if lookahead.peek(syn::token::Brace) {
         let content;
-        let brace_token = syn::braced!(content in input);
+        syn::braced!(content in input);
 
         let mut items = vec![];
 
@@ -316,7 +460,42 @@ fn parse_impl(
             unsafety,
             impl_token,
             trait_path,
-            for_token,
+            self_ty,
+            items,
+        })
+    } else {
+        Err(lookahead.error())
+    }
+}
+
+/// Whether the impl block ahead has no `Trait for` part
+fn peek_inherent_impl(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<syn::token::Impl>().is_ok()
+        && !(fork.parse::<syn::Path>().is_ok() && fork.peek(syn::token::For))
+}
+
+fn parse_inherent_impl(
+    attrs: Vec<syn::Attribute>,
+    input: ParseStream,
+) -> syn::Result<InputInherentImpl> {
+    let impl_token = input.parse()?;
+    let self_ty = input.parse()?;
+
+    let lookahead = input.lookahead1();
+    if lookahead.peek(syn::token::Brace) {
+        let content;
+        let brace_token = syn::braced!(content in input);
+
+        let mut items = vec![];
+
+        while !content.is_empty() {
+            items.push(content.parse()?);
+        }
+
+        Ok(InputInherentImpl {
+            attrs,
+            impl_token,
             self_ty,
             brace_token,
             items,
@@ -358,25 +537,6 @@ impl Parse for ImplItem {
     }
 }
 
-impl Parse for DeriveImplTraitPath {
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        let content;
-        let _ = syn::parenthesized!(content in input);
-
-        let path = content.parse()?;
-
-        Ok(DeriveImplTraitPath(path))
-    }
-}
-
-fn peek_pub_fn(input: ParseStream, vis: &syn::Visibility) -> bool {
-    if let syn::Visibility::Inherited = vis {
-        // 'private' functions aren't interesting
-        return false;
-    }
-    peek_fn(input)
-}
-
 fn peek_fn(input: ParseStream) -> bool {
     if input.peek(syn::token::Fn) {
         return true;
@@ -406,7 +566,6 @@ fn parse_matched_braces_or_ending_semi(input: ParseStream) -> syn::Result<TokenS
     let mut tokens = input.step(|cursor| {
         let mut tokens = TokenStream::new();
 
-        use proc_macro2::Delimiter;
         use proc_macro2::TokenTree;
 
         let mut rest = *cursor;
@@ -414,9 +573,9 @@ fn parse_matched_braces_or_ending_semi(input: ParseStream) -> syn::Result<TokenS
         while let Some((tt, next)) = rest.token_tree() {
             match &tt {
                 TokenTree::Group(group) => {
-                    let is_brace = group.delimiter() == Delimiter::Brace;
+                    let is_body = is_braced_body(group);
                     tokens.extend(std::iter::once(tt));
-                    if is_brace {
+                    if is_body {
                         return Ok((tokens, next));
                     }
                 }
@@ -447,6 +606,21 @@ fn parse_matched_braces_or_ending_semi(input: ParseStream) -> syn::Result<TokenS
     Ok(tokens)
 }
 
+/// Whether the group is a `{ .. }` body.
+///
+/// Tokens interpolated by `macro_rules!` (e.g. a `$body:block` fragment) arrive
+/// wrapped in an invisible (`None`-delimited) group, which must be looked through.
+fn is_braced_body(group: &proc_macro2::Group) -> bool {
+    match group.delimiter() {
+        proc_macro2::Delimiter::Brace => true,
+        proc_macro2::Delimiter::None => match group.stream().into_iter().last() {
+            Some(proc_macro2::TokenTree::Group(inner)) => is_braced_body(&inner),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
 fn disallow_token<T: Spanned>(token: Option<T>) -> syn::Result<()> {
     if let Some(token) = token {
         Err(syn::Error::new(token.span(), "Not allowed here"))
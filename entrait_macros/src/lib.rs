@@ -10,14 +10,23 @@ use proc_macro::TokenStream;
 
 mod analyze_generics;
 mod attributes;
+mod entrait_all;
+mod entrait_app;
 mod entrait_fn;
 mod entrait_impl;
 mod entrait_trait;
+mod expansion;
+mod feature_group;
 mod fn_delegation_codegen;
 mod generics;
+mod graph;
 mod idents;
 mod input;
+mod link_entrait;
+mod mockall_deps;
 mod opt;
+mod register_entrait;
+mod send_cfg;
 mod signature;
 mod sub_attributes;
 mod token_util;
@@ -52,12 +61,67 @@ pub fn entrait_export_unimock(attr: TokenStream, input: TokenStream) -> TokenStr
     })
 }
 
+#[proc_macro_attribute]
+pub fn entrait_all(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let attr = syn::parse_macro_input!(attr as entrait_all::EntraitAllAttr);
+    let input_mod = syn::parse_macro_input!(input as input::InputMod);
+
+    entrait_all::output_tokens(attr, input_mod).into()
+}
+
+#[proc_macro_derive(EntraitApp, attributes(app))]
+pub fn entrait_app(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    entrait_app::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro]
+pub fn link_entrait(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as link_entrait::LinkInput);
+
+    link_entrait::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro]
+pub fn register_entrait(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as register_entrait::RegisterInput);
+
+    register_entrait::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Builds a combined mockall mock for deps with several trait bounds, see the `mockall_deps` module.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __mockall_deps(input: TokenStream) -> TokenStream {
+    mockall_deps::expand(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 fn set_fallbacks<const N: usize>(opts: [&mut Option<opt::SpanOpt<bool>>; N]) {
     for opt in opts.into_iter() {
         opt.get_or_insert(opt::SpanOpt::of(true));
     }
 }
 
+/// Crate-wide configuration from environment variables, which only fills in options not given by the invocation.
+///
+/// The resulting options are checked against the `std` availability.
+fn apply_environment(opts: &mut Opts, trait_ident: Option<&syn::Ident>) -> syn::Result<()> {
+    opts.apply_defaults()?;
+    if let Some(trait_ident) = trait_ident {
+        opts.apply_mock_api_convention(trait_ident)?;
+    }
+    opts.check_std_options()
+}
+
 fn invoke(
     attr: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
@@ -65,14 +129,17 @@ fn invoke(
 ) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as Input);
 
-    let (result, debug) = match input {
+    let (result, debug, group, trait_name) = match input {
         Input::Fn(input_fn) => {
             let mut attr = syn::parse_macro_input!(attr as entrait_fn::input_attr::EntraitFnAttr);
             opts_modifier(&mut attr.opts);
 
             (
-                entrait_fn::entrait_for_single_fn(&attr, input_fn),
+                apply_environment(&mut attr.opts, Some(&attr.trait_ident))
+                    .and_then(|()| entrait_fn::entrait_for_single_fn(&attr, input_fn)),
                 attr.opts.debug_value(),
+                attr.group,
+                attr.trait_ident.to_string(),
             )
         }
         Input::Mod(input_mod) => {
@@ -80,39 +147,89 @@ fn invoke(
             opts_modifier(&mut attr.opts);
 
             (
-                entrait_fn::entrait_for_mod(&attr, input_mod),
+                apply_environment(&mut attr.opts, Some(&attr.trait_ident))
+                    .and_then(|()| entrait_fn::entrait_for_mod(&attr, input_mod)),
+                attr.opts.debug_value(),
+                attr.group,
+                attr.trait_ident.to_string(),
+            )
+        }
+        Input::InherentImpl(input_impl) => {
+            let mut attr = syn::parse_macro_input!(attr as entrait_fn::input_attr::EntraitFnAttr);
+            opts_modifier(&mut attr.opts);
+
+            (
+                apply_environment(&mut attr.opts, Some(&attr.trait_ident))
+                    .and_then(|()| entrait_fn::entrait_for_inherent_impl(&attr, input_impl)),
                 attr.opts.debug_value(),
+                attr.group,
+                attr.trait_ident.to_string(),
             )
         }
         Input::Trait(item_trait) => {
             let mut attr =
                 syn::parse_macro_input!(attr as entrait_trait::input_attr::EntraitTraitAttr);
             opts_modifier(&mut attr.opts);
-            let debug = attr.opts.debug.map(|opt| *opt.value()).unwrap_or(false);
+            let debug = attr.opts.debug.map(|opt| *opt.value()).unwrap_or_default();
 
-            (entrait_trait::output_tokens(attr, item_trait), debug)
+            let group = attr.group.clone();
+            let trait_name = item_trait.ident.to_string();
+
+            (
+                apply_environment(&mut attr.opts, Some(&item_trait.ident))
+                    .and_then(|()| entrait_trait::output_tokens(attr, item_trait)),
+                debug,
+                group,
+                trait_name,
+            )
         }
         Input::Impl(input_impl) => {
             let mut attr =
                 syn::parse_macro_input!(attr as entrait_impl::input_attr::EntraitSimpleImplAttr);
             opts_modifier(&mut attr.opts);
-            let debug = attr.opts.debug.map(|opt| *opt.value()).unwrap_or(false);
+            let debug = attr.opts.debug.map(|opt| *opt.value()).unwrap_or_default();
+            let group = attr.group.clone();
+            let trait_name = input_impl
+                .trait_path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+                .unwrap_or_default();
 
             (
-                entrait_impl::output_tokens_for_impl(attr, input_impl),
+                apply_environment(&mut attr.opts, None)
+                    .and_then(|()| entrait_impl::output_tokens_for_impl(attr, input_impl)),
                 debug,
+                group,
+                trait_name,
             )
         }
     };
 
-    let output = match result {
+    let result = match group {
+        Some(group) => result.and_then(|output| feature_group::gate(output, &group)),
+        None => result,
+    };
+
+    let mut output = match result {
         Ok(token_stream) => token_stream,
         Err(err) => err.into_compile_error(),
     };
 
-    if debug {
+    if debug.print {
         println!("{}", output);
     }
+    if debug.file {
+        if let Err(err) = expansion::write_to_file(&trait_name, &output) {
+            output.extend(
+                syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("Failed to write the expansion of `{trait_name}`: {err}"),
+                )
+                .into_compile_error(),
+            );
+        }
+    }
 
     proc_macro::TokenStream::from(output)
 }
@@ -0,0 +1,69 @@
+//! `link_entrait!`, implementing the traits of `deferred` functions for an application type in the final crate.
+//!
+//! ```text
+//! link_entrait!(App => crate_a::Foo, crate_b::bar::Bar);
+//! ```
+//!
+//! Every trait path is turned into the path of its hidden link macro, `crate_a::__entrait_link_Foo!`,
+//! which gets the module of the trait to call the functions through.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+
+pub struct LinkInput {
+    app: syn::Type,
+    trait_paths: syn::punctuated::Punctuated<syn::Path, syn::token::Comma>,
+}
+
+impl Parse for LinkInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let app = input.parse()?;
+        let _: syn::token::FatArrow = input.parse()?;
+        let trait_paths = input.parse_terminated(syn::Path::parse_mod_style, syn::token::Comma)?;
+
+        Ok(Self { app, trait_paths })
+    }
+}
+
+pub fn expand(input: LinkInput) -> syn::Result<TokenStream> {
+    let app = &input.app;
+    let mut invocations = vec![];
+
+    for trait_path in &input.trait_paths {
+        let span = trait_path.span();
+        let macro_path = hidden_macro_path(trait_path, "__entrait_link_");
+        let module = trait_module(trait_path);
+
+        invocations.push(quote_spanned! { span=>
+            #macro_path! { [#trait_path] [#module] #app }
+        });
+    }
+
+    Ok(quote! { #(#invocations)* })
+}
+
+/// The path of a hidden macro generated next to the trait, named after it with the given prefix
+pub fn hidden_macro_path(trait_path: &syn::Path, prefix: &str) -> syn::Path {
+    let mut macro_path = trait_path.clone();
+    let last_segment = macro_path.segments.last_mut().unwrap();
+    last_segment.ident = format_ident!("{}{}", prefix, last_segment.ident);
+    macro_path
+}
+
+/// The path prefix of the module of the trait
+pub fn trait_module(trait_path: &syn::Path) -> TokenStream {
+    match trait_path.segments.len() {
+        // The trait in scope is in this module
+        1 if trait_path.leading_colon.is_none() => quote! { self:: },
+        _ => {
+            let leading_colon = &trait_path.leading_colon;
+            let segments = trait_path
+                .segments
+                .iter()
+                .take(trait_path.segments.len() - 1);
+            quote! { #leading_colon #(#segments::)* }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Combined mockall mocks for deps with several trait bounds, like `deps: &(impl Foo + Bar)`.
+//!
+//! A `mockall::mock!` implementing several traits needs the method signatures of all of them,
+//! which are not known where the deps are declared. So every trait mocked with mockall also gets a hidden
+//! `__entrait_mockall_{Trait}` macro next to it, which adds an `impl Trait for Mock` with its signatures.
+//! The deps mock is built by a chain of those macros, driven by the hidden `__mockall_deps` macro:
+//!
+//! ```text
+//! __mockall_deps! { [vis] Name { impls.. } [path::__entrait_mockall_Foo] [path::Foo] [path::__entrait_mockall_Bar] [path::Bar] }
+//! ```
+
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned};
+
+use crate::analyze_generics::TraitFn;
+use crate::generics::FnDeps;
+use crate::graph::MARKER_TRAITS;
+use crate::idents::CrateIdents;
+
+/// The hidden macro of a trait mocked with mockall, which adds the trait to a deps mock.
+pub fn gen_trait_macro(
+    crate_idents: &CrateIdents,
+    trait_ident: &syn::Ident,
+    fn_sigs: &[TokenStream],
+    opt_cfg: Option<&TokenStream>,
+) -> TokenStream {
+    let entrait = &crate_idents.entrait;
+    let span = trait_ident.span();
+    let macro_ident = format_ident!("__entrait_mockall_{}", trait_ident);
+
+    quote_spanned! { span=>
+        #opt_cfg
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #macro_ident {
+            ([$($vis:tt)*] $mock:ident { $($impls:tt)* } [$($trait_path:tt)*] $($rest:tt)*) => {
+                ::#entrait::__mockall_deps! {
+                    [$($vis)*] $mock {
+                        $($impls)*
+                        impl $($trait_path)* for $mock {
+                            #(#fn_sigs;)*
+                        }
+                    }
+                    $($rest)*
+                }
+            };
+        }
+
+        #opt_cfg
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub(crate) use #macro_ident;
+    }
+}
+
+/// A `Mock{Method}Deps` for every function whose deps have more than one trait bound.
+pub fn gen_deps_mocks(
+    crate_idents: &CrateIdents,
+    visibility: &impl quote::ToTokens,
+    trait_fns: &[TraitFn],
+    opt_cfg: Option<&TokenStream>,
+) -> TokenStream {
+    let entrait = &crate_idents.entrait;
+
+    let mocks = trait_fns.iter().filter_map(|trait_fn| {
+        let trait_paths = dependency_trait_paths(&trait_fn.deps);
+        if trait_paths.len() < 2 {
+            return None;
+        }
+
+        let fn_ident = &trait_fn.sig().ident;
+        let mock_ident = format_ident!("{}Deps", upper_camel_case(&fn_ident.to_string()));
+        let links = trait_paths.into_iter().map(|trait_path| {
+            let mut macro_path = trait_path.clone();
+            if let Some(segment) = macro_path.segments.last_mut() {
+                segment.ident = format_ident!("__entrait_mockall_{}", segment.ident);
+                segment.arguments = syn::PathArguments::None;
+            }
+            quote! { [#macro_path] [#trait_path] }
+        });
+
+        Some(quote_spanned! { fn_ident.span()=>
+            #opt_cfg
+            ::#entrait::__mockall_deps! { [#visibility] #mock_ident {} #(#links)* }
+        })
+    });
+
+    quote! { #(#mocks)* }
+}
+
+fn dependency_trait_paths(deps: &FnDeps) -> Vec<&syn::Path> {
+    match deps {
+        FnDeps::Generic { trait_bounds, .. } => trait_bounds
+            .iter()
+            .filter_map(|bound| match bound {
+                syn::TypeParamBound::Trait(syn::TraitBound {
+                    modifier: syn::TraitBoundModifier::None,
+                    path,
+                    ..
+                }) => Some(path),
+                _ => None,
+            })
+            .filter(|path| {
+                path.segments.last().map_or(false, |segment| {
+                    !MARKER_TRAITS.contains(&segment.ident.to_string().as_str())
+                })
+            })
+            .collect(),
+        FnDeps::Concrete(_) | FnDeps::NoDeps => vec![],
+    }
+}
+
+fn upper_camel_case(snake_case: &str) -> String {
+    snake_case
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// One step of the chain: call the macro of the next trait, or invoke `mockall::mock!` when all traits are added.
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let MockallDeps {
+        visibility,
+        mock_ident,
+        impls,
+        links,
+    } = syn::parse2(input)?;
+
+    match links.split_first() {
+        Some(((macro_path, trait_path), rest)) => {
+            let rest = rest
+                .iter()
+                .map(|(macro_path, trait_path)| quote! { [#macro_path] [#trait_path] });
+            Ok(quote! {
+                #macro_path! { [#visibility] #mock_ident { #impls } [#trait_path] #(#rest)* }
+            })
+        }
+        None => {
+            // The signatures come from the expansions of different macros,
+            // and their `self` must be the same `self` as in the method bodies generated by mockall
+            let impls = respan_self(impls);
+            Ok(quote! {
+                ::mockall::mock! {
+                    #visibility #mock_ident {}
+                    #impls
+                }
+            })
+        }
+    }
+}
+
+struct MockallDeps {
+    visibility: TokenStream,
+    mock_ident: syn::Ident,
+    impls: TokenStream,
+    links: Vec<(TokenStream, TokenStream)>,
+}
+
+impl syn::parse::Parse for MockallDeps {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::bracketed!(content in input);
+        let visibility = content.parse()?;
+        let mock_ident = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let impls = content.parse()?;
+
+        let mut links = vec![];
+        while !input.is_empty() {
+            let macro_path;
+            syn::bracketed!(macro_path in input);
+            let trait_path;
+            syn::bracketed!(trait_path in input);
+            links.push((macro_path.parse()?, trait_path.parse()?));
+        }
+
+        Ok(Self {
+            visibility,
+            mock_ident,
+            impls,
+            links,
+        })
+    }
+}
+
+fn respan_self(stream: TokenStream) -> TokenStream {
+    stream
+        .into_iter()
+        .map(|token_tree| match token_tree {
+            TokenTree::Ident(ident) if ident == "self" => {
+                TokenTree::Ident(syn::Ident::new("self", Span::call_site()))
+            }
+            TokenTree::Group(group) => {
+                let mut respanned = Group::new(group.delimiter(), respan_self(group.stream()));
+                respanned.set_span(group.span());
+                TokenTree::Group(respanned)
+            }
+            token_tree => token_tree,
+        })
+        .collect()
+}
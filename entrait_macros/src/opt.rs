@@ -1,45 +1,339 @@
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
 use syn::parse::{Parse, ParseStream};
 
+#[derive(Clone)]
 pub struct Opts {
     pub default_span: Span,
 
     pub no_deps: Option<SpanOpt<bool>>,
-    pub debug: Option<SpanOpt<bool>>,
+    pub debug: Option<SpanOpt<DebugOutput>>,
 
     /// Whether to export mocks (i.e. not gated with cfg(test))
     pub export: Option<SpanOpt<bool>>,
 
+    /// A cfg predicate under which mocks are exported, in addition to the mock cfg
+    pub export_cfg: Option<SpanOpt<syn::Meta>>,
+
+    /// The cfg predicate that gates mocks instead of cfg(test)
+    pub mock_cfg: Option<SpanOpt<syn::Meta>>,
+
     pub future_send: Option<SpanOpt<FutureSend>>,
 
+    /// The cfg predicate of the targets with `Send` futures, with a `?Send` variant of the output for the others
+    pub send_cfg: Option<SpanOpt<syn::Meta>>,
+
+    /// Whether the `T` in `Impl<T>` is bounded by `Sync`
+    pub impl_sync: Option<SpanOpt<ImplSync>>,
+
     pub mock_api: Option<MockApiIdent>,
 
     /// Mocking with unimock
     pub unimock: Option<SpanOpt<bool>>,
 
+    /// The unimock version whose API the mock targets
+    pub unimock_version: Option<SpanOpt<UnimockVersion>>,
+
     /// Mocking with mockall
     pub mockall: Option<SpanOpt<bool>>,
+
+    /// Mocking with mry
+    pub mry: Option<SpanOpt<bool>>,
+
+    /// Mocking with a faux struct
+    pub faux: Option<SpanOpt<bool>>,
+
+    /// Whether the trait is implemented for `entrait::Stub`
+    pub stub: Option<SpanOpt<bool>>,
+
+    /// Whether the trait is implemented for `entrait::record::Recorded`
+    pub record: Option<SpanOpt<bool>>,
+
+    /// Whether the trait is implemented for `&T`, `Box<T>` and `Arc<T>` when `T` implements it
+    pub forward_impls: Option<SpanOpt<bool>>,
+
+    /// The real type of a `mockall_double` pair of aliases, with the mockall mock
+    pub double: Option<syn::Type>,
+
+    /// Whether the generated trait and impl are `const` (nightly `const_trait_impl`)
+    pub const_trait: Option<SpanOpt<bool>>,
+
+    /// Whether delegations are logged with the `log` crate
+    pub log: Option<SpanOpt<bool>>,
+
+    /// Whether delegations are wrapped in `tracing` spans, and which arguments become span fields
+    pub instrument: Option<SpanOpt<Vec<syn::Ident>>>,
+
+    /// Whether delegations are recorded in OpenTelemetry metrics
+    pub otel_metrics: Option<SpanOpt<bool>>,
+
+    /// Whether delegations call the hooks of the application's `CallInterceptor`
+    pub intercept: Option<SpanOpt<bool>>,
+
+    /// Whether to emit a fingerprint of the generated trait's signature
+    pub fingerprint: Option<SpanOpt<bool>>,
+
+    /// Whether to emit the names of the dependencies of the generated trait's implementation
+    pub dependencies: Option<SpanOpt<bool>>,
+
+    /// Documentation of the generated trait
+    pub trait_doc: Option<syn::LitStr>,
+
+    /// Whether to generate a `?Send` variant of the trait
+    pub local: Option<SpanOpt<LocalVariant>>,
+
+    /// Whether generated code requiring `std` is rejected
+    pub no_std: Option<SpanOpt<bool>>,
+
+    /// Whether to leave out the parts of the output that only serve docs and diagnostics
+    pub minimal_codegen: Option<SpanOpt<bool>>,
 }
 
 impl Opts {
-    pub fn no_deps_value(&self) -> bool {
-        self.default_option(self.no_deps, false).0
-    }
-
-    pub fn debug_value(&self) -> bool {
-        self.default_option(self.debug, false).0
+    pub fn debug_value(&self) -> DebugOutput {
+        self.default_option(self.debug, DebugOutput::default()).0
     }
 
     pub fn export_value(&self) -> bool {
         self.default_option(self.export, false).0
     }
 
+    /// The cfg predicate that mocks are gated behind, or `None` when they are exported unconditionally
+    pub fn mock_cfg_predicate(&self) -> Option<TokenStream> {
+        let mock_cfg = match &self.mock_cfg {
+            Some(SpanOpt(predicate, _)) => quote! { #predicate },
+            None => quote! { test },
+        };
+        match &self.export_cfg {
+            Some(SpanOpt(predicate, span)) => {
+                Some(quote_spanned! { *span=> any(#mock_cfg, #predicate) })
+            }
+            None if self.export_value() => None,
+            None => Some(mock_cfg),
+        }
+    }
+
+    pub fn const_trait_value(&self) -> bool {
+        self.default_option(self.const_trait, false).0
+    }
+
+    pub fn minimal_codegen_value(&self) -> bool {
+        self.default_option(self.minimal_codegen, false).0
+    }
+
+    pub fn log_value(&self) -> bool {
+        self.default_option(self.log, false).0
+    }
+
+    pub fn otel_metrics_value(&self) -> bool {
+        self.default_option(self.otel_metrics, false).0
+    }
+
+    pub fn intercept_value(&self) -> bool {
+        self.default_option(self.intercept, false).0
+    }
+
+    pub fn stub_value(&self) -> bool {
+        self.default_option(self.stub, false).0
+    }
+
+    pub fn record_value(&self) -> bool {
+        self.default_option(self.record, false).0
+    }
+
+    pub fn forward_impls_value(&self) -> bool {
+        self.default_option(self.forward_impls, false).0
+    }
+
+    pub fn fingerprint_value(&self) -> bool {
+        self.default_option(self.fingerprint, false).0
+    }
+
+    pub fn dependencies_value(&self) -> bool {
+        self.default_option(self.dependencies, false).0
+    }
+
     pub fn future_send(&self) -> FutureSend {
         self.default_option(self.future_send, FutureSend(true)).0
     }
 
+    /// The cfg predicate of the targets with `Send` futures, `wasm32` being the exception by default
+    pub fn send_cfg_predicate(&self) -> TokenStream {
+        match &self.send_cfg {
+            Some(SpanOpt(predicate, _)) => quote! { #predicate },
+            None => quote! { not(target_arch = "wasm32") },
+        }
+    }
+
+    /// The options of the `?Send` variant of the output for the targets outside `send_cfg`,
+    /// unless the futures are `?Send` anyway, there are none, or `send_cfg = all()` leaves no such targets.
+    pub fn non_send_variant(&self, contains_async: bool) -> Option<Self> {
+        if !contains_async || !self.future_send().0 {
+            return None;
+        }
+        let span = match &self.send_cfg {
+            Some(SpanOpt(syn::Meta::List(list), _))
+                if list.path.is_ident("all") && list.tokens.is_empty() =>
+            {
+                return None
+            }
+            Some(SpanOpt(_, span)) => *span,
+            None => self.default_span,
+        };
+        Some(Self {
+            future_send: Some(SpanOpt(FutureSend(false), span)),
+            impl_sync: Some(SpanOpt(ImplSync(false), span)),
+            ..self.clone()
+        })
+    }
+
+    pub fn impl_sync(&self) -> ImplSync {
+        self.default_option(self.impl_sync, ImplSync(true)).0
+    }
+
+    /// The `Sync` bound on the `T` of `Impl<T>`, which is only required by the `Send` futures of async methods,
+    /// as they borrow it across `.await`s. Synchronous methods leave it out, just like `?Sync` does.
+    pub fn required_impl_sync(&self, borrowed_across_await: bool) -> ImplSync {
+        ImplSync(borrowed_across_await && self.future_send().0 && self.impl_sync().0)
+    }
+
+    /// Use the crate-wide `ENTRAIT_DEFAULTS` for the options that are not given explicitly.
+    pub fn apply_defaults(&mut self) -> syn::Result<()> {
+        let defaults = match std::env::var(DEFAULTS_VAR) {
+            Ok(defaults) if !defaults.trim().is_empty() => defaults,
+            _ => return Ok(()),
+        };
+
+        for entrait_opt in parse_defaults(&defaults)? {
+            self.apply_default(entrait_opt)?;
+        }
+        Ok(())
+    }
+
+    fn apply_default(&mut self, entrait_opt: EntraitOpt) -> syn::Result<()> {
+        fn fallback<T>(option: &mut Option<T>, default: T) {
+            option.get_or_insert(default);
+        }
+
+        match entrait_opt {
+            // `export` and `export = cfg(..)` are the same option
+            EntraitOpt::Export(_) | EntraitOpt::ExportCfg(_)
+                if self.export.is_some() || self.export_cfg.is_some() => {}
+            EntraitOpt::Export(opt) => self.export = Some(opt),
+            EntraitOpt::ExportCfg(opt) => self.export_cfg = Some(opt),
+            EntraitOpt::MockCfg(opt) => fallback(&mut self.mock_cfg, opt),
+            EntraitOpt::SendCfg(opt) => fallback(&mut self.send_cfg, opt),
+            EntraitOpt::MaybeSend(opt) => fallback(&mut self.future_send, opt),
+            EntraitOpt::MaybeSync(opt) => fallback(&mut self.impl_sync, opt),
+            EntraitOpt::Unimock(opt) => fallback(&mut self.unimock, opt),
+            EntraitOpt::UnimockVersion(opt) => fallback(&mut self.unimock_version, opt),
+            EntraitOpt::Mockall(opt) => fallback(&mut self.mockall, opt),
+            EntraitOpt::Mry(opt) => fallback(&mut self.mry, opt),
+            EntraitOpt::Faux(opt) => fallback(&mut self.faux, opt),
+            EntraitOpt::Log(opt) => fallback(&mut self.log, opt),
+            EntraitOpt::OtelMetrics(opt) => fallback(&mut self.otel_metrics, opt),
+            EntraitOpt::Intercept(opt) => fallback(&mut self.intercept, opt),
+            EntraitOpt::Fingerprint(opt) => fallback(&mut self.fingerprint, opt),
+            EntraitOpt::NoStd(opt) => fallback(&mut self.no_std, opt),
+            EntraitOpt::MinimalCodegen(opt) => fallback(&mut self.minimal_codegen, opt),
+            EntraitOpt::Local(SpanOpt(LocalVariant { ident: Some(_), .. }, _)) => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!("`{DEFAULTS_VAR}` can't name the `local` variant, which is named after each trait"),
+                ))
+            }
+            EntraitOpt::Local(SpanOpt(local, span)) => fallback(
+                &mut self.local,
+                SpanOpt(
+                    LocalVariant {
+                        by_default: true,
+                        ..local
+                    },
+                    span,
+                ),
+            ),
+            _ => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!("`{DEFAULTS_VAR}` only supports the options `export`, `mock_cfg`, `?Send`, `send_cfg`, `?Sync`, `local`, `unimock`, `unimock_version`, `mockall`, `mry`, `faux`, `log`, `otel_metrics`, `intercept`, `fingerprint`, `no_std` and `minimal_codegen`"),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Name the unimock `mock_api` after the trait with the crate-wide `ENTRAIT_MOCK_API` convention,
+    /// unless it is given explicitly.
+    pub fn apply_mock_api_convention(&mut self, trait_ident: &syn::Ident) -> syn::Result<()> {
+        // The unimock API of a trait mocked with mockall stays hidden
+        if self.mock_api.is_some()
+            || !self.default_option(self.unimock, false).0
+            || self.default_option(self.mockall, false).0
+        {
+            return Ok(());
+        }
+
+        match std::env::var(MOCK_API_VAR) {
+            Ok(convention) if !convention.is_empty() => {
+                self.mock_api = Some(mock_api_by_convention(&convention, trait_ident)?);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Futures of async methods borrow `Impl<T>` across `.await`s, so they can only be `Send` when `T: Sync`
+    pub fn check_impl_sync(&self, contains_async: bool) -> syn::Result<()> {
+        match self.impl_sync {
+            Some(SpanOpt(ImplSync(false), span)) if contains_async && self.future_send().0 => {
+                Err(syn::Error::new(
+                    span,
+                    "`?Sync` can't be combined with `Send` futures of async methods, which borrow `Impl<T>` across `.await`s. Add `?Send` as well",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Generated code using `std` requires the `std` feature of entrait, and is rejected with `no_std`
+    pub fn check_std(&self, what: &str, span: Span) -> syn::Result<()> {
+        match self.no_std {
+            Some(SpanOpt(true, no_std_span)) => {
+                let mut error = syn::Error::new(span, format!("{what} requires `std`"));
+                error.combine(syn::Error::new(
+                    no_std_span,
+                    "`no_std` rules out generated code requiring `std`",
+                ));
+                Err(error)
+            }
+            _ if !cfg!(feature = "std") => Err(syn::Error::new(
+                span,
+                format!("{what} requires the `std` feature of entrait"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The options whose generated code always requires `std`
+    pub fn check_std_options(&self) -> syn::Result<()> {
+        if let Some(SpanOpt(true, span)) = self.otel_metrics {
+            self.check_std("`otel_metrics`", span)?;
+        }
+        if let Some(SpanOpt(true, span)) = self.record {
+            self.check_std("`record`", span)?;
+        }
+        Ok(())
+    }
+
     pub fn mockable(&self) -> Mockable {
-        if (self.unimock.is_some() && self.mock_api.is_some()) || self.mockall.is_some() {
+        if (self.unimock.is_some() && self.mock_api.is_some())
+            || self.mockall.is_some()
+            || self.mry.is_some()
+            || self.faux.is_some()
+            || self.stub_value()
+            || self.record_value()
+            || self.forward_impls_value()
+        {
             Mockable::Yes
         } else {
             Mockable::No
@@ -72,17 +366,69 @@ pub enum Delegate {
     BySelf,
     ByRef(RefDelegate),
     ByTrait(syn::Ident),
+    /// Static dispatch to one of the listed target types, through a generated enum
+    ByEnum(Vec<syn::Type>),
 }
 
 #[derive(Clone)]
 pub enum RefDelegate {
     AsRef,
     Borrow,
+    Arc,
+    Box,
+    Swap,
+}
+
+/// The crate collecting the delegation targets of `registry`
+#[derive(Clone, Copy)]
+pub enum Registry {
+    Inventory,
+    Linkme,
+}
+
+/// The unimock version whose API the generated mock targets, re-exported by entrait under its own name
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum UnimockVersion {
+    /// `entrait::__unimock_0_5`, from the `unimock-0-5` feature
+    V0_5,
+    /// `entrait::__unimock`, from the `unimock` feature
+    V0_6,
+}
+
+/// The default body of a function's trait method
+#[derive(Clone)]
+pub enum DefaultBody {
+    /// `default_body = fn path`: a function called with the receiver and all the arguments
+    Fallback(syn::Path),
+    /// `default_body = expr`: the return value
+    Value(syn::Expr),
 }
 
 #[derive(Clone, Copy)]
 pub struct FutureSend(pub bool);
 
+/// The `?Send` variant of the trait generated with `local`
+#[derive(Clone, Default)]
+pub struct LocalVariant {
+    pub enabled: bool,
+    /// The name of the variant, instead of `Local{Trait}`
+    pub ident: Option<syn::Ident>,
+    /// Whether it was given crate-wide with `ENTRAIT_DEFAULTS`, which only applies to traits with `async` methods
+    pub by_default: bool,
+}
+
+#[derive(Clone, Copy)]
+pub struct ImplSync(pub bool);
+
+/// Where the `debug` option outputs the expansion
+#[derive(Clone, Copy, Default)]
+pub struct DebugOutput {
+    /// Print the tokens to stdout
+    pub print: bool,
+    /// Write the pretty-printed expansion to `target/entrait-expansions`
+    pub file: bool,
+}
+
 #[derive(Copy, Clone)]
 pub struct SpanOpt<T>(pub T, pub Span);
 
@@ -101,17 +447,107 @@ impl<T> SpanOpt<T> {
 ///
 pub enum EntraitOpt {
     NoDeps(SpanOpt<bool>),
-    Debug(SpanOpt<bool>),
+    Debug(SpanOpt<DebugOutput>),
     DelegateBy(SpanOpt<Delegate>),
     /// Whether to export mocks
     Export(SpanOpt<bool>),
+    /// Export mocks when the cfg predicate holds
+    ExportCfg(SpanOpt<syn::Meta>),
+    /// Gate mocks behind the cfg predicate instead of cfg(test)
+    MockCfg(SpanOpt<syn::Meta>),
     MaybeSend(SpanOpt<FutureSend>),
+    /// The cfg predicate of the targets with `Send` futures
+    SendCfg(SpanOpt<syn::Meta>),
+    MaybeSync(SpanOpt<ImplSync>),
     /// How to name the mock API
     MockApi(MockApiIdent),
     /// Whether to generate unimock impl
     Unimock(SpanOpt<bool>),
+    /// Which unimock version the impl targets
+    UnimockVersion(SpanOpt<UnimockVersion>),
     /// Whether to generate mockall impl
     Mockall(SpanOpt<bool>),
+    /// Whether to generate mry mock
+    Mry(SpanOpt<bool>),
+    /// Whether to generate a faux struct
+    Faux(SpanOpt<bool>),
+    /// Whether to implement the trait for `entrait::Stub`
+    Stub(SpanOpt<bool>),
+    /// Whether to implement the trait for `entrait::record::Recorded`
+    Record(SpanOpt<bool>),
+    /// Whether to implement the trait for `&T`, `Box<T>` and `Arc<T>`
+    ForwardImpls(SpanOpt<bool>),
+    /// The real type of `mockall_double` aliases
+    Double(syn::Type),
+    /// Whether to generate a `const` trait and impl
+    ConstTrait(SpanOpt<bool>),
+    /// Whether to log delegations
+    Log(SpanOpt<bool>),
+    /// Whether to wrap delegations in `tracing` spans, with the given arguments as fields
+    Instrument(SpanOpt<Vec<syn::Ident>>),
+    /// Whether to record delegations in OpenTelemetry metrics
+    OtelMetrics(SpanOpt<bool>),
+    /// Whether delegations call the hooks of a `CallInterceptor`
+    Intercept(SpanOpt<bool>),
+    /// Whether to generate a `?Send` variant of the trait
+    Local(SpanOpt<LocalVariant>),
+    /// Whether to emit a signature fingerprint
+    Fingerprint(SpanOpt<bool>),
+    /// Whether to emit the dependency names of the implementation
+    Dependencies(SpanOpt<bool>),
+    /// Documentation of the generated trait
+    TraitDoc(syn::LitStr),
+    /// Whether generated code requiring `std` is rejected
+    NoStd(SpanOpt<bool>),
+    /// Whether to leave out the parts of the output that only serve docs and diagnostics
+    MinimalCodegen(SpanOpt<bool>),
+    /// Whether to implement the trait for `tokio::sync::watch::Receiver`
+    Watch(SpanOpt<bool>),
+    /// Distributed registry of the delegation targets of a leaf trait
+    Registry(SpanOpt<Registry>),
+    /// Whether methods with default bodies are delegated too
+    DelegateDefaults(SpanOpt<bool>),
+    /// Default body of the generated trait method
+    DefaultBody(SpanOpt<DefaultBody>),
+    /// Name of the generated trait method
+    Method(syn::Ident),
+    /// Another trait to generate with the function, next to the trait of its module
+    Also(syn::Ident),
+    /// Wrapper type to generate JavaScript bindings for
+    WasmBindgen(syn::Ident),
+    /// Python class to generate bindings for
+    Pyo3(syn::Ident),
+    /// Application type to generate C bindings for
+    Ffi(syn::Ident),
+    /// Struct to generate as a tower service of the function
+    TowerService(syn::Ident),
+    /// tonic service trait to implement for `Impl<T>` with the functions of a module
+    Tonic(syn::Path),
+    /// Whether to memoize the function in `entrait::memo::Db`
+    Memo(SpanOpt<bool>),
+    /// Whether the delegation is left to `link_entrait!` in the final crate
+    Deferred(SpanOpt<bool>),
+    /// Configuration struct to implement a getter trait for
+    Config(syn::Type),
+    /// Supertraits of the generated trait, also bounding `Self` in the `Impl<T>` impl
+    SelfBounds(SpanOpt<SelfBounds>),
+    /// Whether the generated trait has `Send + Sync` supertraits
+    Threadsafe(SpanOpt<bool>),
+    /// Extra predicates for the where clause of the generated `Impl<T>` impl
+    WhereClause(SpanOpt<WhereClause>),
+    /// Cargo feature that the whole output is gated on
+    Group(syn::LitStr),
+    /// Whether to reject suspicious functions
+    Strict(SpanOpt<bool>),
+    /// Whether to make the trait dyn-compatible
+    Dyn(SpanOpt<bool>),
+    /// Whether private functions of a module are included in the trait
+    IncludePrivate(SpanOpt<bool>),
+    /// Whether the entraited functions are hidden from the docs
+    HideFn(SpanOpt<bool>),
+    ForwardAttrs(SpanOpt<Vec<syn::Path>>),
+    /// Whether a function of a module is left out of the trait
+    Skip(SpanOpt<bool>),
 }
 
 impl EntraitOpt {
@@ -121,10 +557,56 @@ impl EntraitOpt {
             Self::Debug(opt) => opt.1,
             Self::DelegateBy(opt) => opt.1,
             Self::MaybeSend(opt) => opt.1,
+            Self::SendCfg(opt) => opt.1,
+            Self::MaybeSync(opt) => opt.1,
             Self::Export(opt) => opt.1,
+            Self::ExportCfg(opt) => opt.1,
+            Self::MockCfg(opt) => opt.1,
             Self::MockApi(ident) => ident.0.span(),
             Self::Unimock(opt) => opt.1,
+            Self::UnimockVersion(opt) => opt.1,
             Self::Mockall(opt) => opt.1,
+            Self::Mry(opt) => opt.1,
+            Self::Faux(opt) => opt.1,
+            Self::Stub(opt) => opt.1,
+            Self::Record(opt) => opt.1,
+            Self::ForwardImpls(opt) => opt.1,
+            Self::Double(ty) => syn::spanned::Spanned::span(ty),
+            Self::ConstTrait(opt) => opt.1,
+            Self::Log(opt) => opt.1,
+            Self::OtelMetrics(opt) => opt.1,
+            Self::Intercept(opt) => opt.1,
+            Self::Local(opt) => opt.1,
+            Self::Instrument(opt) => opt.1,
+            Self::Fingerprint(opt) => opt.1,
+            Self::Dependencies(opt) => opt.1,
+            Self::TraitDoc(lit_str) => lit_str.span(),
+            Self::NoStd(opt) => opt.1,
+            Self::MinimalCodegen(opt) => opt.1,
+            Self::Watch(opt) => opt.1,
+            Self::Registry(opt) => opt.1,
+            Self::DelegateDefaults(opt) => opt.1,
+            Self::DefaultBody(opt) => opt.1,
+            Self::Method(ident) => ident.span(),
+            Self::Also(ident) => ident.span(),
+            Self::WasmBindgen(ident) => ident.span(),
+            Self::Pyo3(ident) => ident.span(),
+            Self::Ffi(ident) => ident.span(),
+            Self::TowerService(ident) => ident.span(),
+            Self::Tonic(path) => syn::spanned::Spanned::span(path),
+            Self::Memo(opt) => opt.1,
+            Self::Deferred(opt) => opt.1,
+            Self::Config(ty) => syn::spanned::Spanned::span(ty),
+            Self::SelfBounds(opt) => opt.1,
+            Self::Threadsafe(opt) => opt.1,
+            Self::WhereClause(opt) => opt.1,
+            Self::Group(lit_str) => lit_str.span(),
+            Self::Strict(opt) => opt.1,
+            Self::Dyn(opt) => opt.1,
+            Self::IncludePrivate(opt) => opt.1,
+            Self::HideFn(opt) => opt.1,
+            Self::ForwardAttrs(opt) => opt.1,
+            Self::Skip(opt) => opt.1,
         }
     }
 }
@@ -142,11 +624,16 @@ impl Parse for EntraitOpt {
 
             match ident_string.as_str() {
                 "Send" => Ok(MaybeSend(SpanOpt(FutureSend(false), span))),
+                "Sync" => Ok(MaybeSync(SpanOpt(ImplSync(false), span))),
                 _ => Err(syn::Error::new(
                     span,
                     format!("Unkonwn entrait option \"{ident_string}\""),
                 )),
             }
+        } else if input.peek(syn::token::Dyn) {
+            let dyn_token: syn::token::Dyn = input.parse()?;
+
+            Ok(Dyn(parse_eq_bool(input, true, dyn_token.span)?))
         } else {
             let ident: syn::Ident = input.parse()?;
             let span = ident.span();
@@ -154,19 +641,157 @@ impl Parse for EntraitOpt {
 
             match ident_string.as_str() {
                 "no_deps" => Ok(NoDeps(parse_eq_bool(input, true, span)?)),
-                "debug" => Ok(Debug(parse_eq_bool(input, true, span)?)),
+                // The inverse of `no_deps`, for a first parameter that would be taken for something else
+                "deps" => {
+                    let SpanOpt(deps, span) = parse_eq_bool(input, true, span)?;
+                    Ok(NoDeps(SpanOpt(!deps, span)))
+                }
+                "debug" => Ok(Debug(parse_eq_debug(input, span)?)),
                 "delegate_by" => Ok(DelegateBy(parse_eq_delegate_by(
                     input,
                     Delegate::BySelf,
                     span,
                 )?)),
+                "export" if input.peek(syn::token::Eq) && input.peek2(syn::Ident) => {
+                    let _: syn::token::Eq = input.parse()?;
+                    let cfg: syn::Ident = input.parse()?;
+                    if cfg != "cfg" {
+                        return Err(syn::Error::new(
+                            cfg.span(),
+                            "Expected a boolean or `cfg(predicate)`",
+                        ));
+                    }
+                    let content;
+                    syn::parenthesized!(content in input);
+                    Ok(ExportCfg(SpanOpt(content.parse()?, span)))
+                }
                 "export" => Ok(Export(parse_eq_bool(input, true, span)?)),
+                "mock_cfg" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(MockCfg(SpanOpt(input.parse()?, span)))
+                }
+                "send_cfg" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(SendCfg(SpanOpt(input.parse()?, span)))
+                }
                 "mock_api" => {
                     let _: syn::token::Eq = input.parse()?;
                     Ok(Self::MockApi(MockApiIdent(input.parse()?)))
                 }
                 "unimock" => Ok(Unimock(parse_eq_bool(input, true, span)?)),
+                "unimock_version" => Ok(UnimockVersion(parse_eq_unimock_version(input, span)?)),
                 "mockall" => Ok(Mockall(parse_eq_bool(input, true, span)?)),
+                "mry" => Ok(Mry(parse_eq_bool(input, true, span)?)),
+                "faux" => Ok(Faux(parse_eq_bool(input, true, span)?)),
+                "stub" => Ok(Stub(parse_eq_bool(input, true, span)?)),
+                "record" => Ok(Record(parse_eq_bool(input, true, span)?)),
+                "forward_impls" => Ok(ForwardImpls(parse_eq_bool(input, true, span)?)),
+                "double" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(Double(input.parse()?))
+                }
+                "const_trait" => Ok(ConstTrait(parse_eq_bool(input, true, span)?)),
+                "log" => Ok(Log(parse_eq_bool(input, true, span)?)),
+                "otel_metrics" => Ok(OtelMetrics(parse_eq_bool(input, true, span)?)),
+                "intercept" => Ok(Intercept(parse_eq_bool(input, true, span)?)),
+                "local" => Ok(Local(parse_eq_local(input, span)?)),
+                "instrument" => {
+                    if input.peek(syn::token::Eq) {
+                        let _: syn::token::Eq = input.parse()?;
+                        let content;
+                        syn::bracketed!(content in input);
+                        let fields =
+                            syn::punctuated::Punctuated::<syn::Ident, syn::token::Comma>::parse_terminated(
+                                &content,
+                            )?;
+                        Ok(Instrument(SpanOpt(fields.into_iter().collect(), span)))
+                    } else {
+                        Ok(Instrument(SpanOpt(vec![], span)))
+                    }
+                }
+                "fingerprint" => Ok(Fingerprint(parse_eq_bool(input, true, span)?)),
+                "dependencies" => Ok(Dependencies(parse_eq_bool(input, true, span)?)),
+                "trait_doc" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(TraitDoc(input.parse()?))
+                }
+                "no_std" => Ok(NoStd(parse_eq_bool(input, true, span)?)),
+                "minimal_codegen" => Ok(MinimalCodegen(parse_eq_bool(input, true, span)?)),
+                "watch" => Ok(Watch(parse_eq_bool(input, true, span)?)),
+                "registry" => Ok(Registry(parse_eq_registry(input, span)?)),
+                "delegate_defaults" => Ok(DelegateDefaults(parse_eq_bool(input, true, span)?)),
+                "memo" => Ok(Memo(parse_eq_bool(input, true, span)?)),
+                "deferred" => Ok(Deferred(parse_eq_bool(input, true, span)?)),
+                "strict" => Ok(Strict(parse_eq_bool(input, true, span)?)),
+                "include_private" => Ok(IncludePrivate(parse_eq_bool(input, true, span)?)),
+                "hide_fn" => Ok(HideFn(parse_eq_bool(input, true, span)?)),
+                "forward_attrs" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    let paths = content.parse_terminated(syn::Path::parse_mod_style, syn::Token![,])?;
+                    Ok(ForwardAttrs(SpanOpt(paths.into_iter().collect(), span)))
+                }
+                "skip" => Ok(Skip(parse_eq_bool(input, true, span)?)),
+                "config" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(Config(input.parse()?))
+                }
+                "threadsafe" => Ok(Threadsafe(parse_eq_bool(input, true, span)?)),
+                // `self_bounds` is the original name of `supertraits`
+                "supertraits" | "self_bounds" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(SelfBounds(SpanOpt(
+                        syn::punctuated::Punctuated::parse_separated_nonempty(input)?,
+                        span,
+                    )))
+                }
+                "where_clause" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    let content;
+                    syn::bracketed!(content in input);
+                    Ok(WhereClause(SpanOpt(
+                        syn::punctuated::Punctuated::parse_terminated(&content)?,
+                        span,
+                    )))
+                }
+                "group" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(Group(input.parse()?))
+                }
+                "method" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(Method(input.parse()?))
+                }
+                "also" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(Also(input.parse()?))
+                }
+                "tower_service" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(TowerService(input.parse()?))
+                }
+                "tonic" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(Tonic(input.parse()?))
+                }
+                "wasm_bindgen" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(WasmBindgen(input.parse()?))
+                }
+                "pyo3" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(Pyo3(input.parse()?))
+                }
+                "ffi" => {
+                    let _: syn::token::Eq = input.parse()?;
+                    Ok(Ffi(input.parse()?))
+                }
+                "default_body" => Ok(DefaultBody(parse_eq_default_body(input, span)?)),
+                "append" => Err(syn::Error::new(
+                    span,
+                    "Functions can't be appended to a trait generated elsewhere, since every entrait invocation only sees its own item. Put the functions in a module with `#[entrait(pub Trait)] mod ..` instead",
+                )),
+                "module" => Err(syn::Error::new(span, OTHER_MODULE_MSG)),
                 _ => Err(syn::Error::new(
                     span,
                     format!("Unkonwn entrait option \"{ident_string}\""),
@@ -176,12 +801,200 @@ impl Parse for EntraitOpt {
     }
 }
 
+/// Macros only output items where they are invoked
+pub const OTHER_MODULE_MSG: &str = "The trait can't be generated in another module, since the macro only outputs items next to the function. Re-export it from that module with `pub use` instead";
+
+/// Default options of every invocation in the crate, like `unimock, export`
+const DEFAULTS_VAR: &str = "ENTRAIT_DEFAULTS";
+
+fn parse_defaults(defaults: &str) -> syn::Result<Vec<EntraitOpt>> {
+    use syn::parse::Parser;
+
+    syn::punctuated::Punctuated::<EntraitOpt, syn::token::Comma>::parse_terminated
+        .parse_str(defaults)
+        .map(|entrait_opts| entrait_opts.into_iter().collect())
+        .map_err(|err| {
+            syn::Error::new(
+                Span::call_site(),
+                format!("Invalid `{DEFAULTS_VAR}` \"{defaults}\": {err}"),
+            )
+        })
+}
+
+/// The naming convention of unimock mock APIs, like `{}Mock`, where `{}` is the trait name
+const MOCK_API_VAR: &str = "ENTRAIT_MOCK_API";
+
+fn mock_api_by_convention(convention: &str, trait_ident: &syn::Ident) -> syn::Result<MockApiIdent> {
+    if !convention.contains("{}") {
+        return Err(syn::Error::new(
+            trait_ident.span(),
+            format!("`{MOCK_API_VAR}` must contain `{{}}` for the trait name, like `{{}}Mock`"),
+        ));
+    }
+
+    let name = convention.replace("{}", &trait_ident.to_string());
+    match syn::parse_str::<syn::Ident>(&name) {
+        Ok(_) => Ok(MockApiIdent(syn::Ident::new(&name, trait_ident.span()))),
+        Err(_) => Err(syn::Error::new(
+            trait_ident.span(),
+            format!("`{MOCK_API_VAR}` names the mock API `{name}`, which is not an identifier"),
+        )),
+    }
+}
+
+#[derive(Clone)]
 pub struct MockApiIdent(pub syn::Ident);
 
+pub type SelfBounds = syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Plus>;
+
+pub type WhereClause = syn::punctuated::Punctuated<syn::WherePredicate, syn::token::Comma>;
+
+/// Resolve the `where_clause` predicates for an impl block.
+///
+/// `T` in bounded position refers to the type parameter of `Impl<T>`,
+/// which is only available when the impl is generic over it.
+pub fn impl_where_predicates(
+    where_clause: &WhereClause,
+    impl_t: Option<&syn::Ident>,
+) -> syn::Result<Vec<syn::WherePredicate>> {
+    where_clause
+        .iter()
+        .map(|predicate| {
+            let mut predicate = predicate.clone();
+            if let syn::WherePredicate::Type(predicate_type) = &mut predicate {
+                if let syn::Type::Path(type_path) = &mut predicate_type.bounded_ty {
+                    if type_path.qself.is_none() && type_path.path.is_ident("T") {
+                        match impl_t {
+                            Some(impl_t) => {
+                                type_path.path = impl_t.clone().into();
+                            }
+                            None => {
+                                return Err(syn::Error::new(
+                                    type_path.path.segments[0].ident.span(),
+                                    "`T` can't be bounded here, because the impl is not generic over `Impl<T>`. Use `Self` instead",
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(predicate)
+        })
+        .collect()
+}
+
 fn parse_eq_bool(input: ParseStream, default: bool, span: Span) -> syn::Result<SpanOpt<bool>> {
     parse_eq_value_or_default(input, default, |b: syn::LitBool| Ok(b.value()), span)
 }
 
+/// `local`, `local = bool`, or the name of the variant like `local = LocalFoo`
+fn parse_eq_local(input: ParseStream, span: Span) -> syn::Result<SpanOpt<LocalVariant>> {
+    if !input.peek(syn::token::Eq) || input.peek2(syn::LitBool) {
+        let SpanOpt(enabled, span) = parse_eq_bool(input, true, span)?;
+        return Ok(SpanOpt(
+            LocalVariant {
+                enabled,
+                ..Default::default()
+            },
+            span,
+        ));
+    }
+
+    input.parse::<syn::token::Eq>()?;
+    Ok(SpanOpt(
+        LocalVariant {
+            enabled: true,
+            ident: Some(input.parse()?),
+            by_default: false,
+        },
+        span,
+    ))
+}
+
+/// `unimock_version = "0.5"` or `unimock_version = "0.6"`
+fn parse_eq_unimock_version(
+    input: ParseStream,
+    span: Span,
+) -> syn::Result<SpanOpt<UnimockVersion>> {
+    input.parse::<syn::token::Eq>()?;
+    let lit_str = input.parse::<syn::LitStr>()?;
+
+    let version = match lit_str.value().as_str() {
+        "0.5" => UnimockVersion::V0_5,
+        "0.6" => UnimockVersion::V0_6,
+        _ => {
+            return Err(syn::Error::new(
+                lit_str.span(),
+                "Expected \"0.5\" or \"0.6\"",
+            ))
+        }
+    };
+
+    Ok(SpanOpt(version, span))
+}
+
+/// `registry = inventory` or `registry = linkme`
+fn parse_eq_registry(input: ParseStream, span: Span) -> syn::Result<SpanOpt<Registry>> {
+    input.parse::<syn::token::Eq>()?;
+    let ident = input.parse::<syn::Ident>()?;
+
+    let registry = match ident.to_string().as_str() {
+        "inventory" => Registry::Inventory,
+        "linkme" => Registry::Linkme,
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Expected `inventory` or `linkme`",
+            ))
+        }
+    };
+    check_alloc("`registry`", ident.span())?;
+
+    Ok(SpanOpt(registry, span))
+}
+
+/// `default_body = fn path` or `default_body = expr`
+fn parse_eq_default_body(input: ParseStream, span: Span) -> syn::Result<SpanOpt<DefaultBody>> {
+    input.parse::<syn::token::Eq>()?;
+
+    if input.peek(syn::token::Fn) {
+        input.parse::<syn::token::Fn>()?;
+        Ok(SpanOpt(DefaultBody::Fallback(input.parse()?), span))
+    } else {
+        Ok(SpanOpt(DefaultBody::Value(input.parse()?), span))
+    }
+}
+
+/// `debug`, `debug = bool`, or the outputs like `debug = print + file`
+fn parse_eq_debug(input: ParseStream, span: Span) -> syn::Result<SpanOpt<DebugOutput>> {
+    if !input.peek(syn::token::Eq) || input.peek2(syn::LitBool) {
+        let SpanOpt(print, span) = parse_eq_bool(input, true, span)?;
+        return Ok(SpanOpt(DebugOutput { print, file: false }, span));
+    }
+
+    input.parse::<syn::token::Eq>()?;
+
+    let mut output = DebugOutput::default();
+    for target in
+        syn::punctuated::Punctuated::<syn::Ident, syn::token::Plus>::parse_separated_nonempty(
+            input,
+        )?
+    {
+        match target.to_string().as_str() {
+            "print" => output.print = true,
+            "file" => output.file = true,
+            _ => {
+                return Err(syn::Error::new(
+                    target.span(),
+                    "Expected a boolean, `print` or `file`",
+                ))
+            }
+        }
+    }
+
+    Ok(SpanOpt(output, span))
+}
+
 fn parse_eq_delegate_by(
     input: ParseStream,
     default: Delegate,
@@ -199,18 +1012,63 @@ fn parse_eq_delegate_by(
         return Ok(SpanOpt(Delegate::ByRef(RefDelegate::AsRef), span));
     }
 
+    if input.peek(syn::token::Enum) {
+        let enum_token: syn::token::Enum = input.parse()?;
+        let content;
+        syn::parenthesized!(content in input);
+        let targets =
+            syn::punctuated::Punctuated::<syn::Type, syn::token::Comma>::parse_terminated(
+                &content,
+            )?;
+        if targets.is_empty() {
+            return Err(syn::Error::new(
+                enum_token.span,
+                "`delegate_by = enum(..)` needs at least one target type",
+            ));
+        }
+
+        return Ok(SpanOpt(
+            Delegate::ByEnum(targets.into_iter().collect()),
+            span,
+        ));
+    }
+
     let ident = input.parse::<syn::Ident>()?;
 
     Ok(SpanOpt(
         match ident.to_string().as_str() {
             "Self" => Delegate::BySelf,
             "Borrow" => Delegate::ByRef(RefDelegate::Borrow),
+            "Arc" => {
+                check_alloc("`delegate_by = Arc`", ident.span())?;
+                Delegate::ByRef(RefDelegate::Arc)
+            }
+            "Box" => {
+                check_alloc("`delegate_by = Box`", ident.span())?;
+                Delegate::ByRef(RefDelegate::Box)
+            }
+            "swap" => {
+                check_alloc("`delegate_by = swap`", ident.span())?;
+                Delegate::ByRef(RefDelegate::Swap)
+            }
             _ => Delegate::ByTrait(ident),
         },
         span,
     ))
 }
 
+/// Generated code using `alloc`, like boxed futures, requires the `alloc` feature of entrait
+pub fn check_alloc(what: &str, span: Span) -> syn::Result<()> {
+    if cfg!(feature = "alloc") {
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            span,
+            format!("{what} requires the `alloc` feature of entrait"),
+        ))
+    }
+}
+
 fn parse_eq_value_or_default<V, F, O>(
     input: ParseStream,
     default_value: O,
@@ -231,3 +1089,105 @@ where
 
     Ok(SpanOpt(mapper(parsed)?, span))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_name_mock_api_by_convention() {
+        let trait_ident = syn::Ident::new("Foo", Span::call_site());
+
+        let mock_api = mock_api_by_convention("{}Mock", &trait_ident).unwrap();
+        assert_eq!("FooMock", mock_api.0.to_string());
+
+        assert!(mock_api_by_convention("Mock", &trait_ident).is_err());
+        assert!(mock_api_by_convention("{}-Mock", &trait_ident).is_err());
+    }
+
+    #[test]
+    fn should_apply_defaults_to_options_not_given() {
+        let mut opts = Opts {
+            default_span: Span::call_site(),
+            no_deps: None,
+            debug: None,
+            export: None,
+            export_cfg: None,
+            mock_cfg: None,
+            future_send: None,
+            send_cfg: None,
+            impl_sync: None,
+            mock_api: None,
+            unimock: Some(SpanOpt::of(false)),
+            unimock_version: None,
+            mockall: None,
+            mry: None,
+            faux: None,
+            stub: None,
+            record: None,
+            forward_impls: None,
+            double: None,
+            const_trait: None,
+            log: None,
+            instrument: None,
+            otel_metrics: None,
+            intercept: None,
+            fingerprint: None,
+            dependencies: None,
+            trait_doc: None,
+            local: None,
+            no_std: None,
+            minimal_codegen: None,
+        };
+
+        for entrait_opt in parse_defaults("unimock, export, ?Send").unwrap() {
+            opts.apply_default(entrait_opt).unwrap();
+        }
+
+        assert!(!opts.default_option(opts.unimock, false).0);
+        assert!(opts.export_value());
+        assert!(!opts.future_send().0);
+        assert!(parse_defaults("export = maybe").is_err());
+
+        opts.export = None;
+        opts.export_cfg = Some(SpanOpt::of(syn::parse_quote!(feature = "test-util")));
+        for entrait_opt in parse_defaults("export").unwrap() {
+            opts.apply_default(entrait_opt).unwrap();
+        }
+        assert!(!opts.export_value());
+
+        for entrait_opt in parse_defaults("no_std, otel_metrics").unwrap() {
+            opts.apply_default(entrait_opt).unwrap();
+        }
+        assert!(matches!(opts.no_std, Some(SpanOpt(true, _))));
+        assert!(opts.check_std("`env`", Span::call_site()).is_err());
+        assert!(opts.check_std_options().is_err());
+
+        for entrait_opt in parse_defaults("local").unwrap() {
+            opts.apply_default(entrait_opt).unwrap();
+        }
+        assert!(matches!(
+            opts.local,
+            Some(SpanOpt(
+                LocalVariant {
+                    enabled: true,
+                    ident: None,
+                    by_default: true
+                },
+                _
+            ))
+        ));
+        for entrait_opt in parse_defaults("local = LocalFoo").unwrap() {
+            assert!(opts.apply_default(entrait_opt).is_err());
+        }
+
+        opts.future_send = None;
+        assert!(opts.non_send_variant(true).is_some());
+        assert!(opts.non_send_variant(false).is_none());
+        for entrait_opt in parse_defaults("minimal_codegen, send_cfg = all()").unwrap() {
+            opts.apply_default(entrait_opt).unwrap();
+        }
+        assert!(opts.minimal_codegen_value());
+        assert!(opts.non_send_variant(true).is_none());
+    }
+}
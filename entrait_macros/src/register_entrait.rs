@@ -0,0 +1,53 @@
+//! `register_entrait!`, contributing a provider to the `registry` of a leaf trait.
+//!
+//! ```text
+//! register_entrait!(crate_a::Repository, "postgres", || Box::new(Postgres));
+//! ```
+//!
+//! The trait path is turned into the path of its hidden register macro, `crate_a::__entrait_register_Repository!`,
+//! which gets the module of the trait to find its `RepositoryProvider` in.
+
+use crate::link_entrait::{hidden_macro_path, trait_module};
+
+use proc_macro2::TokenStream;
+use quote::quote_spanned;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+
+pub struct RegisterInput {
+    trait_path: syn::Path,
+    name: syn::Expr,
+    create: syn::Expr,
+}
+
+impl Parse for RegisterInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let trait_path = input.call(syn::Path::parse_mod_style)?;
+        let _: syn::token::Comma = input.parse()?;
+        let name = input.parse()?;
+        let _: syn::token::Comma = input.parse()?;
+        let create = input.parse()?;
+        let _: Option<syn::token::Comma> = input.parse()?;
+
+        Ok(Self {
+            trait_path,
+            name,
+            create,
+        })
+    }
+}
+
+pub fn expand(input: RegisterInput) -> syn::Result<TokenStream> {
+    let RegisterInput {
+        trait_path,
+        name,
+        create,
+    } = &input;
+    let span = trait_path.span();
+    let macro_path = hidden_macro_path(trait_path, "__entrait_register_");
+    let module = trait_module(trait_path);
+
+    Ok(quote_spanned! { span=>
+        #macro_path! { [#module] #name, #create }
+    })
+}
@@ -0,0 +1,33 @@
+//! `Send` futures only on the targets of `send_cfg`, which are all but `wasm32` by default,
+//! with a `?Send` variant of the output for the other targets.
+
+use crate::opt::Opts;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// Put every item of the `Send` output behind `#[cfg(predicate)]`, and every item of the `?Send` output behind `#[cfg(not(predicate))]`
+pub fn gate(
+    send_output: TokenStream,
+    non_send_output: TokenStream,
+    opts: &Opts,
+) -> syn::Result<TokenStream> {
+    let predicate = opts.send_cfg_predicate();
+    let send_file: syn::File = syn::parse2(send_output)?;
+    let non_send_file: syn::File = syn::parse2(non_send_output)?;
+
+    let send_items = send_file.items.into_iter().map(|item| {
+        quote! {
+            #[cfg(#predicate)]
+            #item
+        }
+    });
+    let non_send_items = non_send_file.items.into_iter().map(|item| {
+        quote! {
+            #[cfg(not(#predicate))]
+            #item
+        }
+    });
+
+    Ok(send_items.chain(non_send_items).collect())
+}
@@ -0,0 +1,212 @@
+//! Boxed futures and `impl Trait` outputs, for methods in dyn-compatible traits.
+
+use crate::opt::Opts;
+use crate::signature::ungroup_type;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::visit_mut::VisitMut;
+
+/// Rewrite `async fn` into a fn returning a pinned, boxed future
+/// that captures all the input lifetimes.
+pub fn box_future(sig: &mut syn::Signature, core: &syn::Ident, entrait: &syn::Ident, opts: &Opts) {
+    let span = sig.ident.span();
+    let fut_lifetime = capture_input_lifetimes(sig);
+
+    let output_type: syn::Type = match &sig.output {
+        syn::ReturnType::Default => syn::parse_quote! { () },
+        syn::ReturnType::Type(_, ty) => ty.as_ref().clone(),
+    };
+
+    let opt_send = if opts.future_send().0 {
+        Some(quote! { + ::#core::marker::Send })
+    } else {
+        None
+    };
+
+    sig.asyncness = None;
+    sig.output = syn::parse_quote_spanned! {span=>
+        -> ::#core::pin::Pin<
+            ::#entrait::__alloc::Box<
+                dyn ::#core::future::Future<Output = #output_type> #opt_send + #fut_lifetime
+            >
+        >
+    };
+}
+
+/// Rewrite an `impl Trait` output into a boxed trait object, like `Box<dyn Iterator<Item = T> + 'entrait_fut>`,
+/// that captures all the input lifetimes like the opaque type does.
+/// An explicit lifetime bound, like in `impl Iterator<Item = &'a T> + 'a`, is kept instead.
+///
+/// Returns the boxed output type. `async` methods capture the input lifetimes when the future is boxed with [box_future].
+///
+/// Without an explicit lifetime bound, only `self` may be borrowed:
+/// the trait object would otherwise live for the intersection of several input lifetimes,
+/// which the opaque output type of the delegating method can't name.
+pub fn box_impl_output(
+    sig: &mut syn::Signature,
+    entrait: &syn::Ident,
+) -> syn::Result<Option<syn::Type>> {
+    let span = sig.ident.span();
+    let bounds = match &sig.output {
+        syn::ReturnType::Type(_, ty) => match ungroup_type(ty) {
+            syn::Type::ImplTrait(impl_trait) => impl_trait.bounds.clone(),
+            _ => return Ok(None),
+        },
+        syn::ReturnType::Default => return Ok(None),
+    };
+
+    let has_lifetime_bound = bounds
+        .iter()
+        .any(|bound| matches!(bound, syn::TypeParamBound::Lifetime(_)));
+
+    if !has_lifetime_bound {
+        if let Some(pat_type) = sig.inputs.iter().find_map(|arg| match arg {
+            syn::FnArg::Typed(pat_type) if borrows(pat_type.ty.to_token_stream()) => Some(pat_type),
+            _ => None,
+        }) {
+            return Err(syn::Error::new_spanned(
+                &pat_type.ty,
+                "An `impl Trait` output of a dyn-compatible method can only borrow from `self`. Add an explicit lifetime bound to the output, like `+ 'a`",
+            ));
+        }
+    }
+
+    let boxed_output: syn::Type = if has_lifetime_bound {
+        syn::parse_quote_spanned! { span=> ::#entrait::__alloc::Box<dyn #bounds> }
+    } else if sig.asyncness.is_some() {
+        let fut_lifetime = syn::Lifetime::new(FUT_LIFETIME, Span::call_site());
+        syn::parse_quote_spanned! { span=> ::#entrait::__alloc::Box<dyn #bounds + #fut_lifetime> }
+    } else {
+        let fut_lifetime = capture_input_lifetimes(sig);
+        syn::parse_quote_spanned! { span=> ::#entrait::__alloc::Box<dyn #bounds + #fut_lifetime> }
+    };
+
+    sig.output = syn::parse_quote_spanned! { span=> -> #boxed_output };
+    Ok(Some(boxed_output))
+}
+
+/// Box the output of the entraited fn, coercing it to the boxed `impl Trait` output.
+///
+/// The coercion needs the type annotation inside an `async` block.
+pub fn gen_boxed_output(
+    boxed_output: &syn::Type,
+    expr: impl ToTokens,
+    entrait: &syn::Ident,
+    span: Span,
+) -> TokenStream {
+    let output = syn::Ident::new("output", Span::mixed_site());
+    quote::quote_spanned! { span=>
+        {
+            let #output: #boxed_output = ::#entrait::__alloc::Box::new(#expr);
+            #output
+        }
+    }
+}
+
+/// Whether the tokens contain a reference or a lifetime
+fn borrows(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Punct(punct) => punct.as_char() == '&' || punct.as_char() == '\'',
+        TokenTree::Group(group) => borrows(group.stream()),
+        _ => false,
+    })
+}
+
+const FUT_LIFETIME: &str = "'entrait_fut";
+
+/// Name the elided input lifetimes, and add a lifetime outlived by all of them, and by `Self`
+fn capture_input_lifetimes(sig: &mut syn::Signature) -> syn::Lifetime {
+    let fut_lifetime = syn::Lifetime::new(FUT_LIFETIME, Span::call_site());
+
+    let mut de_elider = DeElider::default();
+    for arg in sig.inputs.iter_mut() {
+        match arg {
+            syn::FnArg::Receiver(receiver) => de_elider.de_elide_receiver(receiver),
+            syn::FnArg::Typed(pat_type) => de_elider.visit_type_mut(&mut pat_type.ty),
+        }
+    }
+
+    let mut input_lifetimes: Vec<syn::Lifetime> = sig
+        .generics
+        .lifetimes()
+        .map(|param| param.lifetime.clone())
+        .collect();
+
+    for lifetime in de_elider.lifetimes.iter().rev() {
+        sig.generics.params.insert(
+            0,
+            syn::GenericParam::Lifetime(syn::LifetimeParam::new(lifetime.clone())),
+        );
+    }
+    input_lifetimes.extend(de_elider.lifetimes);
+
+    sig.generics.params.insert(
+        input_lifetimes.len(),
+        syn::GenericParam::Lifetime(syn::LifetimeParam::new(fut_lifetime.clone())),
+    );
+
+    let where_clause = sig.generics.make_where_clause();
+    for lifetime in &input_lifetimes {
+        where_clause
+            .predicates
+            .push(syn::parse_quote! { #lifetime: #fut_lifetime });
+    }
+    where_clause
+        .predicates
+        .push(syn::parse_quote! { Self: #fut_lifetime });
+
+    fut_lifetime
+}
+
+/// Names all elided lifetimes of the inputs
+#[derive(Default)]
+struct DeElider {
+    lifetimes: Vec<syn::Lifetime>,
+}
+
+impl DeElider {
+    fn next_lifetime(&mut self) -> syn::Lifetime {
+        let lifetime = syn::Lifetime::new(
+            &format!("'entrait{}", self.lifetimes.len()),
+            Span::call_site(),
+        );
+        self.lifetimes.push(lifetime.clone());
+        lifetime
+    }
+
+    fn de_elide_receiver(&mut self, receiver: &mut syn::Receiver) {
+        if let Some((_, lifetime @ None)) = &mut receiver.reference {
+            let explicit = self.next_lifetime();
+            *lifetime = Some(explicit.clone());
+
+            if let syn::Type::Reference(type_reference) = receiver.ty.as_mut() {
+                type_reference.lifetime = Some(explicit);
+            }
+        }
+    }
+}
+
+impl VisitMut for DeElider {
+    fn visit_type_reference_mut(&mut self, type_reference: &mut syn::TypeReference) {
+        if type_reference.lifetime.is_none() {
+            type_reference.lifetime = Some(self.next_lifetime());
+        }
+        syn::visit_mut::visit_type_reference_mut(self, type_reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.next_lifetime();
+        }
+    }
+
+    // Elided lifetimes in these positions are higher-ranked, not input lifetimes:
+    fn visit_type_bare_fn_mut(&mut self, _: &mut syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        _: &mut syn::ParenthesizedGenericArguments,
+    ) {
+    }
+}
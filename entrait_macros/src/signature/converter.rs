@@ -1,13 +1,14 @@
+use super::is_closure_bounds;
 use super::{fn_params, ReceiverGeneration};
-use super::{EntraitSignature, ImplReceiverKind, InputSig};
+use super::{ungroup_type, EntraitSignature, ImplReceiverKind, InputSig};
 use crate::{generics::FnDeps, idents::CrateIdents, opt::Opts};
 
 use proc_macro2::Span;
 use syn::spanned::Spanned;
+use syn::visit_mut::VisitMut;
 
 pub struct SignatureConverter<'a> {
     pub crate_idents: &'a CrateIdents,
-    pub trait_span: Span,
     pub opts: &'a Opts,
     pub input_sig: InputSig<'a>,
     pub deps: &'a FnDeps,
@@ -16,9 +17,12 @@ pub struct SignatureConverter<'a> {
 
 impl<'a> SignatureConverter<'a> {
     /// Convert from an standalone `fn` signature to a trait `fn` signature.
-    pub fn convert_fn_to_trait_fn(&self) -> EntraitSignature {
+    pub fn convert_fn_to_trait_fn(&self) -> syn::Result<EntraitSignature> {
         let mut entrait_sig = EntraitSignature::new(self.input_sig.sig.clone());
 
+        // Trait methods can't be `const`, that is expressed with a `const trait` instead
+        entrait_sig.sig.constness = None;
+
         // strip away attributes
         for fn_arg in entrait_sig.sig.inputs.iter_mut() {
             match fn_arg {
@@ -35,16 +39,26 @@ impl<'a> SignatureConverter<'a> {
         self.generate_params(&mut entrait_sig.sig, receiver_generation);
 
         self.remove_generic_type_params(&mut entrait_sig.sig);
+        if self.opts.default_option(self.opts.unimock, false).0 && self.opts.mock_api.is_some() {
+            self.bound_type_params_by_static(&mut entrait_sig.sig);
+        }
+        if self.input_sig.asyncness.is_some() && self.opts.future_send().0 {
+            self.bound_closures_by_send(&mut entrait_sig.sig);
+        }
+        if matches!(self.impl_receiver_kind, ImplReceiverKind::SelfRef) {
+            self.project_deps_to_self(&mut entrait_sig.sig)?;
+            self.bound_self_by_outlives(&mut entrait_sig.sig);
+        }
         tidy_generics(&mut entrait_sig.sig.generics);
 
         fn_params::fix_fn_param_idents(&mut entrait_sig.sig);
 
-        entrait_sig
+        Ok(entrait_sig)
     }
 
     fn detect_receiver_generation(&self, sig: &syn::Signature) -> ReceiverGeneration {
         match self.deps {
-            FnDeps::NoDeps { .. } => ReceiverGeneration::Insert,
+            FnDeps::NoDeps => ReceiverGeneration::Insert,
             _ => {
                 if sig.inputs.is_empty() {
                     ReceiverGeneration::None // bug?
@@ -70,7 +84,7 @@ impl<'a> SignatureConverter<'a> {
                 let input = sig.inputs.first_mut().unwrap();
                 let input_span = input.span();
                 match input {
-                    syn::FnArg::Typed(pat_type) => match pat_type.ty.as_ref() {
+                    syn::FnArg::Typed(pat_type) => match ungroup_type(&pat_type.ty) {
                         syn::Type::Reference(type_reference) => {
                             let and_token = type_reference.and_token;
                             let lifetime = type_reference.lifetime.clone();
@@ -147,7 +161,12 @@ impl<'a> SignatureConverter<'a> {
 
         for param in params.into_iter() {
             match &param {
-                syn::GenericParam::Type(_) => {}
+                // The deps parameter, and parameters of the deps, are not generics of the method
+                syn::GenericParam::Type(type_param)
+                    if Some(&type_param.ident) == deps_ident
+                        || self.deps.mentions_param(&type_param.ident) => {}
+                syn::GenericParam::Const(const_param)
+                    if self.deps.mentions_param(&const_param.ident) => {}
                 _ => {
                     generics.params.push(param);
                 }
@@ -178,8 +197,176 @@ impl<'a> SignatureConverter<'a> {
     }
 }
 
+impl<'a> SignatureConverter<'a> {
+    /// Unimock tells the generic mocks of a method apart by the `TypeId` of its type parameters, which requires them to be `'static`.
+    fn bound_type_params_by_static(&self, sig: &mut syn::Signature) {
+        let span = sig.ident.span();
+        for type_param in sig.generics.type_params_mut() {
+            type_param
+                .bounds
+                .push(syn::parse_quote_spanned! { span=> 'static });
+        }
+    }
+
+    /// Closures are captured by the future of an `async` method, so they must be `Send` for the future to be `Send`.
+    fn bound_closures_by_send(&self, sig: &mut syn::Signature) {
+        let core = &self.crate_idents.core;
+        let send: syn::TypeParamBound = syn::parse_quote! { ::#core::marker::Send };
+
+        for fn_arg in sig.inputs.iter_mut() {
+            if let syn::FnArg::Typed(pat_type) = fn_arg {
+                if let syn::Type::ImplTrait(impl_trait) = pat_type.ty.as_mut() {
+                    if is_closure_bounds(&impl_trait.bounds) {
+                        impl_trait.bounds.push(send.clone());
+                    }
+                }
+            }
+        }
+
+        // Bound where the closure bound is, either inline or in the where clause
+        let mut where_closure_params = vec![];
+        for type_param in sig.generics.type_params_mut() {
+            if is_closure_bounds(&type_param.bounds) {
+                type_param.bounds.push(send.clone());
+            } else {
+                where_closure_params.push(type_param.ident.clone());
+            }
+        }
+        if let Some(where_clause) = &mut sig.generics.where_clause {
+            for predicate in where_clause.predicates.iter_mut() {
+                if let syn::WherePredicate::Type(predicate_type) = predicate {
+                    let bounds_closure_param = match ungroup_type(&predicate_type.bounded_ty) {
+                        syn::Type::Path(type_path) => where_closure_params.iter().any(|ident| {
+                            type_path.qself.is_none() && type_path.path.is_ident(ident)
+                        }),
+                        _ => false,
+                    };
+                    if bounds_closure_param && is_closure_bounds(&predicate_type.bounds) {
+                        predicate_type.bounds.push(send.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Types projecting from the generic deps type `D`, like `<D::Db as Database>::Row`,
+    /// become `<<Self as HasDb>::Db as Database>::Row` in the trait method, which gets a `where Self: HasDb` clause.
+    /// The `D::Db` shorthand is only expanded when `D` has a single trait bound, otherwise it must be written as `<D as HasDb>::Db`.
+    fn project_deps_to_self(&self, sig: &mut syn::Signature) -> syn::Result<()> {
+        let (deps_ident, trait_bounds) = match self.deps {
+            FnDeps::Generic {
+                generic_param: Some(generic_param),
+                trait_bounds,
+                ..
+            } => (generic_param, trait_bounds),
+            _ => return Ok(()),
+        };
+
+        let mut single_trait_bounds = trait_bounds.iter().filter_map(|bound| match bound {
+            syn::TypeParamBound::Trait(trait_bound) => Some(&trait_bound.path),
+            _ => None,
+        });
+        let mut deps_to_self = DepsToSelf {
+            deps_ident,
+            single_trait_bound: match (single_trait_bounds.next(), single_trait_bounds.next()) {
+                (Some(path), None) => Some(path),
+                _ => None,
+            },
+            replaced: false,
+            error: None,
+        };
+
+        for fn_arg in sig.inputs.iter_mut() {
+            if let syn::FnArg::Typed(pat_type) = fn_arg {
+                deps_to_self.visit_type_mut(&mut pat_type.ty);
+            }
+        }
+        deps_to_self.visit_return_type_mut(&mut sig.output);
+        if let Some(where_clause) = &mut sig.generics.where_clause {
+            deps_to_self.visit_where_clause_mut(where_clause);
+        }
+
+        if let Some(error) = deps_to_self.error {
+            return Err(error);
+        }
+
+        if deps_to_self.replaced && !trait_bounds.is_empty() {
+            let span = sig.ident.span();
+            sig.generics
+                .make_where_clause()
+                .predicates
+                .push(syn::parse_quote_spanned! { span=> Self: #(#trait_bounds)+* });
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> SignatureConverter<'a> {
+    /// A deps type outliving lifetimes of the fn, like `D: 'a` when the output borrows from both `deps` and another parameter,
+    /// becomes a `where Self: 'a` clause of the trait method.
+    fn bound_self_by_outlives(&self, sig: &mut syn::Signature) {
+        let outlives = match self.deps {
+            FnDeps::Generic { outlives, .. } if !outlives.is_empty() => outlives,
+            _ => return,
+        };
+
+        let span = sig.ident.span();
+        sig.generics
+            .make_where_clause()
+            .predicates
+            .push(syn::parse_quote_spanned! { span=> Self: #(#outlives)+* });
+    }
+}
+
+/// Replaces the generic deps type with `Self` in type paths.
+///
+/// Associated type shorthands like `Self::Db` do not resolve in impl blocks, so these are fully qualified.
+struct DepsToSelf<'a> {
+    deps_ident: &'a syn::Ident,
+    single_trait_bound: Option<&'a syn::Path>,
+    replaced: bool,
+    error: Option<syn::Error>,
+}
+
+impl<'a> VisitMut for DepsToSelf<'a> {
+    fn visit_type_path_mut(&mut self, type_path: &mut syn::TypePath) {
+        syn::visit_mut::visit_type_path_mut(self, type_path);
+
+        if type_path.qself.is_some() || type_path.path.leading_colon.is_some() {
+            return;
+        }
+        let first = match type_path.path.segments.first() {
+            Some(first) if first.ident == *self.deps_ident && first.arguments.is_empty() => first,
+            _ => return,
+        };
+        let self_ty = syn::Ident::new("Self", first.ident.span());
+
+        if type_path.path.segments.len() == 1 {
+            *type_path = syn::parse_quote! { #self_ty };
+        } else {
+            let trait_path = match self.single_trait_bound {
+                Some(trait_path) => trait_path,
+                None => {
+                    self.error.get_or_insert_with(|| {
+                        syn::Error::new(
+                            first.ident.span(),
+                            "Ambiguous associated type of the deps type. Use the qualified form `<D as Trait>::Assoc`",
+                        )
+                    });
+                    return;
+                }
+            };
+            let rest = type_path.path.segments.iter().skip(1);
+
+            *type_path = syn::parse_quote! { <#self_ty as #trait_path>::#(#rest)::* };
+        }
+        self.replaced = true;
+    }
+}
+
 fn is_type_eq_ident(ty: &syn::Type, ident: &syn::Ident) -> bool {
-    match ty {
+    match ungroup_type(ty) {
         syn::Type::Path(type_path) if type_path.path.segments.len() == 1 => {
             type_path.path.segments.first().unwrap().ident == *ident
         }
@@ -21,6 +21,8 @@ impl ParamStatus {
 }
 
 pub fn fix_fn_param_idents(sig: &mut syn::Signature) {
+    strip_binding_modes(sig);
+
     if fix_ident_conflicts(sig).is_ok() {
         return;
     }
@@ -32,6 +34,29 @@ pub fn fix_fn_param_idents(sig: &mut syn::Signature) {
     autogenerate_for_non_idents(sig);
 }
 
+/// The identifiers of the non-receiver parameters, after [fix_fn_param_idents]
+pub fn fn_param_idents(sig: &syn::Signature) -> impl Iterator<Item = &syn::Ident> {
+    sig.inputs.iter().filter_map(|fn_arg| match fn_arg {
+        syn::FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+            syn::Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+            _ => None,
+        },
+        syn::FnArg::Receiver(_) => None,
+    })
+}
+
+/// `mut f: F` is not allowed in a method without a body
+fn strip_binding_modes(sig: &mut syn::Signature) {
+    for fn_arg in sig.inputs.iter_mut() {
+        if let syn::FnArg::Typed(pat_type) = fn_arg {
+            if let syn::Pat::Ident(param_ident) = pat_type.pat.as_mut() {
+                param_ident.by_ref = None;
+                param_ident.mutability = None;
+            }
+        }
+    }
+}
+
 fn fix_ident_conflicts(sig: &mut syn::Signature) -> ParamStatus {
     let mut status = ParamStatus::Ok;
     let fn_ident_string = sig.ident.to_string();
@@ -197,6 +222,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_strip_binding_modes() {
+        convert_expect(
+            syn::parse_quote! {
+                fn foo(mut f: F, ref g: G)
+            },
+            syn::parse_quote! {
+                fn foo(f: F, g: G)
+            },
+        );
+    }
+
     #[test]
     fn should_extract_only_unambiguous_pat_idents() {
         convert_expect(
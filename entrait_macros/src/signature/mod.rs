@@ -1,6 +1,7 @@
+pub mod boxed_future;
 pub mod converter;
 
-mod fn_params;
+pub mod fn_params;
 
 use std::ops::Deref;
 
@@ -36,29 +37,28 @@ pub enum ImplReceiverKind {
 #[derive(Clone)]
 pub struct EntraitSignature {
     pub sig: syn::Signature,
-    pub et_lifetimes: Vec<EntraitLifetime>,
 }
 
 impl EntraitSignature {
     pub fn new(sig: syn::Signature) -> Self {
-        Self {
-            sig,
-            et_lifetimes: vec![],
-        }
+        Self { sig }
     }
 }
 
-#[derive(Clone)]
-pub struct AssociatedFut {
-    pub ident: syn::Ident,
-    pub output: syn::Type,
-    pub base_lifetime: syn::Lifetime,
+/// Look through invisible groups, which `macro_rules!` wraps around interpolated `$ty:ty` fragments.
+pub fn ungroup_type(mut ty: &syn::Type) -> &syn::Type {
+    while let syn::Type::Group(group) = ty {
+        ty = group.elem.as_ref();
+    }
+    ty
 }
 
-/// Only used for associated future:
-#[derive(Clone)]
-pub struct EntraitLifetime {
-    pub lifetime: syn::Lifetime,
+/// Whether the output is an `impl Trait` type
+pub fn returns_impl_trait(sig: &syn::Signature) -> bool {
+    match &sig.output {
+        syn::ReturnType::Type(_, ty) => matches!(ungroup_type(ty), syn::Type::ImplTrait(_)),
+        syn::ReturnType::Default => false,
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -67,3 +67,36 @@ pub enum ReceiverGeneration {
     Rewrite,
     None,
 }
+
+/// Whether a generic type parameter is bounded by `Fn`, `FnMut` or `FnOnce`.
+///
+/// Such closure parameters stay generic parameters of the trait method, instead of the trait.
+pub fn is_closure_param(generics: &syn::Generics, ident: &syn::Ident) -> bool {
+    let inline = generics
+        .type_params()
+        .any(|type_param| type_param.ident == *ident && is_closure_bounds(&type_param.bounds));
+
+    inline
+        || generics.where_clause.as_ref().map_or(false, |where_clause| {
+            where_clause.predicates.iter().any(|predicate| match predicate {
+                syn::WherePredicate::Type(predicate_type) => {
+                    matches!(ungroup_type(&predicate_type.bounded_ty), syn::Type::Path(type_path) if type_path.qself.is_none() && type_path.path.is_ident(ident))
+                        && is_closure_bounds(&predicate_type.bounds)
+                }
+                _ => false,
+            })
+        })
+}
+
+pub fn is_closure_bounds(
+    bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Plus>,
+) -> bool {
+    bounds.iter().any(|bound| match bound {
+        syn::TypeParamBound::Trait(trait_bound) => {
+            trait_bound.path.segments.last().map_or(false, |segment| {
+                segment.ident == "Fn" || segment.ident == "FnMut" || segment.ident == "FnOnce"
+            })
+        }
+        _ => false,
+    })
+}
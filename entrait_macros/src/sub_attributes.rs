@@ -35,6 +35,19 @@ pub fn contains_async_trait(sub_attributes: &[SubAttribute]) -> bool {
         .any(|sub_attributes| matches!(sub_attributes, SubAttribute::AsyncTrait(_)))
 }
 
+/// Whether there is an `#[async_trait]` with `Send` futures, i.e. not `#[async_trait(?Send)]`
+pub fn contains_send_async_trait(sub_attributes: &[SubAttribute]) -> bool {
+    sub_attributes
+        .iter()
+        .any(|sub_attribute| match sub_attribute {
+            SubAttribute::AsyncTrait(attribute) => match &attribute.meta {
+                syn::Meta::List(list) => list.tokens.to_string().replace(' ', "") != "?Send",
+                _ => true,
+            },
+            _ => false,
+        })
+}
+
 impl<'t> ToTokens for SubAttribute<'t> {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
@@ -38,7 +38,7 @@ pub struct Punctuator<'s, S, P, E: ToTokens> {
 pub fn comma_sep(
     stream: &mut TokenStream,
     span: proc_macro2::Span,
-) -> Punctuator<EmptyToken, syn::token::Comma, EmptyToken> {
+) -> Punctuator<'_, EmptyToken, syn::token::Comma, EmptyToken> {
     Punctuator::new(stream, EmptyToken, syn::token::Comma(span), EmptyToken)
 }
 
@@ -92,3 +92,27 @@ where
         }
     }
 }
+
+/// Whether the ident appears anywhere in the tokens
+pub fn mentions_ident(tokens: TokenStream, ident: &syn::Ident) -> bool {
+    tokens.into_iter().any(|tree| match tree {
+        proc_macro2::TokenTree::Ident(tree_ident) => tree_ident == *ident,
+        proc_macro2::TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// Whether the lifetime appears anywhere in the tokens
+pub fn mentions_lifetime(tokens: TokenStream, lifetime: &syn::Lifetime) -> bool {
+    let mut after_apostrophe = false;
+    tokens.into_iter().any(|tree| {
+        let found = match &tree {
+            proc_macro2::TokenTree::Ident(ident) => after_apostrophe && *ident == lifetime.ident,
+            proc_macro2::TokenTree::Group(group) => mentions_lifetime(group.stream(), lifetime),
+            _ => false,
+        };
+        after_apostrophe =
+            matches!(&tree, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '\'');
+        found
+    })
+}
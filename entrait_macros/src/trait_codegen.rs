@@ -1,15 +1,19 @@
+use std::collections::BTreeSet;
+
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
 
 use crate::{
     analyze_generics::TraitFn,
-    attributes,
+    attributes::{self, IsEmpty},
     generics::{self, TraitDependencyMode, TraitIndirection},
-    idents::CrateIdents,
-    input::FnInputMode,
-    opt::{Opts, SpanOpt},
-    signature::EntraitSignature,
+    graph,
+    idents::{CrateIdents, GenericIdents},
+    input::{FnInputMode, LiteralAttrs},
+    mockall_deps,
+    opt::{MockApiIdent, Opts, SpanOpt, UnimockVersion},
+    signature::{ungroup_type, EntraitSignature},
     sub_attributes::{contains_async_trait, SubAttribute},
     token_util::push_tokens,
 };
@@ -23,22 +27,30 @@ pub struct TraitCodegen<'s> {
 }
 
 impl<'s> TraitCodegen<'s> {
+    #[allow(clippy::too_many_arguments)]
     pub fn gen_trait_def(
         &self,
         visibility: &syn::Visibility,
         trait_ident: &syn::Ident,
         trait_generics: &generics::TraitGenerics,
         supertraits: &Supertraits,
+        trait_types: &[syn::TraitItemType],
+        trait_consts: &[syn::TraitItemConst],
         trait_fns: &[TraitFn],
         fn_input_mode: &FnInputMode<'_>,
     ) -> syn::Result<TokenStream> {
         let span = trait_ident.span();
 
+        // With mockall, `mock_api` names the mockall mock, and the unimock API stays hidden
+        let mockall = self.opts.default_option(self.opts.mockall, false).0;
         let opt_unimock_attr = match self.opts.default_option(self.opts.unimock, false) {
             SpanOpt(true, span) => Some(attributes::ExportGatedAttr {
                 params: attributes::UnimockAttrParams {
-                    trait_ident,
-                    mock_api: self.opts.mock_api.as_ref(),
+                    mock_api: self.opts.mock_api.as_ref().filter(|_| !mockall),
+                    version: self
+                        .opts
+                        .default_option(self.opts.unimock_version, UnimockVersion::V0_6)
+                        .0,
                     trait_indirection: self.trait_indirection,
                     crate_idents: self.crate_idents,
                     trait_fns,
@@ -50,18 +62,35 @@ impl<'s> TraitCodegen<'s> {
             _ => None,
         };
 
-        let opt_entrait_for_trait_attr = match self.trait_dependency_mode {
-            TraitDependencyMode::Concrete(_) => {
-                Some(attributes::Attr(attributes::EntraitForTraitParams {
-                    crate_idents: self.crate_idents,
-                }))
+        // A trait unimock can't implement is only rejected when its mock API is asked for
+        let opt_unimock_attr = match opt_unimock_attr {
+            Some(unimock_attr) => {
+                match unimock_unsupported_item(trait_types, trait_consts, trait_fns) {
+                    Some(error) if unimock_attr.params.mock_api.is_some() => return Err(error),
+                    Some(_) => None,
+                    None => Some(unimock_attr),
+                }
             }
-            _ => None,
+            None => None,
         };
 
+        if let Some(unimock_attr) = &opt_unimock_attr {
+            check_unimock_api_support(&unimock_attr.params, trait_fns, self.sub_attributes)?;
+        }
+
         let opt_mockall_automock_attr = match self.opts.default_option(self.opts.mockall, false) {
+            SpanOpt(true, span) => {
+                check_mockall_support(trait_fns)?;
+                Some(attributes::ExportGatedAttr {
+                    params: attributes::MockallAutomockParams { span },
+                    opts: self.opts,
+                })
+            }
+            _ => None,
+        };
+        let opt_mry_attr = match self.opts.default_option(self.opts.mry, false) {
             SpanOpt(true, span) => Some(attributes::ExportGatedAttr {
-                params: attributes::MockallAutomockParams { span },
+                params: attributes::MryParams { span },
                 opts: self.opts,
             }),
             _ => None,
@@ -71,19 +100,183 @@ impl<'s> TraitCodegen<'s> {
             fn_input_mode,
         };
 
+        let opt_mockall_alias = match (&opt_mockall_automock_attr, &self.opts.mock_api) {
+            (Some(_), Some(mock_api)) => Some(self.gen_mockall_alias(
+                mock_api,
+                &trait_visibility,
+                trait_ident,
+                trait_generics,
+            )?),
+            _ => None,
+        };
+
+        let opt_double_aliases = match &self.opts.double {
+            Some(real_ty) => Some(self.gen_double_aliases(
+                real_ty,
+                opt_mockall_automock_attr.is_some(),
+                &trait_visibility,
+                trait_ident,
+                trait_generics,
+            )?),
+            None => None,
+        };
+
+        // The mockall macro of the trait, and the mocks of multi-bound deps, which are built with such macros
+        let opt_mockall_deps = match &opt_mockall_automock_attr {
+            Some(_) => {
+                let opt_cfg = self
+                    .opts
+                    .mock_cfg_predicate()
+                    .map(|predicate| quote_spanned! { span=> #[cfg(#predicate)] });
+                let opt_trait_macro = if trait_generics.params.is_empty() {
+                    let fn_sigs = trait_fns
+                        .iter()
+                        .map(|trait_fn| {
+                            let cfg_attrs = trait_fn.cfg_attrs();
+                            let sig = make_trait_fn_sig(
+                                &trait_fn.entrait_sig,
+                                self.sub_attributes,
+                                self.opts,
+                            );
+                            quote! { #(#cfg_attrs)* #sig }
+                        })
+                        .collect::<Vec<_>>();
+                    Some(mockall_deps::gen_trait_macro(
+                        self.crate_idents,
+                        trait_ident,
+                        &fn_sigs,
+                        opt_cfg.as_ref(),
+                    ))
+                } else {
+                    None
+                };
+                let deps_mocks = mockall_deps::gen_deps_mocks(
+                    self.crate_idents,
+                    &trait_visibility,
+                    trait_fns,
+                    opt_cfg.as_ref(),
+                );
+                Some(quote! {
+                    #opt_trait_macro
+                    #deps_mocks
+                })
+            }
+            None => None,
+        };
+
+        let opt_faux = match self.opts.default_option(self.opts.faux, false) {
+            SpanOpt(true, span) => Some(self.gen_faux(
+                span,
+                &trait_visibility,
+                trait_ident,
+                trait_generics,
+                trait_types,
+                trait_consts,
+                trait_fns,
+            )?),
+            _ => None,
+        };
+
+        // Supertraits of a raw trait are written by the user, who also implements them for the mocks
+        let opt_mock_supertraits = match (fn_input_mode, supertraits) {
+            (FnInputMode::RawTrait(_), _) | (_, Supertraits::None) => None,
+            (_, Supertraits::Some { bounds, .. }) => self.gen_mock_supertraits(
+                trait_ident,
+                trait_generics,
+                bounds,
+                opt_mockall_automock_attr.is_some(),
+                opt_faux.is_some(),
+            )?,
+        };
+
+        let opt_stub = match self.opts.default_option(self.opts.stub, false) {
+            SpanOpt(true, span) => Some(self.gen_stub(
+                span,
+                trait_ident,
+                trait_generics,
+                trait_types,
+                trait_consts,
+                trait_fns,
+            )?),
+            _ => None,
+        };
+
+        let opt_record = match self.opts.default_option(self.opts.record, false) {
+            SpanOpt(true, span) => {
+                Some(self.gen_record(span, trait_ident, trait_generics, trait_types, trait_fns)?)
+            }
+            _ => None,
+        };
+
+        let opt_forward_impls = match self.opts.default_option(self.opts.forward_impls, false) {
+            SpanOpt(true, span) => Some(self.gen_forward_impls(
+                span,
+                trait_ident,
+                trait_generics,
+                supertraits,
+                trait_types,
+                trait_consts,
+                trait_fns,
+            )?),
+            _ => None,
+        };
+
+        let opt_fingerprint = if self.opts.fingerprint_value() {
+            Some(self.gen_fingerprint(
+                &trait_visibility,
+                trait_ident,
+                trait_generics,
+                supertraits,
+                trait_types,
+                trait_consts,
+                trait_fns,
+            ))
+        } else {
+            None
+        };
+
+        let opt_dependencies = if self.opts.dependencies_value() {
+            Some(self.gen_dependencies(&trait_visibility, trait_ident, trait_fns))
+        } else {
+            None
+        };
+
         let fn_defs = trait_fns.iter().map(|trait_fn| {
             let attrs = &trait_fn.attrs;
             let trait_fn_sig =
                 make_trait_fn_sig(&trait_fn.entrait_sig, self.sub_attributes, self.opts);
 
-            quote! {
-                #(#attrs)*
-                #trait_fn_sig;
+            match &trait_fn.default_body {
+                Some(default_body) => quote! {
+                    #(#attrs)*
+                    #trait_fn_sig #default_body
+                },
+                None => quote! {
+                    #(#attrs)*
+                    #trait_fn_sig;
+                },
             }
         });
 
         let params = trait_generics.trait_params();
         let where_clause = trait_generics.trait_where_clause();
+        let opt_const = if self.opts.const_trait_value() {
+            Some(syn::token::Const(span))
+        } else {
+            None
+        };
+
+        let opt_on_unimplemented = self.gen_on_unimplemented(span, trait_ident, fn_input_mode);
+
+        // Without a `trait_doc`, the trait of a single fn is documented by the summary of the fn's docs
+        let trait_docs = match (&self.opts.trait_doc, fn_input_mode, trait_fns) {
+            (Some(trait_doc), ..) => quote! { #[doc = #trait_doc] },
+            (None, FnInputMode::SingleFn(_), [trait_fn]) => {
+                let summary = doc_summary(&trait_fn.attrs);
+                quote! { #(#summary)* }
+            }
+            _ => quote! {},
+        };
 
         let trait_sub_attributes = self.sub_attributes.iter().filter(|attr| {
             matches!(
@@ -93,14 +286,975 @@ impl<'s> TraitCodegen<'s> {
         });
 
         Ok(quote_spanned! { span=>
+            #opt_on_unimplemented
             #opt_unimock_attr
-            #opt_entrait_for_trait_attr
             #opt_mockall_automock_attr
+            #opt_mry_attr
             #(#trait_sub_attributes)*
-            #trait_visibility trait #trait_ident #params #supertraits #where_clause {
+            #trait_docs
+            #trait_visibility #opt_const trait #trait_ident #params #supertraits #where_clause {
+                #(#trait_types)*
+                #(#trait_consts)*
                 #(#fn_defs)*
             }
+
+            #opt_mockall_alias
+            #opt_double_aliases
+            #opt_mockall_deps
+            #opt_faux
+            #opt_mock_supertraits
+            #opt_stub
+            #opt_record
+            #opt_forward_impls
+            #opt_fingerprint
+            #opt_dependencies
+        })
+    }
+
+    /// A hint for when the trait is not implemented, which is usually because of a missing delegation.
+    /// Traits of concrete dependencies are entraited again as traits, which gives them the hint.
+    fn gen_on_unimplemented(
+        &self,
+        span: Span,
+        trait_ident: &syn::Ident,
+        fn_input_mode: &FnInputMode<'_>,
+    ) -> Option<TokenStream> {
+        if self.opts.minimal_codegen_value() {
+            return None;
+        }
+        let note = match (self.trait_indirection, fn_input_mode, self.trait_dependency_mode) {
+            (
+                TraitIndirection::Plain,
+                FnInputMode::SingleFn(fn_ident),
+                TraitDependencyMode::Generic(_),
+            ) => format!("did you forget to wrap your App type in `Impl::new`, or to implement one of the dependencies of `{fn_ident}`?"),
+            (
+                TraitIndirection::Plain,
+                FnInputMode::Module(mod_ident),
+                TraitDependencyMode::Generic(_),
+            ) => format!("did you forget to wrap your App type in `Impl::new`, or to implement one of the dependencies of the functions in `{mod_ident}`?"),
+            (
+                TraitIndirection::Plain,
+                FnInputMode::InherentImpl(self_ty),
+                TraitDependencyMode::Generic(_),
+            ) => format!(
+                "did you forget to wrap your App type in `Impl::new`, to implement `AsRef<{}>` for it, or to implement one of the dependencies of the methods?",
+                self_ty.to_token_stream().to_string().replace(' ', "")
+            ),
+            (TraitIndirection::Trait, FnInputMode::RawTrait(LiteralAttrs(attrs)), _)
+                if !attrs.iter().any(is_on_unimplemented) =>
+            {
+                format!("did you forget to implement `{trait_ident}` for your App type, or to wrap it in `Impl::new`?")
+            }
+            _ => return None,
+        };
+        let message = format!("`{{Self}}` does not implement `{trait_ident}`");
+        let label = format!("`{trait_ident}` is not implemented for `{{Self}}`");
+        let on_unimplemented = quote_spanned! { span=>
+            diagnostic::on_unimplemented(message = #message, label = #label, note = #note)
+        };
+
+        // Mocking macros copy the attributes of the trait to items where this one is not allowed
+        let mocked_by_attribute = [self.opts.unimock, self.opts.mockall, self.opts.mry]
+            .into_iter()
+            .any(|opt| self.opts.default_option(opt, false).0)
+            || self
+                .sub_attributes
+                .iter()
+                .any(|attr| matches!(attr, SubAttribute::Automock(_)));
+        if !mocked_by_attribute {
+            return Some(quote_spanned! { span=> #[#on_unimplemented] });
+        }
+
+        self.opts.mock_cfg_predicate().map(|predicate| {
+            quote_spanned! { span=>
+                #[cfg_attr(not(#predicate), #on_unimplemented)]
+            }
+        })
+    }
+
+    /// An alias for the mockall mock, named by `mock_api`:
+    ///
+    /// ```text
+    /// #[cfg(test)]
+    /// pub type TraitMock = MockTrait;
+    /// ```
+    fn gen_mockall_alias(
+        &self,
+        mock_api: &MockApiIdent,
+        trait_visibility: &TraitVisibility,
+        trait_ident: &syn::Ident,
+        trait_generics: &generics::TraitGenerics,
+    ) -> syn::Result<TokenStream> {
+        let alias_ident = &mock_api.0;
+        let span = alias_ident.span();
+
+        if !trait_generics.params.is_empty() {
+            return Err(syn::Error::new(
+                span,
+                "`mock_api` is not supported by `mockall` for generic traits",
+            ));
+        }
+
+        let mock_ident = quote::format_ident!("Mock{}", trait_ident);
+        let opt_cfg_test = self
+            .opts
+            .mock_cfg_predicate()
+            .map(|predicate| quote_spanned! { span=> #[cfg(#predicate)] });
+
+        Ok(quote_spanned! { span=>
+            #opt_cfg_test
+            #trait_visibility type #alias_ident = #mock_ident;
+        })
+    }
+
+    /// A pair of aliases for `mockall_double`, with the real type named by `double` and the mockall mock:
+    ///
+    /// ```text
+    /// pub type TraitDouble = Impl<App>;
+    ///
+    /// #[cfg(test)]
+    /// pub type MockTraitDouble = MockTrait;
+    /// ```
+    ///
+    /// `#[double] use path::TraitDouble;` then imports the mock in tests.
+    fn gen_double_aliases(
+        &self,
+        real_ty: &syn::Type,
+        mockall: bool,
+        trait_visibility: &TraitVisibility,
+        trait_ident: &syn::Ident,
+        trait_generics: &generics::TraitGenerics,
+    ) -> syn::Result<TokenStream> {
+        let span = real_ty.span();
+
+        if !mockall {
+            return Err(syn::Error::new(span, "`double` requires `mockall`"));
+        }
+        if !trait_generics.params.is_empty() {
+            return Err(syn::Error::new(
+                span,
+                "`double` is not supported for generic traits",
+            ));
+        }
+
+        let alias_ident = quote::format_ident!("{}Double", trait_ident);
+        let mock_alias_ident = quote::format_ident!("Mock{}Double", trait_ident);
+        let mock_ident = quote::format_ident!("Mock{}", trait_ident);
+        let opt_cfg_test = self
+            .opts
+            .mock_cfg_predicate()
+            .map(|predicate| quote_spanned! { span=> #[cfg(#predicate)] });
+
+        Ok(quote_spanned! { span=>
+            #trait_visibility type #alias_ident = #real_ty;
+
+            #opt_cfg_test
+            #trait_visibility type #mock_alias_ident = #mock_ident;
+        })
+    }
+
+    /// A struct mocked with faux, implementing the trait:
+    ///
+    /// ```text
+    /// #[cfg(test)]
+    /// #[faux::create]
+    /// pub struct FauxTrait;
+    ///
+    /// #[cfg(test)]
+    /// #[faux::methods]
+    /// impl Trait for FauxTrait {
+    ///     fn method(&self, arg: u32) -> String {
+    ///         unimplemented!()
+    ///     }
+    /// }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    fn gen_faux(
+        &self,
+        span: Span,
+        trait_visibility: &TraitVisibility,
+        trait_ident: &syn::Ident,
+        trait_generics: &generics::TraitGenerics,
+        trait_types: &[syn::TraitItemType],
+        trait_consts: &[syn::TraitItemConst],
+        trait_fns: &[TraitFn],
+    ) -> syn::Result<TokenStream> {
+        if !trait_generics.params.is_empty() {
+            return Err(syn::Error::new(
+                span,
+                "`faux` is not supported for generic traits",
+            ));
+        }
+        if let Some(trait_type) = trait_types.first() {
+            return Err(syn::Error::new(
+                trait_type.ident.span(),
+                "`faux` is not supported for traits with associated types",
+            ));
+        }
+        if let Some(trait_const) = trait_consts
+            .iter()
+            .find(|trait_const| trait_const.default.is_none())
+        {
+            return Err(syn::Error::new(
+                trait_const.ident.span(),
+                "`faux` requires associated constants to have a default value",
+            ));
+        }
+
+        let core = &self.crate_idents.core;
+        let struct_ident = quote::format_ident!("Faux{}", trait_ident);
+        let opt_cfg_test = self
+            .opts
+            .mock_cfg_predicate()
+            .map(|predicate| quote_spanned! { span=> #[cfg(#predicate)] });
+        let fn_items = trait_fns.iter().map(|trait_fn| {
+            let sig = trait_fn.sig();
+            let cfg_attrs = trait_fn.cfg_attrs();
+            quote_spanned! { span=>
+                #(#cfg_attrs)*
+                #sig {
+                    ::#core::unimplemented!()
+                }
+            }
+        });
+
+        Ok(quote_spanned! { span=>
+            #opt_cfg_test
+            #[::faux::create]
+            #trait_visibility struct #struct_ident;
+
+            #opt_cfg_test
+            #[::faux::methods]
+            #[allow(unused_variables)]
+            impl #trait_ident for #struct_ident {
+                #(#fn_items)*
+            }
+        })
+    }
+
+    /// Implementations of the `supertraits` for the mock structs,
+    /// which must satisfy them to implement the trait:
+    ///
+    /// ```text
+    /// #[cfg(test)]
+    /// impl Marker for MockTrait {}
+    /// ```
+    ///
+    /// Auto traits, and `Debug` and `Default` which mockall implements itself, are skipped.
+    fn gen_mock_supertraits(
+        &self,
+        trait_ident: &syn::Ident,
+        trait_generics: &generics::TraitGenerics,
+        bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::token::Plus>,
+        mockall: bool,
+        faux: bool,
+    ) -> syn::Result<Option<TokenStream>> {
+        let trait_bounds = bounds
+            .iter()
+            .filter_map(|bound| match bound {
+                syn::TypeParamBound::Trait(trait_bound)
+                    if matches!(trait_bound.modifier, syn::TraitBoundModifier::None) =>
+                {
+                    let ident = &trait_bound.path.segments.last()?.ident;
+                    if graph::MARKER_TRAITS.contains(&ident.to_string().as_str()) {
+                        None
+                    } else {
+                        Some(trait_bound)
+                    }
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if trait_bounds.is_empty() || !(mockall || faux) {
+            return Ok(None);
+        }
+        if !trait_generics.params.is_empty() {
+            return Err(syn::Error::new(
+                trait_bounds[0].span(),
+                "mocks can't implement the `supertraits` of generic traits",
+            ));
+        }
+
+        let opt_cfg = self
+            .opts
+            .mock_cfg_predicate()
+            .map(|predicate| quote! { #[cfg(#predicate)] });
+        let opt_cfg = opt_cfg.as_ref();
+
+        // Along with the traits each mock implements by itself
+        let mut mocks: Vec<(syn::Ident, &[&str])> = vec![];
+        if mockall {
+            mocks.push((
+                quote::format_ident!("Mock{}", trait_ident),
+                &["Debug", "Default"],
+            ));
+        }
+        if faux {
+            mocks.push((quote::format_ident!("Faux{}", trait_ident), &[]));
+        }
+
+        let impls = mocks.iter().flat_map(|(mock_ident, implemented)| {
+            trait_bounds
+                .iter()
+                .filter(|trait_bound| {
+                    let ident = &trait_bound.path.segments.last().unwrap().ident;
+                    !implemented.contains(&ident.to_string().as_str())
+                })
+                .map(move |trait_bound| {
+                    let span = trait_bound.span();
+                    quote_spanned! { span=>
+                        #opt_cfg
+                        impl #trait_bound for #mock_ident {}
+                    }
+                })
+        });
+
+        Ok(Some(quote! { #(#impls)* }))
+    }
+
+    /// An implementation for `entrait::Stub`, panicking in every method:
+    ///
+    /// ```text
+    /// impl Trait for ::entrait::Stub {
+    ///     fn method(&self, arg: u32) -> String {
+    ///         panic!("`Trait::method` is not implemented by `entrait::Stub`")
+    ///     }
+    /// }
+    /// ```
+    fn gen_stub(
+        &self,
+        span: Span,
+        trait_ident: &syn::Ident,
+        trait_generics: &generics::TraitGenerics,
+        trait_types: &[syn::TraitItemType],
+        trait_consts: &[syn::TraitItemConst],
+        trait_fns: &[TraitFn],
+    ) -> syn::Result<TokenStream> {
+        if let Some(trait_type) = trait_types.first() {
+            return Err(syn::Error::new(
+                trait_type.ident.span(),
+                "`stub` is not supported for traits with associated types",
+            ));
+        }
+        if let Some(trait_const) = trait_consts
+            .iter()
+            .find(|trait_const| trait_const.default.is_none())
+        {
+            return Err(syn::Error::new(
+                trait_const.ident.span(),
+                "`stub` requires associated constants to have a default value",
+            ));
+        }
+
+        let entrait = &self.crate_idents.entrait;
+        let core = &self.crate_idents.core;
+        let params = trait_generics.trait_params();
+        let args = trait_generics.arguments(&generics::ImplIndirection::None);
+        let where_clause = trait_generics.trait_where_clause();
+        let impl_sub_attributes = self
+            .sub_attributes
+            .iter()
+            .filter(|sub_attr| matches!(sub_attr, SubAttribute::AsyncTrait(_)));
+        let fn_items = trait_fns.iter().map(|trait_fn| {
+            let sig = trait_fn.sig();
+            let cfg_attrs = trait_fn.cfg_attrs();
+            let message = format!(
+                "`{trait_ident}::{}` is not implemented by `entrait::Stub`",
+                sig.ident
+            );
+            quote_spanned! { span=>
+                #(#cfg_attrs)*
+                #sig {
+                    ::#core::panic!(#message)
+                }
+            }
+        });
+
+        Ok(quote_spanned! { span=>
+            #(#impl_sub_attributes)*
+            #[allow(unused_variables)]
+            impl #params #trait_ident #args for ::#entrait::Stub #where_clause {
+                #(#fn_items)*
+            }
+        })
+    }
+
+    /// An implementation for `entrait::record::Recorded`, recording or replaying the calls to the inner implementation:
+    ///
+    /// ```text
+    /// impl<EntraitT: Sync + 'static> Trait for ::entrait::record::Recorded<EntraitT>
+    /// where
+    ///     EntraitT: Trait,
+    /// {
+    ///     fn method(&self, arg0: u32) -> String {
+    ///         let args = ::entrait::record::args([::entrait::record::json(&arg0)]);
+    ///         match ::entrait::record::Recorded::replay_output(self, "Trait::method", &args) {
+    ///             Some(output) => output,
+    ///             None => {
+    ///                 let output = <EntraitT as Trait>::method(::entrait::record::Recorded::inner(self), arg0);
+    ///                 ::entrait::record::Recorded::record_output(self, "Trait::method", args, &output);
+    ///                 output
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn gen_record(
+        &self,
+        span: Span,
+        trait_ident: &syn::Ident,
+        trait_generics: &generics::TraitGenerics,
+        trait_types: &[syn::TraitItemType],
+        trait_fns: &[TraitFn],
+    ) -> syn::Result<TokenStream> {
+        if let Some(trait_type) = trait_types.first() {
+            return Err(syn::Error::new(
+                trait_type.ident.span(),
+                "`record` is not supported for traits with associated types",
+            ));
+        }
+
+        let entrait = &self.crate_idents.entrait;
+        let core = &self.crate_idents.core;
+        let record = quote_spanned! { span=> ::#entrait::record };
+        let generic_idents = GenericIdents::new(self.crate_idents, span);
+        let impl_t = &generic_idents.impl_t;
+        let params = trait_generics.impl_params_from_idents(
+            &generic_idents,
+            generics::TakesSelfByValue(false),
+            self.opts
+                .required_impl_sync(trait_fns.iter().any(|trait_fn| trait_fn.originally_async)),
+        );
+        let args = trait_generics.arguments(&generics::ImplIndirection::None);
+        let where_predicates = trait_generics.where_predicates.iter();
+        let impl_sub_attributes = self
+            .sub_attributes
+            .iter()
+            .filter(|sub_attr| matches!(sub_attr, SubAttribute::AsyncTrait(_)));
+
+        let args_ident = syn::Ident::new("args", Span::mixed_site());
+        let output_ident = syn::Ident::new("output", Span::mixed_site());
+
+        let fn_items = trait_fns
+            .iter()
+            .map(|trait_fn| {
+                let mut sig = trait_fn.sig().clone();
+                check_record_support(&sig)?;
+
+                // The arguments are both recorded and passed on, so they all need a name
+                let mut arg_idents = vec![];
+                for (index, fn_arg) in sig.inputs.iter_mut().enumerate() {
+                    if let syn::FnArg::Typed(pat_type) = fn_arg {
+                        let arg_ident = quote::format_ident!("arg{}", index, span = span);
+                        *pat_type.pat = syn::parse_quote! { #arg_ident };
+                        arg_idents.push(arg_ident);
+                    }
+                }
+
+                let fn_ident = &sig.ident;
+                let method = format!("{trait_ident}::{fn_ident}");
+                let opt_dot_await = trait_fn.opt_dot_await(span);
+                let call = quote_spanned! { span=>
+                    <#impl_t as #trait_ident #args>::#fn_ident(#record::Recorded::inner(self), #(#arg_idents),*) #opt_dot_await
+                };
+                let call = match &sig.unsafety {
+                    Some(unsafety) => quote_spanned! { span=> #unsafety { #call } },
+                    None => call,
+                };
+
+                let body = quote_spanned! { span=>
+                    let #args_ident = #record::args([#(#record::json(&#arg_idents)),*]);
+                    match #record::Recorded::replay_output(self, #method, &#args_ident) {
+                        ::#core::option::Option::Some(#output_ident) => #output_ident,
+                        ::#core::option::Option::None => {
+                            let #output_ident = #call;
+                            #record::Recorded::record_output(self, #method, #args_ident, &#output_ident);
+                            #output_ident
+                        }
+                    }
+                };
+                let body = if trait_fn.boxed_future {
+                    quote_spanned! { span=>
+                        ::#entrait::__alloc::Box::pin(async move { #body })
+                    }
+                } else {
+                    body
+                };
+
+                let cfg_attrs = trait_fn.cfg_attrs();
+                Ok(quote_spanned! { span=>
+                    #(#cfg_attrs)*
+                    #sig {
+                        #body
+                    }
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(quote_spanned! { span=>
+            #(#impl_sub_attributes)*
+            impl #params #trait_ident #args for #record::Recorded<#impl_t>
+            where
+                #impl_t: #trait_ident #args,
+                #(#where_predicates,)*
+            {
+                #(#fn_items)*
+            }
+        })
+    }
+
+    /// Implementations for `&T`, `Box<T>` and `Arc<T>`, forwarding every item to the `T`:
+    ///
+    /// ```text
+    /// impl<EntraitT> Trait for &EntraitT
+    /// where
+    ///     EntraitT: Trait + ?Sized,
+    /// {
+    ///     fn method(&self, arg1: u32) -> String {
+    ///         <EntraitT as Trait>::method(&**self, arg1)
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// `Box<T>` and `Arc<T>` require the `alloc` feature of entrait.
+    #[allow(clippy::too_many_arguments)]
+    fn gen_forward_impls(
+        &self,
+        span: Span,
+        trait_ident: &syn::Ident,
+        trait_generics: &generics::TraitGenerics,
+        supertraits: &Supertraits,
+        trait_types: &[syn::TraitItemType],
+        trait_consts: &[syn::TraitItemConst],
+        trait_fns: &[TraitFn],
+    ) -> syn::Result<TokenStream> {
+        let entrait = &self.crate_idents.entrait;
+        let generic_idents = GenericIdents::new(self.crate_idents, span);
+        let impl_t = &generic_idents.impl_t;
+        let args = trait_generics.arguments(&generics::ImplIndirection::None);
+        let target = quote_spanned! { span=> #impl_t as #trait_ident #args };
+
+        // Lifetimes must be declared before the `T`
+        let (lifetimes, others): (Vec<_>, Vec<_>) = trait_generics
+            .params
+            .iter()
+            .partition(|param| matches!(param, syn::GenericParam::Lifetime(_)));
+        let params = quote_spanned! { span=> <#(#lifetimes,)* #impl_t: ?Sized #(, #others)*> };
+
+        let mut where_predicates: Vec<TokenStream> = vec![quote_spanned! { span=>
+            #impl_t: #trait_ident #args
+        }];
+        where_predicates.extend(
+            trait_generics
+                .where_predicates
+                .iter()
+                .map(|predicate| predicate.to_token_stream()),
+        );
+        // The pointers implement the supertraits through the `T`, like `Send` and `Sync`, or not at all
+        if let Supertraits::Some { bounds, .. } = supertraits {
+            where_predicates.push(quote_spanned! { span=> Self: #bounds });
+        }
+        // `#[async_trait]` futures borrow `self`, which is only `Send` when the `T` is `Sync`
+        if contains_async_trait(self.sub_attributes) && self.opts.future_send().0 {
+            where_predicates.push(quote_spanned! { span=> #impl_t: Sync });
+        }
+        let impl_sub_attributes = self
+            .sub_attributes
+            .iter()
+            .filter(|sub_attr| matches!(sub_attr, SubAttribute::AsyncTrait(_)))
+            .collect::<Vec<_>>();
+
+        let type_items = trait_types.iter().map(|trait_type| {
+            let syn::TraitItemType {
+                ident, generics, ..
+            } = trait_type;
+            let (_, type_args, where_clause) = generics.split_for_impl();
+            quote_spanned! { span=>
+                type #ident #generics = <#target>::#ident #type_args #where_clause;
+            }
+        });
+        let const_items = trait_consts.iter().map(|trait_const| {
+            let syn::TraitItemConst { ident, ty, .. } = trait_const;
+            quote_spanned! { span=>
+                const #ident: #ty = <#target>::#ident;
+            }
+        });
+        let fn_items = trait_fns
+            .iter()
+            .map(|trait_fn| {
+                let mut sig = make_trait_fn_sig(&trait_fn.entrait_sig, self.sub_attributes, self.opts);
+                let opt_self_arg = match sig.inputs.first() {
+                    Some(syn::FnArg::Receiver(receiver))
+                        if receiver.reference.is_some() && receiver.mutability.is_none() =>
+                    {
+                        Some(quote_spanned! { span=> &**self, })
+                    }
+                    Some(syn::FnArg::Receiver(receiver)) => {
+                        return Err(syn::Error::new(
+                            receiver.self_token.span,
+                            "`forward_impls` requires methods to take `&self`, since the pointers only lend out shared references",
+                        ))
+                    }
+                    _ => None,
+                };
+
+                let mut arg_idents = vec![];
+                for (index, fn_arg) in sig.inputs.iter_mut().enumerate() {
+                    if let syn::FnArg::Typed(pat_type) = fn_arg {
+                        let arg_ident = quote::format_ident!("arg{}", index, span = span);
+                        *pat_type.pat = syn::parse_quote! { #arg_ident };
+                        arg_idents.push(arg_ident);
+                    }
+                }
+
+                let fn_ident = &sig.ident;
+                // Method generics only used by the output can't be inferred
+                let method_params = sig
+                    .generics
+                    .type_params()
+                    .map(|param| &param.ident)
+                    .chain(sig.generics.const_params().map(|param| &param.ident))
+                    .collect::<Vec<_>>();
+                let opt_turbofish = if method_params.is_empty() {
+                    None
+                } else {
+                    Some(quote_spanned! { span=> ::<#(#method_params),*> })
+                };
+                // Only `#[async_trait]` leaves the method `async`, other futures are passed on as they are
+                let opt_dot_await = sig
+                    .asyncness
+                    .map(|_| quote_spanned! { span=> .await });
+                let call = quote_spanned! { span=>
+                    <#target>::#fn_ident #opt_turbofish(#opt_self_arg #(#arg_idents),*) #opt_dot_await
+                };
+                let call = match &sig.unsafety {
+                    Some(unsafety) => quote_spanned! { span=> #unsafety { #call } },
+                    None => call,
+                };
+
+                let cfg_attrs = trait_fn.cfg_attrs();
+                Ok(quote_spanned! { span=>
+                    #(#cfg_attrs)*
+                    #sig {
+                        #call
+                    }
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        let items = quote! {
+            #(#type_items)*
+            #(#const_items)*
+            #(#fn_items)*
+        };
+
+        let mut self_tys = vec![quote_spanned! { span=> &#impl_t }];
+        if cfg!(feature = "alloc") {
+            self_tys.push(quote_spanned! { span=> ::#entrait::__alloc::Box<#impl_t> });
+            self_tys.push(quote_spanned! { span=> ::#entrait::__alloc::Arc<#impl_t> });
+        }
+        let impls = self_tys.into_iter().map(|self_ty| {
+            quote_spanned! { span=>
+                #(#impl_sub_attributes)*
+                impl #params #trait_ident #args for #self_ty
+                where
+                    #(#where_predicates,)*
+                {
+                    #items
+                }
+            }
+        });
+
+        Ok(quote! {
+            #(#impls)*
+        })
+    }
+
+    /// A hidden module next to the trait, with its normalized signature and a hash of it:
+    ///
+    /// ```text
+    /// pub mod __entrait_fingerprint_Trait {
+    ///     pub const SIGNATURE: &str = "trait Trait { fn method(&self, arg: u32) -> String; }";
+    ///     pub const FINGERPRINT: u64 = 0x...;
+    /// }
+    /// ```
+    ///
+    /// Mock attributes and method bodies are left out, so that only API changes alter the fingerprint.
+    #[allow(clippy::too_many_arguments)]
+    fn gen_fingerprint(
+        &self,
+        trait_visibility: &TraitVisibility,
+        trait_ident: &syn::Ident,
+        trait_generics: &generics::TraitGenerics,
+        supertraits: &Supertraits,
+        trait_types: &[syn::TraitItemType],
+        trait_consts: &[syn::TraitItemConst],
+        trait_fns: &[TraitFn],
+    ) -> TokenStream {
+        let span = trait_ident.span();
+        let params = trait_generics.trait_params();
+        let where_clause = trait_generics.trait_where_clause();
+        let fn_sigs = trait_fns.iter().map(|trait_fn| {
+            make_trait_fn_sig(&trait_fn.entrait_sig, self.sub_attributes, self.opts)
+        });
+
+        // Default values are not part of the API
+        let types = trait_types.iter().map(|trait_type| syn::TraitItemType {
+            attrs: vec![],
+            default: None,
+            ..trait_type.clone()
+        });
+        let consts = trait_consts.iter().map(|trait_const| {
+            let syn::TraitItemConst {
+                ident,
+                generics,
+                ty,
+                ..
+            } = trait_const;
+            quote! { const #ident #generics: #ty; }
+        });
+
+        let signature = normalized_tokens(quote! {
+            trait #trait_ident #params #supertraits #where_clause {
+                #(#types)*
+                #(#consts)*
+                #(#fn_sigs;)*
+            }
+        });
+        let fingerprint = syn::LitInt::new(&format!("{:#018x}", fnv1a(&signature)), span);
+        let mod_ident = quote::format_ident!("__entrait_fingerprint_{}", trait_ident);
+
+        quote_spanned! { span=>
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            #trait_visibility mod #mod_ident {
+                pub const SIGNATURE: &str = #signature;
+                pub const FINGERPRINT: u64 = #fingerprint;
+            }
+        }
+    }
+
+    /// A hidden module next to the trait, with the sorted names of the dependencies of its implementation:
+    ///
+    /// ```text
+    /// pub mod __entrait_dependencies_Trait {
+    ///     pub const DEPENDENCIES: &[&str] = &["FetchUser", "Repository"];
+    /// }
+    /// ```
+    ///
+    /// The names are the same as in the `ENTRAIT_GRAPH_OUT` graph: the last path segment of dependency traits, or a concrete type.
+    fn gen_dependencies(
+        &self,
+        trait_visibility: &TraitVisibility,
+        trait_ident: &syn::Ident,
+        trait_fns: &[TraitFn],
+    ) -> TokenStream {
+        let span = trait_ident.span();
+        let dependencies = trait_fns
+            .iter()
+            .flat_map(|trait_fn| graph::dependencies(&trait_fn.deps))
+            .collect::<BTreeSet<_>>()
+            .into_iter();
+        let mod_ident = quote::format_ident!("__entrait_dependencies_{}", trait_ident);
+
+        quote_spanned! { span=>
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            #trait_visibility mod #mod_ident {
+                pub const DEPENDENCIES: &[&str] = &[#(#dependencies),*];
+            }
+        }
+    }
+}
+
+/// Tokens separated by single spaces, since the `Display` of `TokenStream` varies between compilers
+fn normalized_tokens(stream: TokenStream) -> String {
+    use proc_macro2::{Delimiter, Spacing, TokenTree};
+
+    /// `joint` is whether the last token was a punctuation joined to the next one, like the `-` in `->`
+    fn push_words(words: &mut Vec<String>, joint: &mut bool, stream: TokenStream) {
+        for token_tree in stream {
+            let (word, next_joint) = match token_tree {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => {
+                            push_words(words, joint, group.stream());
+                            continue;
+                        }
+                    };
+                    words.push(open.to_string());
+                    *joint = false;
+                    push_words(words, joint, group.stream());
+                    (close.to_string(), false)
+                }
+                TokenTree::Punct(punct) => (
+                    punct.as_char().to_string(),
+                    punct.spacing() == Spacing::Joint,
+                ),
+                TokenTree::Ident(ident) => (ident.to_string(), false),
+                TokenTree::Literal(literal) => (literal.to_string(), false),
+            };
+
+            match words.last_mut() {
+                Some(last) if *joint => last.push_str(&word),
+                _ => words.push(word),
+            }
+            *joint = next_joint;
+        }
+    }
+
+    let mut words = vec![];
+    push_words(&mut words, &mut false, stream);
+    words.join(" ")
+}
+
+/// 64-bit FNV-1a, which unlike `std`'s hashers is stable across Rust versions
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Unimock 0.5 predates native `async fn` in traits
+fn check_unimock_api_support(
+    params: &attributes::UnimockAttrParams,
+    trait_fns: &[TraitFn],
+    sub_attributes: &[SubAttribute],
+) -> syn::Result<()> {
+    if params.version != UnimockVersion::V0_5
+        || params.is_empty()
+        || contains_async_trait(sub_attributes)
+    {
+        return Ok(());
+    }
+
+    match trait_fns.iter().find(|trait_fn| trait_fn.originally_async) {
+        Some(trait_fn) => Err(syn::Error::new(
+            trait_fn.sig().ident.span(),
+            "unimock 0.5 does not support native `async fn` in traits. Use `#[async_trait]`, or unimock 0.6 instead of `unimock_version = \"0.5\"`",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// An item that the implementation of the trait for `Unimock` would need, which unimock can't generate
+fn unimock_unsupported_item(
+    trait_types: &[syn::TraitItemType],
+    trait_consts: &[syn::TraitItemConst],
+    trait_fns: &[TraitFn],
+) -> Option<syn::Error> {
+    if let Some(trait_type) = trait_types.first() {
+        return Some(syn::Error::new(
+            trait_type.ident.span(),
+            "unimock can't mock traits with associated types",
+        ));
+    }
+    if let Some(trait_const) = trait_consts
+        .iter()
+        .find(|trait_const| trait_const.default.is_none())
+    {
+        return Some(syn::Error::new(
+            trait_const.ident.span(),
+            "unimock can't mock traits with associated constants without a default value",
+        ));
+    }
+    trait_fns
+        .iter()
+        .map(TraitFn::sig)
+        .find(|sig| outputs_impl_trait(sig))
+        .map(|sig| {
+            syn::Error::new_spanned(
+                &sig.output,
+                "unimock can't mock `impl Trait` outputs, other than the futures of async methods",
+            )
         })
+}
+
+/// Whether the output, or the output of the future of an async method, is an `impl Trait` type
+fn outputs_impl_trait(sig: &syn::Signature) -> bool {
+    let output = match &sig.output {
+        syn::ReturnType::Type(_, ty) => ungroup_type(ty),
+        syn::ReturnType::Default => return false,
+    };
+    let impl_trait = match output {
+        syn::Type::ImplTrait(impl_trait) => impl_trait,
+        _ => return false,
+    };
+    if sig.asyncness.is_some() {
+        return true;
+    }
+
+    // `impl Future<Output = T>`, like the desugared output of an entraited `async fn`
+    let future_output = impl_trait.bounds.iter().find_map(|bound| match bound {
+        syn::TypeParamBound::Trait(trait_bound) => {
+            let segment = trait_bound.path.segments.last()?;
+            if segment.ident != "Future" {
+                return None;
+            }
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(arguments) => {
+                    arguments.args.iter().find_map(|argument| match argument {
+                        syn::GenericArgument::AssocType(assoc_type)
+                            if assoc_type.ident == "Output" =>
+                        {
+                            Some(&assoc_type.ty)
+                        }
+                        _ => None,
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    });
+    match future_output {
+        Some(future_output) => matches!(ungroup_type(future_output), syn::Type::ImplTrait(_)),
+        None => true,
+    }
+}
+
+/// Recorded calls are replayed from JSON, so the method must be callable with the recorded arguments alone
+fn check_record_support(sig: &syn::Signature) -> syn::Result<()> {
+    match sig.inputs.first() {
+        Some(syn::FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+        Some(syn::FnArg::Receiver(receiver)) => {
+            return Err(syn::Error::new(
+                receiver.self_token.span,
+                "`record` requires methods to take `&self`",
+            ))
+        }
+        _ => {
+            return Err(syn::Error::new(
+                sig.ident.span(),
+                "`record` requires methods to take `&self`",
+            ))
+        }
+    }
+    match sig.generics.type_params().next() {
+        Some(type_param) => Err(syn::Error::new(
+            type_param.ident.span(),
+            "`record` is not supported for generic methods",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Mockall can't mock const generic methods
+fn check_mockall_support(trait_fns: &[TraitFn]) -> syn::Result<()> {
+    match trait_fns
+        .iter()
+        .find_map(|trait_fn| trait_fn.sig().generics.const_params().next())
+    {
+        Some(const_param) => Err(syn::Error::new(
+            const_param.ident.span(),
+            "mockall does not support const generic methods",
+        )),
+        None => Ok(()),
     }
 }
 
@@ -151,7 +1305,7 @@ impl<'a> ToTokens for TraitVisibility<'a> {
                     }
                 }
             }
-            FnInputMode::SingleFn(_) | FnInputMode::RawTrait(_) => {
+            FnInputMode::SingleFn(_) | FnInputMode::InherentImpl(_) | FnInputMode::RawTrait(_) => {
                 push_tokens!(stream, self.visibility);
             }
         }
@@ -195,3 +1349,29 @@ fn make_trait_fn_sig(
 
     sig
 }
+
+fn is_on_unimplemented(attr: &syn::Attribute) -> bool {
+    let segments = &attr.path().segments;
+    segments.len() == 2
+        && segments[0].ident == "diagnostic"
+        && segments[1].ident == "on_unimplemented"
+}
+
+/// The `#[doc]` attributes of the first paragraph of the docs
+fn doc_summary(attrs: &[syn::Attribute]) -> Vec<&syn::Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .take_while(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(line),
+                        ..
+                    }),
+                ..
+            }) => !line.value().trim().is_empty(),
+            _ => false,
+        })
+        .collect()
+}
@@ -0,0 +1,104 @@
+//! Extracting entraited dependencies in [actix-web](https://docs.rs/actix-web) handlers.
+//!
+//! The application is registered as app data with [register], and the [Deps] extractor hands it to handlers.
+//! Handlers are written generically, bounded on the traits they call,
+//! so that the same routes serve the real application and mocks in tests:
+//!
+//! ```rust
+//! use actix_web::web;
+//! use entrait::actix_deps::{register, Deps};
+//! use entrait::*;
+//!
+//! #[entrait(pub GetGreeting, no_deps)]
+//! async fn get_greeting(name: String) -> String {
+//!     format!("Hello, {name}!")
+//! }
+//!
+//! async fn greet<D: GetGreeting + 'static>(deps: Deps<D>) -> String {
+//!     deps.get_greeting("world".to_string()).await
+//! }
+//!
+//! fn routes<D: GetGreeting + 'static>(config: &mut web::ServiceConfig) {
+//!     config.route("/greeting", web::get().to(greet::<D>));
+//! }
+//!
+//! struct App;
+//!
+//! let app = actix_web::App::new()
+//!     .configure(register(Impl::new(App)))
+//!     .configure(routes::<Impl<App>>);
+//! ```
+//!
+//! The dependencies are stored in a [web::Data], which shares them between the workers of the server behind an `Arc`.
+//! Unlike axum state, they don't need to be [Clone], and mocks can be registered as they are.
+
+use core::fmt;
+use core::ops::Deref;
+use std::future::{ready, Ready};
+
+use ::actix_web::dev::Payload;
+use ::actix_web::{web, FromRequest, HttpRequest};
+
+/// Register dependencies as app data, for [Deps] to extract.
+///
+/// Used with `App::configure`, or `ServiceConfig::configure` for a scope.
+pub fn register<D: 'static>(deps: D) -> impl FnOnce(&mut web::ServiceConfig) {
+    register_data(web::Data::new(deps))
+}
+
+/// Register dependencies already shared in a [web::Data], like a `dyn` trait object, for [Deps] to extract.
+pub fn register_data<D: ?Sized + 'static>(
+    deps: web::Data<D>,
+) -> impl FnOnce(&mut web::ServiceConfig) {
+    move |config| {
+        config.app_data(deps);
+    }
+}
+
+/// Extractor of the dependencies of a handler, registered with [register].
+///
+/// Dereferences to the dependencies, so that trait methods can be called directly on it.
+/// When no dependencies of the type are registered, the request fails with an internal server error.
+pub struct Deps<D: ?Sized>(web::Data<D>);
+
+impl<D: ?Sized> Deps<D> {
+    /// The shared dependencies
+    pub fn into_inner(self) -> web::Data<D> {
+        self.0
+    }
+}
+
+impl<D: ?Sized> Clone for Deps<D> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<D: ?Sized> Deref for Deps<D> {
+    type Target = D;
+
+    fn deref(&self) -> &D {
+        &self.0
+    }
+}
+
+impl<D: ?Sized + fmt::Debug> fmt::Debug for Deps<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Deps").field(&&*self.0).finish()
+    }
+}
+
+impl<D: ?Sized + 'static> FromRequest for Deps<D> {
+    type Error = ::actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(match req.app_data::<web::Data<D>>() {
+            Some(deps) => Ok(Self(deps.clone())),
+            None => Err(::actix_web::error::ErrorInternalServerError(format!(
+                "no dependencies of type `{}` are registered with `entrait::actix_deps::register`",
+                core::any::type_name::<D>()
+            ))),
+        })
+    }
+}
@@ -0,0 +1,82 @@
+//! Extracting entraited dependencies in [axum](https://docs.rs/axum) handlers.
+//!
+//! The [Deps] extractor takes the dependencies of a handler out of the router state,
+//! which is either the application itself, like an `Impl<App>`, or a state struct that the application can be taken from with [FromRef].
+//!
+//! Handlers are written generically, bounded on the traits they call, and the router is built for any state satisfying them.
+//! The same router then serves the real application, and mocks in tests:
+//!
+//! ```rust
+//! use axum::routing::get;
+//! use axum::Router;
+//! use entrait::axum_deps::{Deps, RouterDeps};
+//! use entrait::*;
+//!
+//! #[entrait(pub GetGreeting, no_deps)]
+//! async fn get_greeting(name: String) -> String {
+//!     format!("Hello, {name}!")
+//! }
+//!
+//! async fn greet<D: GetGreeting>(Deps(deps): Deps<D>) -> String {
+//!     deps.get_greeting("world".to_string()).await
+//! }
+//!
+//! fn routes<D: GetGreeting + RouterDeps>() -> Router<D> {
+//!     Router::new().route("/greeting", get(greet::<D>))
+//! }
+//!
+//! #[derive(Clone)]
+//! struct App;
+//!
+//! let router: Router = routes().with_state(Impl::new(App));
+//! ```
+//!
+//! The application must be [Clone], since the state is cloned for every request.
+//! A [SharedImpl](crate::SharedImpl) makes the clones cheap.
+
+use core::convert::Infallible;
+use core::ops::{Deref, DerefMut};
+
+use ::axum::extract::{FromRef, FromRequestParts};
+use ::axum::http::request::Parts;
+
+/// Extractor of the dependencies of a handler from the router state.
+///
+/// Unlike `State<D>`, it names its purpose in handler signatures, and it never rejects a request.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Deps<D>(pub D);
+
+impl<D> Deref for Deps<D> {
+    type Target = D;
+
+    fn deref(&self) -> &D {
+        &self.0
+    }
+}
+
+impl<D> DerefMut for Deps<D> {
+    fn deref_mut(&mut self) -> &mut D {
+        &mut self.0
+    }
+}
+
+#[::axum::async_trait]
+impl<S, D> FromRequestParts<S> for Deps<D>
+where
+    D: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(D::from_ref(state)))
+    }
+}
+
+/// The bounds axum puts on router state, for routers generic over their dependencies.
+///
+/// Implemented for every type satisfying them, like an `Impl<App>` of a [Clone] application,
+/// or a mock shared in an `Arc`, which implements the trait with the `forward_impls` option.
+pub trait RouterDeps: Clone + Send + Sync + 'static {}
+
+impl<D: Clone + Send + Sync + 'static> RouterDeps for D {}
@@ -0,0 +1,248 @@
+//! Assembling an [Impl] from the components of the application type.
+//!
+//! [ImplBuilderExt] adds `Impl::builder()`, which collects components one at a time.
+//! `build` converts the collected components, as a tuple in the order they were added, into the application type with [From].
+//!
+//! The [components](crate::components) macro defines an application struct together with that [From] implementation:
+//!
+//! ```rust
+//! use entrait::*;
+//!
+//! pub struct Config {
+//!     pub name: String,
+//! }
+//!
+//! pub struct DbPool;
+//!
+//! entrait::components! {
+//!     pub struct App {
+//!         config: Config,
+//!         db_pool: DbPool,
+//!     }
+//! }
+//!
+//! let app: Impl<App> = Impl::builder()
+//!     .with(Config { name: "app".to_string() })
+//!     .with(DbPool)
+//!     .build();
+//!
+//! assert_eq!("app", app.config.name);
+//! ```
+//!
+//! The [app](crate::app) macro goes one step further, and also wires the delegations of each field.
+
+use crate::Impl;
+
+use core::marker::PhantomData;
+
+/// Adds [builder](ImplBuilderExt::builder) to [Impl].
+pub trait ImplBuilderExt {
+    /// The application type
+    type App;
+
+    /// Start collecting the components of the application type
+    fn builder() -> ImplBuilder<Self::App, ()>;
+}
+
+impl<T> ImplBuilderExt for Impl<T> {
+    type App = T;
+
+    fn builder() -> ImplBuilder<T, ()> {
+        ImplBuilder {
+            components: (),
+            app: PhantomData,
+        }
+    }
+}
+
+/// Collected components of an application type `T`.
+///
+/// The components `C` are nested pairs, like `(((), A), B)`.
+pub struct ImplBuilder<T, C> {
+    components: C,
+    app: PhantomData<fn() -> T>,
+}
+
+impl<T, C> ImplBuilder<T, C> {
+    /// Add the next component
+    pub fn with<N>(self, component: N) -> ImplBuilder<T, (C, N)> {
+        ImplBuilder {
+            components: (self.components, component),
+            app: PhantomData,
+        }
+    }
+
+    /// Convert the components into the application type, and wrap it in [Impl]
+    pub fn build(self) -> Impl<T>
+    where
+        C: Components,
+        T: From<C::Tuple>,
+    {
+        Impl::new(T::from(self.components.into_tuple()))
+    }
+}
+
+/// Nested pairs of components, convertible into a flat tuple.
+pub trait Components {
+    /// The components as a tuple, like `(A, B)`
+    type Tuple;
+
+    /// Flatten the components
+    fn into_tuple(self) -> Self::Tuple;
+}
+
+/// Nests idents in pairs, in type or pattern position: `nested!((), A, B)` is `(((), A), B)`
+macro_rules! nested {
+    ($acc:tt) => {
+        $acc
+    };
+    ($acc:tt, $head:ident $(, $tail:ident)*) => {
+        nested!(($acc, $head) $(, $tail)*)
+    };
+}
+
+macro_rules! components_impls {
+    ($($component:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($component),*> Components for nested!(() $(, $component)*) {
+            type Tuple = ($($component,)*);
+
+            fn into_tuple(self) -> Self::Tuple {
+                let nested!(() $(, $component)*) = self;
+                ($($component,)*)
+            }
+        }
+    };
+}
+
+components_impls!(A);
+components_impls!(A, B);
+components_impls!(A, B, C);
+components_impls!(A, B, C, D);
+components_impls!(A, B, C, D, E);
+components_impls!(A, B, C, D, E, F);
+components_impls!(A, B, C, D, E, F, G);
+components_impls!(A, B, C, D, E, F, G, H);
+components_impls!(A, B, C, D, E, F, G, H, I);
+components_impls!(A, B, C, D, E, F, G, H, I, J);
+components_impls!(A, B, C, D, E, F, G, H, I, J, K);
+components_impls!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Define an application struct along with a [From] implementation for a tuple of its fields,
+/// in declaration order, so that it can be assembled with [ImplBuilderExt::builder].
+///
+/// ```rust
+/// # pub struct Config;
+/// # pub struct DbPool;
+/// entrait::components! {
+///     #[derive(Default)]
+///     pub struct App {
+///         pub config: Option<Config>,
+///         db_pool: Option<DbPool>,
+///     }
+/// }
+///
+/// let app = App::from((Some(Config), None));
+/// ```
+#[macro_export]
+macro_rules! components {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $app:ident {
+            $($(#[$field_attr:meta])* $field_vis:vis $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $app {
+            $($(#[$field_attr])* $field_vis $field: $ty),*
+        }
+
+        impl ::core::convert::From<($($ty,)*)> for $app {
+            fn from(($($field,)*): ($($ty,)*)) -> Self {
+                Self { $($field),* }
+            }
+        }
+    };
+}
+
+/// Define an application struct, its constructor and its delegation impls in one place.
+///
+/// Each field can be followed by `=>` and an option of [EntraitApp](crate::EntraitApp),
+/// selecting the delegation that the field provides. Options are repeated for fields with several delegations.
+/// Like [components](crate::components), the struct also gets a [From] implementation for a tuple of its fields.
+///
+/// ```rust
+/// use entrait::*;
+///
+/// #[entrait(delegate_by = ref)]
+/// trait ReadConfig: 'static {
+///     fn read_config(&self) -> &str;
+/// }
+///
+/// #[entrait(RepositoryImpl, delegate_by = DelegateRepository)]
+/// pub trait Repository {
+///     fn fetch(&self) -> i32;
+/// }
+///
+/// pub struct Config(String);
+///
+/// impl ReadConfig for Config {
+///     fn read_config(&self) -> &str {
+///         &self.0
+///     }
+/// }
+///
+/// pub struct PostgresRepository;
+///
+/// #[entrait]
+/// impl RepositoryImpl for PostgresRepository {
+///     fn fetch<D>(_: &D) -> i32 {
+///         42
+///     }
+/// }
+///
+/// entrait::app! {
+///     pub struct App {
+///         config: Config => as_ref => as_ref = dyn ReadConfig,
+///         repository: PostgresRepository => delegate = DelegateRepository,
+///         name: &'static str,
+///     }
+/// }
+///
+/// let app = Impl::new(App::new(Config("config".to_string()), PostgresRepository, "app"));
+///
+/// assert_eq!("config", app.read_config());
+/// assert_eq!(42, app.fetch());
+/// ```
+#[macro_export]
+macro_rules! app {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $app:ident {
+            $(
+                $(#[$field_attr:meta])* $field_vis:vis $field:ident: $ty:ty
+                $(=> $delegation:ident $(= $target:ty)?)*
+            ),* $(,)?
+        }
+    ) => {
+        $crate::components! {
+            $(#[$attr])*
+            #[derive($crate::EntraitApp)]
+            $vis struct $app {
+                $(
+                    $(#[$field_attr])*
+                    $(#[app($delegation $(= $target)?)])*
+                    $field_vis $field: $ty
+                ),*
+            }
+        }
+
+        impl $app {
+            /// Create the application from its fields, in declaration order
+            #[allow(clippy::too_many_arguments)]
+            $vis fn new($($field: $ty),*) -> Self {
+                Self { $($field),* }
+            }
+        }
+    };
+}
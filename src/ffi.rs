@@ -0,0 +1,122 @@
+//! Conversions for the C bindings generated with the `ffi` option.
+//!
+//! Every parameter of an exported function is received as the [FromFfi::Ffi] type of the Rust parameter,
+//! and the output is returned as its [IntoFfi::Ffi] type.
+//! Numbers, `bool` and `()` are passed as they are.
+//! Implement these traits for application types to choose their C representation.
+//!
+//! Strings are special-cased by the generated code, since reading them is `unsafe`:
+//! `&str` and `String` parameters are received as nul-terminated `*const c_char`s,
+//! and invalid UTF-8 is replaced with `U+FFFD`.
+//! A `String` output is returned as a `*mut c_char`, which the C side must free with the generated `{module}_string_free` function.
+//!
+//! ```rust
+//! use entrait::*;
+//! use entrait::ffi::{FromFfi, IntoFfi};
+//!
+//! pub struct UserId(u32);
+//!
+//! impl FromFfi for UserId {
+//!     type Ffi = u32;
+//!
+//!     fn from_ffi(ffi: u32) -> Self {
+//!         UserId(ffi)
+//!     }
+//! }
+//!
+//! #[derive(Default)]
+//! pub struct App;
+//!
+//! #[entrait(pub Users, ffi = App)]
+//! mod users {
+//!     use super::UserId;
+//!
+//!     pub fn user_name(deps: &impl std::any::Any, id: UserId) -> String {
+//!         format!("user{}", id.0)
+//!     }
+//! }
+//!
+//! # fn main() {
+//! // extern "C" fns generated in `users`:
+//! let app = users::users_create();
+//! let name = unsafe { users::users_user_name(app, 42) };
+//! assert_eq!(
+//!     "user42",
+//!     unsafe { core::ffi::CStr::from_ptr(name) }.to_str().unwrap()
+//! );
+//! unsafe { users::users_string_free(name) };
+//! unsafe { users::users_destroy(app) };
+//! # }
+//! ```
+
+use alloc::ffi::CString;
+use alloc::string::String;
+use core::ffi::c_char;
+
+/// Conversion from the C representation of a parameter
+pub trait FromFfi: Sized {
+    /// The type received from C
+    type Ffi;
+
+    /// Convert the received value
+    fn from_ffi(ffi: Self::Ffi) -> Self;
+}
+
+/// Conversion into the C representation of an output
+pub trait IntoFfi {
+    /// The type returned to C
+    type Ffi;
+
+    /// Convert the output
+    fn into_ffi(self) -> Self::Ffi;
+}
+
+macro_rules! ffi_identity {
+    ($($ty:ty),*) => {
+        $(
+            impl FromFfi for $ty {
+                type Ffi = $ty;
+
+                fn from_ffi(ffi: $ty) -> Self {
+                    ffi
+                }
+            }
+
+            impl IntoFfi for $ty {
+                type Ffi = $ty;
+
+                fn into_ffi(self) -> $ty {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+ffi_identity!(
+    (),
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    isize,
+    f32,
+    f64
+);
+
+/// A nul-terminated copy of the string, or null if the string contains nul bytes.
+impl IntoFfi for String {
+    type Ffi = *mut c_char;
+
+    fn into_ffi(self) -> *mut c_char {
+        CString::new(self)
+            .map(CString::into_raw)
+            .unwrap_or(core::ptr::null_mut())
+    }
+}
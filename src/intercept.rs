@@ -0,0 +1,63 @@
+//! Hooks around the delegations of `Impl<T>`.
+//!
+//! Functions entraited with the `intercept` option call the [CallInterceptor] of the application type in every delegating method:
+//! [before](CallInterceptor::before) the call, [error](CallInterceptor::error) when a `Result` output is an `Err`,
+//! and [after](CallInterceptor::after) the call.
+//! All hooks do nothing by default, so an application only implements the ones it needs.
+//!
+//! ```rust
+//! use entrait::*;
+//! use entrait::intercept::{Call, CallInterceptor};
+//! use std::sync::Mutex;
+//!
+//! #[derive(Default)]
+//! struct App {
+//!     calls: Mutex<Vec<String>>,
+//! }
+//!
+//! impl CallInterceptor for App {
+//!     fn before(&self, call: &Call) {
+//!         self.calls.lock().unwrap().push(format!("{}::{}", call.trait_name, call.method));
+//!     }
+//! }
+//!
+//! #[entrait(Double, intercept)]
+//! fn double(_deps: &impl std::any::Any, n: i32) -> i32 {
+//!     n * 2
+//! }
+//!
+//! let app = Impl::new(App::default());
+//! assert_eq!(4, app.double(2));
+//! assert_eq!(vec!["Double::double"], *app.calls.lock().unwrap());
+//! ```
+//!
+//! Mocks of the trait don't call the hooks, and `intercept` requires generic dependencies, as the hooks are implemented by the `T` of `Impl<T>`.
+
+use core::fmt::Debug;
+
+/// A delegated call, identified by the generated trait and method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Call {
+    /// The name of the trait, like `Double`
+    pub trait_name: &'static str,
+    /// The name of the trait method, like `double`
+    pub method: &'static str,
+}
+
+/// Hooks that `Impl<T>` calls around delegations of functions entraited with `intercept`, when implemented by `T`.
+pub trait CallInterceptor {
+    /// Called before the entraited function
+    fn before(&self, call: &Call) {
+        let _ = call;
+    }
+
+    /// Called after the entraited function, including when it returned an `Err`
+    fn after(&self, call: &Call) {
+        let _ = call;
+    }
+
+    /// Called with the `Err` output of an entraited function returning a `Result`, before [after](Self::after)
+    fn error(&self, call: &Call, error: &dyn Debug) {
+        let _ = (call, error);
+    }
+}
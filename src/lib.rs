@@ -23,7 +23,7 @@
 //! ```
 //!
 //! which generates a new single-method trait named `MyFunction`, with the method signature derived from the original function.
-//! Entrait is a pure append-only macro: It will never alter the syntax of your function.
+//! Entrait is a pure append-only macro: It will never alter the syntax of your function (the `hide_fn` option only adds `#[doc(hidden)]` to it).
 //! The new language items it generates will appear below the function.
 //!
 //! In the first example, `my_function` has a single parameter called `deps` which is generic over a type `D`, and represents dependencies injected into the function.
@@ -51,6 +51,28 @@
 //! When the dependency parameter is generic, its trait bounds specifiy what methods we expect to be callable inside the function.
 //!
 //! Multiple bounds can be expressed using the `&(impl A + B)` syntax.
+//! When the same long list of bounds keeps showing up in entrypoint signatures, `bundle!` names it with an umbrella trait,
+//! implemented for every type that implements all of the traits:
+//!
+//! ```rust
+//! # use entrait::*;
+//! #[entrait(pub FetchUser)]
+//! fn fetch_user(_: &impl std::any::Any, id: u32) -> String {
+//!     format!("user{id}")
+//! }
+//!
+//! #[entrait(pub DeleteUser)]
+//! fn delete_user(_: &impl std::any::Any, _id: u32) {}
+//!
+//! entrait::bundle!(pub UserRepo: FetchUser + DeleteUser);
+//!
+//! fn remove_user(deps: &impl UserRepo, id: u32) -> String {
+//!     let user = deps.fetch_user(id);
+//!     deps.delete_user(id);
+//!     user
+//! }
+//! ```
+//! The application, and mocks implementing all of the traits like unimock's, implement the umbrella trait without further ado.
 //!
 //! The single-value dependency design means that it is always the same reference that is passed around everywhere.
 //! But a reference to what, exactly?
@@ -93,12 +115,15 @@
 //! # trait Foo { fn foo(&self) -> i32; }
 //! # trait Bar { fn bar(&self) -> i32; }
 //! # fn foo(deps: &impl Bar) -> i32 { deps.bar() }
-//! impl<T: Sync> Foo for Impl<T> where Self: Bar {
+//! impl<T> Foo for Impl<T> where Self: Bar {
 //!     fn foo(&self) -> i32 {
 //!         foo(self) // <---- calls your function
 //!     }
 //! }
 //! ```
+//!
+//! With `async` functions, the `Send` futures borrow `Impl<T>` across `.await`s, so the implementation is for `Impl<T>` where `T: Sync`.
+//! Those taking the deps by value get `T: Send` instead, and synchronous functions need neither.
 //! </details>
 //!
 //! `Impl` is generic, so we can put whatever type we want into it.
@@ -130,7 +155,7 @@
 //! The parameter of `use_the_config` is in the first position, so it represents the dependency.
 //!
 //! We will notice two interesting things:
-//! * Functions that depend on `UseTheConfig`, either directly or indirectly, now have only one valid dependency type: `Impl<Config>`<sup>[1](#case-1-concrete-leaf-dependencies)</sup>.
+//! * Functions that depend on `UseTheConfig`, either directly or indirectly, now need an `Impl<T>` where `T` provides a `Config`, like `Impl<Config>`<sup>[1](#case-1-concrete-leaf-dependencies)</sup>.
 //! * Inside `use_the_config`, we have a `&Config` reference instead of `&Impl<Config>`. This means we cannot call other entraited functions, because they are not implemented for `Config`.
 //!
 //! The last point means that a concrete dependency is the end of the line, a leaf in the dependency graph.
@@ -139,6 +164,141 @@
 //! They ideally function as accessors, providing a loosely coupled abstraction layer over concrete application state.
 //!
 //!
+//! ### Owned dependencies
+//! The dependency can also be taken by value, which is useful for moving it into a spawned task.
+//! The trait method then takes `self` by value, so the caller gives away its `Impl<T>`, usually a clone of it:
+//!
+//! ```rust
+//! # use entrait::*;
+//! # use std::sync::Arc;
+//! #[entrait(Foo)]
+//! fn foo<D>(_: &D) -> i32 {
+//!     42
+//! }
+//!
+//! #[entrait(SpawnFoo)]
+//! fn spawn_foo(deps: impl Foo + Clone + Send + Sync + 'static) -> std::thread::JoinHandle<i32> {
+//!     std::thread::spawn(move || deps.foo())
+//! }
+//!
+//! #[entrait(FooTwice)]
+//! fn foo_twice(deps: &(impl SpawnFoo + Clone)) -> i32 {
+//!     let first = deps.clone().spawn_foo();
+//!     let second = deps.clone().spawn_foo();
+//!     first.join().unwrap() + second.join().unwrap()
+//! }
+//!
+//! #[derive(Clone)]
+//! struct App {
+//!     state: Arc<String>,
+//! }
+//! # let app = Impl::new(App { state: Arc::new(String::new()) });
+//! # assert_eq!(84, app.foo_twice());
+//! ```
+//!
+//! `Impl<T>` is `Clone` when `T` is, so the application should be cheap to clone, for example by keeping its state behind an `Arc`.
+//! The `T` of an `Impl<T>` passed by value must also be `Send`.
+//!
+//! A concrete dependency taken by value, like `config: Config`, can't be moved out of an application,
+//! so the trait is only implemented for the concrete type itself.
+//! `memo`, `intercept` and the `&self` methods of [impl blocks](#impl-block-support) borrow from the `Impl<T>`, so they can't be combined with owned dependencies.
+//!
+//! ### Dependencies behind pointers
+//! Spawned tasks and actors often hold their dependencies behind a pointer, like an `Arc<Impl<App>>`.
+//! With the `forward_impls` option, the trait is also implemented for `&T`, `Box<T>` and `Arc<T>` when `T` implements it,
+//! so these pointers, and boxed mocks, can be passed where the trait is expected:
+//!
+//! ```rust
+//! # use entrait::*;
+//! # use std::sync::Arc;
+//! #[entrait(FetchUser, forward_impls)]
+//! fn fetch_user<D>(_: &D, id: u32) -> String {
+//!     format!("user{id}")
+//! }
+//!
+//! fn spawn_greeting(deps: impl FetchUser + Send + 'static) -> std::thread::JoinHandle<String> {
+//!     std::thread::spawn(move || format!("Hello {}", deps.fetch_user(1)))
+//! }
+//!
+//! let app = Arc::new(Impl::new(()));
+//! assert_eq!("Hello user1", spawn_greeting(app.clone()).join().unwrap());
+//! ```
+//!
+//! The methods must take `&self`. `T` can be unsized, like in a `Box<dyn FetchUser>`.
+//! The `Impl<T>` implementation of the trait is not a blanket implementation, so that it doesn't overlap with the forwarding ones.
+//! The `Box<T>` and `Arc<T>` implementations require the `alloc` feature.
+//!
+//!
+//! ### Supertraits
+//! The `supertraits` option gives the generated trait supertraits, like `supertraits = Clone + Send + Sync + 'static`,
+//! so that dependencies can be cloned or shared with spawned threads without repeating these bounds at every dependency site:
+//!
+//! ```rust
+//! # use entrait::*;
+//! #[entrait(Greet, supertraits = Clone + Send + 'static)]
+//! fn greet(_: &impl std::any::Any, name: &str) -> String {
+//!     format!("Hello {name}")
+//! }
+//!
+//! fn greet_in_background(deps: &impl Greet) -> std::thread::JoinHandle<String> {
+//!     let deps = deps.clone();
+//!     std::thread::spawn(move || deps.greet("world"))
+//! }
+//!
+//! assert_eq!("Hello world", greet_in_background(&Impl::new(())).join().unwrap());
+//! ```
+//!
+//! The `Impl<T>` implementation carries the same bounds, so the trait is only implemented when `Impl<T>` satisfies them.
+//! Domain marker traits work the same way, but they must be implemented for `Impl<T>` by hand.
+//! The `mockall` and `faux` mocks get empty implementations of the supertraits that aren't auto traits,
+//! which is enough for marker traits. `self_bounds` is an older name of this option.
+//!
+//!
+//! ### Generic functions
+//! Type and const parameters other than the deps parameter become generic parameters of the trait method, along with their bounds:
+//!
+//! ```rust
+//! # use entrait::*;
+//! use std::str::FromStr;
+//!
+//! #[entrait(Parse)]
+//! fn parse<D, T: FromStr>(deps: &D, s: &str) -> Option<T> {
+//!     s.parse().ok()
+//! }
+//!
+//! fn parse_both(deps: &impl Parse) -> (Option<u32>, Option<bool>) {
+//!     (deps.parse("42"), deps.parse("true"))
+//! }
+//!
+//! assert_eq!((Some(42), Some(true)), parse_both(&Impl::new(())));
+//! ```
+//!
+//! A generic parameter that is part of the dependency, like `T` in `deps: &impl Get<T>`, is needed by the `Impl<T>` implementation,
+//! so it becomes a generic parameter of the trait instead.
+//!
+//! Generic methods are mocked with `with_types::<T>()` on the unimock `MockFn`, while mockall doesn't support const parameters.
+//! Both require type parameters to be `'static`, which the trait method gets as a bound when it has a unimock `mock_api`.
+//!
+//! An `unsafe fn` becomes an `unsafe` trait method, so that the safety contract is carried over to every caller of the trait.
+//!
+//! An `impl Trait` output, like `impl Iterator<Item = T>`, stays an `impl Trait` output of the trait method, so nothing needs to be collected or boxed.
+//! Traits generated with the `dyn` option box it as `Box<dyn Iterator<Item = T>>` instead.
+//!
+//! The `#[cfg]`, `#[must_use]` and lint attributes (`#[allow]`, `#[deny]` etc.) of the function are copied onto the trait method,
+//! so that callers through the trait get the same lints as direct callers.
+//! The delegating method gets the `#[cfg]` and lint attributes, and an `#[expect]` is copied as an `#[allow]`, since the generated code may not trigger the lint.
+//!
+//! The `forward_attrs` option lists the attributes to copy explicitly instead, like `forward_attrs(doc, cfg, allow, tracing::instrument)`,
+//! for example to keep the attributes of body-generating macros away from the generated code.
+//! Listed attributes other than docs, `#[cfg]`, `#[must_use]` and lints are only copied onto the delegating method, since they may need a body,
+//! so that `forward_attrs(track_caller)` makes `#[track_caller]` work through the trait.
+//!
+//! The doc comments of the function are copied onto the trait method as well.
+//! A trait generated from a single function is documented by the first paragraph of the function's docs,
+//! so that generated traits are not left undocumented in rustdoc.
+//! The `trait_doc` option documents the trait with a string of its own instead, also for modules.
+//!
+//!
 //! ### Module support
 //! To reduce the number of generated traits, entrait can be used as a `mod` attribute.
 //! When used in this mode, the macro will look for non-private functions directly within the module scope, to be represented as methods on the resulting trait.
@@ -160,6 +320,156 @@
 //! ```
 //! This example generates a `MyModule` trait containing the methods `foo` and `bar`.
 //!
+//! Private functions are left out, unless the `include_private` option is used.
+//! Functions of nested modules (`pub mod`, or private ones with `include_private`) are flattened into the same trait,
+//! and the delegating implementation calls them by their module path.
+//! Their signatures become part of the trait in the entraited module, so the names they use must resolve there too.
+//! A function inside the module can be given a different method name with `#[entrait(method = name)]`,
+//! or be left out of the trait with `#[entrait(skip)]`.
+//!
+//! A function can also be added to narrower traits with `#[entrait(also = Trait)]`, repeated for each trait.
+//! These traits are generated inside the module with the same options as the module trait, and re-exported next to it,
+//! so that code depending on only a few functions can take `&impl FetchUser` instead of the whole facade trait:
+//!
+//! ```rust
+//! # use entrait::*;
+//! #[entrait(pub Users)]
+//! mod users {
+//!     #[entrait(also = FetchUser)]
+//!     pub fn fetch_user(deps: &impl std::any::Any, id: u32) -> String {
+//!         format!("user{id}")
+//!     }
+//!
+//!     pub fn count_users(deps: &impl std::any::Any) -> usize {
+//!         42
+//!     }
+//! }
+//!
+//! fn greet(deps: &impl FetchUser) -> String {
+//!     format!("Hello, {}!", deps.fetch_user(1))
+//! }
+//!
+//! assert_eq!("Hello, user1!", greet(&Impl::new(())));
+//! ```
+//! A renamed `mock_api` is renamed for each of these traits, by replacing the module trait's name in it.
+//! The `local`, `double` and `trait_doc` options, and the bindings, only apply to the module trait.
+//! `method`, `skip` and `also` are the only options supported on functions inside the module.
+//!
+//! A module (or an [impl block](#impl-block-support)) is the only way to get several functions into one trait.
+//! Every `#[entrait]` invocation only sees the item it is attached to, so standalone functions elsewhere can't contribute methods to the same trait.
+//! To depend on such functions through a single bound, write a trait with these traits as supertraits, and a blanket implementation for every `T` implementing all of them.
+//!
+//! The opposite is also possible: [`#[entrait_all]`](entrait_all) on a `mod` generates one trait _per_ function, named after the function.
+//!
+//! For the same reason, the trait is always generated next to the function or module, and a path like `#[entrait(pub traits::Foo)]` (or a `module` option) is rejected.
+//! To collect generated traits under one path, re-export them from a module of their own, e.g. `pub mod traits { pub use crate::users::{FetchUser, StoreUser}; }`.
+//!
+//! #### JavaScript bindings with `wasm_bindgen`
+//! With the `wasm-bindgen` cargo feature, a module can be exposed to JavaScript without a hand-written shim layer.
+//! The `wasm_bindgen` option names a [wasm-bindgen](https://docs.rs/wasm-bindgen) tuple struct, declared next to the module, which holds the application.
+//! Every trait method becomes a JavaScript method of that struct, with arguments and return values converted by [serde-wasm-bindgen](https://docs.rs/serde-wasm-bindgen):
+//!
+//! ```rust
+//! # #[cfg(feature = "wasm-bindgen")]
+//! # mod demo {
+//! # use entrait::*;
+//! # use wasm_bindgen::prelude::*;
+//! #[entrait(pub Greeter, wasm_bindgen = JsGreeter)]
+//! mod greeter {
+//!     pub fn greet(deps: &impl std::any::Any, name: &str) -> String {
+//!         format!("Hello, {name}!")
+//!     }
+//! }
+//!
+//! #[wasm_bindgen]
+//! pub struct JsGreeter(Impl<()>);
+//! # }
+//! ```
+//!
+//! Non-`async` methods return `Result<JsValue, JsValue>`, failing when an argument can't be deserialized.
+//! `async` methods return a `Promise`, which requires the struct's field to be `Clone + 'static` (e.g. an `Rc<Impl<T>>`).
+//! Generic methods are not supported.
+//!
+//! #### Python bindings with `pyo3`
+//! The `pyo3` option (with the `pyo3` cargo feature) does the same for Python, with a [pyo3](https://docs.rs/pyo3) `#[pyclass]` tuple struct.
+//! Arguments and return values are converted by pyo3 itself.
+//! `async` methods return Python awaitables that run on the tokio runtime of [pyo3-async-runtimes](https://docs.rs/pyo3-async-runtimes),
+//! which requires the struct's field to be `Clone + Send + 'static` (e.g. an `Arc<Impl<T>>`):
+//!
+//! ```rust
+//! # #[cfg(feature = "pyo3")]
+//! # mod demo {
+//! # use entrait::*;
+//! # use pyo3::prelude::*;
+//! # use std::sync::Arc;
+//! #[entrait(pub Greeter, pyo3 = PyGreeter)]
+//! mod greeter {
+//!     pub fn greet(deps: &impl std::any::Any, name: &str) -> String {
+//!         format!("Hello, {name}!")
+//!     }
+//!
+//!     pub async fn greet_later(deps: &impl std::any::Any, name: &str) -> String {
+//!         format!("Hello again, {name}!")
+//!     }
+//! }
+//!
+//! #[pyclass]
+//! pub struct PyGreeter(Arc<Impl<()>>);
+//! # }
+//! ```
+//!
+//! Since the generated `#[pymethods]` block is the class's only one, additional Python methods (like a constructor) require pyo3's `multiple-pymethods` feature.
+//!
+//! #### C bindings with `ffi`
+//! The `ffi` option (with the `ffi` cargo feature) takes an application type implementing `Default`,
+//! and generates `extern "C"` functions in the module for embedding it in C or C++ hosts.
+//! The functions are prefixed with the module name: `{module}_create` and `{module}_destroy` manage an opaque `Impl<App>` handle,
+//! and each trait method gets a function taking the handle as its first parameter.
+//! Parameters and outputs are converted with the `FromFfi` and `IntoFfi` traits of the `entrait::ffi` module.
+//! The functions are exported with `#[unsafe(no_mangle)]`, which requires Rust 1.82.
+//!
+//! ### Impl block support
+//! Existing services written as structs with methods can be migrated to entrait one at a time,
+//! by putting the attribute on their inherent `impl` block.
+//! The methods take the service as `&self`, followed by the dependencies:
+//!
+//! ```rust
+//! # use entrait::*;
+//! # #[entrait(FetchUser)]
+//! # fn fetch_user(_: &impl std::any::Any, id: u32) -> String { format!("user{id}") }
+//! pub struct UserRepo {
+//!     prefix: String,
+//! }
+//!
+//! #[entrait(pub UserService)]
+//! impl UserRepo {
+//!     pub fn find(&self, deps: &impl FetchUser, id: u32) -> String {
+//!         format!("{}{}", self.prefix, deps.fetch_user(id))
+//!     }
+//! }
+//!
+//! struct App {
+//!     users: UserRepo,
+//! }
+//!
+//! impl AsRef<UserRepo> for App {
+//!     fn as_ref(&self) -> &UserRepo {
+//!         &self.users
+//!     }
+//! }
+//!
+//! let app = Impl::new(App { users: UserRepo { prefix: "> ".to_string() } });
+//! assert_eq!("> user42", app.find(42));
+//! ```
+//!
+//! The `UserService` trait gets the methods without the `&self` of the service,
+//! and is implemented for `Impl<T>` where `T: AsRef<UserRepo>`, which lends the service to the methods.
+//! Methods without a receiver are included too, like functions of a module.
+//! Otherwise, impl blocks work like modules: private methods are left out unless `include_private` is used,
+//! and methods support `#[entrait(method = name)]`, `#[entrait(skip)]` and `#[entrait(also = Trait)]`.
+//! The options for modules apply to impl blocks too, except `const_trait`, `memo` and the bindings (`wasm_bindgen`, `pyo3` and `ffi`).
+//! Generic impl blocks and concrete dependencies are not supported.
+//!
 //!
 //! # Testing
 //! ## Trait mocking with `Unimock`
@@ -202,6 +512,23 @@
 //! assert_eq!(42, my_func(&mocked_deps));
 //! ```
 //!
+//! Instead of supplying `mock_api` everywhere, a crate can name its mock APIs by a convention,
+//! with the `ENTRAIT_MOCK_API` environment variable set during compilation, for example in `.cargo/config.toml`:
+//!
+//! ```toml
+//! [env]
+//! ENTRAIT_MOCK_API = "{}Mock"
+//! ```
+//!
+//! Every trait with unimock support and no explicit `mock_api` then gets the API named by replacing `{}` with the trait name, like `FooMock`.
+//! Like with `ENTRAIT_GRAPH_OUT`, cargo doesn't recompile a crate just because the variable changed.
+//!
+//! The mocks target unimock 0.6, which the `unimock` feature depends on.
+//! A crate pinned to unimock 0.5 enables the `unimock-0-5` feature, and selects its API with `unimock_version = "0.5"`,
+//! either per invocation like `#[entrait(Foo, unimock, unimock_version = "0.5", mock_api=FooMock)]`, or crate-wide in `ENTRAIT_DEFAULTS`.
+//! Both features can be enabled together, so that crates pinned to different versions can share a dependency graph.
+//! Unimock 0.5 can't mock native `async fn`s in traits, so these require `#[async_trait]`.
+//!
 //! #### Deep integration testing with unimock
 //! Entrait with unimock supports _un-mocking_. This means that the test environment can be _partially mocked!_
 //!
@@ -249,7 +576,7 @@
 //! ### Alternative mocking: Mockall
 //! If you instead wish to use a more established mocking crate, there is also support for [mockall](https://docs.rs/mockall/latest/mockall/).
 //! Note that mockall has some limitations.
-//! Multiple trait bounds are not supported, and deep tests will not work.
+//! Deep tests will not work, and deps with multiple trait bounds need a combined mock (see below).
 //! Also, mockall tends to generate a lot of code, often an order of magnitude more than unimock.
 //!
 //! Enabling mockall is done using the `mockall` entrait option.
@@ -273,6 +600,84 @@
 //! }
 //! ```
 //!
+//! The mock is named by prefixing the trait name with `Mock`.
+//! With `mock_api`, like `#[entrait(Foo, mockall, mock_api=FooMock)]`, it also gets a type alias with that name.
+//! The unimock API of a trait that is also mocked with `unimock` then stays hidden. An alias is not supported for generic traits.
+//!
+//! For the [mockall_double](https://docs.rs/mockall_double) workflow, `double` names the real type that code holds on to,
+//! like `#[entrait(pub Foo, mockall, double = Impl<App>)]`.
+//! It emits a `FooDouble` alias for that type, and a `MockFooDouble` alias for the mock next to it,
+//! so that `#[cfg_attr(test, double)] use crate::FooDouble;` swaps in the mock for tests.
+//!
+//! When the deps of a function with the `mockall` option have several trait bounds, like `deps: &(impl Foo + Bar)`,
+//! a single mock struct implementing all of them is generated with `mockall::mock!`, named after the function: `Mock{Function}Deps`.
+//! The bounds must be non-generic traits that also have the `mockall` option, entraited in the same crate,
+//! and the types in their method signatures must be in scope where the function is:
+//!
+//! ```rust
+//! # use entrait::entrait_export as entrait;
+//! #[entrait(Foo, mockall)]
+//! fn foo<D>(_: &D) -> u32 {
+//!     unimplemented!()
+//! }
+//!
+//! #[entrait(Bar, mockall)]
+//! fn bar<D>(_: &D) -> u32 {
+//!     unimplemented!()
+//! }
+//!
+//! #[entrait(Sum, mockall)]
+//! fn sum(deps: &(impl Foo + Bar)) -> u32 {
+//!     deps.foo() + deps.bar()
+//! }
+//!
+//! fn main() {
+//!     let mut deps = MockSumDeps::new();
+//!     deps.expect_foo().returning(|| 40);
+//!     deps.expect_bar().returning(|| 2);
+//!     assert_eq!(42, sum(&deps));
+//! }
+//! ```
+//!
+//! [mry](https://docs.rs/mry/latest/mry/) is supported in the same way, with the `mry` option.
+//! It puts `#[mry::mry]` on the generated trait, which generates a `MockFoo` struct with `mock_*` methods for setting up behaviour.
+//! Like with mockall, `mry` must be a (dev-)dependency of the crate using the option.
+//!
+//! [faux](https://docs.rs/faux/latest/faux/) mocks structs rather than traits.
+//! With the `faux` option, entrait generates a `#[faux::create]` struct named `FauxFoo`, with a `#[faux::methods]` implementation of the trait.
+//! A mock is then created with `FauxFoo::faux()`, and set up with `faux::when!`.
+//!
+//! ### Stubs without a mock library
+//! The `stub` option implements the trait for the zero-sized [Stub] type, panicking in every method with a message naming the trait and method.
+//! A stub stands in for dependencies that a test, or a partially wired application, never calls:
+//!
+//! ```rust
+//! # use entrait::*;
+//! #[entrait(FetchUser, stub)]
+//! fn fetch_user<D>(_: &D, id: u32) -> String {
+//!     format!("user{id}")
+//! }
+//!
+//! #[entrait(Greet)]
+//! fn greet(deps: &impl FetchUser, id: Option<u32>) -> String {
+//!     match id {
+//!         Some(id) => format!("Hello {}", deps.fetch_user(id)),
+//!         None => "Hello stranger".to_string(),
+//!     }
+//! }
+//!
+//! assert_eq!("Hello stranger", greet(&Stub, None));
+//! ```
+//!
+//! The `Impl<T>` implementation of a stubbed trait is not a blanket implementation, so that it doesn't overlap with the one for `Stub`.
+//!
+//! ### Recording and replaying calls
+//! With the `record` feature, the `record` option implements the trait for `entrait::record::Recorded<T>`,
+//! which delegates to `T` while recording every call with its arguments and output as JSON,
+//! or replays previously recorded outputs without calling `T`.
+//! Recorded calls can be saved as golden files for integration tests of a service layer.
+//! See the `entrait::record` module for details.
+//!
 //!
 //! # Multi-crate architecture
 //!
@@ -293,8 +698,7 @@
 //!
 //!
 //! ### Case 1: Concrete leaf dependencies
-//! Earlier it was mentioned that when concrete-type dependencies are used, the `T` in `Impl<T>`, your application, and the type of the dependency have to match.
-//! But this is only partially true.
+//! Earlier it was mentioned that when concrete-type dependencies are used, the `T` in `Impl<T>` has to provide the type of the dependency.
 //! It really comes down to which traits are implemented on what types:
 //!
 //! ```rust
@@ -319,24 +723,26 @@
 //! trait GetFoo {
 //!     fn get_foo(&self) -> &str;
 //! }
-//! impl<T: GetFoo> GetFoo for Impl<T> {
+//! impl GetFoo for Config {
 //!     fn get_foo(&self) -> &str {
-//!         self.as_ref().get_foo()
+//!         get_foo(self)
 //!     }
 //! }
-//! impl GetFoo for Config {
+//! impl<T: ::core::borrow::Borrow<Config>> GetFoo for Impl<T> {
 //!     fn get_foo(&self) -> &str {
-//!         get_foo(self)
+//!         get_foo(::core::borrow::Borrow::<Config>::borrow(&**self))
 //!     }
 //! }
 //! ```
 //!
 //! </details>
 //!
-//! Here we actually have a trait `GetFoo` that is implemented two times: for `Impl<T> where T: GetFoo` and for `Config`.
-//! The first implementation is delegating to the other one.
+//! Here we actually have a trait `GetFoo` that is implemented two times: for `Config` and for `Impl<T> where T: Borrow<Config>`.
+//! The second implementation borrows the `Config` from the application.
+//! Every type borrows as itself, so `Impl<Config>` implements `GetFoo`.
 //!
-//! For making this work with _any_ downstream application type, we just have to manually implement `GetFoo` for that application:
+//! For making this work with _any_ downstream application type, we just have to implement [Borrow](::core::borrow::Borrow) for that application,
+//! once for each concrete dependency type it provides:
 //!
 //! ```rust
 //! # mod some_upstream_crate {
@@ -347,14 +753,54 @@
 //! #         &config.foo
 //! #     }
 //! # }
+//! # use entrait::*;
+//! # use some_upstream_crate::GetFoo;
+//! # pub struct DbPool;
+//! use std::borrow::Borrow;
+//!
 //! struct App {
 //!     config: some_upstream_crate::Config,
+//!     db_pool: DbPool,
 //! }
-//! impl some_upstream_crate::GetFoo for App {
-//!     fn get_foo(&self) -> &str {
-//!         self.config.get_foo()
+//! impl Borrow<some_upstream_crate::Config> for App {
+//!     fn borrow(&self) -> &some_upstream_crate::Config {
+//!         &self.config
 //!     }
 //! }
+//! impl Borrow<DbPool> for App {
+//!     fn borrow(&self) -> &DbPool {
+//!         &self.db_pool
+//!     }
+//! }
+//! # let app = Impl::new(App { config: some_upstream_crate::Config { foo: "foo".to_string() }, db_pool: DbPool });
+//! # assert_eq!("foo", app.get_foo());
+//! ```
+//!
+//! With [`#[derive(EntraitApp)]`](crate::EntraitApp), these are the fields annotated with `#[app(borrow)]`.
+//!
+//! A concrete dependency can also be a trait object, like `&dyn Plugin`.
+//! `Borrow` is only implemented for sized types, so trait objects are borrowed with [AsRef](::core::convert::AsRef) instead:
+//!
+//! ```rust
+//! # use entrait::*;
+//! pub trait Plugin {
+//!     fn name(&self) -> String;
+//! }
+//!
+//! #[entrait(PluginName)]
+//! fn plugin_name(plugin: &dyn Plugin) -> String {
+//!     plugin.name()
+//! }
+//!
+//! #[derive(EntraitApp)]
+//! struct App {
+//!     #[app(as_ref = dyn Plugin)]
+//!     plugin: Box<dyn Plugin + Send + Sync>,
+//! }
+//! # struct Upper;
+//! # impl Plugin for Upper { fn name(&self) -> String { "UPPER".to_string() } }
+//! # let app = Impl::new(App { plugin: Box::new(Upper) });
+//! # assert_eq!("UPPER", app.plugin_name());
 //! ```
 //!
 //!
@@ -389,6 +835,42 @@
 //!
 //! To use with some `App`, the app type itself should implement the trait.
 //!
+//! Methods with default bodies keep them, and are not delegated: they run on `Impl<T>`, calling the delegated methods.
+//! With the `delegate_defaults` option they are delegated like the other methods, so that the app's overrides are called instead.
+//! The `Send` future of a native `async` default method holds the receiver, so like with `async_trait`, it gets a `Self: Sync` bound (`Self: Send` for `&mut self` and `self`).
+//!
+//! Associated types are taken from the app: `Impl<T>` gets `type Item = <T as Trait>::Item`.
+//! Generic associated types work the same way, like the guard of a lock-returning trait:
+//!
+//! ```rust
+//! # use entrait::*;
+//! # use std::sync::{Mutex, MutexGuard};
+//! #[entrait]
+//! pub trait Counter {
+//!     type Guard<'a>: std::ops::DerefMut<Target = u32>
+//!     where
+//!         Self: 'a;
+//!
+//!     fn lock(&self) -> Self::Guard<'_>;
+//! }
+//!
+//! struct App(Mutex<u32>);
+//!
+//! impl Counter for App {
+//!     type Guard<'a> = MutexGuard<'a, u32>;
+//!
+//!     fn lock(&self) -> Self::Guard<'_> {
+//!         self.0.lock().unwrap()
+//!     }
+//! }
+//!
+//! let app = Impl::new(App(Mutex::new(0)));
+//! *app.lock() += 1;
+//! assert_eq!(1, *app.lock());
+//! ```
+//!
+//! A `dyn` trait would have to specify them, so they are not supported with `delegate_by = ref`.
+//!
 //!
 //! ### Case 3: Hand-written trait as a leaf dependency using _dynamic dispatch_
 //! Sometimes it might be desirable to have a delegation that involves dynamic dispatch.
@@ -420,6 +902,29 @@
 //!
 //! To use this together with some `App`, it should implement the [`AsRef<dyn ReadConfig>`](::core::convert::AsRef) trait.
 //!
+//! When the implementation also needs to be shared outside the app, for example with background tasks, use `delegate_by = Arc` instead.
+//! The app then implements `AsRef<Arc<dyn ReadConfig + Send + Sync>>`, and the `Arc` can be cloned freely.
+//!
+//! This is also the delegation that supports `self: Arc<Self>` methods, like those of actor-style leaf dependencies:
+//! `Arc<Impl<T>>` delegates them to a clone of the app's `Arc<dyn ReadConfig + Send + Sync>`.
+//! With other delegations they are rejected, since the implementation for `Impl<T>` can't get an `Arc` of `T`.
+//!
+//! An app owning its implementation exclusively, for example one selected at runtime from configuration, can use `delegate_by = Box`
+//! and implement [`BoxedDelegate<dyn ReadConfig + Send + Sync>`](crate::BoxedDelegate) instead of `AsRef`.
+//!
+//! To replace the implementation while the app is running, for example after a configuration reload or a failover,
+//! use `delegate_by = swap` (and the `arc-swap` cargo feature).
+//! The app then implements `AsRef<ArcSwap<Box<dyn ReadConfig + Send + Sync>>>`, and every delegated call loads the current target,
+//! so a `store` into the [ArcSwap](https://docs.rs/arc-swap) takes effect for the calls that follow.
+//! Calls already in progress keep the target they loaded, and since the target can be swapped out at any time,
+//! synchronous methods can't return data borrowed from it.
+//!
+//! When the implementations are known up front, like a few database backends selected by configuration,
+//! `delegate_by = enum(Postgres, Sqlite)` avoids the boxing and dynamic dispatch altogether.
+//! It generates a `ReadConfigEnum` with a variant (and a `From` implementation) for each of the listed types,
+//! which implements the trait by matching on the variant, and the app implements `AsRef<ReadConfigEnum>`.
+//! The methods need a receiver, and the trait can't have associated types, associated constants or `impl Trait` outputs, which could differ between the variants.
+//!
 //!
 //! ### Case 4: Truly inverted _internal dependencies_ - static dispatch
 //! All cases up to this point have been _leaf dependencies_.
@@ -540,6 +1045,10 @@
 //! # } // demo
 //! ```
 //!
+//! Generic traits carry their own parameters through all three traits, placed after `T`:
+//! `Store<K, V>` gets the delegation target `StoreImpl<T, K, V>` and the selector `DelegateStore<T, K, V>`,
+//! so the implementation block is written `impl StoreImpl<u32, String> for MyStore`.
+//!
 //!
 //! ### Case 5: Truly inverted internal dependencies - dynamic dispatch
 //! A small variation of case 4: Use `delegate_by=ref` instead of a custom trait.
@@ -569,57 +1078,509 @@
 //! The app must now implement [`AsRef<dyn RepositoryImpl<Self>>`](::core::convert::AsRef).
 //!
 //!
+//! ### Deriving the application wiring
+//! The impls that connect an app to the delegations of all these cases can be derived with [`EntraitApp`],
+//! by annotating each field of the app with the delegation it provides:
 //!
+//! ```rust
+//! # use entrait::*;
+//! mod clock {
+//!     # use entrait::*;
+//!     #[entrait]
+//!     pub trait Clock {
+//!         fn now(&self) -> u64;
+//!     }
+//!     # pub struct SystemClock;
+//!     # impl Clock for SystemClock { fn now(&self) -> u64 { 42 } }
+//! }
 //!
-//! # Options and features
+//! #[entrait(delegate_by = ref)]
+//! trait ReadConfig: 'static {
+//!     fn read_config(&self) -> &str;
+//! }
+//! # struct Config;
+//! # impl ReadConfig for Config { fn read_config(&self) -> &str { "config" } }
 //!
-//! #### Trait visibility
-//! by default, entrait generates a trait that is module-private (no visibility keyword).
-//! To change this, just put a visibility specifier before the trait name:
+//! #[entrait(RepositoryImpl, delegate_by = DelegateRepository)]
+//! pub trait Repository {
+//!     fn fetch(&self) -> i32;
+//! }
+//! # pub struct MyRepository;
+//! # #[entrait]
+//! # impl RepositoryImpl for MyRepository {
+//! #     fn fetch<D>(_: &D) -> i32 { 1 }
+//! # }
 //!
-//! ```rust
-//! use entrait::*;
-//! #[entrait(pub Foo)]   // <-- public trait
-//! fn foo<D>(deps: &D) { // <-- private function
+//! #[derive(EntraitApp)]
+//! struct App {
+//!     #[app(leaf = clock::Clock)]
+//!     clock: clock::SystemClock,
+//!     #[app(as_ref = dyn ReadConfig)]
+//!     config: Box<Config>,
+//!     #[app(delegate = DelegateRepository)]
+//!     repository: MyRepository,
 //! }
+//! # use clock::Clock;
+//! # let app = Impl::new(App { clock: clock::SystemClock, config: Box::new(Config), repository: MyRepository });
+//! # assert_eq!(42, app.now());
+//! # assert_eq!("config", app.read_config());
+//! # assert_eq!(1, app.fetch());
 //! ```
 //!
-//! #### async support
-//! Zero-cost, static-dispatch `async` works out of the box[^1].
-//!
-//! When dynamic dispatch is needed, for example in combination with `delegate_by=ref`, entrait understands the `#[async_trait]` attribute when applied _after_ the entrait macro.
-//! Entrait will re-apply that macro to the various generated impl blocks as needed.
+//! `leaf` implements a leaf trait for the app by forwarding to the field, using a hidden macro generated next to the trait.
+//! It supports non-generic traits with `&self` methods, and the types in their signatures must also resolve where the app is defined.
 //!
-//! ##### async `Send`-ness
-//! Similar to `async_trait`, entrait generates a [Send]-bound on futures by default.
-//! To opt out of the Send bound, pass `?Send` as a macro argument:
+//! In the executable crate, the [`app!`](crate::app) macro declares the app struct, its constructor and these impls together,
+//! with the delegation selected after each field:
 //!
 //! ```rust
-//! # use std::{rc::Rc, any::Any};
 //! # use entrait::*;
-//! #[entrait(ReturnRc, ?Send)]
-//! async fn return_rc(_deps: impl Any) -> Rc<i32> {
-//!     Rc::new(42)
+//! # mod crate1 { pub trait DelegateRepository<T> { type Target; } }
+//! # mod crate2 { pub struct MyRepository; }
+//! # pub struct Config;
+//! entrait::app! {
+//!     pub struct App {
+//!         repository: crate2::MyRepository => delegate = crate1::DelegateRepository,
+//!         config: Config => as_ref,
+//!     }
 //! }
+//!
+//! let app = Impl::new(App::new(crate2::MyRepository, Config));
 //! ```
 //!
-//! #### Integrating with other `fn`-targeting macros, and `no_deps`
-//! Some macros are used to transform the body of a function, or generate a body from scratch.
-//! For example, we can use [`feignhttp`](https://docs.rs/feignhttp/latest/feignhttp/) to generate an HTTP client. Entrait will try as best as it
-//! can to co-exist with macros like these. Since `entrait` is a higher-level macro that does not touch fn bodies (it does not even try to parse them),
-//! entrait should be processed after, which means it should be placed _before_ lower level macros. Example:
+//! ### Deferred delegation
+//! Every library crate expands and type checks the delegation of its traits for `Impl<T>`, which adds up in large workspaces.
+//! With `deferred`, a function or module only gets its trait, next to a hidden macro generating the delegation.
+//! The executable crate invokes these macros for all the traits at once with [`link_entrait!`](crate::link_entrait), moving the cost to that one crate:
 //!
 //! ```rust
-//! # use entrait::entrait;
-//! #[entrait(FetchThing, no_deps)]
-//! #[feignhttp::get("https://my.api.org/api/{param}")]
-//! async fn fetch_thing(#[path] param: String) -> feignhttp::Result<String> {}
-//! ```
+//! mod users { // a library crate
+//!     use entrait::*;
 //!
-//! Here we had to use the `no_deps` entrait option.
+//!     #[entrait(pub GetUsername, deferred)]
+//!     pub fn get_username(_deps: &impl std::any::Any, id: u32) -> String {
+//!         format!("user{id}")
+//!     }
+//!
+//!     #[entrait(pub Greeting, deferred)]
+//!     pub mod greeting {
+//!         use super::GetUsername;
+//!
+//!         pub fn greet(deps: &impl GetUsername, id: u32) -> String {
+//!             format!("Hello, {}!", deps.get_username(id))
+//!         }
+//!     }
+//! }
+//!
+//! struct App;
+//!
+//! entrait::link_entrait!(App => users::GetUsername, users::Greeting);
+//!
+//! # fn main() {
+//! use users::Greeting;
+//! assert_eq!("Hello, user42!", App.greet(42));
+//! # }
+//! ```
+//!
+//! The traits are implemented for the app type itself, since the executable crate can't implement them for `Impl<T>`.
+//! The functions are called through the path of the trait, so they must be public next to it, or in the module of a module trait.
+//! The types in their signatures must be public there too, or written as full paths.
+//! The dependencies must be generic, and options that add to the delegation or require it for `Impl<T>`, like `log`, `intercept`, `memo` or `tonic`, can't be combined with `deferred`.
+//!
+//! ### Discovering delegation targets
+//! Implementations of a leaf trait may be contributed by crates that are only linked into some builds, for example behind cargo features.
+//! With `registry = inventory` or `registry = linkme` (and the cargo feature of the same name), a leaf trait delegating by `ref`, `Arc`, `Box` or `swap`
+//! gets a `{Trait}Provider` registry collected by that crate, which the providers are added to with [`register_entrait!`](crate::register_entrait).
+//! The executable then finds them by name, without naming the crates that contributed them:
+//!
+//! ```rust
+//! # #[cfg(feature = "inventory")]
+//! # mod demo {
+//! # use entrait::*;
+//! mod repository { // the crate of the trait
+//!     # use entrait::*;
+//!     #[entrait(delegate_by = ref, registry = inventory)]
+//!     pub trait Repository: 'static {
+//!         fn fetch(&self, id: u32) -> String;
+//!     }
+//! }
+//!
+//! mod postgres { // a crate behind a cargo feature
+//!     pub struct Postgres;
+//!
+//!     impl super::repository::Repository for Postgres {
+//!         fn fetch(&self, id: u32) -> String {
+//!             format!("row{id}")
+//!         }
+//!     }
+//!
+//!     entrait::register_entrait!(super::repository::Repository, "postgres", || Box::new(Postgres));
+//! }
+//!
+//! use repository::{Repository, RepositoryProvider};
+//!
+//! struct App {
+//!     repository: Box<dyn Repository + Send + Sync>,
+//! }
+//!
+//! impl AsRef<dyn Repository> for App {
+//!     fn as_ref(&self) -> &dyn Repository {
+//!         self.repository.as_ref()
+//!     }
+//! }
+//!
+//! # fn demo() {
+//! let provider = RepositoryProvider::find("postgres").unwrap();
+//! let app = Impl::new(App { repository: provider.create() });
+//! assert_eq!("row42", app.fetch(42));
+//! # }
+//! # }
+//! ```
+//!
+//! The providers create a `Box<dyn Trait + Send + Sync>`, or of the `Dyn{Trait}` companion for traits with `async` methods,
+//! which fits any of the reference delegations. `{Trait}Provider::all()` lists all of them, in no particular order.
+//! Implementation traits like `RepositoryImpl<T>` of cases 4 and 5 are generic over the application, so they can't be registered in a static registry.
+//!
+//!
+//!
+//!
+//! # Options and features
+//!
+//! #### Trait visibility
+//! by default, entrait generates a trait that is module-private (no visibility keyword).
+//! To change this, just put a visibility specifier before the trait name:
+//!
+//! ```rust
+//! use entrait::*;
+//! #[entrait(pub Foo)]   // <-- public trait
+//! fn foo<D>(deps: &D) { // <-- private function
+//! }
+//! ```
+//!
+//! #### async support
+//! Zero-cost, static-dispatch `async` works out of the box[^1].
+//!
+//! When dynamic dispatch is needed, for example in combination with `delegate_by=ref`, entrait understands the `#[async_trait]` attribute when applied _after_ the entrait macro.
+//! Entrait will re-apply that macro to the various generated impl blocks as needed.
+//!
+//! Without `#[async_trait]`, a `delegate_by=ref` leaf trait with `async` methods instead gets a dyn-compatible companion,
+//! named by prefixing the trait name with `Dyn`, where every future is boxed (this requires the `alloc` feature, enabled by `std`).
+//! The trait itself is implemented with plain `async fn`s, and the app provides [`AsRef`](::core::convert::AsRef) to the companion trait object:
+//!
+//! ```rust
+//! # use entrait::*;
+//! #[entrait(delegate_by=ref)]
+//! trait FetchUser: 'static {
+//!     async fn fetch_user(&self, id: u32) -> String;
+//! }
+//!
+//! struct Db;
+//!
+//! impl FetchUser for Db {
+//!     async fn fetch_user(&self, id: u32) -> String {
+//!         format!("user {id}")
+//!     }
+//! }
+//!
+//! struct App {
+//!     db: Db,
+//! }
+//!
+//! impl AsRef<dyn DynFetchUser> for App {
+//!     fn as_ref(&self) -> &dyn DynFetchUser {
+//!         &self.db
+//!     }
+//! }
+//! ```
+//!
+//! Methods returning `impl Trait` get the same companion, where the output is boxed as `Box<dyn Trait>`.
+//! Such an output can only borrow from parameters other than `self` when it has an explicit lifetime bound, like `impl Iterator<Item = &'a str> + 'a`.
+//!
+//! Other leaf traits get the companion with the `dyn` option, which also implements the trait for the companion trait object and a `Box` of it
+//! (and, like any leaf trait, for an `Arc` of it).
+//! With `Send` futures, the trait object is `dyn DynFoo + Send + Sync`.
+//! Code that is generic over the trait then also accepts trait objects, while static dispatch stays free of boxing:
+//!
+//! ```rust
+//! # use entrait::*;
+//! #[entrait(dyn)]
+//! trait FetchUser {
+//!     async fn fetch_user(&self, id: u32) -> String;
+//! }
+//!
+//! struct Db;
+//!
+//! impl FetchUser for Db {
+//!     async fn fetch_user(&self, id: u32) -> String {
+//!         format!("user {id}")
+//!     }
+//! }
+//!
+//! async fn greet(deps: &impl FetchUser) -> String {
+//!     format!("Hello, {}!", deps.fetch_user(42).await)
+//! }
+//!
+//! let db: Box<dyn DynFetchUser + Send + Sync> = Box::new(Db);
+//! # let _ = async move {
+//! assert_eq!("Hello, user 42!", greet(&db).await);
+//! # };
+//! ```
+//!
+//! ##### async `Send`-ness
+//! Similar to `async_trait`, entrait generates a [Send]-bound on futures by default.
+//! To opt out of the Send bound, pass `?Send` as a macro argument:
+//!
+//! ```rust
+//! # use std::{rc::Rc, any::Any};
+//! # use entrait::*;
+//! #[entrait(ReturnRc, ?Send)]
+//! async fn return_rc(_deps: impl Any) -> Rc<i32> {
+//!     Rc::new(42)
+//! }
+//! ```
+//!
+//! On `wasm32`, where futures are rarely `Send`, the Send bound is dropped automatically.
+//! The output for functions and traits with `async` methods is generated twice:
+//! with `Send` futures behind `#[cfg(not(target_arch = "wasm32"))]`, and with `?Send` futures and no `T: Sync` bound on `Impl<T>` for wasm.
+//! The targets with `Send` futures can be chosen with another cfg predicate,
+//! like `send_cfg = not(any(target_arch = "wasm32", feature = "single-threaded"))`, and `send_cfg = all()` keeps them `Send` everywhere, generating the output only once.
+//! Traits with an `#[async_trait]` attribute are left alone, as the attribute already decides whether their futures are `Send`.
+//!
+//! To serve both multi-threaded runtimes and `LocalSet`s from the same functions, pass `local` instead.
+//! Next to the `Send` trait `Foo`, it generates `LocalFoo`, with the same methods and delegation but `?Send` futures,
+//! implemented for `Impl<T>` without the `T: Sync` bound.
+//! Code generic over its dependencies picks one of them, like `deps: &impl LocalFoo`.
+//! Where both traits are in scope for the same concrete type, call the methods with the trait path, like `Foo::foo(&app)`.
+//! Mocks and bindings are only generated for the `Send` trait.
+//! The variant can be named like with `trait_variant::make`, e.g. `#[entrait(Foo, local = UnsyncFoo)]`.
+//!
+//! Crates that need both flavors of every async trait can give `local` in `ENTRAIT_DEFAULTS` instead of a global `?Send`.
+//! As a default, it only applies to traits with `async` functions that are not `?Send`.
+//!
+//! #### Integrating with other `fn`-targeting macros, and `no_deps`
+//! Some macros are used to transform the body of a function, or generate a body from scratch.
+//! For example, we can use [`feignhttp`](https://docs.rs/feignhttp/latest/feignhttp/) to generate an HTTP client. Entrait will try as best as it
+//! can to co-exist with macros like these. Since `entrait` is a higher-level macro that does not touch fn bodies (it does not even try to parse them),
+//! entrait should be processed after, which means it should be placed _before_ lower level macros. Example:
+//!
+//! ```rust
+//! # use entrait::entrait;
+//! #[entrait(FetchThing, no_deps)]
+//! #[feignhttp::get("https://my.api.org/api/{param}")]
+//! async fn fetch_thing(#[path] param: String) -> feignhttp::Result<String> {}
+//! ```
+//!
+//! Here we used the `no_deps` entrait option.
 //! This is used to tell entrait that the function does not have a `deps` parameter as its first input.
 //! Instead, all the function's inputs get promoted to the generated trait method.
 //!
+//! Entrait also detects functions that clearly don't take deps, so that `no_deps` is only needed in the remaining cases:
+//! functions without parameters, and functions whose first parameter has attributes, like `#[path]` above,
+//! or is an owned primitive or `String`.
+//! A borrowed one, like `&str`, is still taken as deps, for a trait implemented by `str`.
+//! The `deps` option is the escape hatch the other way around, taking the first parameter as deps anyway.
+//!
+//! #### Live-reloadable configuration with `watch`
+//! Configuration is often published through a [`tokio::sync::watch`](https://docs.rs/tokio/latest/tokio/sync/watch/index.html) channel, so that it can be reloaded at runtime.
+//! With the `watch` option (and the `tokio` cargo feature), a leaf trait also gets implemented for `watch::Receiver<T>` when `T` implements it.
+//! Every call reads the latest value seen by the receiver:
+//!
+//! ```rust
+//! # #[cfg(feature = "tokio")]
+//! # mod demo {
+//! # use entrait::*;
+//! #[entrait(watch)]
+//! pub trait GetTimeout {
+//!     fn get_timeout(&self) -> u64;
+//! }
+//!
+//! pub struct Config {
+//!     timeout: u64,
+//! }
+//!
+//! impl GetTimeout for Config {
+//!     fn get_timeout(&self) -> u64 {
+//!         self.timeout
+//!     }
+//! }
+//!
+//! # fn demo() {
+//! let (tx, rx) = tokio::sync::watch::channel(Config { timeout: 10 });
+//! let app = Impl::new(rx);
+//! tx.send_replace(Config { timeout: 20 });
+//! assert_eq!(20, app.get_timeout());
+//! # }
+//! # }
+//! ```
+//!
+//! Since the value is borrowed only for the duration of each call, methods can not return references into it, and `async` methods are not supported.
+//!
+//! #### Configuration getters with `config`
+//! A leaf trait of getters can be implemented directly for a configuration struct, typically deserialized with serde (from a file, or with crates like `figment` or `config`).
+//! With `config = Type`, each method reads the field of the same name, cloning it unless the method returns a reference.
+//! A `#[config(..)]` attribute on a method adjusts this:
+//! * `field = ident` reads another field.
+//! * `default = expr` is for `Option` fields, and is the value when the field is `None`.
+//! * `env = "VAR"` lets an environment variable override the value, when it is set and parses into the return type.
+//!
+//! ```rust
+//! # use entrait::*;
+//! #[derive(serde::Deserialize)]
+//! pub struct AppConfig {
+//!     db_url: String,
+//!     timeout: Option<u64>,
+//! }
+//!
+//! #[entrait(config = AppConfig)]
+//! pub trait Settings {
+//!     #[config(field = db_url)]
+//!     fn database_url(&self) -> &str;
+//!
+//!     #[config(default = 30, env = "APP_TIMEOUT")]
+//!     fn timeout(&self) -> u64;
+//! }
+//!
+//! let app = Impl::new(AppConfig { db_url: "postgres://localhost".to_string(), timeout: None });
+//! assert_eq!("postgres://localhost", app.database_url());
+//! # std::env::remove_var("APP_TIMEOUT");
+//! assert_eq!(30, app.timeout());
+//! ```
+//!
+//! #### axum handlers
+//! With the `axum` cargo feature, the `entrait::axum_deps` module integrates with [axum](https://docs.rs/axum) 0.7.
+//! Its `Deps<D>` extractor takes the dependencies of a handler out of the router state, either the application itself or a state struct implementing `FromRef`.
+//! Handlers and routers are written generically, bounded on the traits they call and on `RouterDeps`, the bounds axum puts on state:
+//!
+//! ```rust
+//! # #[cfg(feature = "axum")]
+//! # mod demo {
+//! # use axum::{routing::get, Router};
+//! # use entrait::axum_deps::{Deps, RouterDeps};
+//! # use entrait::*;
+//! # #[entrait(GetGreeting, no_deps)]
+//! # async fn get_greeting(name: String) -> String { name }
+//! async fn greet<D: GetGreeting>(Deps(deps): Deps<D>) -> String {
+//!     deps.get_greeting("world".to_string()).await
+//! }
+//!
+//! fn routes<D: GetGreeting + RouterDeps>() -> Router<D> {
+//!     Router::new().route("/greeting", get(greet::<D>))
+//! }
+//! # }
+//! ```
+//!
+//! The same router is then served with `routes().with_state(Impl::new(app))`, and tested with a mock as the state.
+//! Mocks are not `Clone`, but an `Arc` of a mock implements the trait with the [`forward_impls`](#dependencies-behind-pointers) option.
+//!
+//! #### actix-web handlers
+//! With the `actix` cargo feature, the `entrait::actix_deps` module does the same for [actix-web](https://docs.rs/actix-web) 4.
+//! `register(Impl::new(app))` stores the application as app data, to be passed to `App::configure`,
+//! and the `Deps<D>` extractor hands it to handlers, which are written generically like above:
+//!
+//! ```rust
+//! # #[cfg(feature = "actix")]
+//! # mod demo {
+//! # use actix_web::web;
+//! # use entrait::actix_deps::Deps;
+//! # use entrait::*;
+//! # #[entrait(GetGreeting, no_deps)]
+//! # async fn get_greeting(name: String) -> String { name }
+//! async fn greet<D: GetGreeting + 'static>(deps: Deps<D>) -> String {
+//!     deps.get_greeting("world".to_string()).await
+//! }
+//!
+//! fn routes<D: GetGreeting + 'static>(config: &mut web::ServiceConfig) {
+//!     config.route("/greeting", web::get().to(greet::<D>));
+//! }
+//! # }
+//! ```
+//!
+//! The application is shared between the workers of the server in a `web::Data`, so it doesn't need to be `Clone`.
+//! `register_data` registers a `web::Data` directly, like a `web::Data<dyn GetGreeting>` for routes of `dyn GetGreeting`.
+//!
+//! #### tower services
+//! With the `tower` cargo feature, an `async fn` taking a request as its only parameter besides the deps can be turned into a [tower](https://docs.rs/tower) `Service`,
+//! to plug business logic straight into hyper, tonic or other tower middleware stacks.
+//! `tower_service = CreateUserService` generates a `CreateUserService<D>(pub D)` struct,
+//! implementing `Service<NewUser>` when `D` implements the trait and is `Clone`:
+//!
+//! ```rust
+//! # #[cfg(feature = "tower")]
+//! # mod demo {
+//! # use entrait::*;
+//! # pub struct NewUser;
+//! # pub struct UserId;
+//! # pub struct StoreError;
+//! # #[entrait(pub StoreUser, no_deps)]
+//! # fn store_user(_new_user: NewUser) -> Result<UserId, StoreError> { Ok(UserId) }
+//! #[entrait(pub CreateUser, tower_service = CreateUserService)]
+//! async fn create_user(deps: &impl StoreUser, new_user: NewUser) -> Result<UserId, StoreError> {
+//!     deps.store_user(new_user)
+//! }
+//!
+//! # fn demo() {
+//! # let app = ();
+//! let service = CreateUserService(Impl::new(app));
+//! # }
+//! # }
+//! ```
+//!
+//! A `Result` output becomes the response and error of the service. Any other output is the response, with `Infallible` as the error.
+//! Each call clones the deps into a boxed future, which is `Send` unless the trait uses `?Send`.
+//! The service is a struct of its own, since `Impl<T>` and `Service` are both foreign to the crate using the option.
+//!
+//! #### tonic services
+//! With the `tonic` cargo feature, a module of `async` functions can serve a [tonic](https://docs.rs/tonic) gRPC service.
+//! `tonic = greeter_server::Greeter` implements the service trait generated by `tonic-build` for `Impl<T>`,
+//! delegating each RPC to the trait method of the same name:
+//!
+//! ```rust
+//! # #[cfg(feature = "tonic")]
+//! # mod demo {
+//! # use entrait::*;
+//! # pub struct HelloRequest { pub id: u32 }
+//! # pub struct HelloReply { pub message: String }
+//! # pub struct Error;
+//! # impl From<Error> for tonic::Status {
+//! #     fn from(_: Error) -> Self { tonic::Status::not_found("no name") }
+//! # }
+//! # #[entrait(pub GetName, no_deps)]
+//! # async fn get_name(_id: u32) -> Result<String, Error> { Ok("world".to_string()) }
+//! # pub mod greeter_server {
+//! #     #[tonic::async_trait]
+//! #     pub trait Greeter: Send + Sync + 'static {
+//! #         async fn say_hello(
+//! #             &self,
+//! #             request: tonic::Request<super::HelloRequest>,
+//! #         ) -> Result<tonic::Response<super::HelloReply>, tonic::Status>;
+//! #     }
+//! #     pub struct GreeterServer<T>(T);
+//! #     impl<T: Greeter> GreeterServer<T> {
+//! #         pub fn new(inner: T) -> Self { Self(inner) }
+//! #     }
+//! # }
+//! #[entrait(pub Greeting, tonic = greeter_server::Greeter)]
+//! mod greeting {
+//!     use super::*;
+//!
+//!     pub async fn say_hello(deps: &impl GetName, request: HelloRequest) -> Result<HelloReply, Error> {
+//!         let name = deps.get_name(request.id).await?;
+//!         Ok(HelloReply { message: format!("Hello, {name}!") })
+//!     }
+//! }
+//!
+//! # fn demo() {
+//! # let app = ();
+//! let server = greeter_server::GreeterServer::new(Impl::new(app));
+//! # }
+//! # }
+//! ```
+//!
+//! The functions take the request message, or the whole `tonic::Request<HelloRequest>` to read its metadata,
+//! and return the reply message or a `tonic::Response<HelloReply>`.
+//! Errors only have to convert into `tonic::Status`, and outputs that aren't a `Result` always succeed.
+//! Since tonic serves requests from many threads, the trait can't use `?Send`, and the `T` of `Impl<T>` must be `Send + Sync + 'static`.
+//!
 //! #### Conditional compilation of mocks
 //! Most often, you will only need to generate mock implementations for test code, and skip this for production code.
 //! A notable exception to this is when building libraries.
@@ -641,10 +1602,107 @@
 //!
 //! It is also possible to reduce noise by doing `use entrait::entrait_export as entrait`.
 //!
+//! To keep mocks out of the release builds of downstream crates, the export can be gated by a cfg predicate instead,
+//! typically a feature that consumers enable for their tests:
+//!
+//! ```
+//! # use entrait::*;
+//! #[entrait(pub Baz, export = cfg(feature = "test-util"))]
+//! fn baz(deps: &()) {}
+//! ```
+//!
+//! Mocks that are not exported are gated behind `#[cfg(test)]`, so integration tests in `tests/` and benches can't see them.
+//! The `mock_cfg` option replaces that predicate:
+//!
+//! ```
+//! # use entrait::*;
+//! #[entrait(pub Qux, mock_cfg = any(test, feature = "mocks"))]
+//! fn qux(deps: &()) {}
+//! ```
+//!
+//! #### Crate-wide default options
+//! Options that every invocation in a crate should have, like `unimock` or `export` in a library,
+//! can be given once with the `ENTRAIT_DEFAULTS` environment variable, for example in `.cargo/config.toml`:
+//!
+//! ```toml
+//! [env]
+//! ENTRAIT_DEFAULTS = "unimock, export, ?Send"
+//! ```
+//!
+//! The defaults are written like the options of the attribute, and an option given in the attribute takes precedence,
+//! so `#[entrait(Foo, export = false)]` opts out of the export.
+//! Only options that make sense for every invocation are supported:
+//! `export` (also as `export = cfg(..)`), `mock_cfg`, `?Send`, `send_cfg`, `?Sync`, `local`, `unimock`, `unimock_version`, `mockall`, `mry`, `faux`, `log`, `otel_metrics`, `intercept`, `fingerprint`, `no_std` and `minimal_codegen`.
+//! As with `ENTRAIT_GRAPH_OUT`, cargo doesn't recompile a crate just because the variable changed.
+//!
+//! #### Expansion size
+//! In crates with hundreds of entrait invocations, expanding the macros and compiling their output can take a good share of the build.
+//! `minimal_codegen` leaves out the parts of the output that only serve docs and diagnostics:
+//! the doc comments mirrored from the functions onto the trait methods (unless listed in `forward_attrs`), the trait docs derived from them,
+//! and the `#[diagnostic::on_unimplemented]` hints for missing delegations.
+//! It is meant to be given crate-wide:
+//!
+//! ```toml
+//! [env]
+//! ENTRAIT_DEFAULTS = "minimal_codegen, send_cfg = all()"
+//! ```
+//!
+//! The turbofish of a delegating call is only written out when the method has generic parameters that can't be inferred,
+//! and a module already gets a single trait and implementation for all its functions, instead of one per function.
+//! Crates that don't target `wasm32` can add `send_cfg = all()`, which generates traits with `async` methods once instead of twice.
+//!
+//! For 600 documented functions with one dependency each, half of them `async`,
+//! the expanded source shrinks from 480 kB to 234 kB with `minimal_codegen`, and a clean build of the crate goes from about 2.0 s to 1.8 s,
+//! or 1.4 s with `send_cfg = all()` as well.
+//!
+//! #### Dependency graph
+//! When the `ENTRAIT_GRAPH_OUT` environment variable is set to a file path during compilation,
+//! every entraited function and module records the edges from its trait to the traits it depends on (or its concrete dependency type)
+//! in that file, as a Graphviz `digraph`:
+//!
+//! ```sh
+//! cargo clean -p my_app && ENTRAIT_GRAPH_OUT=$PWD/deps.dot cargo build
+//! dot -Tsvg deps.dot > deps.svg
+//! ```
+//!
+//! Traits are identified by name, and a recompiled trait replaces its previously recorded edges.
+//! Cargo doesn't recompile a crate just because the variable changed, hence the `cargo clean` for a complete graph.
+//!
+//! #### `no_std` support
+//! Entrait works in `no_std` crates. Delegation of plain and `async` methods only uses `core`,
+//! so it also works with `default-features = false`, without `alloc`.
+//! Generated code that needs more, reports the missing feature of entrait where it is requested:
+//!
+//! * `alloc`: `delegate_by = Arc`, `delegate_by = Box`, `dyn`, the `Box` and `Arc` implementations of `forward_impls`, and `delegate_by = ref` traits with `async` methods or `impl Trait` outputs, which box their futures and outputs.
+//! * `std`: `config` with `env` overrides, `watch`, `memo` and `otel_metrics`.
+//!
+//! Since cargo unifies features, a dependency may enable `std` on behalf of a `no_std` crate.
+//! The `no_std` option rejects the generated code requiring `std` regardless, and can be given crate-wide with `ENTRAIT_DEFAULTS = "no_std"`.
+//! Mocks are not affected, as they are only compiled for tests by default.
+//!
 //! #### Feature overview
 //! | Feature                  | Implies         | Description         |
 //! | -------------------      | --------------- | ------------------- |
 //! | `unimock`                |                 | Adds the [unimock] dependency, and turns on Unimock implementations for all traits. |
+//! | `unimock-0-5`            |                 | Adds the unimock 0.5 dependency, for the `unimock_version = "0.5"` option. Can be enabled together with `unimock`. |
+//! | `std`                    | `alloc`         | Enabled by default. |
+//! | `memo`                   | `std`           | Enables the `entrait::memo` module and the `memo` option, for incremental computation. |
+//! | `record`                 | `std`           | Adds the `serde` and `serde_json` dependencies, and enables the `entrait::record` module and the `record` option, for recording and replaying calls. |
+//! | `alloc`                  |                 | Enables generated code that needs heap allocation, like boxed futures. |
+//! | `log`                    |                 | Adds the `log` dependency (with `kv`), required by the `log` option. |
+//! | `tracing`                |                 | Adds the `tracing` dependency, required by the `instrument` option. |
+//! | `opentelemetry`          | `std`           | Adds the `opentelemetry` dependency (`metrics` only), and enables the `entrait::otel_metrics` module required by the `otel_metrics` option. |
+//! | `tokio`                  | `std`           | Adds the `tokio` dependency (`sync` only), required by the `watch` option. |
+//! | `wasm-bindgen`           | `alloc`         | Adds the `wasm-bindgen`, `wasm-bindgen-futures` and `serde-wasm-bindgen` dependencies, required by the `wasm_bindgen` option. |
+//! | `pyo3`                   | `alloc`         | Adds the `pyo3` and `pyo3-async-runtimes` (tokio) dependencies, required by the `pyo3` option. |
+//! | `ffi`                    | `alloc`         | Enables the `entrait::ffi` module, required by the `ffi` option. |
+//! | `axum`                   | `std`           | Adds the `axum` dependency, and enables the `entrait::axum_deps` module with a dependency extractor for axum handlers. |
+//! | `actix`                  | `std`           | Adds the `actix-web` dependency, and enables the `entrait::actix_deps` module with a dependency extractor for actix-web handlers. |
+//! | `tower`                  | `alloc`         | Adds the `tower-service` dependency, required by the `tower_service` option. |
+//! | `tonic`                  | `std`           | Adds the `tonic` dependency (`codegen` only), required by the `tonic` option. |
+//! | `arc-swap`               | `std`           | Adds the `arc-swap` dependency, required by `delegate_by = swap`. |
+//! | `inventory`              | `alloc`         | Adds the `inventory` dependency, required by `registry = inventory`. |
+//! | `linkme`                 | `alloc`         | Adds the `linkme` dependency, required by `registry = linkme`. |
 //!
 //!
 //! # "Philosophy"
@@ -661,12 +1719,12 @@
 //!
 //! * Representing a _graph_ of objects (even if acyclic) in Rust usually requires reference counting/heap allocation.
 //! * Each "dependency" abstraction often contains a lot of different functionality.
-//!     As an example, consider [DDD](https://en.wikipedia.org/wiki/Domain-driven_design)-based applications consisting of `DomainServices`.
-//!     There will typically be one such class per domain object, with a lot of methods in each.
-//!     This results in dependency graphs with fewer nodes overall, but the number of possible _call graphs_ is much larger.
-//!     A common problem with this is that the _actual dependencies_—the functions actually getting called—are encapsulated
-//!         and hidden away from public interfaces.
-//!     To construct valid dependency mocks in unit tests, a developer will have to read through full function bodies instead of looking at signatures.
+//!   As an example, consider [DDD](https://en.wikipedia.org/wiki/Domain-driven_design)-based applications consisting of `DomainServices`.
+//!   There will typically be one such class per domain object, with a lot of methods in each.
+//!   This results in dependency graphs with fewer nodes overall, but the number of possible _call graphs_ is much larger.
+//!   A common problem with this is that the _actual dependencies_—the functions actually getting called—are encapsulated
+//!   and hidden away from public interfaces.
+//!   To construct valid dependency mocks in unit tests, a developer will have to read through full function bodies instead of looking at signatures.
 //!
 //! `entrait` solves this by:
 //!
@@ -683,6 +1741,21 @@
 //! In fact, this is not a limit of entrait itself, but with Rust's trait solver.
 //! It is not able to prove that a type implements a trait if it needs to prove that it does in order to prove it.
 //!
+//! Entrait reports a trait that depends on itself, by its own name or through `self::`:
+//!
+//! ```compile_fail
+//! # use entrait::*;
+//! #[entrait(Countdown)]
+//! fn countdown(deps: &impl Countdown, n: u32) -> u32 {
+//!     if n == 0 { 0 } else { deps.countdown(n - 1) }
+//! }
+//! // error: `Countdown` depends on itself, so it can't be implemented for `Impl<T>`
+//!
+//! assert_eq!(0, Impl::new(()).countdown(3));
+//! ```
+//!
+//! A cycle through other traits is only reported by the trait solver, since a macro can't resolve which traits their paths name. Mocked traits are not checked, since a mock can stand in for the trait itself.
+//!
 //! While this is a limitation, it is not necessarily a bad one.
 //! One might say that a layered application architecture should never contain cycles.
 //! If you do need recursive algorithms, you could model this as utility functions outside of the entraited APIs of the application.
@@ -691,6 +1764,9 @@
 
 #![forbid(unsafe_code)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 #[cfg(feature = "unimock")]
 mod macros {
     pub use entrait_macros::entrait_export_unimock as entrait_export;
@@ -717,7 +1793,7 @@ mod macros {
 /// ```
 ///
 /// * `$visibility`: Optional visibility specifier for the generated trait.
-///     See the [Rust documentation](https://doc.rust-lang.org/reference/visibility-and-privacy.html) for valid values.
+///   See the [Rust documentation](https://doc.rust-lang.org/reference/visibility-and-privacy.html) for valid values.
 /// * `$TraitIdent`: Any valid Rust identifier that starts with an upper-case character, used as the name of the new trait.
 ///
 /// with options:
@@ -873,13 +1949,60 @@ mod macros {
 ///
 /// | Option              | Type                      | Target             | Default     | Description         |
 /// | ------------------- | ------------------------- | ------------------ | ----------- | ------------------- |
-/// | `no_deps`           | `bool`                    | `fn`               | `false`     | Disables the dependency parameter, so that the first parameter is just interpreted as a normal function parameter. Useful for reducing noise in some situations. |
-/// | `export`            | `bool`                    | `fn`+`mod`         | `false`     | If mocks are generated, exports these mocks even in release builds. Only relevant for libraries. |
-/// | `mock_api`          | `ident`                   | `fn`+`mod`+`trait` |             | The identifier to use for mock APIs (for libraries that support custom identifiers. The `unimock` library requires this to be explicitly specified. |
+/// | `no_deps`           | `bool`                    | `fn`               | detected    | Disables the dependency parameter, so that the first parameter is just interpreted as a normal function parameter. Useful for reducing noise in some situations. Detected for functions without parameters, or whose first parameter has attributes or is an owned primitive or `String`. |
+/// | `deps`              | `bool`                    | `fn`               | detected    | The inverse of `no_deps`, taking the first parameter as deps even when it looks like a normal function parameter. |
+/// | `debug`             | `bool`, `print`, `file` or `print + file` | `fn`+`mod`+`trait` | `false` | Outputs the expansion of the macro invocation for auditing: `print` (or `true`) prints its tokens during compilation, and `file` writes it pretty-printed to `target/entrait-expansions/<crate>/<Trait>.rs`, so that expansions can be diffed between versions. |
+/// | `export`            | `bool` or `cfg(predicate)` | `fn`+`mod`         | `false`     | If mocks are generated, exports these mocks even in release builds. Only relevant for libraries. With `export = cfg(feature = "test-util")`, mocks are only exported when the predicate holds (and in tests), so that consumers opt in with a feature. |
+/// | `mock_api`          | `ident`                   | `fn`+`mod`+`trait` |             | The identifier to use for mock APIs (for libraries that support custom identifiers. The `unimock` library requires this to be explicitly specified, or named by the `ENTRAIT_MOCK_API` convention. With `mockall`, it's an alias for the mock struct. |
+/// | `mock_cfg`          | cfg predicate             | `fn`+`mod`+`trait` | `test`      | The cfg predicate that gates generated mocks when they are not exported, e.g. `mock_cfg = any(test, feature = "mocks")` to make them visible to integration tests and benches. |
 /// | `unimock`           | `bool`                    | `fn`+`mod`+`trait` | `false`[^1] | Used to turn _off_ unimock implementation when the `unimock` _feature_ is enabled. |
-/// | `mockall`           | `bool`                    | `fn`+`mod`+`trait` | `false`     | Enable mockall mocks. |
-/// | `delegate_by`       | `Self`/`ref`/custom ident | `trait`            | `Self`      | Controls the generated `Impl<T>` delegation of this trait. `Self` generates a `T: Trait` bound. `ref` generates a [`T: AsRef<dyn Trait>`](::core::convert::AsRef) bound. `Borrow` is deprecated and uses the [core::borrow::Borrow] trait. Any other value generates a new trait with that name which controls the delegation. |
+/// | `unimock_version`   | `"0.5"` or `"0.6"`        | `fn`+`mod`+`trait` | `"0.6"`     | The unimock version whose API the unimock implementation targets. `"0.5"` requires the `unimock-0-5` feature. |
+/// | `mockall`           | `bool`                    | `fn`+`mod`+`trait` | `false`     | Enable mockall mocks, and a combined `Mock{Function}Deps` mock for deps with multiple trait bounds. |
+/// | `double`            | type                      | `fn`+`mod`         |             | Emits the aliases `{Trait}Double` for the given type and `Mock{Trait}Double` for the mockall mock, for `#[double]` imports with `mockall_double`. Requires `mockall`, and is not supported for generic traits. |
+/// | `mry`               | `bool`                    | `fn`+`mod`+`trait` | `false`     | Enable mry mocks. |
+/// | `faux`              | `bool`                    | `fn`+`mod`+`trait` | `false`     | Generates a `Faux{Trait}` struct implementing the trait, mockable with faux. |
+/// | `stub`              | `bool`                    | `fn`+`mod`+`trait` | `false`     | Implements the trait for [Stub], panicking in every method with a message naming the trait and method. Not supported for traits with associated types. |
+/// | `record`            | `bool`                    | `fn`+`mod`+`trait` | `false`     | Implements the trait for `entrait::record::Recorded<T>`, recording or replaying the calls to `T` with their JSON-serialized arguments and outputs. Methods must take `&self` and can't be generic. Requires the `record` feature. |
+/// | `forward_impls`     | `bool`                    | `fn`+`mod`+`trait` | `false`     | Implements the trait for `&T`, `Box<T>` and `Arc<T>` when `T` implements it, forwarding every call. Methods must take `&self`. `Box` and `Arc` require the `alloc` feature. |
+/// | `delegate_by`       | `Self`/`ref`/`Arc`/`Box`/`swap`/`enum(..)`/custom ident | `trait`            | `Self`      | Controls the generated `Impl<T>` delegation of this trait. `Self` generates a `T: Trait` bound. `ref` generates a [`T: AsRef<dyn Trait>`](::core::convert::AsRef) bound. `Arc` generates a `T: AsRef<Arc<dyn Trait + Send + Sync>>` bound. `Box` generates a [`T: BoxedDelegate<dyn Trait + Send + Sync>`](crate::BoxedDelegate) bound. `swap` generates a `T: AsRef<ArcSwap<Box<dyn Trait + Send + Sync>>>` bound, loading the current target on every call, and requires the `arc-swap` feature. `enum(A, B)` generates a `{Trait}Enum` with a variant for each listed type, implementing the trait by matching on the variant, and a `T: AsRef<{Trait}Enum>` bound. `Borrow` is deprecated and uses the [core::borrow::Borrow] trait. Any other value generates a new trait with that name which controls the delegation. |
 /// | `?Send`             | `true`                    | `fn`+`mod`+`trait` | `false`     | Opts out of `Send` bounds for Future outputs from `async` functions in generated traits.|
+/// | `send_cfg`          | cfg predicate             | `fn`+`mod`+`trait` | `not(target_arch = "wasm32")` | The targets with `Send` futures. For the others, the output is generated with `?Send` futures and no `T: Sync` bound instead. |
+/// | `local`             | `bool` or `Ident`         | `fn`+`mod`         | `false`     | Also generates a `Local{Trait}` variant of the trait (or the named one) with `?Send` futures and no `T: Sync` bound, for `LocalSet` contexts. Requires `async` functions. |
+/// | `?Sync`             | `true`                    | `fn`+`mod`+`trait` | `false`     | Asserts that the `T` in the generated `Impl<T>` implementation needs no `T: Sync` bound, for single-threaded applications. The bound is only generated for the `Send` futures of `async` functions, which borrow `Impl<T>`, so it requires `?Send` when there are any. |
+/// | `supertraits`       | bounds                    | `fn`+`mod`         |             | Supertraits of the generated trait (e.g. `Clone + Send + Sync + 'static`, or a marker trait). The implementation carries the same bounds, so that dependencies can be used like `deps.clone()` without repeating them at each dependency site, and the `mockall` and `faux` mocks get empty implementations of the non-auto traits. Also accepted as `self_bounds`. |
+/// | `threadsafe`        | `bool`                    | `fn`+`mod`         | `false`     | Adds `Send + Sync` to the `supertraits`, so that the generated trait is thread-safe at the trait level, e.g. for `Box<dyn Trait>` shared with spawned tasks. Can't be combined with `?Sync`. |
+/// | `where_clause`      | `[predicates]`            | `fn`+`mod`+`trait` |             | Extra predicates for the where clause of the generated implementation (e.g. `[T: Send + 'static, Self: Marker]`), leaving the trait itself unchanged. `T` is the type parameter of `Impl<T>`, which only exists when the dependencies are generic. In a module, paths resolve from inside the module. |
+/// | `const_trait`       | `bool`                    | `fn`+`mod`         | `false`     | Generates a `const trait` with a `const` implementation, so that `const fn`s stay const-callable through the trait. Requires nightly Rust with `#![feature(const_trait_impl)]`, and dependencies taken as `&impl [const] Trait`. |
+/// | `group`             | string                    | `fn`+`mod`+`trait` |             | Gates everything the invocation outputs (the item itself, the trait, impls and mocks) behind `#[cfg(feature = "group")]`, so that a subsystem can be compiled out with a cargo feature of the same name. |
+/// | `method`            | identifier                | `fn`               | fn name     | The name of the generated trait method, when it should differ from the name of the function. In a module, it goes on the function: `#[entrait(method = name)]`. |
+/// | `also`              | identifier                | `mod`+`impl`       |             | Goes on a function inside a module (or a method of an impl block): `#[entrait(also = Trait)]` also puts the function in a narrower trait named `Trait`, generated with the options of the module. Can be repeated. |
+/// | `include_private`   | `bool`                    | `mod`+`impl`       | `false`     | Also includes the private functions of the module (or methods of the impl block) in the trait, like helpers that should be mockable. |
+/// | `hide_fn`           | `bool`                    | `fn`+`mod`         | `false`     | Hides the entraited functions from the docs with `#[doc(hidden)]`, keeping their visibility, so that an exported library only documents the trait methods as its API. |
+/// | `wasm_bindgen`      | identifier                | `mod`              |             | Generates JavaScript bindings for the trait's methods on the given `#[wasm_bindgen]` tuple struct, whose field implements the trait. Requires the `wasm-bindgen` feature. |
+/// | `pyo3`              | identifier                | `mod`              |             | Generates Python bindings for the trait's methods on the given `#[pyclass]` tuple struct, whose field implements the trait. Requires the `pyo3` feature. |
+/// | `ffi`               | identifier                | `mod`              |             | Generates `extern "C"` functions for the trait's methods, operating on an opaque handle to `Impl<T>` of the given `Default` application type. Requires the `ffi` feature. |
+/// | `tower_service`     | identifier                | `fn`               |             | Generates a struct with the given name, wrapping the deps, that implements `tower::Service` for the request type of an `async fn` taking the request as its only parameter. Requires the `tower` feature. |
+/// | `tonic`             | path                      | `mod`              |             | Implements the given tonic-generated service trait for `Impl<T>`, delegating each RPC to the `async` trait method of the same name. Requires the `tonic` feature. |
+/// | `log`               | `bool`                    | `fn`+`mod`         | `false`     | Logs every delegation with a `log::debug!` record, and `Err` outputs of functions returning a `Result` with a `log::error!` record including the `Debug`-formatted error. The records have `trait_name` and `method` key-values. Requires the `log` feature. |
+/// | `instrument`        | `bool` or `[params]`      | `fn`+`mod`         |             | Runs every delegation in a `tracing::info_span!` named like `Trait::method`, with the listed parameters as `Debug`-formatted span fields (e.g. `instrument = [user_id]`). The span is entered for sync calls and attached to the future of `async` calls. Requires the `tracing` feature. |
+/// | `otel_metrics`      | `bool`                    | `fn`+`mod`         | `false`     | Records every delegation in the OpenTelemetry metrics of the [`otel_metrics`](crate::otel_metrics) module: a call counter and a latency histogram, with the trait, method and outcome as attributes. Requires the `opentelemetry` feature. |
+/// | `intercept`         | `bool`                    | `fn`+`mod`         | `false`     | Calls the hooks of the application's [`CallInterceptor`](crate::intercept::CallInterceptor) in every delegation: `before` and `after` the call, and `error` with the `Debug`-formatted error when a `Result` output is an `Err`. Requires generic dependencies, and adds a `T: CallInterceptor` bound to the `Impl<T>` implementation. |
+/// | `no_std`            | `bool`                    | `fn`+`mod`+`trait` | `false`     | Rejects options whose generated code requires `std`, like `env` overrides of `config`, `watch`, `memo` and `otel_metrics`. |
+/// | `minimal_codegen`   | `bool`                    | `fn`+`mod`+`trait` | `false`     | Leaves out the parts of the output that only serve docs and diagnostics, like mirrored doc comments and `#[diagnostic::on_unimplemented]` hints, to shrink the expansion. |
+/// | `fingerprint`       | `bool`                    | `fn`+`mod`+`trait` | `false`     | Emits a hidden `__entrait_fingerprint_{Trait}` module next to the trait, with the normalized trait signature as `SIGNATURE` and a stable hash of it as `FINGERPRINT`, so that tooling can detect changes to the generated API. |
+/// | `dependencies`      | `bool`                    | `fn`+`mod`         | `false`     | Emits a hidden `__entrait_dependencies_{Trait}` module next to the trait, with the sorted names of the dependency traits (or the concrete dependency type) of its implementation as `DEPENDENCIES: &[&str]`, named like in the `ENTRAIT_GRAPH_OUT` graph. |
+/// | `trait_doc`         | string                    | `fn`+`mod`         |             | Documentation of the generated trait, e.g. `trait_doc = "Fetching of users."`, for exported traits whose users never see the functions. It replaces the summary of the function's docs that documents the trait of a single function. |
+/// | `strict`            | `bool`                    | `fn`+`mod`         | `false`     | Rejects suspicious functions: a deps parameter the body never uses (use `no_deps` instead), generic parameters not used by any parameter or the output, `Impl<T>` as concrete deps (not a leaf), and `?Send` without `async` functions or combined with `Send` bounds on the deps or `supertraits`. |
+/// | `forward_attrs`     | `(path, ..)`              | `fn`+`mod`         |             | The attributes of the functions to copy onto the generated code, instead of the docs, `#[cfg]`, `#[must_use]` and lint attributes. Attributes other than these are only copied onto the delegating methods. |
+/// | `dyn`               | `bool`                    | `fn`+`mod`         | `false`     | Makes the generated trait dyn-compatible, for use as `Box<dyn Trait>`: `async` methods return boxed futures, `impl Trait` outputs are boxed as `Box<dyn Trait>`, and closure parameters are passed as `&dyn Fn`, `&mut dyn FnMut` or `Box<dyn FnOnce>`. Other generic methods are rejected, and the trait is asserted to be dyn-compatible. |
+/// | `dyn`               | `bool`                    | `trait`            | `false`     | Generates the `Dyn{Trait}` companion of a leaf trait with `async` methods or `impl Trait` outputs, and implements the trait for `dyn Dyn{Trait}` (`+ Send + Sync` with `Send` futures) and a `Box` of it. Associated types and constants are not supported. |
+/// | `memo`              | `bool`                    | `fn`+`mod`         | `false`     | Memoizes the outputs of the delegating methods in the `entrait::memo::Db` of the application, recomputing them only when their inputs change. Requires the `memo` feature. |
+/// | `deferred`          | `bool`                    | `fn`+`mod`         | `false`     | Generates a hidden macro next to the trait instead of the implementation for `Impl<T>`, for [link_entrait!] to implement the trait for an application type in the executable crate. See [Deferred delegation](crate#deferred-delegation). |
+/// | `default_body`      | `fn` path or expression   | `fn`               |             | Gives the generated trait method a default body, so that hand-written implementations of the trait (for other types than [Impl]) may leave it out. `default_body = fn path` calls a fallback function with the receiver and all arguments, any other expression (including paths like `None` or a constant) is the return value (for `async` functions: the output). |
+/// | `config`            | type                      | `trait`            |             | Implements a trait of `&self` getters for the given configuration struct, reading the fields with the same names. See the crate docs for the `#[config(..)]` method attribute. |
+/// | `delegate_defaults` | `bool`                    | `trait`            | `false`     | Also delegates the methods with default bodies, instead of leaving them to run on `Impl<T>`, so that overrides in the delegation target are called. |
+/// | `watch`             | `bool`                    | `trait`            | `false`     | Also implements a leaf trait for `tokio::sync::watch::Receiver<T>` where `T` implements it, delegating to the latest value. Requires the `tokio` feature. |
+/// | `registry`          | `inventory`/`linkme`      | `trait`            |             | Generates a `{Trait}Provider` registry of the delegation targets of a leaf trait delegating by `ref`, `Arc`, `Box` or `swap`, filled with [register_entrait!] and collected by the given crate. Requires the feature of the same name. See [Discovering delegation targets](crate#discovering-delegation-targets). |
 ///
 /// [^1]: Enabled by default by turning on the `unimock` cargo feature.
 pub use macros::entrait;
@@ -891,10 +2014,312 @@ pub use macros::entrait;
 /// A good way to reduce noise can to to import it as `use entrait::entrait_export as entrait;`.
 pub use macros::entrait_export;
 
+/// Applies the [`entrait`](entrait) macro to every eligible function in a module, each getting its own trait.
+///
+/// A function is eligible when it is non-private, has at least one parameter (any number with `no_deps`) and is not already annotated with its own `#[entrait]` attribute.
+/// `#[entrait(skip)]` leaves a function out without giving it a trait.
+/// The trait name is derived from the function name in `UpperCamelCase`, and the trait gets the same visibility as the function.
+///
+/// Inner procedural macro attributes (`#![entrait_all]`) are not supported on stable Rust, so the attribute goes on the `mod` item itself:
+///
+/// ```rust
+/// use entrait::*;
+///
+/// #[entrait_all]
+/// mod user {
+///     use entrait::*;
+///
+///     pub fn fetch_user(deps: &impl FetchName, id: u32) -> String {
+///         format!("{id}: {}", deps.fetch_name(id))
+///     }
+///
+///     pub fn fetch_name(_deps: &impl std::any::Any, id: u32) -> String {
+///         format!("user{id}")
+///     }
+///
+///     // Overrides the derived trait name and options:
+///     #[entrait(pub Greet, no_deps)]
+///     pub fn greet(name: &str) -> String {
+///         format!("Hello, {name}")
+///     }
+/// }
+///
+/// use user::{FetchUser, Greet};
+///
+/// let app = Impl::new(());
+/// assert_eq!("1: user1", app.fetch_user(1));
+/// assert_eq!("Hello, world", app.greet("world"));
+/// ```
+///
+/// #### Syntax
+/// ```no_compile
+/// #[entrait_all($option, ...)]
+/// mod some_module {
+///     pub fn ...
+/// }
+/// ```
+///
+/// The options are passed on to every generated `#[entrait]` attribute.
+/// Supported options are `no_deps`, `export`, `debug`, `?Send`, `unimock`, `unimock_version`, `mockall`, `mry`, `faux` and `dyn`.
+pub use entrait_macros::entrait_all;
+
+/// Derive the delegation impls of an application struct from annotations on its fields.
+///
+/// Each field can have an `#[app(..)]` attribute with these options:
+///
+/// | Option            | Generates                                                                    |
+/// | ----------------- | ---------------------------------------------------------------------------- |
+/// | `as_ref`          | `impl AsRef<FieldType> for App`                                              |
+/// | `as_ref = Type`   | `impl AsRef<Type> for App`, e.g. `as_ref = dyn Repository` for `delegate_by = ref` |
+/// | `borrow`          | `impl Borrow<FieldType> for App`, for functions with the field type as [concrete dependency](crate#concrete-dependencies) |
+/// | `boxed`           | `impl BoxedDelegate<D> for App` for a `Box<D>` field, for `delegate_by = Box` |
+/// | `delegate = Path` | `impl Path<Self> for App { type Target = FieldType; }`, for custom delegation traits |
+/// | `leaf = Trait`    | `impl Trait for App`, forwarding every method to the field                   |
+///
+/// See [Deriving the application wiring](crate#deriving-the-application-wiring).
+pub use entrait_macros::EntraitApp;
+
+/// Implement the traits of `deferred` functions and modules for an application type.
+///
+/// ```no_compile
+/// link_entrait!(App => crate_a::Foo, crate_b::bar::Bar);
+/// ```
+///
+/// Each trait is given by the path it is defined or re-exported at, and its functions are called through the same path.
+///
+/// See [Deferred delegation](crate#deferred-delegation).
+pub use entrait_macros::link_entrait;
+
+/// Register a provider of a leaf trait with a `registry`, by the name it is looked up by and a function creating it.
+///
+/// ```no_compile
+/// register_entrait!(crate_a::Repository, "postgres", || Box::new(Postgres::default()));
+/// ```
+///
+/// The trait is given by the path it is defined or re-exported at, where its `{Trait}Provider` is found too.
+///
+/// See [Discovering delegation targets](crate#discovering-delegation-targets).
+pub use entrait_macros::register_entrait;
+
 /// Re-exported from the [implementation] crate.
+///
+/// `Impl<T>` dereferences to the application `T`, and [ImplInnerExt::inner] borrows it explicitly.
+/// [ImplInnerExt::into_inner] moves the application out again.
 pub use ::implementation::Impl;
 
+/// Adds [inner](ImplInnerExt::inner) and [into_inner](ImplInnerExt::into_inner) to [Impl].
+///
+/// `Impl<T>` already implements `Deref<Target = T>` and `DerefMut`, so there is no `Deref` counterpart here.
+pub trait ImplInnerExt {
+    /// The application type
+    type App;
+
+    /// Borrow the application.
+    ///
+    /// Equivalent to [AsRef::as_ref], but can't be confused with the `AsRef` implementations of the application itself.
+    fn inner(&self) -> &Self::App;
+
+    /// Unwrap the application.
+    fn into_inner(self) -> Self::App
+    where
+        Self: Sized;
+}
+
+impl<T> ImplInnerExt for Impl<T> {
+    type App = T;
+
+    fn inner(&self) -> &T {
+        self.as_ref()
+    }
+
+    fn into_inner(self) -> T {
+        // The inherent method
+        Impl::into_inner(self)
+    }
+}
+
+/// A stand-in implementation of the traits generated with the `stub` option, whose methods panic.
+///
+/// Useful for wiring up an application or a test without implementing the traits it doesn't call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Stub;
+
+/// Define an umbrella trait for several traits, implemented for every type that implements all of them.
+///
+/// Entrypoints can then take `deps: &impl AllRepoDeps` instead of listing each bound,
+/// and applications and mocks implementing the traits implement the umbrella trait for free.
+///
+/// ```rust
+/// use entrait::*;
+///
+/// #[entrait(pub FetchUser)]
+/// fn fetch_user(_: &impl std::any::Any, id: u32) -> String {
+///     format!("user{id}")
+/// }
+///
+/// #[entrait(pub DeleteUser)]
+/// fn delete_user(_: &impl std::any::Any, _id: u32) {}
+///
+/// entrait::bundle!(pub UserRepo: FetchUser + DeleteUser);
+///
+/// fn remove_user(deps: &impl UserRepo, id: u32) -> String {
+///     let user = deps.fetch_user(id);
+///     deps.delete_user(id);
+///     user
+/// }
+///
+/// assert_eq!("user1", remove_user(&Impl::new(()), 1));
+/// ```
+#[macro_export]
+macro_rules! bundle {
+    ($(#[$attr:meta])* $vis:vis $bundle:ident: $($bounds:tt)+) => {
+        $(#[$attr])*
+        $vis trait $bundle: $($bounds)+ {}
+
+        impl<EntraitT: ?Sized + $($bounds)+> $bundle for EntraitT {}
+    };
+}
+
+pub mod builder;
+
+pub use builder::ImplBuilderExt;
+
+pub mod intercept;
+
+/// An [Impl] of an application shared behind an [Arc](alloc::sync::Arc), so that its clones are pointer copies.
+///
+/// Leaf traits are implemented for `Arc<T>` when `T` implements them, so the shared form works everywhere `Impl<T>` does.
+#[cfg(feature = "alloc")]
+pub type SharedImpl<T> = Impl<alloc::sync::Arc<T>>;
+
+/// Adds [shared](ImplSharedExt::shared) to [Impl].
+#[cfg(feature = "alloc")]
+pub trait ImplSharedExt {
+    /// The application type
+    type App;
+
+    /// Wrap the application in an [Arc](alloc::sync::Arc) and an [Impl]
+    fn shared(app: Self::App) -> SharedImpl<Self::App>;
+}
+
+#[cfg(feature = "alloc")]
+impl<T> ImplSharedExt for Impl<T> {
+    type App = T;
+
+    fn shared(app: T) -> SharedImpl<T> {
+        Impl::new(alloc::sync::Arc::new(app))
+    }
+}
+
+/// Access to the delegation target owned by an application, for traits using `delegate_by = Box`.
+///
+/// Unlike [AsRef], this conversion can't be confused with other conversions of the application type.
+#[cfg(feature = "alloc")]
+pub trait BoxedDelegate<D: ?Sized> {
+    /// The boxed delegation target
+    fn boxed_delegate(&self) -> &alloc::boxed::Box<D>;
+}
+
+#[cfg(feature = "memo")]
+pub mod memo;
+
+#[cfg(feature = "record")]
+pub mod record;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "opentelemetry")]
+pub mod otel_metrics;
+
+#[cfg(feature = "axum")]
+pub mod axum_deps;
+
+#[cfg(feature = "actix")]
+pub mod actix_deps;
+
+/// Std re-export for macros, reachable from `no_std` crates
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use ::std as __std;
+
+/// Allocation re-exports for macros
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub mod __alloc {
+    pub use ::alloc::borrow::ToOwned;
+    pub use ::alloc::boxed::Box;
+    pub use ::alloc::ffi::CString;
+    pub use ::alloc::sync::Arc;
+}
+
 /// Optional mock re-exports for macros
 #[cfg(feature = "unimock")]
 #[doc(hidden)]
 pub use ::unimock as __unimock;
+#[cfg(feature = "unimock-0-5")]
+#[doc(hidden)]
+pub use ::unimock_0_5 as __unimock_0_5;
+
+/// Combined mockall mocks of multi-bound deps
+#[doc(hidden)]
+pub use entrait_macros::__mockall_deps;
+
+/// Optional log re-export for macros
+#[cfg(feature = "log")]
+#[doc(hidden)]
+pub use ::log as __log;
+
+/// Optional tracing re-export for macros
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use ::tracing as __tracing;
+
+/// Optional tokio re-export for macros
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+pub use ::tokio as __tokio;
+
+/// Optional JavaScript binding re-exports for macros
+#[cfg(feature = "wasm-bindgen")]
+#[doc(hidden)]
+pub use ::serde_wasm_bindgen as __serde_wasm_bindgen;
+#[cfg(feature = "wasm-bindgen")]
+#[doc(hidden)]
+pub use ::wasm_bindgen as __wasm_bindgen;
+#[cfg(feature = "wasm-bindgen")]
+#[doc(hidden)]
+pub use ::wasm_bindgen_futures as __wasm_bindgen_futures;
+
+/// Optional Python binding re-exports for macros
+#[cfg(feature = "pyo3")]
+#[doc(hidden)]
+pub use ::pyo3 as __pyo3;
+#[cfg(feature = "pyo3")]
+#[doc(hidden)]
+pub use ::pyo3_async_runtimes as __pyo3_async_runtimes;
+
+/// Optional tower re-export for macros
+#[cfg(feature = "tower")]
+#[doc(hidden)]
+pub use ::tower_service as __tower_service;
+
+/// Optional tonic re-export for macros
+#[cfg(feature = "tonic")]
+#[doc(hidden)]
+pub use ::tonic as __tonic;
+
+/// Optional arc-swap re-export for macros
+#[cfg(feature = "arc-swap")]
+#[doc(hidden)]
+pub use ::arc_swap as __arc_swap;
+
+/// Optional inventory re-export for macros
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub use ::inventory as __inventory;
+
+/// Optional linkme re-export for macros
+#[cfg(feature = "linkme")]
+#[doc(hidden)]
+pub use ::linkme as __linkme;
@@ -0,0 +1,242 @@
+//! Incremental memoization of entraited functions.
+//!
+//! Functions entraited with the `memo` option become _queries_: their outputs are cached per argument list
+//! in a [Db], which the application type provides through `AsRef<Db>`.
+//! A query is recomputed only when one of the [Input]s it read (directly, or through other queries) has been [set](Db::set) since.
+//!
+//! ```rust
+//! use entrait::*;
+//! use entrait::memo::{Db, Input};
+//!
+//! struct SourceText;
+//!
+//! impl Input for SourceText {
+//!     type Key = String;
+//!     type Value = String;
+//! }
+//!
+//! #[entrait(ReadSource)]
+//! fn read_source(db: &Db, file: &str) -> String {
+//!     db.get::<SourceText>(&file.to_string()).unwrap_or_default()
+//! }
+//!
+//! #[entrait(LineCount, memo)]
+//! fn line_count(deps: &impl ReadSource, file: &str) -> usize {
+//!     deps.read_source(file).lines().count()
+//! }
+//!
+//! let app = Impl::new(Db::new());
+//! app.set::<SourceText>("a.txt".to_string(), "1\n2".to_string());
+//! assert_eq!(2, app.line_count("a.txt"));
+//!
+//! // Cached until `a.txt` changes:
+//! assert_eq!(2, app.line_count("a.txt"));
+//! app.set::<SourceText>("a.txt".to_string(), "1\n2\n3".to_string());
+//! assert_eq!(3, app.line_count("a.txt"));
+//! ```
+//!
+//! There is no early cutoff: a changed input invalidates every query that depends on it,
+//! even if an intermediate query would produce the same output as before.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// An input to the incremental computation, identified by the implementing type.
+///
+/// Input values are set from the outside with [Db::set], and read by entraited functions with [Db::get].
+pub trait Input: 'static {
+    /// The key identifying one value of this input
+    type Key: Hash + Eq + Send + 'static;
+
+    /// The value of this input
+    type Value: Clone + Send + 'static;
+}
+
+/// Storage for inputs and memoized query outputs.
+#[derive(Default)]
+pub struct Db {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    revision: u64,
+    changed_at: HashMap<InputId, u64>,
+    tables: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct InputId {
+    input: TypeId,
+    key_hash: u64,
+}
+
+impl InputId {
+    fn of<I: Input>(key: &I::Key) -> Self {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        // Hash collisions only lead to more recomputation, never to stale outputs
+        Self {
+            input: TypeId::of::<I>(),
+            key_hash: hasher.finish(),
+        }
+    }
+}
+
+struct Memo<V> {
+    value: V,
+    inputs: Arc<[InputId]>,
+    computed_at: u64,
+}
+
+thread_local! {
+    /// The inputs read by each query being computed on this thread, innermost last
+    static ACTIVE_QUERIES: RefCell<Vec<Vec<InputId>>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Db {
+    /// Create an empty database
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current revision, which is incremented by every [Db::set]
+    pub fn revision(&self) -> u64 {
+        self.lock().revision
+    }
+
+    /// Set an input value, invalidating all the queries that have read it
+    pub fn set<I: Input>(&self, key: I::Key, value: I::Value) {
+        let mut state = self.lock();
+        state.revision += 1;
+        let revision = state.revision;
+
+        state.changed_at.insert(InputId::of::<I>(&key), revision);
+        table::<I::Key, I::Value>(&mut state.tables, TypeId::of::<I>()).insert(key, value);
+    }
+
+    /// Get an input value, recording it as a dependency of the queries being computed
+    pub fn get<I: Input>(&self, key: &I::Key) -> Option<I::Value> {
+        record_inputs(&[InputId::of::<I>(key)]);
+
+        let mut state = self.lock();
+        table::<I::Key, I::Value>(&mut state.tables, TypeId::of::<I>())
+            .get(key)
+            .cloned()
+    }
+
+    /// Called by the code generated for `memo` functions
+    #[doc(hidden)]
+    pub fn memoize<Q: 'static, K, V>(&self, key: K, compute: impl FnOnce(&K) -> V) -> V
+    where
+        K: Hash + Eq + Send + 'static,
+        V: Clone + Send + 'static,
+    {
+        let computed_at = {
+            let mut state = self.lock();
+            let State {
+                revision,
+                changed_at,
+                tables,
+            } = &mut *state;
+
+            if let Some(memo) = table::<K, Memo<V>>(tables, TypeId::of::<Q>()).get(&key) {
+                let up_to_date = memo.inputs.iter().all(|input| {
+                    changed_at
+                        .get(input)
+                        .map_or(true, |changed_at| *changed_at <= memo.computed_at)
+                });
+
+                if up_to_date {
+                    let value = memo.value.clone();
+                    let inputs = memo.inputs.clone();
+                    drop(state);
+                    record_inputs(&inputs);
+                    return value;
+                }
+            }
+
+            *revision
+        };
+
+        let (value, mut inputs) = track_inputs(|| compute(&key));
+        inputs.sort();
+        inputs.dedup();
+        let inputs: Arc<[InputId]> = inputs.into();
+        record_inputs(&inputs);
+
+        let mut state = self.lock();
+        table::<K, Memo<V>>(&mut state.tables, TypeId::of::<Q>()).insert(
+            key,
+            Memo {
+                value: value.clone(),
+                inputs,
+                computed_at,
+            },
+        );
+
+        value
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl AsRef<Db> for Db {
+    fn as_ref(&self) -> &Db {
+        self
+    }
+}
+
+fn table<K: Send + 'static, V: Send + 'static>(
+    tables: &mut HashMap<TypeId, Box<dyn Any + Send>>,
+    id: TypeId,
+) -> &mut HashMap<K, V> {
+    tables
+        .entry(id)
+        .or_insert_with(|| Box::new(HashMap::<K, V>::new()))
+        .downcast_mut()
+        .expect("memo tables are keyed by type")
+}
+
+fn record_inputs(inputs: &[InputId]) {
+    ACTIVE_QUERIES.with(|active| {
+        if let Some(frame) = active.borrow_mut().last_mut() {
+            frame.extend_from_slice(inputs);
+        }
+    });
+}
+
+/// Run `f`, collecting the inputs it reads
+fn track_inputs<R>(f: impl FnOnce() -> R) -> (R, Vec<InputId>) {
+    // Pops the frame also when unwinding
+    struct Frame;
+
+    impl Drop for Frame {
+        fn drop(&mut self) {
+            ACTIVE_QUERIES.with(|active| {
+                active.borrow_mut().pop();
+            });
+        }
+    }
+
+    ACTIVE_QUERIES.with(|active| active.borrow_mut().push(vec![]));
+    let frame = Frame;
+    let output = f();
+    let inputs = ACTIVE_QUERIES.with(|active| {
+        active
+            .borrow_mut()
+            .last_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    });
+    drop(frame);
+
+    (output, inputs)
+}
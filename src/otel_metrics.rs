@@ -0,0 +1,57 @@
+//! OpenTelemetry metrics of the delegations generated with the `otel_metrics` option.
+//!
+//! Every delegation is recorded with the `entrait` meter of the [global](opentelemetry::global) meter provider, in two instruments:
+//!
+//! | Instrument              | Kind              | Unit     |
+//! | ----------------------- | ----------------- | -------- |
+//! | `entrait.calls`         | `u64` counter     | `{call}` |
+//! | `entrait.call.duration` | `f64` histogram   | `s`      |
+//!
+//! Both have the attributes `trait` and `method`, naming the generated trait and method,
+//! and `outcome`, which is `error` when a `Result` output is an `Err` and `ok` otherwise.
+//!
+//! The instruments are looked up for every call, so the meter provider may be installed after the first delegation.
+
+use opentelemetry::KeyValue;
+use std::time::Instant;
+
+const METER: &str = "entrait";
+
+/// Measures the duration of a delegation, and records it when finished.
+#[doc(hidden)]
+pub struct CallTimer {
+    trait_name: &'static str,
+    method: &'static str,
+    start: Instant,
+}
+
+impl CallTimer {
+    pub fn start(trait_name: &'static str, method: &'static str) -> Self {
+        Self {
+            trait_name,
+            method,
+            start: Instant::now(),
+        }
+    }
+
+    pub fn finish(self, failed: bool) {
+        let seconds = self.start.elapsed().as_secs_f64();
+        let attributes = [
+            KeyValue::new("trait", self.trait_name),
+            KeyValue::new("method", self.method),
+            KeyValue::new("outcome", if failed { "error" } else { "ok" }),
+        ];
+
+        let meter = opentelemetry::global::meter(METER);
+        meter
+            .u64_counter("entrait.calls")
+            .with_unit("{call}")
+            .build()
+            .add(1, &attributes);
+        meter
+            .f64_histogram("entrait.call.duration")
+            .with_unit("s")
+            .build()
+            .record(seconds, &attributes);
+    }
+}
@@ -0,0 +1,169 @@
+//! Recording and replaying of the calls to entraited traits, for golden-file style tests.
+//!
+//! Traits entraited with the `record` option are implemented for [Recorded], which wraps another implementation of them.
+//! A recording [Recorded] delegates every call to its inner implementation,
+//! and records the method, its arguments and its output as a [RecordedCall], serialized as JSON.
+//! A replaying [Recorded] returns the outputs of previously recorded calls instead, in order, without calling its inner implementation.
+//!
+//! ```rust
+//! use entrait::*;
+//! use entrait::record::Recorded;
+//!
+//! #[entrait(FetchUser, record, stub)]
+//! fn fetch_user(_deps: &impl std::any::Any, id: u32) -> String {
+//!     format!("user{id}")
+//! }
+//!
+//! fn greet(deps: &impl FetchUser) -> String {
+//!     format!("Hello, {}!", deps.fetch_user(42))
+//! }
+//!
+//! let recording = Recorded::record(Impl::new(()));
+//! assert_eq!("Hello, user42!", greet(&recording));
+//!
+//! let calls = recording.into_calls();
+//! assert_eq!("FetchUser::fetch_user", calls[0].method);
+//!
+//! // `Stub` would panic if it was called:
+//! let replaying = Recorded::replay(Stub, calls);
+//! assert_eq!("Hello, user42!", greet(&replaying));
+//! ```
+//!
+//! The calls are `serde` types, so that they can be written to and read from golden files, e.g. with `serde_json`.
+//! The arguments of the recorded methods must implement `Serialize`, and their outputs `Serialize` and `DeserializeOwned`.
+//!
+//! Replaying panics when a call doesn't match the next recorded call, by method or arguments,
+//! and when there are no recorded calls left.
+//! Calls that were recorded but not replayed are returned by [Recorded::into_calls].
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A call to an entraited trait, recorded by [Recorded].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    /// The called method, like `"Trait::method"`
+    pub method: String,
+    /// The arguments of the call, as a JSON array
+    pub args: serde_json::Value,
+    /// The output of the call
+    pub output: serde_json::Value,
+}
+
+/// An implementation of the traits entraited with `record`, which records or replays the calls to an inner implementation.
+pub struct Recorded<T> {
+    inner: T,
+    mode: Mode,
+}
+
+enum Mode {
+    Record(Mutex<Vec<RecordedCall>>),
+    Replay(Mutex<VecDeque<RecordedCall>>),
+}
+
+impl<T> Recorded<T> {
+    /// Record the calls to `inner`.
+    pub fn record(inner: T) -> Self {
+        Self {
+            inner,
+            mode: Mode::Record(Mutex::new(vec![])),
+        }
+    }
+
+    /// Replay the outputs of `calls`, instead of calling `inner`.
+    pub fn replay(inner: T, calls: impl IntoIterator<Item = RecordedCall>) -> Self {
+        Self {
+            inner,
+            mode: Mode::Replay(Mutex::new(calls.into_iter().collect())),
+        }
+    }
+
+    /// The inner implementation.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// The calls recorded so far, or the calls left to replay.
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        match &self.mode {
+            Mode::Record(calls) => lock(calls).clone(),
+            Mode::Replay(calls) => lock(calls).iter().cloned().collect(),
+        }
+    }
+
+    /// Take the recorded calls, or the calls that were not replayed.
+    pub fn into_calls(self) -> Vec<RecordedCall> {
+        match self.mode {
+            Mode::Record(calls) => calls.into_inner().unwrap_or_else(PoisonError::into_inner),
+            Mode::Replay(calls) => calls
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .into(),
+        }
+    }
+
+    /// The output of the next recorded call when replaying, which must be a call to `method` with `args`.
+    #[doc(hidden)]
+    pub fn replay_output<O: DeserializeOwned>(
+        &self,
+        method: &str,
+        args: &serde_json::Value,
+    ) -> Option<O> {
+        let calls = match &self.mode {
+            Mode::Record(_) => return None,
+            Mode::Replay(calls) => calls,
+        };
+        let call = match lock(calls).pop_front() {
+            Some(call) => call,
+            None => {
+                panic!("`{method}` was called with {args}, but there are no recorded calls left")
+            }
+        };
+        if call.method != method || &call.args != args {
+            panic!(
+                "`{method}` was called with {args}, but the next recorded call is `{}` with {}",
+                call.method, call.args
+            );
+        }
+
+        match serde_json::from_value(call.output) {
+            Ok(output) => Some(output),
+            Err(err) => {
+                panic!("The recorded output of `{method}` could not be deserialized: {err}")
+            }
+        }
+    }
+
+    /// Record the call to `method` when recording.
+    #[doc(hidden)]
+    pub fn record_output<O: Serialize>(&self, method: &str, args: serde_json::Value, output: &O) {
+        if let Mode::Record(calls) = &self.mode {
+            lock(calls).push(RecordedCall {
+                method: method.to_string(),
+                args,
+                output: json(output),
+            });
+        }
+    }
+}
+
+/// The arguments of a call, as a JSON array.
+#[doc(hidden)]
+pub fn args<const N: usize>(args: [serde_json::Value; N]) -> serde_json::Value {
+    serde_json::Value::Array(args.into())
+}
+
+#[doc(hidden)]
+pub fn json<V: Serialize + ?Sized>(value: &V) -> serde_json::Value {
+    match serde_json::to_value(value) {
+        Ok(value) => value,
+        Err(err) => panic!("Failed to record a value as JSON: {err}"),
+    }
+}
+
+fn lock<V>(mutex: &Mutex<V>) -> std::sync::MutexGuard<'_, V> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
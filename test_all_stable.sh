@@ -2,6 +2,10 @@
 set -e
 set -x
 
-cargo hack --feature-powerset --exclude-features "default" --exclude-no-default-features test
-cargo test --workspace --features "unimock"
+cargo hack --feature-powerset --depth 2 --exclude-features "default" check --all-targets
+cargo hack --each-feature --exclude-features "default" test
+cargo test --workspace --features "unimock unimock-0-5"
+cargo test --test unimock_0_5 --features "unimock-0-5"
 cargo test --doc --features "unimock"
+cargo hack --each-feature --exclude-features "default" clippy --all-targets -- -D warnings
+CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner cargo test --test it --target wasm32-unknown-unknown --features wasm-bindgen -- js_bindings
@@ -0,0 +1,65 @@
+use actix_web::{test, web};
+use entrait::actix_deps::{register, register_data, Deps};
+use entrait::*;
+use std::sync::Arc;
+
+#[entrait(GetGreeting, mockall)]
+fn get_greeting<D>(_: &D, name: String) -> String {
+    format!("Hello, {name}!")
+}
+
+async fn greet<D: GetGreeting + ?Sized + 'static>(deps: Deps<D>) -> String {
+    deps.get_greeting("world".to_string())
+}
+
+fn routes<D: GetGreeting + ?Sized + 'static>(config: &mut web::ServiceConfig) {
+    config.route("/greeting", web::get().to(greet::<D>));
+}
+
+struct App;
+
+/// Serve `GET /greeting` with an app set up by `configure`
+async fn get_greeting_response(configure: impl FnOnce(&mut web::ServiceConfig)) -> (u16, String) {
+    let service = test::init_service(actix_web::App::new().configure(configure)).await;
+    let request = test::TestRequest::get().uri("/greeting").to_request();
+    let response = test::call_service(&service, request).await;
+    let status = response.status().as_u16();
+    let body = test::read_body(response).await;
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[test]
+fn serves_the_app() {
+    actix_web::rt::System::new().block_on(async {
+        let response = get_greeting_response(|config| {
+            config.configure(register(Impl::new(App)));
+            routes::<Impl<App>>(config);
+        })
+        .await;
+        assert_eq!((200, "Hello, world!".to_string()), response);
+    });
+}
+
+#[test]
+fn serves_a_mock_as_a_trait_object() {
+    actix_web::rt::System::new().block_on(async {
+        let mut mock = MockGetGreeting::new();
+        mock.expect_get_greeting()
+            .returning(|name| format!("Mocked, {name}!"));
+        let deps: Arc<dyn GetGreeting> = Arc::new(mock);
+        let response = get_greeting_response(|config| {
+            config.configure(register_data(web::Data::from(deps)));
+            routes::<dyn GetGreeting>(config);
+        })
+        .await;
+        assert_eq!((200, "Mocked, world!".to_string()), response);
+    });
+}
+
+#[test]
+fn fails_without_registered_deps() {
+    actix_web::rt::System::new().block_on(async {
+        let (status, _) = get_greeting_response(routes::<Impl<App>>).await;
+        assert_eq!(500, status);
+    });
+}
@@ -0,0 +1,63 @@
+use axum::extract::{FromRef, FromRequestParts};
+use axum::routing::get;
+use axum::Router;
+use entrait::axum_deps::{Deps, RouterDeps};
+use entrait::*;
+
+#[entrait(GetGreeting, mockall, forward_impls)]
+async fn get_greeting<D>(_: &D, name: String) -> String {
+    format!("Hello, {name}!")
+}
+
+async fn greet<D: GetGreeting>(Deps(deps): Deps<D>) -> String {
+    deps.get_greeting("world".to_string()).await
+}
+
+fn routes<D: GetGreeting + RouterDeps>() -> Router<D> {
+    Router::new().route("/greeting", get(greet::<D>))
+}
+
+#[derive(Clone)]
+struct App;
+
+#[derive(Clone)]
+struct AppState {
+    app: Impl<App>,
+    name: &'static str,
+}
+
+impl FromRef<AppState> for Impl<App> {
+    fn from_ref(state: &AppState) -> Self {
+        state.app.clone()
+    }
+}
+
+fn request_parts() -> axum::http::request::Parts {
+    axum::http::Request::new(()).into_parts().0
+}
+
+#[tokio::test]
+async fn extracts_the_app_from_itself() {
+    let Deps(app) = Deps::<Impl<App>>::from_request_parts(&mut request_parts(), &Impl::new(App))
+        .await
+        .unwrap();
+    assert_eq!("Hello, world!", greet(Deps(app)).await);
+}
+
+#[tokio::test]
+async fn extracts_the_app_from_a_state_struct() {
+    let state = AppState {
+        app: Impl::new(App),
+        name: "state",
+    };
+    let deps = Deps::<Impl<App>>::from_request_parts(&mut request_parts(), &state)
+        .await
+        .unwrap();
+    assert_eq!("Hello, you!", deps.get_greeting("you".to_string()).await);
+}
+
+#[test]
+fn routes_serve_the_app_and_mocks() {
+    let _: Router = routes().with_state(Impl::new(App));
+    let _: Router = routes().with_state(std::sync::Arc::new(MockGetGreeting::new()));
+}
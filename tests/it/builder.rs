@@ -0,0 +1,117 @@
+use entrait::*;
+
+pub struct Config {
+    greeting: &'static str,
+}
+
+pub struct Db {
+    users: Vec<&'static str>,
+}
+
+entrait::components! {
+    pub struct App {
+        config: Config,
+        db: Db,
+    }
+}
+
+#[entrait(Greet)]
+fn greet(deps: &App, index: usize) -> String {
+    format!("{} {}", deps.config.greeting, deps.db.users[index])
+}
+
+#[test]
+fn test_builder() {
+    let app: Impl<App> = Impl::builder()
+        .with(Config { greeting: "hello" })
+        .with(Db {
+            users: vec!["alice", "bob"],
+        })
+        .build();
+
+    assert_eq!("hello bob", app.greet(1));
+}
+
+#[test]
+fn test_single_component() {
+    struct Single(u32);
+
+    impl From<(u32,)> for Single {
+        fn from((value,): (u32,)) -> Self {
+            Self(value)
+        }
+    }
+
+    let app: Impl<Single> = Impl::builder().with(42).build();
+    assert_eq!(42, app.0);
+}
+
+mod wired_app {
+    use entrait::*;
+
+    mod clock {
+        use entrait::*;
+
+        #[entrait]
+        pub trait Clock {
+            fn now(&self) -> u64;
+        }
+
+        pub struct FixedClock(pub u64);
+
+        impl Clock for FixedClock {
+            fn now(&self) -> u64 {
+                self.0
+            }
+        }
+    }
+
+    #[entrait(delegate_by = ref)]
+    trait Repository {
+        fn fetch(&self, id: u32) -> String;
+    }
+
+    pub struct InMemoryRepository;
+
+    impl Repository for InMemoryRepository {
+        fn fetch(&self, id: u32) -> String {
+            format!("user{id}")
+        }
+    }
+
+    entrait::app! {
+        pub struct App {
+            clock: clock::FixedClock => leaf = clock::Clock,
+            repository: Box<InMemoryRepository> => as_ref => as_ref = dyn Repository,
+            pub name: &'static str,
+        }
+    }
+
+    #[entrait(Describe)]
+    fn describe(deps: &(impl clock::Clock + Repository), id: u32) -> String {
+        format!("{} at {}", deps.fetch(id), deps.now())
+    }
+
+    #[test]
+    fn test_constructor() {
+        let app = Impl::new(App::new(
+            clock::FixedClock(42),
+            Box::new(InMemoryRepository),
+            "app",
+        ));
+
+        assert_eq!("user1 at 42", app.describe(1));
+        assert_eq!("app", app.name);
+    }
+
+    #[test]
+    fn test_builder() {
+        let app: Impl<App> = Impl::builder()
+            .with(clock::FixedClock(7))
+            .with(Box::new(InMemoryRepository))
+            .with("app")
+            .build();
+
+        assert_eq!("user2 at 7", app.describe(2));
+    }
+}
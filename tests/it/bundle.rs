@@ -0,0 +1,53 @@
+use entrait::*;
+
+#[entrait(pub FetchUser, stub)]
+fn fetch_user(_: &impl std::any::Any, id: u32) -> String {
+    format!("user{id}")
+}
+
+#[entrait(pub StoreUser, stub)]
+fn store_user(_: &impl std::any::Any, _name: String) -> u32 {
+    1
+}
+
+#[entrait(pub CountUsers)]
+fn count_users(_: &impl std::any::Any) -> usize {
+    2
+}
+
+entrait::bundle!(
+    /// Everything the user repository provides
+    pub UserRepo: FetchUser + StoreUser + CountUsers
+);
+
+entrait::bundle!(UserWrites: StoreUser + Send + Sync + 'static);
+
+fn register(deps: &impl UserRepo, name: &str) -> String {
+    let id = deps.store_user(name.to_string());
+    format!("{} of {}", deps.fetch_user(id), deps.count_users())
+}
+
+#[test]
+fn test_app_implements_bundle() {
+    assert_eq!("user1 of 2", register(&Impl::new(()), "name"));
+}
+
+fn store_in_background(deps: impl UserWrites) -> u32 {
+    std::thread::spawn(move || deps.store_user("name".to_string()))
+        .join()
+        .unwrap()
+}
+
+#[test]
+fn test_bundle_with_auto_traits() {
+    assert_eq!(1, store_in_background(Impl::new(())));
+}
+
+#[test]
+#[should_panic(expected = "`StoreUser::store_user` is not implemented by `entrait::Stub`")]
+fn test_stub_implements_bundle() {
+    fn store(deps: &impl UserWrites) -> u32 {
+        deps.store_user("name".to_string())
+    }
+    store(&Stub);
+}
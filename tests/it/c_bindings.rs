@@ -0,0 +1,60 @@
+use entrait::ffi::{FromFfi, IntoFfi};
+use entrait::*;
+use std::ffi::{CStr, CString};
+
+#[derive(Default)]
+pub struct App {
+    greeting: &'static str,
+}
+
+pub struct Celsius(f64);
+
+impl FromFfi for Celsius {
+    type Ffi = f64;
+
+    fn from_ffi(ffi: f64) -> Self {
+        Celsius(ffi)
+    }
+}
+
+impl IntoFfi for Celsius {
+    type Ffi = f64;
+
+    fn into_ffi(self) -> f64 {
+        self.0
+    }
+}
+
+#[entrait(pub Weather, ffi = App)]
+mod weather {
+    use super::Celsius;
+
+    pub fn warmer(deps: &impl std::any::Any, temperature: &Celsius, delta: i32) -> Celsius {
+        Celsius(temperature.0 + delta as f64)
+    }
+
+    pub fn describe(deps: &impl std::any::Any, city: &str, temperature: Celsius) -> String {
+        format!("{city}: {}", temperature.0)
+    }
+
+    pub fn is_nice(deps: &impl std::any::Any, city: String) -> bool {
+        city == "Oslo"
+    }
+}
+
+#[test]
+fn test_c_bindings() {
+    let app = weather::weather_create();
+    let oslo = CString::new("Oslo").unwrap();
+
+    unsafe {
+        assert_eq!(12.5, weather::weather_warmer(app, 10.5, 2));
+        assert!(weather::weather_is_nice(app, oslo.as_ptr()));
+
+        let description = weather::weather_describe(app, oslo.as_ptr(), 3.0);
+        assert_eq!("Oslo: 3", CStr::from_ptr(description).to_str().unwrap());
+        weather::weather_string_free(description);
+
+        weather::weather_destroy(app);
+    }
+}
@@ -0,0 +1,48 @@
+use entrait::*;
+
+/// Stands in for a library crate, which leaves the delegation to the final crate
+mod library {
+    use entrait::*;
+
+    #[entrait(pub GetName, deferred)]
+    pub fn get_name(_deps: &impl std::any::Any) -> String {
+        "world".to_string()
+    }
+
+    #[entrait(pub Greeting, deferred)]
+    pub mod greeting {
+        use super::GetName;
+
+        pub fn greet(deps: &impl GetName, greeting: &str) -> String {
+            format!("{greeting} {}", deps.get_name())
+        }
+
+        pub async fn greet_later(deps: &impl GetName) -> String {
+            format!("later {}", deps.get_name())
+        }
+
+        pub fn into_name(deps: impl GetName) -> String {
+            deps.get_name()
+        }
+    }
+}
+
+#[entrait(Shout, deferred)]
+fn shout(deps: &impl library::Greeting) -> String {
+    deps.greet("hello").to_uppercase()
+}
+
+struct App;
+
+link_entrait!(App => library::GetName, library::Greeting, Shout);
+
+#[tokio::test]
+async fn links_the_delegations_for_the_app() {
+    use library::{GetName, Greeting};
+
+    assert_eq!("world", App.get_name());
+    assert_eq!("hi world", App.greet("hi"));
+    assert_eq!("later world", App.greet_later().await);
+    assert_eq!("HELLO WORLD", App.shout());
+    assert_eq!("world", App.into_name());
+}
@@ -80,3 +80,460 @@ mod borrow_dyn_with_async_trait {
         app.foo().await;
     }
 }
+
+#[cfg(feature = "alloc")]
+mod borrow_dyn_native_async {
+    use super::*;
+    use entrait::*;
+
+    #[entrait(Foo)]
+    async fn foo(deps: &impl Bar, arg: &str) -> String {
+        format!("{}{}", deps.bar(arg).await, deps.baz())
+    }
+
+    #[entrait(delegate_by=ref)]
+    trait Bar: Sync + 'static {
+        async fn bar(&self, arg: &str) -> String;
+        fn baz(&self) -> i32;
+    }
+
+    struct Baz;
+
+    struct App(Baz);
+
+    impl AsRef<dyn DynBar> for App {
+        fn as_ref(&self) -> &dyn DynBar {
+            &self.0
+        }
+    }
+
+    impl Bar for Baz {
+        async fn bar(&self, arg: &str) -> String {
+            format!("bar{arg}")
+        }
+
+        fn baz(&self) -> i32 {
+            42
+        }
+    }
+
+    #[tokio::test]
+    async fn test_native_async_borrow() {
+        let app = Impl::new(App(Baz));
+
+        assert_is_send(&app);
+        assert_is_sync(&app);
+
+        assert_eq!("bar!42", app.foo("!").await);
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod arc_dyn {
+    use super::*;
+    use entrait::*;
+    use std::sync::Arc;
+
+    #[entrait(Foo)]
+    async fn foo(deps: &impl Bar, arg: &str) -> String {
+        format!("{}{}", deps.bar(arg).await, deps.baz())
+    }
+
+    #[entrait(delegate_by = Arc)]
+    trait Bar: 'static {
+        async fn bar(&self, arg: &str) -> String;
+        fn baz(&self) -> i32;
+    }
+
+    struct Baz;
+
+    struct App(Arc<dyn DynBar + Send + Sync>);
+
+    impl AsRef<Arc<dyn DynBar + Send + Sync>> for App {
+        fn as_ref(&self) -> &Arc<dyn DynBar + Send + Sync> {
+            &self.0
+        }
+    }
+
+    impl Bar for Baz {
+        async fn bar(&self, arg: &str) -> String {
+            format!("bar{arg}")
+        }
+
+        fn baz(&self) -> i32 {
+            42
+        }
+    }
+
+    #[tokio::test]
+    async fn test_arc_shared_with_thread() {
+        let bar: Arc<dyn DynBar + Send + Sync> = Arc::new(Baz);
+        let app = Impl::new(App(bar.clone()));
+
+        assert_is_send(&app);
+        assert_is_sync(&app);
+
+        let background = std::thread::spawn(move || bar.dyn_baz());
+
+        assert_eq!("bar!42", app.foo("!").await);
+        assert_eq!(42, background.join().unwrap());
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod arc_receiver {
+    use entrait::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[entrait(delegate_by = Arc)]
+    trait Mailbox {
+        fn tell(self: Arc<Self>, msg: u32) -> std::thread::JoinHandle<u32>;
+        fn received(&self) -> u32;
+    }
+
+    #[derive(Default)]
+    struct Actor {
+        received: AtomicU32,
+    }
+
+    impl Mailbox for Actor {
+        fn tell(self: Arc<Self>, msg: u32) -> std::thread::JoinHandle<u32> {
+            std::thread::spawn(move || self.received.fetch_add(msg, Ordering::SeqCst) + msg)
+        }
+
+        fn received(&self) -> u32 {
+            self.received.load(Ordering::SeqCst)
+        }
+    }
+
+    struct App(Arc<dyn Mailbox + Send + Sync>);
+
+    impl AsRef<Arc<dyn Mailbox + Send + Sync>> for App {
+        fn as_ref(&self) -> &Arc<dyn Mailbox + Send + Sync> {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_arc_receiver_delegates_to_the_shared_actor() {
+        let app = Arc::new(Impl::new(App(Arc::new(Actor::default()))));
+
+        assert_eq!(1, app.clone().tell(1).join().unwrap());
+        assert_eq!(3, app.clone().tell(2).join().unwrap());
+        assert_eq!(3, app.received());
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod box_dyn {
+    use super::*;
+    use entrait::*;
+
+    #[entrait(Foo)]
+    fn foo(deps: &impl Bar) -> String {
+        deps.bar()
+    }
+
+    #[entrait(delegate_by = Box)]
+    trait Bar: 'static {
+        fn bar(&self) -> String;
+    }
+
+    struct English;
+    struct Norwegian;
+
+    impl Bar for English {
+        fn bar(&self) -> String {
+            "hello".to_string()
+        }
+    }
+
+    impl Bar for Norwegian {
+        fn bar(&self) -> String {
+            "hei".to_string()
+        }
+    }
+
+    struct App(Box<dyn Bar + Send + Sync>);
+
+    impl App {
+        fn from_config(language: &str) -> Self {
+            match language {
+                "no" => Self(Box::new(Norwegian)),
+                _ => Self(Box::new(English)),
+            }
+        }
+    }
+
+    impl BoxedDelegate<dyn Bar + Send + Sync> for App {
+        fn boxed_delegate(&self) -> &Box<dyn Bar + Send + Sync> {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_box_from_config() {
+        let app = Impl::new(App::from_config("no"));
+
+        assert_is_sync(&app);
+
+        assert_eq!("hei", app.foo());
+        assert_eq!("hello", Impl::new(App::from_config("en")).foo());
+    }
+}
+
+mod enum_dispatch {
+    use super::*;
+    use entrait::*;
+
+    #[entrait(Load)]
+    async fn load(deps: &impl Storage, key: &str) -> String {
+        format!("{}:{}", deps.name(), deps.fetch(key).await)
+    }
+
+    #[entrait(delegate_by = enum(Postgres, Sqlite, InMemory))]
+    trait Storage {
+        fn name(&self) -> &'static str;
+        async fn fetch(&self, key: &str) -> String;
+    }
+
+    struct Postgres;
+    struct Sqlite;
+    struct InMemory(String);
+
+    impl Storage for Postgres {
+        fn name(&self) -> &'static str {
+            "postgres"
+        }
+
+        async fn fetch(&self, key: &str) -> String {
+            format!("select {key}")
+        }
+    }
+
+    impl Storage for Sqlite {
+        fn name(&self) -> &'static str {
+            "sqlite"
+        }
+
+        async fn fetch(&self, key: &str) -> String {
+            format!("select {key}")
+        }
+    }
+
+    impl Storage for InMemory {
+        fn name(&self) -> &'static str {
+            "memory"
+        }
+
+        async fn fetch(&self, key: &str) -> String {
+            format!("{}{key}", self.0)
+        }
+    }
+
+    struct App(StorageEnum);
+
+    impl App {
+        fn from_config(backend: &str) -> Self {
+            match backend {
+                "postgres" => Self(Postgres.into()),
+                "sqlite" => Self(Sqlite.into()),
+                _ => Self(InMemory("cached ".to_string()).into()),
+            }
+        }
+    }
+
+    impl AsRef<StorageEnum> for App {
+        fn as_ref(&self) -> &StorageEnum {
+            &self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enum_from_config() {
+        let app = Impl::new(App::from_config("sqlite"));
+
+        assert_is_send(&app);
+        assert_is_sync(&app);
+
+        assert_eq!("sqlite:select key", app.load("key").await);
+        assert_eq!(
+            "postgres:select key",
+            Impl::new(App::from_config("postgres")).load("key").await
+        );
+        assert_eq!(
+            "memory:cached key",
+            Impl::new(App::from_config("memory")).load("key").await
+        );
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod derive_app {
+    use super::*;
+    use entrait::*;
+    use std::sync::Arc;
+
+    mod clock {
+        use entrait::*;
+
+        #[entrait]
+        pub trait Clock {
+            fn now(&self) -> u64;
+        }
+
+        pub struct FixedClock(pub u64);
+
+        impl Clock for FixedClock {
+            fn now(&self) -> u64 {
+                self.0
+            }
+        }
+
+        #[entrait]
+        #[async_trait::async_trait]
+        pub trait Timezone {
+            async fn timezone(&self) -> String;
+        }
+
+        #[async_trait::async_trait]
+        impl Timezone for FixedClock {
+            async fn timezone(&self) -> String {
+                "UTC".to_string()
+            }
+        }
+    }
+
+    #[entrait(delegate_by = ref)]
+    trait Repository {
+        fn fetch(&self) -> String;
+    }
+
+    struct InMemoryRepository;
+
+    impl Repository for InMemoryRepository {
+        fn fetch(&self) -> String {
+            "stored".to_string()
+        }
+    }
+
+    #[entrait(delegate_by = Box)]
+    trait Greeting: 'static {
+        fn greeting(&self) -> String;
+    }
+
+    struct English;
+
+    impl Greeting for English {
+        fn greeting(&self) -> String {
+            "hello".to_string()
+        }
+    }
+
+    #[entrait(FormatterImpl, delegate_by = DelegateFormatter)]
+    trait Formatter {
+        fn format(&self, input: &str) -> String;
+    }
+
+    struct Uppercase;
+
+    #[entrait]
+    impl FormatterImpl for Uppercase {
+        fn format<D>(_: &D, input: &str) -> String {
+            input.to_uppercase()
+        }
+    }
+
+    #[derive(EntraitApp)]
+    struct App {
+        #[app(leaf = clock::Clock, leaf = clock::Timezone)]
+        clock: clock::FixedClock,
+        #[app(as_ref = dyn Repository)]
+        repository: Arc<InMemoryRepository>,
+        #[app(boxed)]
+        greeting: Box<dyn Greeting + Send + Sync>,
+        #[app(delegate = DelegateFormatter)]
+        formatter: Uppercase,
+    }
+
+    #[tokio::test]
+    async fn test_derived_delegations() {
+        use clock::{Clock, Timezone};
+
+        let app = Impl::new(App {
+            clock: clock::FixedClock(42),
+            repository: Arc::new(InMemoryRepository),
+            greeting: Box::new(English),
+            formatter: Uppercase,
+        });
+
+        assert_is_sync(&app);
+
+        assert_eq!(42, app.now());
+        assert_eq!("UTC", app.timezone().await);
+        assert_eq!("stored", app.fetch());
+        assert_eq!("hello", app.greeting());
+        assert_eq!("HELLO", app.format("hello"));
+    }
+}
+
+// Without `Send` futures on wasm32, the bridge is implemented for `dyn DynBar` instead
+#[cfg(all(feature = "alloc", not(target_arch = "wasm32")))]
+mod dyn_bridge {
+    use super::*;
+    use entrait::*;
+    use std::sync::Arc;
+
+    #[entrait(Foo)]
+    async fn foo(deps: &impl Bar, arg: &str) -> String {
+        format!(
+            "{}{:?}",
+            deps.bar(arg).await,
+            deps.digits().collect::<Vec<_>>()
+        )
+    }
+
+    #[entrait(dyn)]
+    trait Bar: 'static {
+        async fn bar(&self, arg: &str) -> String;
+        fn digits(&self) -> impl Iterator<Item = u32>;
+    }
+
+    struct Baz;
+
+    impl Bar for Baz {
+        async fn bar(&self, arg: &str) -> String {
+            format!("bar{arg}")
+        }
+
+        fn digits(&self) -> impl Iterator<Item = u32> {
+            [4, 2].into_iter()
+        }
+    }
+
+    async fn bar_unsized(bar: &(impl Bar + ?Sized)) -> String {
+        bar.bar("?").await
+    }
+
+    #[tokio::test]
+    async fn test_boxed_trait_object() {
+        let bar: Box<dyn DynBar + Send + Sync> = Box::new(Baz);
+        assert_eq!("bar?", bar_unsized(&*bar).await);
+
+        let app = Impl::new(bar);
+        assert_is_send(&app);
+        assert_is_sync(&app);
+
+        assert_eq!("bar![4, 2]", app.foo("!").await);
+    }
+
+    #[tokio::test]
+    async fn test_shared_trait_object() {
+        let bar: Arc<dyn DynBar + Send + Sync> = Arc::new(Baz);
+        let app = Impl::new(bar.clone());
+
+        assert_eq!("bar![4, 2]", app.foo("!").await);
+        assert_eq!(vec![4, 2], bar.digits().collect::<Vec<_>>());
+    }
+}
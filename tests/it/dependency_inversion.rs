@@ -92,6 +92,133 @@ mod simple_dyn {
     }
 }
 
+#[cfg(feature = "alloc")]
+mod simple_arc {
+    use entrait::*;
+    use std::sync::Arc;
+
+    #[entrait(FoobarImpl, delegate_by = Arc)]
+    trait Foobar {
+        fn foo(&self) -> i32;
+    }
+
+    struct Implementor;
+
+    #[entrait(ref)]
+    impl FoobarImpl for Implementor {
+        pub fn foo(deps: &impl super::Baz) -> i32 {
+            deps.baz()
+        }
+    }
+
+    struct App {
+        foobar: Arc<dyn FoobarImpl<Self> + Send + Sync>,
+    }
+
+    impl AsRef<Arc<dyn FoobarImpl<Self> + Send + Sync>> for App {
+        fn as_ref(&self) -> &Arc<dyn FoobarImpl<Self> + Send + Sync> {
+            &self.foobar
+        }
+    }
+
+    #[test]
+    fn test_impl_block() {
+        let app = Impl::new(App {
+            foobar: Arc::new(Implementor),
+        });
+
+        assert_eq!(42, app.foo());
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod simple_box {
+    use entrait::*;
+
+    #[entrait(FoobarImpl, delegate_by = Box)]
+    trait Foobar {
+        fn foo(&self) -> i32;
+    }
+
+    struct Implementor;
+
+    #[entrait(ref)]
+    impl FoobarImpl for Implementor {
+        pub fn foo(deps: &impl super::Baz) -> i32 {
+            deps.baz()
+        }
+    }
+
+    struct App {
+        foobar: Box<dyn FoobarImpl<Self> + Send + Sync>,
+    }
+
+    impl BoxedDelegate<dyn FoobarImpl<Self> + Send + Sync> for App {
+        fn boxed_delegate(&self) -> &Box<dyn FoobarImpl<Self> + Send + Sync> {
+            &self.foobar
+        }
+    }
+
+    #[test]
+    fn test_impl_block() {
+        let app = Impl::new(App {
+            foobar: Box::new(Implementor),
+        });
+
+        assert_eq!(42, app.foo());
+    }
+}
+
+#[cfg(feature = "arc-swap")]
+mod simple_swap {
+    use entrait::*;
+    use std::sync::Arc;
+
+    #[entrait(FoobarImpl, delegate_by = swap)]
+    trait Foobar {
+        fn foo(&self) -> i32;
+    }
+
+    struct Implementor;
+    struct Replacement;
+
+    #[entrait(ref)]
+    impl FoobarImpl for Implementor {
+        pub fn foo(deps: &impl super::Baz) -> i32 {
+            deps.baz()
+        }
+    }
+
+    #[entrait(ref)]
+    impl FoobarImpl for Replacement {
+        pub fn foo(deps: &impl super::Baz) -> i32 {
+            deps.baz() + 1
+        }
+    }
+
+    struct App {
+        foobar: arc_swap::ArcSwap<Box<dyn FoobarImpl<Self> + Send + Sync>>,
+    }
+
+    impl AsRef<arc_swap::ArcSwap<Box<dyn FoobarImpl<Self> + Send + Sync>>> for App {
+        fn as_ref(&self) -> &arc_swap::ArcSwap<Box<dyn FoobarImpl<Self> + Send + Sync>> {
+            &self.foobar
+        }
+    }
+
+    #[test]
+    fn test_impl_block() {
+        let app = Impl::new(App {
+            foobar: arc_swap::ArcSwap::from_pointee(Box::new(Implementor)),
+        });
+
+        assert_eq!(42, app.foo());
+
+        app.foobar.store(Arc::new(Box::new(Replacement)));
+        assert_eq!(43, app.foo());
+    }
+}
+
 mod async_static {
     use entrait::*;
 
@@ -195,3 +322,219 @@ mod issue_29 {
         assert_eq!("foo", app.foo("foo"));
     }
 }
+
+mod associated_consts {
+    use entrait::*;
+
+    #[entrait(LimitsImpl, delegate_by = DelegateLimits)]
+    pub trait Limits {
+        const MAX_ITEMS: usize;
+        const NAME: &'static str = "limits";
+
+        fn max_items(&self) -> usize;
+    }
+
+    pub struct SmallLimits;
+
+    #[entrait]
+    impl LimitsImpl for SmallLimits {
+        const MAX_ITEMS: usize = 3;
+
+        fn max_items<D>(_: &D) -> usize {
+            Self::MAX_ITEMS
+        }
+    }
+
+    impl DelegateLimits<Self> for () {
+        type Target = SmallLimits;
+    }
+
+    fn max_items_of<T: Limits>(_: &T) -> usize {
+        T::MAX_ITEMS
+    }
+
+    #[test]
+    fn test_associated_consts() {
+        let app = Impl::new(());
+
+        assert_eq!(3, max_items_of(&app));
+        assert_eq!(3, app.max_items());
+        assert_eq!("limits", <Impl<()> as Limits>::NAME);
+    }
+}
+
+mod associated_types {
+    use entrait::*;
+
+    #[entrait(SourceImpl, delegate_by = DelegateSource)]
+    pub trait Source {
+        type Item;
+
+        fn next_item(&self) -> Self::Item;
+    }
+
+    pub struct Numbers;
+
+    #[entrait]
+    impl SourceImpl for Numbers {
+        type Item = u32;
+
+        fn next_item<D>(_: &D) -> u32 {
+            7
+        }
+    }
+
+    impl DelegateSource<Self> for () {
+        type Target = Numbers;
+    }
+
+    fn next_of<S: Source<Item = u32>>(source: &S) -> u32 {
+        source.next_item()
+    }
+
+    #[test]
+    fn test_associated_types() {
+        let app = Impl::new(());
+
+        assert_eq!(7, next_of(&app));
+    }
+}
+
+mod generic_associated_types {
+    use entrait::*;
+    use std::sync::{Mutex, MutexGuard};
+
+    #[entrait(LockImpl, delegate_by = DelegateLock)]
+    pub trait Lock {
+        type Guard<'a>: std::ops::DerefMut<Target = u32>
+        where
+            Self: 'a;
+
+        fn lock(&self) -> Self::Guard<'_>;
+    }
+
+    pub struct GlobalLock;
+
+    static COUNTER: Mutex<u32> = Mutex::new(0);
+
+    #[entrait]
+    impl LockImpl for GlobalLock {
+        type Guard<'a>
+            = MutexGuard<'static, u32>
+        where
+            Self: 'a;
+
+        fn lock<D>(_: &D) -> MutexGuard<'static, u32> {
+            COUNTER.lock().unwrap()
+        }
+    }
+
+    impl DelegateLock<Self> for () {
+        type Target = GlobalLock;
+    }
+
+    #[test]
+    fn test_generic_associated_types() {
+        let app = Impl::new(());
+
+        *app.lock() += 1;
+        assert_eq!(1, *app.lock());
+    }
+}
+
+mod default_methods {
+    use entrait::*;
+
+    #[entrait(RepositoryImpl, delegate_by = DelegateRepository)]
+    pub trait Repository {
+        fn fetch(&self) -> i32;
+
+        fn fetch_twice(&self) -> i32 {
+            self.fetch() * 2
+        }
+    }
+
+    pub struct Seven;
+
+    #[entrait]
+    impl RepositoryImpl for Seven {
+        fn fetch<D>(_: &D) -> i32 {
+            7
+        }
+    }
+
+    impl DelegateRepository<Self> for () {
+        type Target = Seven;
+    }
+
+    #[test]
+    fn test_default_methods_are_not_part_of_the_impl_trait() {
+        let app = Impl::new(());
+
+        assert_eq!(7, app.fetch());
+        assert_eq!(14, app.fetch_twice());
+    }
+}
+
+mod generic_trait {
+    use entrait::*;
+    use std::collections::HashMap;
+
+    #[entrait(StoreImpl, delegate_by = DelegateStore)]
+    pub trait Store<K, V> {
+        fn get(&self, key: &K) -> Option<V>;
+    }
+
+    pub struct DisplayStore;
+
+    #[entrait]
+    impl StoreImpl<u32, String> for DisplayStore {
+        fn get<D>(_: &D, key: &u32) -> Option<String> {
+            Some(key.to_string())
+        }
+    }
+
+    impl DelegateStore<Self, u32, String> for () {
+        type Target = DisplayStore;
+    }
+
+    #[test]
+    fn test_custom_delegation() {
+        let app = Impl::new(());
+
+        assert_eq!(Some("3".to_string()), app.get(&3));
+    }
+
+    #[entrait(LookupImpl, delegate_by = ref)]
+    pub trait Lookup<K> {
+        fn lookup(&self, key: &K) -> Option<&'static str>;
+    }
+
+    struct Names(HashMap<u32, &'static str>);
+
+    impl LookupImpl<App, u32> for Names {
+        fn lookup(&self, _: &Impl<App>, key: &u32) -> Option<&'static str> {
+            self.0.get(key).copied()
+        }
+    }
+
+    struct App {
+        names: Names,
+    }
+
+    impl AsRef<dyn LookupImpl<Self, u32>> for App {
+        fn as_ref(&self) -> &dyn LookupImpl<Self, u32> {
+            &self.names
+        }
+    }
+
+    #[test]
+    fn test_ref_delegation() {
+        let app = Impl::new(App {
+            names: Names(HashMap::from([(1, "one")])),
+        });
+
+        assert_eq!(Some("one"), app.lookup(&1));
+        assert_eq!(None, app.lookup(&2));
+    }
+}
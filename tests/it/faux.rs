@@ -0,0 +1,37 @@
+mod basic {
+    use entrait::*;
+
+    #[entrait(FetchUser, faux)]
+    fn fetch_user(_deps: &(), id: u32) -> String {
+        format!("user{id}")
+    }
+
+    fn greet(deps: &impl FetchUser, id: u32) -> String {
+        format!("Hello {}", deps.fetch_user(id))
+    }
+
+    #[test]
+    fn test() {
+        let mut mock = FauxFetchUser::faux();
+        faux::when!(mock.fetch_user(1)).then_return("mocked".to_string());
+
+        assert_eq!("Hello mocked", greet(&mock, 1));
+    }
+}
+
+mod entrait_for_trait {
+    use entrait::*;
+
+    #[entrait(faux)]
+    trait Trait {
+        fn method(&self, arg: i32) -> i32;
+    }
+
+    #[test]
+    fn test() {
+        let mut mock = FauxTrait::faux();
+        faux::when!(mock.method).then(|arg| arg + 1);
+
+        assert_eq!(42, mock.method(41));
+    }
+}
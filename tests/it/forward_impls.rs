@@ -0,0 +1,112 @@
+use entrait::*;
+use std::sync::Arc;
+
+#[entrait(FetchUser, forward_impls, mockall)]
+fn fetch_user<D>(_: &D, id: u32) -> String {
+    format!("user{id}")
+}
+
+fn greet(deps: &impl FetchUser, id: u32) -> String {
+    format!("Hello {}", deps.fetch_user(id))
+}
+
+fn greet_owned(deps: impl FetchUser, id: u32) -> String {
+    format!("Hello {}", deps.fetch_user(id))
+}
+
+#[test]
+fn pointers_to_the_app_implement_the_trait() {
+    let app = Impl::new("app".to_string());
+    assert_eq!("Hello user1", greet(&&app, 1));
+    assert_eq!("Hello user2", greet_owned(&app, 2));
+    assert_eq!("Hello user3", greet_owned(Box::new(app.clone()), 3));
+
+    let shared = Arc::new(app);
+    let handle = std::thread::spawn({
+        let shared = shared.clone();
+        move || greet_owned(shared, 4)
+    });
+    assert_eq!("Hello user4", handle.join().unwrap());
+}
+
+#[test]
+fn boxed_mocks_implement_the_trait() {
+    let mut mock = MockFetchUser::new();
+    mock.expect_fetch_user().return_const("mock".to_string());
+
+    let boxed: Box<dyn FetchUser> = Box::new(mock);
+    assert_eq!("Hello mock", greet_owned(boxed, 1));
+}
+
+mod async_fn {
+    use entrait::*;
+    use std::sync::Arc;
+
+    #[entrait(FetchCount, forward_impls)]
+    async fn fetch_count<D>(_: &D) -> u32 {
+        42
+    }
+
+    async fn double_count(deps: impl FetchCount) -> u32 {
+        deps.fetch_count().await * 2
+    }
+
+    #[tokio::test]
+    async fn test_async_forwarding() {
+        let app = Arc::new(Impl::new(()));
+        assert_eq!(84, double_count(&*app).await);
+        assert_eq!(84, double_count(app).await);
+    }
+}
+
+mod module {
+    use entrait::*;
+
+    #[entrait(pub Repo, forward_impls)]
+    mod repo {
+        pub fn get<T: Default>(_: &impl std::any::Any) -> T {
+            T::default()
+        }
+
+        pub fn put(_: &impl std::any::Any, _value: String) {}
+    }
+
+    #[test]
+    fn test_generic_method_forwarding() {
+        let app = Box::new(Impl::new(()));
+        let value: u32 = Repo::get(&app);
+        assert_eq!(0, value);
+        Repo::put(&&*app, "value".to_string());
+    }
+}
+
+mod leaf_trait {
+    use entrait::*;
+    use std::sync::Arc;
+
+    #[entrait(forward_impls)]
+    pub trait Clock {
+        const EPOCH: u64 = 0;
+
+        fn now(&self) -> u64;
+    }
+
+    struct Fixed;
+
+    impl Clock for Fixed {
+        fn now(&self) -> u64 {
+            1000
+        }
+    }
+
+    fn elapsed<C: Clock>(clock: C) -> u64 {
+        clock.now() - C::EPOCH
+    }
+
+    #[test]
+    fn test_leaf_trait_forwarding() {
+        assert_eq!(1000, elapsed(&Fixed));
+        assert_eq!(1000, elapsed(Arc::new(Fixed)));
+        assert_eq!(1000, elapsed(Box::new(Impl::new(Fixed))));
+    }
+}
@@ -0,0 +1,104 @@
+use arc_swap::ArcSwap;
+use entrait::*;
+
+#[entrait(delegate_by = swap)]
+trait Greeter: 'static {
+    fn greet(&self, name: &str) -> String;
+}
+
+#[entrait(delegate_by = swap)]
+trait Storage: 'static {
+    async fn fetch(&self, key: &str) -> String;
+    fn backend(&self) -> &'static str;
+}
+
+struct English;
+struct Norwegian;
+
+impl Greeter for English {
+    fn greet(&self, name: &str) -> String {
+        format!("Hello, {name}")
+    }
+}
+
+impl Greeter for Norwegian {
+    fn greet(&self, name: &str) -> String {
+        format!("Hei, {name}")
+    }
+}
+
+struct Memory;
+struct Disk;
+
+impl Storage for Memory {
+    async fn fetch(&self, key: &str) -> String {
+        format!("memory:{key}")
+    }
+
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+}
+
+impl Storage for Disk {
+    async fn fetch(&self, key: &str) -> String {
+        format!("disk:{key}")
+    }
+
+    fn backend(&self) -> &'static str {
+        "disk"
+    }
+}
+
+#[entrait(Welcome)]
+fn welcome(deps: &impl Greeter, name: &str) -> String {
+    deps.greet(name)
+}
+
+struct App {
+    greeter: ArcSwap<Box<dyn Greeter + Send + Sync>>,
+    storage: ArcSwap<Box<dyn DynStorage + Send + Sync>>,
+}
+
+impl AsRef<ArcSwap<Box<dyn Greeter + Send + Sync>>> for App {
+    fn as_ref(&self) -> &ArcSwap<Box<dyn Greeter + Send + Sync>> {
+        &self.greeter
+    }
+}
+
+impl AsRef<ArcSwap<Box<dyn DynStorage + Send + Sync>>> for App {
+    fn as_ref(&self) -> &ArcSwap<Box<dyn DynStorage + Send + Sync>> {
+        &self.storage
+    }
+}
+
+fn app() -> Impl<App> {
+    Impl::new(App {
+        greeter: ArcSwap::from_pointee(Box::new(English)),
+        storage: ArcSwap::from_pointee(Box::new(Memory)),
+    })
+}
+
+#[test]
+fn swaps_the_target_at_runtime() {
+    let app = app();
+    assert_eq!("Hello, world", app.welcome("world"));
+
+    let greeter: &ArcSwap<Box<dyn Greeter + Send + Sync>> = app.as_ref().as_ref();
+    greeter.store(std::sync::Arc::new(Box::new(Norwegian)));
+
+    assert_eq!("Hei, world", app.welcome("world"));
+}
+
+#[tokio::test]
+async fn swaps_an_async_target_at_runtime() {
+    let app = app();
+    assert_eq!("memory", app.backend());
+    assert_eq!("memory:key", app.fetch("key").await);
+
+    let storage: &ArcSwap<Box<dyn DynStorage + Send + Sync>> = app.as_ref().as_ref();
+    storage.store(std::sync::Arc::new(Box::new(Disk)));
+
+    assert_eq!("disk", app.backend());
+    assert_eq!("disk:key", app.fetch("key").await);
+}
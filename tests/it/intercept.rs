@@ -0,0 +1,64 @@
+use entrait::intercept::{Call, CallInterceptor};
+use entrait::*;
+use std::sync::Mutex;
+
+/// Records every hook call
+#[derive(Default)]
+struct App {
+    hooks: Mutex<Vec<String>>,
+}
+
+impl CallInterceptor for App {
+    fn before(&self, call: &Call) {
+        self.record(format!("before {}::{}", call.trait_name, call.method));
+    }
+
+    fn after(&self, call: &Call) {
+        self.record(format!("after {}::{}", call.trait_name, call.method));
+    }
+
+    fn error(&self, call: &Call, error: &dyn std::fmt::Debug) {
+        self.record(format!(
+            "error {}::{}: {error:?}",
+            call.trait_name, call.method
+        ));
+    }
+}
+
+impl App {
+    fn record(&self, hook: String) {
+        self.hooks.lock().unwrap().push(hook);
+    }
+}
+
+#[entrait(ParseNumber, intercept)]
+fn parse_number(_deps: &impl std::any::Any, text: &str) -> Result<u32, std::num::ParseIntError> {
+    text.parse()
+}
+
+#[entrait(pub Sum, intercept)]
+mod sum {
+    pub async fn sum(deps: &impl super::ParseNumber, a: &str, b: &str) -> u32 {
+        tokio::task::yield_now().await;
+        deps.parse_number(a).unwrap_or(0) + deps.parse_number(b).unwrap_or(0)
+    }
+}
+
+#[tokio::test]
+async fn test_intercept_hooks() {
+    let app = Impl::new(App::default());
+    assert_eq!(3, app.sum("3", "x").await);
+
+    assert_eq!(
+        vec![
+            "before Sum::sum",
+            "before ParseNumber::parse_number",
+            "after ParseNumber::parse_number",
+            "before ParseNumber::parse_number",
+            "error ParseNumber::parse_number: ParseIntError { kind: InvalidDigit }",
+            "after ParseNumber::parse_number",
+            "after Sum::sum",
+        ],
+        *app.hooks.lock().unwrap()
+    );
+}
@@ -0,0 +1,91 @@
+use entrait::*;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::js_sys;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct User {
+    name: String,
+    age: u8,
+}
+
+#[entrait(pub Users, wasm_bindgen = JsUsers)]
+mod users {
+    use super::User;
+
+    pub fn greet(deps: &impl std::any::Any, user: &User) -> String {
+        format!("Hello, {}!", user.name)
+    }
+
+    pub fn birthday(deps: &impl std::any::Any, user: User, years: u8) -> User {
+        User {
+            age: user.age + years,
+            ..user
+        }
+    }
+
+    pub async fn count_chars(deps: &impl std::any::Any, text: &str) -> usize {
+        text.len()
+    }
+}
+
+#[wasm_bindgen]
+pub struct JsUsers(Rc<Impl<()>>);
+
+#[tokio::test]
+async fn test_js_bindings() {
+    let users = JsUsers(Rc::new(Impl::new(())));
+    let user = User {
+        name: "Ann".to_string(),
+        age: 30,
+    };
+
+    assert_eq!("Hello, Ann!", users.0.greet(&user));
+    assert_eq!(31, users.0.birthday(user, 1).age);
+    assert_eq!(3, users.0.count_chars("abc").await);
+
+    // `JsValue`s only exist in a wasm runtime, where the wrapper is called in `mod wasm`
+    let _: fn(&JsUsers, JsValue) -> Result<JsValue, JsValue> = JsUsers::greet;
+    let _: fn(&JsUsers, JsValue, JsValue) -> Result<JsValue, JsValue> = JsUsers::birthday;
+    let _: fn(&JsUsers, JsValue) -> js_sys::Promise = JsUsers::count_chars;
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use super::*;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn ann() -> JsValue {
+        let user = User {
+            name: "Ann".to_string(),
+            age: 30,
+        };
+        serde_wasm_bindgen::to_value(&user).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_js_wrapper() {
+        let users = JsUsers(Rc::new(Impl::new(())));
+
+        let greeting = users.greet(ann()).unwrap();
+        assert_eq!(Some("Hello, Ann!".to_string()), greeting.as_string());
+
+        let user: User =
+            serde_wasm_bindgen::from_value(users.birthday(ann(), JsValue::from(1)).unwrap())
+                .unwrap();
+        assert_eq!(31, user.age);
+
+        assert!(users.greet(JsValue::from("not a user")).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_js_wrapper_promise() {
+        let users = JsUsers(Rc::new(Impl::new(())));
+
+        let count = JsFuture::from(users.count_chars(JsValue::from("abc")))
+            .await
+            .unwrap();
+        assert_eq!(Some(3.0), count.as_f64());
+    }
+}
@@ -0,0 +1,54 @@
+use entrait::*;
+use std::sync::Mutex;
+
+struct Recorder(Mutex<Vec<String>>);
+
+impl log::Log for Recorder {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", record.level(), record.args()));
+    }
+
+    fn flush(&self) {}
+}
+
+static RECORDER: Recorder = Recorder(Mutex::new(vec![]));
+
+#[entrait(ParseNumber, log)]
+fn parse_number(_deps: &impl std::any::Any, text: &str) -> Result<u32, std::num::ParseIntError> {
+    text.parse()
+}
+
+#[entrait(pub Shout, log)]
+mod shout {
+    pub async fn shout(_deps: &impl std::any::Any, text: &str) -> String {
+        text.to_uppercase()
+    }
+}
+
+#[tokio::test]
+async fn test_log_records() {
+    log::set_logger(&RECORDER).unwrap();
+    log::set_max_level(log::LevelFilter::Debug);
+
+    let app = Impl::new(());
+    assert_eq!(Ok(42), app.parse_number("42"));
+    assert!(app.parse_number("x").is_err());
+    assert_eq!("HEY", app.shout("hey").await);
+
+    assert_eq!(
+        vec![
+            "DEBUG ParseNumber::parse_number",
+            "DEBUG ParseNumber::parse_number",
+            "ERROR ParseNumber::parse_number failed: ParseIntError { kind: InvalidDigit }",
+            "DEBUG Shout::shout",
+        ],
+        *RECORDER.0.lock().unwrap()
+    );
+}
@@ -2,12 +2,67 @@
 #![allow(unused)]
 #![allow(clippy::disallowed_names)]
 
+mod builder;
+mod bundle;
+mod deferred;
 mod delegation_modes;
 mod dependency_inversion;
+mod faux;
+mod intercept;
 mod mockall;
+mod mry;
 mod simple;
+mod stub;
 
 #[cfg(feature = "unimock")]
 mod unimock;
 
+#[cfg(feature = "alloc")]
+mod forward_impls;
+
+#[cfg(feature = "tokio")]
+mod watch;
+
+#[cfg(feature = "arc-swap")]
+mod hot_swap;
+
+#[cfg(any(feature = "inventory", feature = "linkme"))]
+mod registry;
+
+#[cfg(feature = "memo")]
+mod memo;
+
+#[cfg(feature = "record")]
+mod record;
+
+#[cfg(feature = "wasm-bindgen")]
+mod js_bindings;
+
+#[cfg(feature = "pyo3")]
+mod py_bindings;
+
+#[cfg(feature = "ffi")]
+mod c_bindings;
+
+#[cfg(feature = "log")]
+mod logging;
+
+#[cfg(feature = "tracing")]
+mod tracing_spans;
+
+#[cfg(feature = "opentelemetry")]
+mod otel_metrics;
+
+#[cfg(feature = "axum")]
+mod axum_deps;
+
+#[cfg(feature = "actix")]
+mod actix_deps;
+
+#[cfg(feature = "tower")]
+mod tower_services;
+
+#[cfg(feature = "tonic")]
+mod tonic_services;
+
 fn main() {}
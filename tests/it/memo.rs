@@ -0,0 +1,92 @@
+use entrait::memo::{Db, Input};
+use entrait::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct SourceText;
+
+impl Input for SourceText {
+    type Key = String;
+    type Value = String;
+}
+
+#[derive(Default)]
+struct App {
+    db: Db,
+    word_count_runs: AtomicUsize,
+}
+
+impl AsRef<Db> for App {
+    fn as_ref(&self) -> &Db {
+        &self.db
+    }
+}
+
+#[entrait(ReadSource)]
+fn read_source(app: &App, file: &str) -> String {
+    app.db
+        .get::<SourceText>(&file.to_string())
+        .unwrap_or_default()
+}
+
+#[entrait(CountRuns)]
+fn count_run(app: &App) {
+    app.word_count_runs.fetch_add(1, Ordering::SeqCst);
+}
+
+#[entrait(WordCount, memo)]
+fn word_count(deps: &(impl ReadSource + CountRuns), file: &str) -> usize {
+    deps.count_run();
+    deps.read_source(file).split_whitespace().count()
+}
+
+#[entrait(TotalWords, memo)]
+fn total_words(deps: &impl WordCount, files: Vec<String>) -> usize {
+    files.iter().map(|file| deps.word_count(file)).sum()
+}
+
+#[entrait(pub Lengths, memo)]
+mod lengths {
+    use super::ReadSource;
+
+    pub fn char_count(deps: &impl ReadSource, file: &str) -> usize {
+        deps.read_source(file).chars().count()
+    }
+
+    pub fn double(deps: &impl std::any::Any, value: u32) -> u32 {
+        value * 2
+    }
+}
+
+#[test]
+fn recomputes_only_invalidated_queries() {
+    let app = Impl::new(App::default());
+    let runs = || app.word_count_runs.load(Ordering::SeqCst);
+    let files = || vec!["a".to_string(), "b".to_string()];
+
+    app.db
+        .set::<SourceText>("a".to_string(), "one two".to_string());
+    app.db
+        .set::<SourceText>("b".to_string(), "three".to_string());
+
+    assert_eq!(3, app.total_words(files()));
+    assert_eq!(2, runs());
+
+    assert_eq!(3, app.total_words(files()));
+    assert_eq!(2, runs());
+
+    app.db
+        .set::<SourceText>("b".to_string(), "three four".to_string());
+    assert_eq!(4, app.total_words(files()));
+    // Only the word count of "b" was recomputed
+    assert_eq!(3, runs());
+}
+
+#[test]
+fn reads_inputs_that_are_set_later() {
+    let app = Impl::new(App::default());
+
+    assert_eq!(0, app.char_count("c"));
+    app.db.set::<SourceText>("c".to_string(), "abc".to_string());
+    assert_eq!(3, app.char_count("c"));
+    assert_eq!(4, app.double(2));
+}
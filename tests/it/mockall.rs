@@ -37,3 +37,264 @@ mod entrait_for_trait {
         assert_eq!(42, mock.method());
     }
 }
+
+mod closure_params {
+    use entrait::*;
+
+    #[entrait(MapValue, mockall)]
+    fn map_value<F: Fn(i32) -> i32 + 'static>(_deps: &(), f: F) -> i32 {
+        f(1)
+    }
+
+    #[test]
+    fn test() {
+        let mut mock = MockMapValue::new();
+        mock.expect_map_value().returning(|f| f(41));
+
+        assert_eq!(42, mock.map_value(|arg| arg + 1));
+    }
+}
+
+mod method_generics {
+    use entrait::*;
+    use std::str::FromStr;
+
+    // mockall requires the type parameters of generic methods to be `'static`
+    #[entrait(Parse, mockall)]
+    fn parse<T: FromStr + 'static>(_deps: &(), s: &str) -> Option<T> {
+        s.parse().ok()
+    }
+
+    #[test]
+    fn test() {
+        let mut mock = MockParse::new();
+        mock.expect_parse::<u32>().returning(|_| Some(42));
+
+        assert_eq!(Some(42), mock.parse::<u32>("1"));
+    }
+}
+
+mod impl_trait_outputs {
+    use entrait::*;
+
+    #[entrait(Evens, mockall)]
+    fn evens(_deps: &(), limit: u32) -> impl Iterator<Item = u32> {
+        (0..limit).filter(|n| n % 2 == 0)
+    }
+
+    #[test]
+    fn test() {
+        let mut mock = MockEvens::new();
+        mock.expect_evens()
+            .returning(|_| Box::new(std::iter::once(42)));
+
+        assert_eq!(vec![42], mock.evens(10).collect::<Vec<_>>());
+    }
+}
+
+mod mock_api {
+    use entrait::*;
+
+    #[entrait(Greet, mockall, mock_api = GreetMock)]
+    fn greet(_deps: &(), name: &str) -> String {
+        format!("Hello, {name}!")
+    }
+
+    #[entrait(mockall, mock_api = StoreMock)]
+    trait Store {
+        fn get(&self, key: u32) -> Option<String>;
+    }
+
+    #[test]
+    fn test() {
+        let mut greet = GreetMock::new();
+        greet.expect_greet().return_const("Hi".to_string());
+        assert_eq!("Hi", greet.greet("you"));
+
+        let mut store = StoreMock::new();
+        store.expect_get().returning(|key| Some(key.to_string()));
+        assert_eq!(Some("7".to_string()), store.get(7));
+    }
+}
+
+mod double {
+    mod users {
+        use entrait::*;
+
+        pub struct App;
+
+        #[entrait(pub FetchUser, mockall, double = Impl<App>)]
+        fn fetch_user(_deps: &impl std::any::Any, id: u32) -> String {
+            format!("user{id}")
+        }
+    }
+
+    use users::FetchUser;
+    #[cfg_attr(test, mockall_double::double)]
+    use users::FetchUserDouble;
+
+    struct Greeter {
+        users: FetchUserDouble,
+    }
+
+    impl Greeter {
+        fn greet(&self, id: u32) -> String {
+            format!("Hello {}", self.users.fetch_user(id))
+        }
+    }
+
+    #[test]
+    fn test() {
+        let mut users = FetchUserDouble::new();
+        users.expect_fetch_user().return_const("mocked".to_string());
+        assert_eq!("Hello mocked", Greeter { users }.greet(1));
+
+        let real: users::FetchUserDouble = entrait::Impl::new(users::App);
+        assert_eq!("user1", real.fetch_user(1));
+    }
+}
+
+mod export_cfg {
+    use entrait::*;
+
+    #[entrait(pub Count, mockall, mock_api = CountMock, export = cfg(feature = "std"))]
+    fn count(_deps: &(), text: &str) -> usize {
+        text.len()
+    }
+
+    #[test]
+    fn test() {
+        let mut count = CountMock::new();
+        count.expect_count().return_const(3usize);
+        assert_eq!(3, count.count("hello"));
+    }
+}
+
+mod mock_cfg {
+    use entrait::*;
+
+    #[entrait(Double, mockall, mock_cfg = any(test, feature = "std"))]
+    fn double(_deps: &(), n: i32) -> i32 {
+        n * 2
+    }
+
+    #[entrait(mockall, mock_cfg = all())]
+    trait Clock {
+        fn now(&self) -> u64;
+    }
+
+    #[test]
+    fn test() {
+        let mut double = MockDouble::new();
+        double.expect_double().return_const(5);
+        assert_eq!(5, double.double(1));
+
+        let mut clock = MockClock::new();
+        clock.expect_now().return_const(7u64);
+        assert_eq!(7, clock.now());
+    }
+}
+
+mod multiple_bounds {
+    use entrait::*;
+
+    #[entrait(FetchName, mockall)]
+    fn fetch_name(_deps: &(), id: u32) -> String {
+        format!("#{id}")
+    }
+
+    #[entrait(Shout, mockall)]
+    fn shout(_deps: &(), text: String) -> String {
+        text.to_uppercase()
+    }
+
+    #[entrait(Greet, mockall)]
+    fn greet(deps: &(impl FetchName + Shout), id: u32) -> String {
+        deps.shout(format!("hello, {}", deps.fetch_name(id)))
+    }
+
+    #[test]
+    fn test() {
+        let mut deps = MockGreetDeps::new();
+        deps.expect_fetch_name()
+            .returning(|id| format!("user {id}"));
+        deps.expect_shout().returning(|text| format!("{text}!"));
+
+        assert_eq!("hello, user 1!", greet(&deps, 1));
+    }
+}
+
+mod also_traits {
+    use entrait::*;
+
+    #[entrait(pub Users, mockall)]
+    pub mod users {
+        #[entrait(also = FetchUser)]
+        pub fn fetch_user(_: &impl std::any::Any, id: u32) -> String {
+            format!("user{id}")
+        }
+
+        #[entrait(also = ListUsers, also = Admins)]
+        pub fn fetch_admin(deps: &impl FetchUser) -> String {
+            format!("admin {}", deps.fetch_user(0))
+        }
+
+        pub fn count_users(_: &impl std::any::Any) -> usize {
+            2
+        }
+    }
+
+    fn greet(deps: &impl FetchUser) -> String {
+        format!("Hello, {}!", deps.fetch_user(1))
+    }
+
+    fn summary(deps: &impl Users) -> String {
+        format!("{} users, {}", deps.count_users(), deps.fetch_admin())
+    }
+
+    #[test]
+    fn test_narrow_and_facade_traits() {
+        let app = Impl::new(());
+        assert_eq!("Hello, user1!", greet(&app));
+        assert_eq!("2 users, admin user0", summary(&app));
+        assert_eq!("admin user0", ListUsers::fetch_admin(&app));
+        assert_eq!("admin user0", Admins::fetch_admin(&app));
+    }
+
+    #[test]
+    fn test_narrow_mock() {
+        let mut deps = users::MockFetchUser::new();
+        deps.expect_fetch_user()
+            .withf(|id| *id == 1)
+            .return_const("mocked".to_string());
+        assert_eq!("Hello, mocked!", greet(&deps));
+    }
+}
+
+mod supertraits {
+    use entrait::*;
+
+    /// A marker for dependencies allowed to touch audited data
+    pub trait Audited {}
+
+    impl<T> Audited for Impl<T> {}
+
+    #[entrait(FetchSecret, supertraits = Audited + std::fmt::Debug + Send + Sync + 'static, mockall)]
+    fn fetch_secret(_: &impl std::any::Any) -> String {
+        "secret".to_string()
+    }
+
+    fn audited_fetch(deps: &impl FetchSecret) -> String {
+        format!("{deps:?} fetched {}", deps.fetch_secret())
+    }
+
+    #[test]
+    fn test_app_and_mock_satisfy_supertraits() {
+        assert_eq!("Impl(()) fetched secret", audited_fetch(&Impl::new(())));
+
+        let mut deps = MockFetchSecret::new();
+        deps.expect_fetch_secret()
+            .return_const("mocked".to_string());
+        assert!(audited_fetch(&deps).ends_with("fetched mocked"));
+    }
+}
@@ -0,0 +1,91 @@
+use entrait::*;
+use opentelemetry::metrics::{
+    Counter, Histogram, HistogramBuilder, InstrumentBuilder, InstrumentProvider, Meter,
+    MeterProvider, SyncInstrument,
+};
+use opentelemetry::{InstrumentationScope, KeyValue};
+use std::sync::{Arc, Mutex};
+
+type Measurements = Arc<Mutex<Vec<String>>>;
+
+/// Records the name and attributes of every measurement
+struct Recorder(Measurements);
+
+struct Instrument {
+    name: String,
+    measurements: Measurements,
+}
+
+impl<T> SyncInstrument<T> for Instrument {
+    fn measure(&self, _: T, attributes: &[KeyValue]) {
+        let attributes = attributes
+            .iter()
+            .map(|kv| format!(" {}={}", kv.key.as_str(), kv.value.as_str()))
+            .collect::<String>();
+        self.measurements
+            .lock()
+            .unwrap()
+            .push(format!("{}{attributes}", self.name));
+    }
+}
+
+impl Recorder {
+    fn instrument(&self, name: &str) -> Arc<Instrument> {
+        Arc::new(Instrument {
+            name: name.to_string(),
+            measurements: self.0.clone(),
+        })
+    }
+}
+
+impl InstrumentProvider for Recorder {
+    fn u64_counter(&self, builder: InstrumentBuilder<'_, Counter<u64>>) -> Counter<u64> {
+        Counter::new(self.instrument(&builder.name))
+    }
+
+    fn f64_histogram(&self, builder: HistogramBuilder<'_, Histogram<f64>>) -> Histogram<f64> {
+        Histogram::new(self.instrument(&builder.name))
+    }
+}
+
+impl MeterProvider for Recorder {
+    fn meter_with_scope(&self, _: InstrumentationScope) -> Meter {
+        Meter::new(Arc::new(Recorder(self.0.clone())))
+    }
+}
+
+#[entrait(ParseNumber, otel_metrics)]
+fn parse_number(_deps: &impl std::any::Any, text: &str) -> Result<u32, std::num::ParseIntError> {
+    text.parse()
+}
+
+#[entrait(pub Shout, otel_metrics)]
+mod shout {
+    pub async fn shout(_deps: &impl std::any::Any, text: &str) -> String {
+        tokio::task::yield_now().await;
+        text.to_uppercase()
+    }
+}
+
+#[tokio::test]
+async fn test_otel_metrics() {
+    let measurements = Measurements::default();
+    opentelemetry::global::set_meter_provider(Recorder(measurements.clone()));
+
+    let app = Impl::new(());
+    assert_eq!(Ok(42), app.parse_number("42"));
+    assert!(app.parse_number("x").is_err());
+    assert_eq!("HEY", app.shout("hey").await);
+
+    assert_eq!(
+        vec![
+            "entrait.calls trait=ParseNumber method=parse_number outcome=ok",
+            "entrait.call.duration trait=ParseNumber method=parse_number outcome=ok",
+            "entrait.calls trait=ParseNumber method=parse_number outcome=error",
+            "entrait.call.duration trait=ParseNumber method=parse_number outcome=error",
+            "entrait.calls trait=Shout method=shout outcome=ok",
+            "entrait.call.duration trait=Shout method=shout outcome=ok",
+        ],
+        *measurements.lock().unwrap()
+    );
+}
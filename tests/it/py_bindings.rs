@@ -0,0 +1,62 @@
+use entrait::*;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::Arc;
+
+#[entrait(pub Inventory, pyo3 = PyInventory)]
+mod inventory {
+    pub fn item_name(deps: &impl std::any::Any, id: u32) -> String {
+        format!("item{id}")
+    }
+
+    pub fn count_words(deps: &impl std::any::Any, text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+
+    pub async fn total(deps: &impl std::any::Any, prices: &[u32], tag: &str) -> u32 {
+        prices.iter().sum::<u32>() + tag.len() as u32
+    }
+}
+
+#[pyclass]
+pub struct PyInventory(Arc<Impl<()>>);
+
+#[tokio::test]
+async fn test_py_bindings() {
+    let inventory = PyInventory(Arc::new(Impl::new(())));
+
+    assert_eq!("item1", inventory.0.item_name(1));
+    assert_eq!(2, inventory.0.count_words("a b"));
+    assert_eq!(6, inventory.0.total(&[1, 2], "abc").await);
+}
+
+#[test]
+fn test_py_wrapper() {
+    pyo3::prepare_freethreaded_python();
+
+    Python::with_gil(|py| -> PyResult<()> {
+        let inventory = Bound::new(py, PyInventory(Arc::new(Impl::new(()))))?;
+
+        let name: String = inventory.call_method1("item_name", (1,))?.extract()?;
+        assert_eq!("item1", name);
+        let count: usize = inventory.call_method1("count_words", ("a b",))?.extract()?;
+        assert_eq!(2, count);
+
+        // The awaitable is created on the running event loop
+        let globals = PyDict::new(py);
+        globals.set_item("inventory", &inventory)?;
+        py.run(
+            c"import asyncio
+async def main():
+    return await inventory.total([1, 2], 'abc')
+total = asyncio.run(main())",
+            Some(&globals),
+            None,
+        )?;
+        let total: u32 = globals.get_item("total")?.unwrap().extract()?;
+        assert_eq!(6, total);
+
+        Ok(())
+    })
+    .unwrap();
+}
@@ -0,0 +1,144 @@
+use entrait::record::{Recorded, RecordedCall};
+use entrait::*;
+
+#[entrait(FetchUser, record, stub)]
+fn fetch_user<D>(_: &D, id: u32, verbose: bool) -> Option<String> {
+    if verbose {
+        Some(format!("user number {id}"))
+    } else {
+        Some(format!("user{id}"))
+    }
+}
+
+#[entrait(Greet)]
+fn greet(deps: &impl FetchUser, ids: &[u32]) -> Vec<String> {
+    ids.iter()
+        .map(|id| match deps.fetch_user(*id, false) {
+            Some(user) => format!("Hello {user}"),
+            None => "Hello stranger".to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn should_record_calls_as_json() {
+    let recording = Recorded::record(Impl::new(()));
+    assert_eq!(
+        vec!["Hello user1", "Hello user2"],
+        greet(&recording, &[1, 2])
+    );
+
+    assert_eq!(
+        r#"[{"method":"FetchUser::fetch_user","args":[1,false],"output":"user1"},{"method":"FetchUser::fetch_user","args":[2,false],"output":"user2"}]"#,
+        serde_json::to_string(&recording.into_calls()).unwrap()
+    );
+}
+
+#[test]
+fn should_replay_recorded_outputs() {
+    let calls: Vec<RecordedCall> = serde_json::from_str(
+        r#"[{"method":"FetchUser::fetch_user","args":[1,false],"output":"golden"},{"method":"FetchUser::fetch_user","args":[2,false],"output":null}]"#,
+    )
+    .unwrap();
+
+    let replaying = Recorded::replay(Stub, calls);
+    assert_eq!(
+        vec!["Hello golden", "Hello stranger"],
+        greet(&replaying, &[1, 2])
+    );
+    assert!(replaying.into_calls().is_empty());
+}
+
+#[test]
+#[should_panic(
+    expected = "`FetchUser::fetch_user` was called with [2,false], but the next recorded call is `FetchUser::fetch_user` with [1,false]"
+)]
+fn should_panic_when_replaying_another_call() {
+    let recording = Recorded::record(Impl::new(()));
+    greet(&recording, &[1]);
+
+    greet(&Recorded::replay(Stub, recording.into_calls()), &[2]);
+}
+
+#[test]
+#[should_panic(
+    expected = "`FetchUser::fetch_user` was called with [1,false], but there are no recorded calls left"
+)]
+fn should_panic_when_replaying_too_many_calls() {
+    greet(&Recorded::replay(Stub, vec![]), &[1]);
+}
+
+mod async_module {
+    use entrait::record::Recorded;
+    use entrait::*;
+
+    #[entrait(pub Accounts, record)]
+    mod accounts {
+        pub async fn balance<D>(_: &D, account: String) -> Result<i64, String> {
+            match account.as_str() {
+                "savings" => Ok(100),
+                _ => Err(format!("no account {account}")),
+            }
+        }
+
+        pub async fn owner<D>(_: &D) -> String {
+            "Alice".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn should_record_async_methods() {
+        let recording = Recorded::record(Impl::new(()));
+        assert_eq!(Ok(100), recording.balance("savings".to_string()).await);
+        assert_eq!(
+            Err("no account checking".to_string()),
+            recording.balance("checking".to_string()).await
+        );
+        assert_eq!("Alice", recording.owner().await);
+
+        let calls = recording.calls();
+        assert_eq!(
+            vec!["Accounts::balance", "Accounts::balance", "Accounts::owner"],
+            calls
+                .iter()
+                .map(|call| call.method.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            serde_json::json!({ "Err": "no account checking" }),
+            calls[1].output
+        );
+        assert_eq!(serde_json::json!([]), calls[2].args);
+
+        let replaying = Recorded::replay(Impl::new(()), calls);
+        assert_eq!(Ok(100), replaying.balance("savings".to_string()).await);
+        assert_eq!(2, replaying.calls().len());
+    }
+}
+
+mod hand_written_trait {
+    use entrait::record::Recorded;
+    use entrait::*;
+
+    #[entrait(record)]
+    pub trait Clock {
+        fn now(&self) -> u64;
+    }
+
+    struct App;
+
+    impl Clock for App {
+        fn now(&self) -> u64 {
+            1234
+        }
+    }
+
+    #[test]
+    fn should_record_hand_written_trait() {
+        let recording = Recorded::record(Impl::new(App));
+        assert_eq!(1234, recording.now());
+
+        let replaying = Recorded::replay(Impl::new(App), recording.into_calls());
+        assert_eq!(1234, replaying.now());
+    }
+}
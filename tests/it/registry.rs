@@ -0,0 +1,117 @@
+#[cfg(feature = "inventory")]
+mod inventory_registry {
+    use entrait::*;
+
+    mod repository {
+        use entrait::*;
+
+        #[entrait(delegate_by = ref, registry = inventory)]
+        pub trait Repository: 'static {
+            fn fetch(&self, id: u32) -> String;
+        }
+    }
+
+    mod postgres {
+        pub struct Postgres;
+
+        impl super::repository::Repository for Postgres {
+            fn fetch(&self, id: u32) -> String {
+                format!("postgres{id}")
+            }
+        }
+
+        entrait::register_entrait!(super::repository::Repository, "postgres", || Box::new(
+            Postgres
+        ));
+    }
+
+    mod sqlite {
+        use super::repository::Repository;
+
+        pub struct Sqlite;
+
+        impl Repository for Sqlite {
+            fn fetch(&self, id: u32) -> String {
+                format!("sqlite{id}")
+            }
+        }
+
+        fn create() -> Box<dyn Repository + Send + Sync> {
+            Box::new(Sqlite)
+        }
+
+        entrait::register_entrait!(super::repository::Repository, "sqlite", create);
+    }
+
+    use repository::{Repository, RepositoryProvider};
+
+    struct App(Box<dyn Repository + Send + Sync>);
+
+    impl AsRef<dyn Repository> for App {
+        fn as_ref(&self) -> &dyn Repository {
+            self.0.as_ref()
+        }
+    }
+
+    #[test]
+    fn finds_providers_by_name() {
+        let app = Impl::new(App(RepositoryProvider::find("sqlite").unwrap().create()));
+        assert_eq!("sqlite1", app.fetch(1));
+
+        let app = Impl::new(App(RepositoryProvider::find("postgres").unwrap().create()));
+        assert_eq!("postgres1", app.fetch(1));
+
+        assert!(RepositoryProvider::find("mysql").is_none());
+    }
+
+    #[test]
+    fn lists_all_providers() {
+        let mut names: Vec<_> = RepositoryProvider::all()
+            .map(|provider| provider.name())
+            .collect();
+        names.sort();
+
+        assert_eq!(vec!["postgres", "sqlite"], names);
+    }
+}
+
+#[cfg(feature = "linkme")]
+mod linkme_registry {
+    use entrait::*;
+
+    #[entrait(delegate_by = Box, registry = linkme)]
+    pub trait Storage {
+        async fn load(&self, key: &str) -> String;
+    }
+
+    struct Memory;
+
+    impl Storage for Memory {
+        async fn load(&self, key: &str) -> String {
+            format!("memory:{key}")
+        }
+    }
+
+    register_entrait!(Storage, "memory", || Box::new(Memory));
+
+    struct App(Box<dyn DynStorage + Send + Sync>);
+
+    impl BoxedDelegate<dyn DynStorage + Send + Sync> for App {
+        fn boxed_delegate(&self) -> &Box<dyn DynStorage + Send + Sync> {
+            &self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn finds_async_provider() {
+        assert_eq!(
+            vec!["memory"],
+            StorageProvider::all()
+                .map(|provider| provider.name())
+                .collect::<Vec<_>>()
+        );
+
+        let app = Impl::new(App(StorageProvider::find("memory").unwrap().create()));
+        assert_eq!("memory:key", app.load("key").await);
+    }
+}
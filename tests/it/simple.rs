@@ -82,6 +82,7 @@ mod bounds {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 mod no_deps_and_feign {
     use entrait::entrait;
 
@@ -93,6 +94,60 @@ mod no_deps_and_feign {
     #[entrait(CallMyApi, no_deps)]
     #[get("https://my.api.org/api/{param}")]
     async fn call_my_api(#[path] param: String) -> feignhttp::Result<String> {}
+
+    #[entrait(CallMyOtherApi)]
+    #[get("https://my.api.org/api/other/{param}")]
+    async fn call_my_other_api(#[path] param: String) -> feignhttp::Result<String> {}
+}
+
+mod detected_no_deps {
+    use entrait::*;
+
+    #[entrait(Uptime)]
+    fn uptime() -> u64 {
+        42
+    }
+
+    #[entrait(Shout)]
+    fn shout(text: String, times: usize) -> String {
+        text.to_uppercase().repeat(times)
+    }
+
+    #[entrait(pub Double, deps)]
+    fn double(deps: u32) -> u32 {
+        deps * 2
+    }
+
+    #[test]
+    fn test_detected_no_deps() {
+        let app = Impl::new(());
+        assert_eq!(42, app.uptime());
+        assert_eq!("HEY!HEY!", app.shout("hey!".to_string(), 2));
+        assert_eq!(14, 7.double());
+    }
+}
+
+mod no_deps_zero_params {
+    use entrait::*;
+
+    #[entrait(Now, no_deps)]
+    fn now() -> std::time::Instant {
+        std::time::Instant::now()
+    }
+
+    #[entrait(pub Clock, no_deps)]
+    mod clock {
+        pub fn epoch() -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn test_zero_params() {
+        let app = Impl::new(());
+        assert!(app.now() <= std::time::Instant::now());
+        assert_eq!(0, app.epoch());
+    }
 }
 
 mod test_tracing_instrument {
@@ -208,6 +263,68 @@ mod module {
     #[entrait(PrivateTrait)]
     mod private_trait {}
 
+    #[entrait(pub WithHelpers, include_private)]
+    mod with_helpers {
+        pub fn greet(_deps: &impl super::Dep1, name: &str) -> String {
+            format!("Hello, {}!", capitalize(&(), name))
+        }
+
+        fn capitalize(_deps: &impl std::any::Any, name: &str) -> String {
+            name.to_uppercase()
+        }
+    }
+
+    #[entrait(pub Api)]
+    mod api {
+        // The signatures of nested functions are part of the trait in this module
+        use super::Dep1;
+
+        pub mod users {
+            use super::Dep1;
+
+            pub fn get_user(_deps: &impl Dep1, id: u32) -> String {
+                format!("user{id}")
+            }
+
+            pub mod admin {
+                #[entrait(method = get_admin)]
+                pub fn get_user(_deps: &impl std::any::Any, id: u32) -> String {
+                    format!("admin{id}")
+                }
+            }
+        }
+
+        pub fn version(_deps: &impl std::any::Any) -> u32 {
+            1
+        }
+
+        // Without deps, this could not be a trait method
+        #[entrait(skip)]
+        pub fn api_name() -> &'static str {
+            "api"
+        }
+
+        mod internal {
+            pub fn not_included(_deps: &impl std::any::Any) {}
+        }
+    }
+
+    #[test]
+    fn test_nested_modules() {
+        let app = Impl::new(());
+        assert_eq!("user1", app.get_user(1));
+        assert_eq!("admin2", app.get_admin(2));
+        assert_eq!(1, app.version());
+        assert_eq!("api", api::api_name());
+    }
+
+    #[test]
+    fn test_include_private() {
+        let app = Impl::new(());
+        assert_eq!("Hello, WORLD!", app.greet("world"));
+        assert_eq!("APP", app.capitalize("app"));
+    }
+
     // This test is behind this flag because
     // we cannot have private/crate-private types in interfaces
     // implemented by external crates
@@ -236,7 +353,8 @@ mod cfg_attributes {
     trait Trait {
         fn compiled(&self);
 
-        #[cfg(feature = "always-disabled")]
+        // `any()` is always false
+        #[cfg(any())]
         fn not_compiled(&self) -> NonExistentType;
     }
 
@@ -244,10 +362,33 @@ mod cfg_attributes {
         fn compiled(&self) {}
     }
 
+    // `any()` is always false
+    #[cfg(feature = "alloc")]
+    #[entrait(delegate_by=ref)]
+    trait Fetch {
+        async fn fetch(&self);
+
+        #[cfg(any())]
+        async fn not_fetched(&self) -> NonExistentType;
+    }
+
+    #[entrait(pub Module)]
+    mod module {
+        pub fn module_compiled(_deps: &impl std::any::Any) {}
+
+        #[cfg(any())]
+        pub fn module_not_compiled(_deps: &impl std::any::Any) -> NonExistentType {}
+    }
+
+    #[entrait(NotCompiled)]
+    #[cfg(any())]
+    fn not_compiled(_deps: &impl std::any::Any) -> NonExistentType {}
+
     #[test]
     fn call_compiled() {
         let app = Impl::new(());
         app.compiled();
+        app.module_compiled();
     }
 }
 
@@ -270,3 +411,1817 @@ mod future_send_opt_out {
         Rc::new(42)
     }
 }
+
+mod macro_generated {
+    use entrait::*;
+
+    macro_rules! entraited_fn {
+        ($(#[$meta:meta])* $vis:vis fn $name:ident($deps:ident: $deps_ty:ty $(, $arg:ident: $arg_ty:ty)*) -> $ret:ty $body:block) => {
+            $(#[$meta])*
+            $vis fn $name($deps: $deps_ty $(, $arg: $arg_ty)*) -> $ret $body
+        };
+    }
+
+    entraited_fn! {
+        #[entrait(Double)]
+        fn double(deps: &impl Get, factor: u32) -> u32 {
+            deps.get() * factor
+        }
+    }
+
+    entraited_fn! {
+        #[entrait(Get)]
+        fn get(deps: &u32) -> u32 {
+            *deps
+        }
+    }
+
+    macro_rules! entraited_mod {
+        ($(#[$meta:meta])* mod $mod_name:ident { $($vis:vis fn $name:ident($deps:ident: $deps_ty:ty) -> $ret:ty $body:block)* }) => {
+            $(#[$meta])*
+            mod $mod_name {
+                use super::*;
+                $($vis fn $name($deps: $deps_ty) -> $ret $body)*
+            }
+        };
+    }
+
+    entraited_mod! {
+        #[entrait(pub OneTwo)]
+        mod one_two {
+            pub fn one(deps: &impl Double) -> u32 { deps.double(1) }
+            pub fn two(deps: &impl Double) -> u32 { deps.double(2) }
+        }
+    }
+
+    macro_rules! entraited_trait {
+        ($(#[$meta:meta])* trait $trait_name:ident { fn $method:ident(&self) -> $ret:ty; }) => {
+            $(#[$meta])*
+            trait $trait_name {
+                fn $method(&self) -> $ret;
+            }
+
+            impl $trait_name for u32 {
+                fn $method(&self) -> $ret {
+                    *self
+                }
+            }
+        };
+    }
+
+    entraited_trait! {
+        #[entrait]
+        trait Leaf {
+            fn leaf(&self) -> u32;
+        }
+    }
+
+    #[test]
+    fn test_macro_generated() {
+        let app = Impl::new(21_u32);
+        assert_eq!(42, app.double(2));
+        assert_eq!(21, app.one());
+        assert_eq!(42, app.two());
+        assert_eq!(21, app.leaf());
+    }
+}
+
+mod entrait_all_module {
+    use entrait::*;
+
+    #[entrait_all]
+    pub mod services {
+        use entrait::*;
+
+        pub fn double_value(deps: &impl GetValue) -> u32 {
+            deps.get_value() * 2
+        }
+
+        pub fn get_value(_: &impl std::any::Any) -> u32 {
+            21
+        }
+
+        #[entrait(pub Add, no_deps)]
+        pub fn add(a: u32, b: u32) -> u32 {
+            a + b
+        }
+
+        pub fn no_params() -> u32 {
+            0
+        }
+
+        #[entrait(skip)]
+        pub fn helper(_: &()) -> u32 {
+            1
+        }
+
+        fn private(_: &()) {}
+    }
+
+    #[entrait_all(no_deps)]
+    mod pure {
+        pub(super) fn triple(value: u32) -> u32 {
+            value * 3
+        }
+
+        pub(super) fn seed() -> u32 {
+            7
+        }
+    }
+
+    use pure::{Seed, Triple};
+    use services::{Add, DoubleValue, GetValue};
+
+    #[test]
+    fn test_entrait_all() {
+        let app = Impl::new(());
+
+        assert_eq!(21, app.get_value());
+        assert_eq!(42, app.double_value());
+        assert_eq!(3, app.add(1, 2));
+        assert_eq!(9, app.triple(3));
+        assert_eq!(7, app.seed());
+        assert_eq!(0, services::no_params());
+        assert_eq!(1, services::helper(&()));
+    }
+}
+
+mod default_body {
+    use entrait::*;
+    use std::cmp::Ordering;
+
+    #[entrait(OnEvent, default_body = fn ignore_event)]
+    fn on_event(deps: &impl std::any::Any, event: &str) -> usize {
+        event.len()
+    }
+
+    fn ignore_event<T: ?Sized>(_: &T, _event: &str) -> usize {
+        0
+    }
+
+    #[entrait(Priority, no_deps, default_body = 5)]
+    fn priority(level: u8) -> u8 {
+        level
+    }
+
+    #[entrait(Flush, default_body = true)]
+    async fn flush(deps: &impl std::any::Any) -> bool {
+        false
+    }
+
+    const RETRIES: u32 = 3;
+
+    // Paths without `fn` are values
+    #[entrait(Retries, no_deps, default_body = RETRIES)]
+    fn retries() -> u32 {
+        0
+    }
+
+    #[entrait(Nickname, no_deps, default_body = None)]
+    fn nickname(name: &str) -> Option<String> {
+        Some(name.to_lowercase())
+    }
+
+    #[entrait(Compare, no_deps, default_body = Ordering::Less)]
+    fn compare(a: u32, b: u32) -> Ordering {
+        a.cmp(&b)
+    }
+
+    struct Noop;
+
+    impl OnEvent for Noop {}
+    impl Priority for Noop {}
+    impl Flush for Noop {}
+    impl Retries for Noop {}
+    impl Nickname for Noop {}
+    impl Compare for Noop {}
+
+    #[tokio::test]
+    async fn test_default_body() {
+        assert_eq!(0, Noop.on_event("abc"));
+        assert_eq!(5, Noop.priority(1));
+        assert!(Noop.flush().await);
+        assert_eq!(3, Noop.retries());
+        assert_eq!(None, Noop.nickname("Ann"));
+        assert_eq!(Ordering::Less, Noop.compare(2, 1));
+
+        let app = Impl::new(());
+        assert_eq!(3, app.on_event("abc"));
+        assert_eq!(1, app.priority(1));
+        assert!(!app.flush().await);
+        assert_eq!(0, app.retries());
+        assert_eq!(Some("ann".to_string()), app.nickname("Ann"));
+        assert_eq!(Ordering::Greater, app.compare(2, 1));
+    }
+}
+
+mod method_rename {
+    use entrait::*;
+
+    #[entrait(FetchUser, method = fetch_user)]
+    fn fetch_user_impl(deps: &impl std::any::Any, id: u32) -> String {
+        format!("user{id}")
+    }
+
+    #[entrait(Double, no_deps, method = double)]
+    async fn double_v2(value: u32) -> u32 {
+        value * 2
+    }
+
+    #[entrait(pub Repo)]
+    mod repo {
+        #[entrait(method = find)]
+        pub fn repo_find_user(_deps: &impl std::any::Any, id: u32) -> String {
+            format!("user{id}")
+        }
+
+        pub fn count_users(_deps: &impl std::any::Any) -> usize {
+            1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_method_rename() {
+        let app = Impl::new(());
+        assert_eq!("user1", app.fetch_user(1));
+        assert_eq!(4, app.double(2).await);
+        assert_eq!("user2", app.find(2));
+        assert_eq!(1, app.count_users());
+        assert_eq!("user3", repo::repo_find_user(&(), 3));
+    }
+}
+
+#[cfg(feature = "std")]
+mod config_leaf {
+    use entrait::*;
+
+    #[derive(serde::Deserialize)]
+    struct AppConfig {
+        name: String,
+        db_url: String,
+        port: Option<u16>,
+        workers: Option<usize>,
+    }
+
+    #[entrait(config = AppConfig)]
+    trait Settings {
+        fn name(&self) -> &str;
+
+        #[config(field = db_url)]
+        fn database_url(&self) -> String;
+
+        #[config(default = 8080)]
+        fn port(&self) -> u16;
+
+        #[config(default = 4, env = "ENTRAIT_TEST_CONFIG_WORKERS")]
+        fn workers(&self) -> usize;
+    }
+
+    #[test]
+    fn test_config_leaf() {
+        let config = AppConfig {
+            name: "app".to_string(),
+            db_url: "postgres://localhost".to_string(),
+            port: None,
+            workers: Some(2),
+        };
+        let app = Impl::new(config);
+
+        assert_eq!("app", app.name());
+        assert_eq!("postgres://localhost", app.database_url());
+        assert_eq!(8080, app.port());
+        assert_eq!(2, app.workers());
+
+        std::env::set_var("ENTRAIT_TEST_CONFIG_WORKERS", "16");
+        assert_eq!(16, app.workers());
+        std::env::remove_var("ENTRAIT_TEST_CONFIG_WORKERS");
+    }
+}
+
+mod self_bounds {
+    use entrait::*;
+
+    #[entrait(Greet, self_bounds = Clone + Send + Sync + 'static)]
+    fn greet(_deps: &impl std::any::Any, name: &str) -> String {
+        format!("hello {name}")
+    }
+
+    #[entrait(GreetInBackground)]
+    fn greet_in_background(deps: &impl Greet, name: String) -> String {
+        let deps = deps.clone();
+        std::thread::spawn(move || deps.greet(&name))
+            .join()
+            .unwrap()
+    }
+
+    #[entrait(pub Counter, self_bounds = Sync)]
+    mod counter {
+        pub fn count(_deps: &impl std::any::Any) -> usize {
+            1
+        }
+    }
+
+    fn assert_sync(_: &impl Counter) {}
+
+    #[test]
+    fn test_self_bounds() {
+        let app = Impl::new(());
+        assert_eq!("hello world", app.greet_in_background("world".to_string()));
+        assert_sync(&app);
+        assert_eq!(1, app.count());
+    }
+}
+
+mod threadsafe {
+    use entrait::*;
+
+    #[entrait(Describe, threadsafe)]
+    fn describe(_deps: &impl std::any::Any) -> String {
+        "app".to_string()
+    }
+
+    #[entrait(pub Measure, threadsafe, self_bounds = 'static)]
+    mod measure {
+        pub fn measure(_deps: &impl std::any::Any) -> usize {
+            3
+        }
+    }
+
+    #[test]
+    fn test_threadsafe() {
+        // `dyn Describe` is `Send + Sync` without repeating the bounds
+        let describe: Box<dyn Describe> = Box::new(Impl::new(()));
+        let measure: std::sync::Arc<dyn Measure> = std::sync::Arc::new(Impl::new(()));
+        let background = std::thread::spawn(move || (describe.describe(), measure.measure()));
+        assert_eq!(("app".to_string(), 3), background.join().unwrap());
+    }
+}
+
+mod where_clause {
+    use entrait::*;
+
+    pub trait Audited {}
+
+    struct App;
+
+    impl Audited for App {}
+
+    #[entrait(Audit, where_clause = [T: Audited + 'static])]
+    fn audit(_deps: &impl std::any::Any) -> &'static str {
+        "audited"
+    }
+
+    #[entrait(pub Reports, where_clause = [Self: Send + Sync, T: super::Audited])]
+    mod reports {
+        pub fn report(_deps: &impl std::any::Any) -> &'static str {
+            "report"
+        }
+    }
+
+    #[entrait(where_clause = [T: Audited])]
+    pub trait Lookup {
+        fn lookup(&self) -> u32;
+    }
+
+    impl Lookup for App {
+        fn lookup(&self) -> u32 {
+            42
+        }
+    }
+
+    #[test]
+    fn test_where_clause() {
+        let app = Impl::new(App);
+        assert_eq!("audited", app.audit());
+        assert_eq!("report", app.report());
+        assert_eq!(42, app.lookup());
+    }
+}
+
+mod maybe_sync {
+    use entrait::*;
+    use std::cell::Cell;
+
+    #[entrait(?Sync)]
+    pub trait Count {
+        fn count(&self) -> u32;
+    }
+
+    struct App(Cell<u32>);
+
+    impl Count for App {
+        fn count(&self) -> u32 {
+            self.0.set(self.0.get() + 1);
+            self.0.get()
+        }
+    }
+
+    #[entrait(CountTwice, ?Sync)]
+    fn count_twice(deps: &impl Count) -> u32 {
+        deps.count();
+        deps.count()
+    }
+
+    #[entrait(CountAsync, ?Send, ?Sync)]
+    async fn count_async(deps: &impl Count) -> u32 {
+        deps.count()
+    }
+
+    #[tokio::test]
+    async fn test_maybe_sync() {
+        let app = Impl::new(App(Cell::new(0)));
+        assert_eq!(2, app.count_twice());
+        assert_eq!(3, app.count_async().await);
+    }
+}
+
+mod local_variant {
+    use entrait::*;
+    use std::rc::Rc;
+
+    #[entrait(Fetch, local)]
+    async fn fetch(_deps: &impl std::any::Any, id: u32) -> String {
+        tokio::task::yield_now().await;
+        format!("item {id}")
+    }
+
+    #[entrait(pub Store, local)]
+    mod store {
+        pub async fn store(_deps: &impl std::any::Any, item: String) -> usize {
+            item.len()
+        }
+    }
+
+    #[entrait(Count, local = UnsyncCount)]
+    async fn count(_deps: &impl std::any::Any, item: String) -> usize {
+        item.chars().count()
+    }
+
+    async fn fetch_and_store(deps: &(impl LocalFetch + LocalStore)) -> usize {
+        deps.store(deps.fetch(1).await).await
+    }
+
+    #[tokio::test]
+    async fn test_send_traits() {
+        let app = Impl::new(());
+        let spawned =
+            tokio::spawn(async move { Store::store(&app, Fetch::fetch(&app, 1).await).await });
+        assert_eq!(6, spawned.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_local_traits_with_a_non_sync_app() {
+        let app = Impl::new(Rc::new(()));
+        let stored = tokio::task::LocalSet::new()
+            .run_until(async move {
+                tokio::task::spawn_local(async move { fetch_and_store(&app).await }).await
+            })
+            .await;
+        assert_eq!(6, stored.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_named_local_trait() {
+        async fn count_local(deps: &impl UnsyncCount) -> usize {
+            deps.count("item".to_string()).await
+        }
+
+        let app = Impl::new(Rc::new(()));
+        let counted = tokio::task::LocalSet::new()
+            .run_until(async move {
+                tokio::task::spawn_local(async move { count_local(&app).await }).await
+            })
+            .await;
+        assert_eq!(4, counted.unwrap());
+        assert_eq!(4, Count::count(&Impl::new(()), "item".to_string()).await);
+    }
+}
+
+mod unsafe_fn {
+    use entrait::*;
+
+    #[entrait(Mmio)]
+    fn base(_deps: &impl std::any::Any) -> *const u32 {
+        REGISTERS.as_ptr()
+    }
+
+    static REGISTERS: [u32; 2] = [7, 42];
+
+    /// # Safety
+    /// `index` must be within the register block.
+    #[entrait(ReadRegister)]
+    unsafe fn read_register(deps: &impl Mmio, index: usize) -> u32 {
+        unsafe { *deps.base().add(index) }
+    }
+
+    #[entrait(pub Registers)]
+    mod registers {
+        /// # Safety
+        /// The register block has at least one register.
+        pub unsafe fn first(deps: &impl super::ReadRegister) -> u32 {
+            unsafe { deps.read_register(0) }
+        }
+    }
+
+    #[test]
+    fn test_unsafe_fn() {
+        let app = Impl::new(());
+        assert_eq!(42, unsafe { app.read_register(1) });
+        assert_eq!(7, unsafe { app.first() });
+    }
+}
+
+mod forwarded_attrs {
+    use entrait::*;
+
+    /// Sum of the bytes.
+    ///
+    /// Wrapping is not a concern, since it's a sum of at most seven bytes.
+    #[entrait(Checksum)]
+    #[must_use]
+    #[expect(clippy::too_many_arguments)]
+    fn checksum(
+        _deps: &impl std::any::Any,
+        a: u8,
+        b: u8,
+        c: u8,
+        d: u8,
+        e: u8,
+        f: u8,
+        g: u8,
+    ) -> u32 {
+        [a, b, c, d, e, f, g]
+            .iter()
+            .map(|byte| u32::from(*byte))
+            .sum()
+    }
+
+    #[entrait(pub Parity)]
+    mod parity {
+        /// Whether the checksum of the byte is even
+        #[must_use]
+        #[allow(clippy::needless_bool)]
+        pub fn is_even(deps: &impl super::Checksum, byte: u8) -> bool {
+            if deps.checksum(byte, 0, 0, 0, 0, 0, 0) % 2 == 0 {
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    #[test]
+    fn test_forwarded_attrs() {
+        let app = Impl::new(());
+        assert_eq!(28, app.checksum(1, 2, 3, 4, 5, 6, 7));
+        assert!(app.is_even(4));
+    }
+
+    /// The line of the call
+    #[entrait(CallerLine, forward_attrs(doc, track_caller))]
+    #[track_caller]
+    fn caller_line(_deps: &impl std::any::Any) -> u32 {
+        std::panic::Location::caller().line()
+    }
+
+    #[test]
+    fn test_explicitly_forwarded_attrs() {
+        let app = Impl::new(());
+        assert_eq!(line!(), app.caller_line());
+    }
+}
+
+mod trait_doc {
+    use entrait::*;
+
+    /// Implementation detail, not for the trait
+    #[entrait(pub Greet, trait_doc = "Greeting of users.\n\nImplemented for every application.")]
+    fn greet(_deps: &impl std::any::Any, name: &str) -> String {
+        format!("Hello, {name}!")
+    }
+
+    #[entrait(pub Farewell, trait_doc = "Farewells to users.")]
+    mod farewell {
+        pub fn farewell(_deps: &impl std::any::Any, name: &str) -> String {
+            format!("Bye, {name}!")
+        }
+    }
+
+    #[test]
+    fn test_trait_doc() {
+        let app = Impl::new(());
+        assert_eq!("Hello, Ann!", app.greet("Ann"));
+        assert_eq!("Bye, Ann!", app.farewell("Ann"));
+    }
+}
+
+mod hide_fn {
+    use entrait::*;
+
+    /// Documented on the trait method only
+    #[entrait(pub Shout, hide_fn)]
+    pub fn shout(_deps: &impl std::any::Any, text: &str) -> String {
+        text.to_uppercase()
+    }
+
+    #[entrait(pub Whisper, hide_fn)]
+    pub mod whisper {
+        pub fn whisper(_deps: &impl std::any::Any, text: &str) -> String {
+            text.to_lowercase()
+        }
+    }
+
+    #[test]
+    fn test_hide_fn() {
+        let app = Impl::new(());
+        assert_eq!("HEY", app.shout("hey"));
+        assert_eq!("hey", app.whisper("HEY"));
+        assert_eq!("hey", whisper::whisper(&(), "HEY"));
+    }
+}
+
+mod const_fn {
+    use entrait::*;
+
+    #[entrait(Double)]
+    const fn double(_deps: &impl std::any::Any, x: u32) -> u32 {
+        x * 2
+    }
+
+    const FOUR: u32 = double(&(), 2);
+
+    #[test]
+    fn test_const_fn() {
+        assert_eq!(FOUR, Impl::new(()).double(2));
+    }
+}
+
+mod feature_group {
+    use entrait::*;
+
+    #[entrait(Billing, group = "std")]
+    fn billing(_deps: &impl std::any::Any) -> &'static str {
+        "billed"
+    }
+
+    #[entrait(pub Reporting, group = "std")]
+    mod reporting {
+        pub fn report(deps: &impl super::Billing) -> String {
+            format!("report: {}", deps.billing())
+        }
+    }
+
+    // Would not compile without the `memo` feature
+    #[entrait(Memoized, group = "memo")]
+    fn memoized(db: &entrait::memo::Db) -> u64 {
+        db.revision()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_feature_group() {
+        let app = Impl::new(());
+        assert_eq!("report: billed", app.report());
+    }
+
+    // A `report` method on every type, which would be ambiguous if the group was enabled
+    #[cfg(not(feature = "std"))]
+    trait Absent {
+        fn report(&self) -> &'static str {
+            "absent"
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    impl<T> Absent for T {}
+
+    #[cfg(not(feature = "std"))]
+    #[test]
+    fn test_feature_group_disabled() {
+        let app = Impl::new(());
+        assert_eq!("absent", app.report());
+    }
+}
+
+mod fingerprint {
+    use entrait::*;
+
+    #[entrait(pub Lookup, fingerprint)]
+    fn lookup(_deps: &impl std::any::Any, key: &str) -> Option<u32> {
+        None
+    }
+
+    #[entrait(pub Fetch, fingerprint)]
+    async fn fetch(_deps: &impl Lookup, key: &str) -> Option<u32> {
+        None
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            "trait Lookup { fn lookup ( & self , key : & str ) -> Option < u32 > ; }",
+            __entrait_fingerprint_Lookup::SIGNATURE
+        );
+        assert_eq!(
+            0xeeaa157210d7c5d5,
+            __entrait_fingerprint_Lookup::FINGERPRINT
+        );
+        assert_eq!(
+            "trait Fetch { fn fetch ( & self , key : & str ) -> impl :: core :: future :: Future < Output = Option < u32 > > + :: core :: marker :: Send ; }",
+            __entrait_fingerprint_Fetch::SIGNATURE
+        );
+    }
+}
+
+mod dependency_metadata {
+    use entrait::*;
+
+    #[entrait(pub Clock, dependencies)]
+    fn clock(_deps: &impl std::any::Any) -> u64 {
+        1
+    }
+
+    pub struct Config {
+        pub prefix: String,
+    }
+
+    #[entrait(pub Prefix, dependencies)]
+    fn prefix(config: &Config) -> String {
+        config.prefix.clone()
+    }
+
+    #[entrait(pub Reports, dependencies)]
+    mod reports {
+        use super::*;
+
+        pub fn daily(deps: &(impl Clock + Prefix), name: &str) -> String {
+            format!("{}{name}@{}", deps.prefix(), deps.clock())
+        }
+
+        pub fn weekly(deps: &(impl Clock + Send), name: &str) -> String {
+            format!("{name}@{}", deps.clock() / 7)
+        }
+    }
+
+    #[test]
+    fn test_dependencies() {
+        assert!(__entrait_dependencies_Clock::DEPENDENCIES.is_empty());
+        assert_eq!(["Config"], __entrait_dependencies_Prefix::DEPENDENCIES);
+        assert_eq!(
+            ["Clock", "Prefix"],
+            reports::__entrait_dependencies_Reports::DEPENDENCIES
+        );
+    }
+}
+
+mod deps_associated_types {
+    use entrait::*;
+
+    pub trait Database {
+        type Row;
+    }
+
+    pub trait HasDb {
+        type Db: Database;
+    }
+
+    #[entrait(Store)]
+    fn store<D: HasDb>(deps: &D, row: <D::Db as Database>::Row) -> Vec<<D::Db as Database>::Row> {
+        vec![row]
+    }
+
+    #[entrait(pub Fetch)]
+    mod fetch {
+        use super::{Database, HasDb};
+
+        pub fn fetch<D>(deps: &D, count: usize) -> Option<<<D as HasDb>::Db as Database>::Row>
+        where
+            D: HasDb + Send,
+            <<D as HasDb>::Db as Database>::Row: Default,
+        {
+            (count > 0).then(Default::default)
+        }
+    }
+
+    pub struct Postgres;
+
+    impl Database for Postgres {
+        type Row = String;
+    }
+
+    struct App;
+
+    impl HasDb for Impl<App> {
+        type Db = Postgres;
+    }
+
+    #[test]
+    fn test_deps_associated_types() {
+        let app = Impl::new(App);
+        assert_eq!(vec!["row".to_string()], app.store("row".to_string()));
+        assert_eq!(Some(String::new()), app.fetch(1));
+    }
+}
+
+mod strict {
+    use entrait::*;
+
+    #[entrait(CountChars, strict)]
+    fn count_chars(deps: &impl Source, text: &str) -> usize {
+        deps.source().len() + text.len()
+    }
+
+    #[entrait(Echo, strict, no_deps)]
+    fn echo<T: Clone>(value: &T) -> T {
+        value.clone()
+    }
+
+    #[entrait(pub Fetch, strict, ?Send)]
+    mod fetch {
+        use super::Source;
+
+        pub async fn fetch<D: Source>(deps: &D) -> String {
+            deps.source()
+        }
+    }
+
+    #[entrait(Source, no_deps)]
+    fn source() -> String {
+        "source".to_string()
+    }
+
+    #[tokio::test]
+    async fn test_strict() {
+        let app = Impl::new(());
+        assert_eq!(9, app.count_chars("abc"));
+        assert_eq!(5, app.echo(&5));
+        assert_eq!("source", app.fetch().await);
+    }
+}
+
+mod closure_params {
+    use entrait::*;
+
+    pub struct Tx(u32);
+
+    #[entrait(Begin, no_deps)]
+    fn begin() -> Tx {
+        Tx(0)
+    }
+
+    #[entrait(WithTx)]
+    fn with_tx<E>(deps: &impl Begin, f: impl FnOnce(&mut Tx) -> Result<(), E>) -> Result<u32, E> {
+        let mut tx = deps.begin();
+        f(&mut tx)?;
+        Ok(tx.0)
+    }
+
+    #[entrait(SumBy)]
+    fn sum_by<F, T>(_deps: &impl Begin, items: &[T], mut f: F) -> u32
+    where
+        F: FnMut(&T) -> u32,
+    {
+        items.iter().map(&mut f).sum()
+    }
+
+    #[entrait(WithTxAsync)]
+    async fn with_tx_async<F: Fn(&mut Tx)>(deps: &impl Begin, f: F) -> u32 {
+        let mut tx = deps.begin();
+        f(&mut tx);
+        tx.0
+    }
+
+    #[tokio::test]
+    async fn test_closure_params() {
+        let app = Impl::new(());
+        assert_eq!(
+            Ok(1),
+            app.with_tx(|tx| {
+                tx.0 += 1;
+                Ok::<_, ()>(())
+            })
+        );
+        assert_eq!(6, app.sum_by(&[1, 2, 3], |item| *item));
+        assert_eq!(2, app.sum_by(&["a", "b"], |item| item.len() as u32));
+        assert_eq!(3, app.with_tx_async(|tx| tx.0 = 3).await);
+    }
+}
+
+mod associated_consts {
+    use entrait::*;
+
+    #[entrait]
+    pub trait Version {
+        const MAJOR: u32;
+
+        fn version(&self) -> String;
+    }
+
+    struct App;
+
+    impl Version for App {
+        const MAJOR: u32 = 2;
+
+        fn version(&self) -> String {
+            format!("v{}", Self::MAJOR)
+        }
+    }
+
+    #[test]
+    fn test_associated_consts() {
+        let app = Impl::new(App);
+
+        assert_eq!(2, <Impl<App> as Version>::MAJOR);
+        assert_eq!("v2", app.version());
+    }
+}
+
+mod associated_types {
+    use entrait::*;
+
+    #[entrait]
+    pub trait Connection {
+        type Row: std::fmt::Debug;
+        type Rows<'a>: Iterator<Item = &'a Self::Row>
+        where
+            Self: 'a;
+
+        fn rows(&self) -> Self::Rows<'_>;
+    }
+
+    struct Db(Vec<u32>);
+
+    impl Connection for Db {
+        type Row = u32;
+        type Rows<'a> = std::slice::Iter<'a, u32>;
+
+        fn rows(&self) -> Self::Rows<'_> {
+            self.0.iter()
+        }
+    }
+
+    fn first_row<C: Connection>(connection: &C) -> Option<&C::Row> {
+        connection.rows().next()
+    }
+
+    #[test]
+    fn test_associated_types() {
+        let app = Impl::new(Db(vec![1, 2]));
+
+        assert_eq!(Some(&1), first_row(&app));
+        assert_eq!(vec![&1, &2], app.rows().collect::<Vec<_>>());
+    }
+}
+
+mod default_methods {
+    use entrait::*;
+
+    #[entrait]
+    pub trait Greeting {
+        fn name(&self) -> String;
+
+        fn greet(&self) -> String {
+            format!("Hello, {}", self.name())
+        }
+
+        async fn shout(&self) -> String {
+            self.greet().to_uppercase()
+        }
+    }
+
+    #[entrait(delegate_defaults)]
+    pub trait Farewell {
+        fn farewell(&self) -> String {
+            "Bye".to_string()
+        }
+    }
+
+    struct App;
+
+    impl Greeting for App {
+        fn name(&self) -> String {
+            "app".to_string()
+        }
+
+        fn greet(&self) -> String {
+            "not delegated".to_string()
+        }
+    }
+
+    impl Farewell for App {
+        fn farewell(&self) -> String {
+            "See you".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_methods() {
+        let app = Impl::new(App);
+
+        // The defaults run on `Impl<App>`, delegating to the required methods
+        assert_eq!("Hello, app", app.greet());
+        assert_eq!("HELLO, APP", app.shout().await);
+        // Overrides of the app are called with `delegate_defaults`
+        assert_eq!("See you", app.farewell());
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod dyn_compatible {
+    use entrait::*;
+
+    #[entrait(Greeting, no_deps)]
+    fn greeting() -> String {
+        "hello".to_string()
+    }
+
+    #[entrait(pub Registry, dyn)]
+    mod registry {
+        use super::Greeting;
+
+        pub async fn greet(deps: &impl Greeting, name: &str) -> String {
+            format!("{} {name}", deps.greeting())
+        }
+
+        pub fn map_value(_deps: &impl Greeting, value: i32, f: impl Fn(i32) -> i32) -> i32 {
+            f(value)
+        }
+
+        pub fn count<F: FnMut(u32)>(_deps: &impl Greeting, mut f: F) {
+            f(1);
+            f(2);
+        }
+
+        pub fn consume<F>(_deps: &impl Greeting, f: F) -> String
+        where
+            F: FnOnce() -> String,
+        {
+            f()
+        }
+
+        pub fn letters(deps: &impl Greeting) -> impl Iterator<Item = char> {
+            deps.greeting().chars().collect::<Vec<_>>().into_iter()
+        }
+
+        pub fn words<'a>(
+            _deps: &impl Greeting,
+            text: &'a str,
+        ) -> impl Iterator<Item = &'a str> + 'a {
+            text.split(' ')
+        }
+
+        pub async fn lengths(
+            _deps: &impl Greeting,
+            names: Vec<String>,
+        ) -> impl Iterator<Item = usize> {
+            names.into_iter().map(|name| name.len())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trait_object() {
+        let registry: Box<dyn Registry + Send + Sync> = Box::new(Impl::new(()));
+
+        assert_eq!("hello world", registry.greet("world").await);
+        assert_eq!(4, registry.map_value(2, &|value| value * 2));
+
+        let mut sum = 0;
+        registry.count(&mut |value| sum += value);
+        assert_eq!(3, sum);
+
+        let owned = "owned".to_string();
+        assert_eq!("owned", registry.consume(Box::new(move || owned)));
+
+        assert_eq!("hello", registry.letters().collect::<String>());
+        assert_eq!(vec!["a", "b"], registry.words("a b").collect::<Vec<_>>());
+        assert_eq!(
+            vec![1, 2],
+            registry
+                .lengths(vec!["a".to_string(), "bb".to_string()])
+                .await
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod shared_impl {
+    use entrait::*;
+
+    pub struct App {
+        greeting: String,
+    }
+
+    #[entrait(Greeting)]
+    fn greeting(app: &App) -> &str {
+        &app.greeting
+    }
+
+    #[entrait]
+    trait Clock {
+        fn now(&self) -> u64;
+    }
+
+    impl Clock for App {
+        fn now(&self) -> u64 {
+            42
+        }
+    }
+
+    #[entrait]
+    #[async_trait::async_trait]
+    trait Timezone {
+        async fn timezone(&self) -> String;
+    }
+
+    #[async_trait::async_trait]
+    impl Timezone for App {
+        async fn timezone(&self) -> String {
+            "UTC".to_string()
+        }
+    }
+
+    #[entrait(Greet)]
+    fn greet(deps: &(impl Greeting + Clock), name: &str) -> String {
+        format!("{} {name} at {}", deps.greeting(), deps.now())
+    }
+
+    #[test]
+    fn test_shared_clones() {
+        let app = Impl::shared(App {
+            greeting: "hello".to_string(),
+        });
+
+        let task = {
+            let app = app.clone();
+            std::thread::spawn(move || app.greet("thread"))
+        };
+
+        assert_eq!("hello main at 42", app.greet("main"));
+        assert_eq!("hello thread at 42", task.join().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_shared_async_trait() {
+        let app = Impl::shared(App {
+            greeting: "hello".to_string(),
+        });
+
+        assert_eq!("UTC", app.timezone().await);
+    }
+}
+
+mod impl_inner {
+    use entrait::*;
+
+    pub struct App {
+        name: &'static str,
+    }
+
+    impl AsRef<str> for App {
+        fn as_ref(&self) -> &str {
+            self.name
+        }
+    }
+
+    #[test]
+    fn test_inner() {
+        let app = Impl::new(App { name: "app" });
+
+        assert_eq!("app", app.inner().name);
+        assert_eq!("app", app.inner().as_ref() as &str);
+        assert_eq!("app", app.name);
+        assert_eq!("app", ImplInnerExt::into_inner(app).name);
+    }
+}
+
+mod method_generics {
+    use entrait::*;
+    use std::str::FromStr;
+
+    #[entrait(Parse)]
+    fn parse<D, T: FromStr>(_deps: &D, s: &str) -> Option<T> {
+        s.parse().ok()
+    }
+
+    #[entrait(Describe)]
+    fn describe<T>(deps: &impl Parse, s: &str) -> String
+    where
+        T: FromStr + std::fmt::Debug,
+    {
+        format!("{:?}", deps.parse::<T>(s))
+    }
+
+    pub trait Get<T> {
+        fn get(&self) -> T;
+    }
+
+    impl Get<u32> for () {
+        fn get(&self) -> u32 {
+            42
+        }
+    }
+
+    // `T` is part of the deps bounds, so it's a parameter of the trait
+    #[entrait(GetTwice)]
+    fn get_twice<T: std::ops::Add<Output = T>>(deps: &impl Get<T>) -> T {
+        deps.get() + deps.get()
+    }
+
+    fn takes_parse(deps: &impl Parse) -> (Option<u8>, Option<bool>) {
+        (deps.parse("7"), deps.parse("true"))
+    }
+
+    #[test]
+    fn test_method_generics() {
+        let app = Impl::new(());
+
+        assert_eq!((Some(7), Some(true)), takes_parse(&app));
+        assert_eq!(Some(-1), app.parse::<i64>("-1"));
+        assert_eq!("Some(3.5)", app.describe::<f32>("3.5"));
+        assert_eq!("None", app.describe::<u8>("x"));
+        assert_eq!(84, GetTwice::<u32>::get_twice(&()));
+    }
+}
+
+mod borrowing_outputs {
+    use entrait::*;
+
+    pub struct Row(u32);
+
+    #[entrait(Index, no_deps)]
+    fn index() -> usize {
+        1
+    }
+
+    #[entrait(Select)]
+    fn select<'a>(deps: &impl Index, rows: &'a [Row]) -> &'a Row {
+        &rows[deps.index()]
+    }
+
+    #[entrait(Longest)]
+    async fn longest<'a, 'b: 'a>(_deps: &impl Index, a: &'a str, b: &'b str) -> &'a str {
+        if b.len() > a.len() {
+            b
+        } else {
+            a
+        }
+    }
+
+    #[entrait(Pick)]
+    fn pick<'a, T>(_deps: &impl Index, items: &'a [T]) -> Option<&'a T>
+    where
+        T: 'a,
+    {
+        items.first()
+    }
+
+    #[entrait(Label)]
+    fn label<'a, D>(deps: &'a D, prefix: &'a str) -> (&'a str, &'a D)
+    where
+        D: Index + 'a,
+    {
+        (prefix, deps)
+    }
+
+    #[tokio::test]
+    async fn test_borrowing_outputs() {
+        let app = Impl::new(());
+        let rows = vec![Row(0), Row(1)];
+
+        assert_eq!(1, app.select(&rows).0);
+        assert_eq!("world", app.longest("hi", "world").await);
+        assert_eq!(Some(&1), app.pick(&[1, 2]));
+        assert_eq!("label", app.label("label").0);
+    }
+}
+
+mod const_generics {
+    use entrait::*;
+
+    #[entrait(Chunk)]
+    fn chunk<D, const N: usize>(_deps: &D, data: &[u8]) -> [u8; N] {
+        let mut chunk = [0; N];
+        chunk.copy_from_slice(&data[..N]);
+        chunk
+    }
+
+    #[entrait(Repeat, no_deps)]
+    fn repeat<T: Copy, const N: usize>(value: T) -> [T; N] {
+        [value; N]
+    }
+
+    // `N` is part of the concrete deps, so it's a parameter of the trait
+    #[entrait(Capacity)]
+    fn capacity<const N: usize>(_deps: &[u8; N]) -> usize {
+        N
+    }
+
+    #[test]
+    fn test_const_generics() {
+        let app = Impl::new(());
+        let data = [1, 2, 3];
+
+        let pair: [u8; 2] = app.chunk(&data);
+        assert_eq!([1, 2], pair);
+        assert_eq!([1, 2, 3], app.chunk::<3>(&data));
+        assert_eq!([7, 7], app.repeat::<_, 2>(7));
+        assert_eq!(4, [0u8; 4].capacity());
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod impl_trait_outputs {
+    use entrait::*;
+
+    #[entrait(Numbers, no_deps)]
+    fn numbers() -> Vec<u32> {
+        vec![1, 2, 3, 4]
+    }
+
+    #[entrait(Evens)]
+    fn evens(deps: &impl Numbers) -> impl Iterator<Item = u32> {
+        deps.numbers().into_iter().filter(|n| n % 2 == 0)
+    }
+
+    #[entrait(Window)]
+    fn window<'a>(_deps: &impl Numbers, items: &'a [u32]) -> impl Iterator<Item = &'a u32> + 'a {
+        items.iter().skip(1)
+    }
+
+    #[entrait(delegate_by=ref)]
+    pub trait Source {
+        fn values(&self) -> impl Iterator<Item = u32>;
+
+        // Borrowing other parameters than `self` requires an explicit lifetime bound
+        fn labels<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = String> + 'a;
+
+        async fn fetch(&self) -> impl std::fmt::Display;
+    }
+
+    pub struct Range(u32);
+
+    impl Source for Range {
+        fn values(&self) -> impl Iterator<Item = u32> {
+            0..self.0
+        }
+
+        fn labels<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = String> + 'a {
+            (0..self.0).map(move |value| format!("{prefix}{value}"))
+        }
+
+        async fn fetch(&self) -> impl std::fmt::Display {
+            self.0
+        }
+    }
+
+    pub struct App(Box<dyn DynSource + Send + Sync>);
+
+    impl AsRef<dyn DynSource> for App {
+        fn as_ref(&self) -> &(dyn DynSource + 'static) {
+            self.0.as_ref()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_impl_trait_outputs() {
+        let app = Impl::new(());
+        assert_eq!(vec![2, 4], app.evens().collect::<Vec<_>>());
+        assert_eq!(vec![&2, &3], app.window(&[1, 2, 3]).collect::<Vec<_>>());
+
+        let app = Impl::new(App(Box::new(Range(3))));
+        assert_eq!(3, app.values().count());
+        assert_eq!(Some("v2".to_string()), app.labels("v").last());
+        assert_eq!("3", app.fetch().await.to_string());
+    }
+}
+
+mod inherent_impl {
+    use entrait::*;
+
+    #[entrait(pub(crate) FetchUser)]
+    fn fetch_user(_deps: &impl std::any::Any, id: u32) -> String {
+        format!("user{id}")
+    }
+
+    pub struct UserRepo {
+        prefix: &'static str,
+    }
+
+    #[entrait(pub(crate) UserService)]
+    impl UserRepo {
+        pub(crate) fn find(&self, deps: &impl FetchUser, id: u32) -> String {
+            format!("{}{}", self.prefix, deps.fetch_user(id))
+        }
+
+        #[entrait(method = find_later)]
+        pub(crate) async fn find_async(&self, deps: &impl FetchUser, id: u32) -> String {
+            self.find(deps, id)
+        }
+
+        pub(crate) fn name_len(deps: &impl FetchUser) -> usize {
+            deps.fetch_user(1).len()
+        }
+
+        fn prefix_len(&self) -> usize {
+            self.prefix.len()
+        }
+    }
+
+    struct App {
+        users: UserRepo,
+    }
+
+    impl AsRef<UserRepo> for App {
+        fn as_ref(&self) -> &UserRepo {
+            &self.users
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inherent_impl() {
+        let app = Impl::new(App {
+            users: UserRepo { prefix: "> " },
+        });
+        assert_eq!("> user1", app.find(1));
+        assert_eq!("> user2", app.find_later(2).await);
+        assert_eq!(5, app.name_len());
+        assert_eq!(2, app.users.prefix_len());
+    }
+}
+
+mod no_std_option {
+    use entrait::*;
+
+    #[entrait(Double, no_std)]
+    fn double<D>(_: &D, value: u32) -> u32 {
+        value * 2
+    }
+
+    #[entrait(Quadruple, no_std)]
+    async fn quadruple(deps: &impl Double, value: u32) -> u32 {
+        deps.double(deps.double(value))
+    }
+
+    #[entrait(no_std)]
+    trait Offset {
+        fn offset(&self) -> u32;
+    }
+
+    struct App;
+
+    impl Offset for App {
+        fn offset(&self) -> u32 {
+            1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_core_only_delegation() {
+        let app = Impl::new(App);
+
+        assert_eq!(8, app.quadruple(2).await);
+        assert_eq!(1, app.offset());
+    }
+}
+
+mod several_concrete_deps {
+    use entrait::*;
+
+    pub struct Config {
+        greeting: &'static str,
+    }
+
+    pub struct Db {
+        users: Vec<&'static str>,
+    }
+
+    #[entrait(Greeting)]
+    fn greeting(config: &Config) -> &'static str {
+        config.greeting
+    }
+
+    #[entrait(FetchUser)]
+    async fn fetch_user(db: &Db, index: usize) -> &'static str {
+        db.users[index]
+    }
+
+    #[entrait(Greet)]
+    async fn greet(deps: &(impl Greeting + FetchUser), index: usize) -> String {
+        format!("{} {}", deps.greeting(), deps.fetch_user(index).await)
+    }
+
+    #[derive(EntraitApp)]
+    struct App {
+        #[app(borrow)]
+        config: Config,
+        #[app(borrow)]
+        db: Db,
+    }
+
+    #[tokio::test]
+    async fn test_app_with_several_concrete_deps() {
+        let app = Impl::new(App {
+            config: Config { greeting: "hello" },
+            db: Db {
+                users: vec!["alice", "bob"],
+            },
+        });
+
+        assert_eq!("hello bob", app.greet(1).await);
+        assert_eq!("hello", Impl::new(Config { greeting: "hello" }).greeting());
+    }
+}
+
+mod trait_object_concrete_dep {
+    use entrait::*;
+
+    pub trait Plugin {
+        fn name(&self) -> String;
+    }
+
+    struct Upper;
+
+    impl Plugin for Upper {
+        fn name(&self) -> String {
+            "UPPER".to_string()
+        }
+    }
+
+    #[entrait(PluginName)]
+    fn plugin_name(plugin: &dyn Plugin) -> String {
+        plugin.name()
+    }
+
+    #[entrait(SyncPluginName)]
+    async fn sync_plugin_name(plugin: &(dyn Plugin + Send + Sync), suffix: &str) -> String {
+        format!("{}{suffix}", plugin.name())
+    }
+
+    #[derive(EntraitApp)]
+    struct App {
+        #[app(as_ref = dyn Plugin, as_ref = dyn Plugin + Send + Sync)]
+        plugin: Box<dyn Plugin + Send + Sync>,
+    }
+
+    #[tokio::test]
+    async fn test_trait_object_dep_through_as_ref() {
+        let app = Impl::new(App {
+            plugin: Box::new(Upper),
+        });
+
+        assert_eq!("UPPER", app.plugin_name());
+        assert_eq!("UPPER!", app.sync_plugin_name("!").await);
+    }
+}
+
+mod owned_deps {
+    use entrait::*;
+    use std::sync::Arc;
+
+    #[derive(Clone)]
+    pub struct Greeting(&'static str);
+
+    #[entrait(Greet)]
+    fn greet(greeting: Greeting, name: &str) -> String {
+        format!("{} {name}", greeting.0)
+    }
+
+    #[entrait(GetName)]
+    fn get_name<D>(_: &D) -> String {
+        "world".to_string()
+    }
+
+    #[entrait(SpawnGreeting)]
+    async fn spawn_greeting(deps: impl GetName + Clone + Send + Sync + 'static) -> String {
+        tokio::spawn(async move { format!("hello {}", deps.get_name()) })
+            .await
+            .unwrap()
+    }
+
+    #[entrait(GreetTwice)]
+    async fn greet_twice(deps: &(impl SpawnGreeting + Clone)) -> (String, String) {
+        (
+            deps.clone().spawn_greeting().await,
+            deps.clone().spawn_greeting().await,
+        )
+    }
+
+    #[derive(Clone)]
+    struct App {
+        _state: Arc<()>,
+    }
+
+    #[tokio::test]
+    async fn test_owned_generic_deps() {
+        let app = Impl::new(App {
+            _state: Arc::new(()),
+        });
+
+        assert_eq!("hello world", app.clone().spawn_greeting().await);
+        assert_eq!(
+            ("hello world".to_string(), "hello world".to_string()),
+            app.greet_twice().await
+        );
+    }
+
+    #[test]
+    fn test_owned_concrete_deps() {
+        assert_eq!("hi world", Greeting("hi").greet("world"));
+    }
+}
+
+mod send_cfg {
+    use entrait::*;
+    use std::rc::Rc;
+
+    // `test` stands in for `wasm32`: the test build gets the `?Send` variant
+    #[entrait(Fetch, send_cfg = not(test))]
+    async fn fetch(_deps: &impl std::any::Any, id: u32) -> Rc<String> {
+        let item = Rc::new(format!("item {id}"));
+        tokio::task::yield_now().await;
+        item
+    }
+
+    #[entrait(pub Store, send_cfg = not(test))]
+    mod store {
+        use std::rc::Rc;
+
+        pub async fn store(_deps: &impl std::any::Any, item: Rc<String>) -> usize {
+            tokio::task::yield_now().await;
+            item.len()
+        }
+    }
+
+    #[entrait(send_cfg = not(test))]
+    trait Count {
+        async fn count(&self, item: Rc<String>) -> usize;
+    }
+
+    impl Count for Rc<()> {
+        async fn count(&self, item: Rc<String>) -> usize {
+            item.chars().count()
+        }
+    }
+
+    #[entrait(Greet)]
+    async fn greet(_deps: &impl std::any::Any) -> String {
+        "hello".to_string()
+    }
+
+    #[tokio::test]
+    async fn test_non_send_futures_with_a_non_sync_app() {
+        let app = Impl::new(Rc::new(()));
+        let item = app.fetch(1).await;
+        assert_eq!(6, app.count(item.clone()).await);
+        assert_eq!(6, app.store(item).await);
+    }
+
+    #[tokio::test]
+    async fn test_send_futures_by_default() {
+        fn assert_send<T: Send>(value: T) -> T {
+            value
+        }
+
+        let app = Impl::new(());
+        assert_eq!("hello", assert_send(app.greet()).await);
+    }
+}
+
+mod minimal_codegen {
+    use entrait::*;
+
+    /// Doubles the number.
+    ///
+    /// Not mirrored on the trait method.
+    #[entrait(Double, minimal_codegen)]
+    fn double(deps: &impl Get, factor: u32) -> u32 {
+        deps.get() * factor
+    }
+
+    #[entrait(Get, no_deps, minimal_codegen)]
+    fn get() -> u32 {
+        21
+    }
+
+    #[entrait(pub Count, minimal_codegen, send_cfg = all())]
+    mod count {
+        /// Counts the letters.
+        pub async fn count(_deps: &impl std::any::Any, item: String) -> usize {
+            item.len()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_minimal_codegen() {
+        let app = Impl::new(());
+        assert_eq!(42, app.double(2));
+        assert_eq!(4, app.count("item".to_string()).await);
+    }
+}
+
+mod shared_module_deps {
+    use entrait::*;
+
+    #[entrait(GetName, no_deps)]
+    fn get_name() -> String {
+        "world".to_string()
+    }
+
+    #[entrait(GetGreeting, no_deps)]
+    fn get_greeting() -> String {
+        "hello".to_string()
+    }
+
+    // The impl block states `GetName` and `GetGreeting` only once
+    #[entrait(pub Greet)]
+    mod greet {
+        use super::*;
+
+        pub fn greet(deps: &(impl GetGreeting + GetName)) -> String {
+            format!("{} {}", deps.get_greeting(), deps.get_name())
+        }
+
+        pub fn shout(deps: &(impl GetGreeting + GetName)) -> String {
+            format!("{} {}", deps.get_greeting(), deps.get_name()).to_uppercase()
+        }
+
+        pub fn name(deps: &impl GetName) -> String {
+            deps.get_name()
+        }
+    }
+
+    #[test]
+    fn test_shared_deps() {
+        let app = Impl::new(());
+        assert_eq!("hello world", app.greet());
+        assert_eq!("HELLO WORLD", app.shout());
+        assert_eq!("world", app.name());
+    }
+}
+
+mod impl_t_bounds_on_demand {
+    use entrait::*;
+    use std::cell::Cell;
+
+    fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+
+    #[entrait(GetValue)]
+    fn get_value(deps: &impl std::any::Any) -> i32 {
+        let _ = deps;
+        42
+    }
+
+    // `Cell` is `Send`, which is all a future holding the deps by value needs
+    #[entrait(ConsumeValue)]
+    async fn consume_value(deps: impl GetValue) -> i32 {
+        deps.get_value()
+    }
+
+    #[tokio::test]
+    async fn test_non_sync_app() {
+        let app = Impl::new(Cell::new(0));
+        assert_eq!(42, app.get_value());
+        assert_eq!(42, assert_send(app.consume_value()).await);
+    }
+}
+
+mod same_named_traits {
+    use entrait::*;
+
+    mod a {
+        use entrait::*;
+
+        #[entrait(pub Foo)]
+        fn foo(deps: &impl super::b::Bar) -> String {
+            format!("a({})", deps.bar())
+        }
+    }
+
+    mod b {
+        use entrait::*;
+
+        #[entrait(pub Bar)]
+        fn bar(deps: &impl super::c::Foo) -> String {
+            format!("b({})", deps.foo())
+        }
+    }
+
+    mod c {
+        use entrait::*;
+
+        #[entrait(pub Foo)]
+        fn foo(_deps: &impl std::any::Any) -> String {
+            "c".to_string()
+        }
+    }
+
+    #[test]
+    fn test_same_named_traits_are_not_a_cycle() {
+        use a::Foo;
+
+        assert_eq!("a(b(c))", Impl::new(()).foo());
+    }
+}
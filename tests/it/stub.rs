@@ -0,0 +1,110 @@
+use entrait::*;
+
+#[entrait(FetchUser, stub)]
+fn fetch_user<D>(_: &D, id: u32) -> String {
+    format!("user{id}")
+}
+
+#[entrait(Greet)]
+fn greet(deps: &impl FetchUser, id: Option<u32>) -> String {
+    match id {
+        Some(id) => format!("Hello {}", deps.fetch_user(id)),
+        None => "Hello stranger".to_string(),
+    }
+}
+
+#[test]
+fn stub_fills_in_unused_deps() {
+    assert_eq!("Hello stranger", greet(&Stub, None));
+    assert_eq!("Hello user1", Impl::new(()).greet(Some(1)));
+}
+
+#[test]
+#[should_panic(expected = "`FetchUser::fetch_user` is not implemented by `entrait::Stub`")]
+fn stub_panics_with_the_method_name() {
+    greet(&Stub, Some(1));
+}
+
+mod async_fn {
+    use entrait::*;
+
+    #[entrait(FetchCount, stub)]
+    async fn fetch_count<D>(_: &D) -> u32 {
+        42
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "`FetchCount::fetch_count` is not implemented by `entrait::Stub`")]
+    async fn test_async_stub() {
+        Stub.fetch_count().await;
+    }
+}
+
+mod generic_trait {
+    use entrait::*;
+
+    pub trait Get<T> {
+        fn get(&self) -> T;
+    }
+
+    // `T` is part of the deps bounds, so it's a parameter of the trait
+    #[entrait(GetTwice, stub)]
+    fn get_twice<T: std::ops::Add<Output = T>>(deps: &impl Get<T>) -> T {
+        deps.get() + deps.get()
+    }
+
+    #[test]
+    #[should_panic(expected = "`GetTwice::get_twice` is not implemented by `entrait::Stub`")]
+    fn test_generic_stub() {
+        GetTwice::<u32>::get_twice(&Stub);
+    }
+}
+
+mod module {
+    use entrait::*;
+
+    #[entrait(Clock, stub)]
+    mod clock {
+        pub fn now<D>(_: &D) -> u64 {
+            1
+        }
+
+        pub fn today<D>(_: &D) -> u64 {
+            2
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "`Clock::today` is not implemented by `entrait::Stub`")]
+    fn test_module_stub() {
+        Stub.today();
+    }
+}
+
+mod hand_written_trait {
+    use entrait::*;
+
+    #[entrait(stub, delegate_by = ref)]
+    pub trait Repository {
+        fn find(&self, id: u32) -> Option<String>;
+    }
+
+    struct App {
+        repository: Box<dyn Repository + Send + Sync>,
+    }
+
+    impl AsRef<dyn Repository> for App {
+        fn as_ref(&self) -> &(dyn Repository + 'static) {
+            self.repository.as_ref()
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "`Repository::find` is not implemented by `entrait::Stub`")]
+    fn test_stub_as_delegation_target() {
+        let app = Impl::new(App {
+            repository: Box::new(Stub),
+        });
+        app.find(1);
+    }
+}
@@ -0,0 +1,131 @@
+use entrait::*;
+use greeter_server::Greeter;
+use tonic::{Code, Request, Response, Status};
+
+#[derive(Debug)]
+pub struct HelloRequest {
+    name: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct HelloReply {
+    message: String,
+}
+
+#[derive(Debug)]
+pub struct EmptyName;
+
+impl From<EmptyName> for Status {
+    fn from(_: EmptyName) -> Self {
+        Status::invalid_argument("empty name")
+    }
+}
+
+/// Like the service trait generated by `tonic-build`
+pub mod greeter_server {
+    use super::*;
+
+    #[tonic::async_trait]
+    pub trait Greeter: Send + Sync + 'static {
+        async fn say_hello(
+            &self,
+            request: Request<HelloRequest>,
+        ) -> Result<Response<HelloReply>, Status>;
+
+        async fn say_goodbye(
+            &self,
+            request: Request<HelloRequest>,
+        ) -> Result<Response<HelloReply>, Status>;
+
+        async fn count_letters(
+            &self,
+            request: Request<HelloRequest>,
+        ) -> Result<Response<usize>, Status>;
+    }
+}
+
+#[entrait(pub GetGreeting, no_deps)]
+fn get_greeting(name: &str) -> Result<String, EmptyName> {
+    if name.is_empty() {
+        Err(EmptyName)
+    } else {
+        Ok(format!("Hello, {name}!"))
+    }
+}
+
+#[entrait(pub Greeting, tonic = greeter_server::Greeter)]
+mod greeting {
+    use super::*;
+
+    pub async fn say_hello(
+        deps: &impl GetGreeting,
+        request: HelloRequest,
+    ) -> Result<HelloReply, EmptyName> {
+        let message = deps.get_greeting(&request.name)?;
+        Ok(HelloReply { message })
+    }
+
+    pub async fn say_goodbye(
+        _: &impl GetGreeting,
+        request: Request<HelloRequest>,
+    ) -> Result<Response<HelloReply>, Status> {
+        let language = request
+            .metadata()
+            .get("language")
+            .ok_or_else(|| Status::failed_precondition("no language"))?
+            .to_str()
+            .map_err(|_| Status::invalid_argument("language"))?;
+        let message = match language {
+            "nb" => format!("Ha det, {}!", request.get_ref().name),
+            _ => format!("Goodbye, {}!", request.get_ref().name),
+        };
+        Ok(Response::new(HelloReply { message }))
+    }
+
+    pub async fn count_letters(_: &impl GetGreeting, request: HelloRequest) -> usize {
+        request.name.len()
+    }
+}
+
+fn request(name: &str) -> Request<HelloRequest> {
+    Request::new(HelloRequest {
+        name: name.to_string(),
+    })
+}
+
+#[tokio::test]
+async fn messages_are_unwrapped_from_requests_and_wrapped_in_responses() {
+    let app = Impl::new(());
+    let reply = Greeter::say_hello(&app, request("world")).await.unwrap();
+    assert_eq!("Hello, world!", reply.into_inner().message);
+
+    let count = Greeter::count_letters(&app, request("world"))
+        .await
+        .unwrap();
+    assert_eq!(5, count.into_inner());
+}
+
+#[tokio::test]
+async fn errors_are_converted_into_status() {
+    let status = Greeter::say_hello(&Impl::new(()), request(""))
+        .await
+        .unwrap_err();
+    assert_eq!(Code::InvalidArgument, status.code());
+    assert_eq!("empty name", status.message());
+}
+
+#[tokio::test]
+async fn whole_requests_and_responses_are_passed_through() {
+    let app = Impl::new(());
+    let mut goodbye = request("verden");
+    goodbye
+        .metadata_mut()
+        .insert("language", "nb".parse().unwrap());
+    let reply = Greeter::say_goodbye(&app, goodbye).await.unwrap();
+    assert_eq!("Ha det, verden!", reply.into_inner().message);
+
+    let status = Greeter::say_goodbye(&app, request("world"))
+        .await
+        .unwrap_err();
+    assert_eq!(Code::FailedPrecondition, status.code());
+}
@@ -0,0 +1,65 @@
+use entrait::*;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tower_service::Service;
+
+#[derive(Debug)]
+pub struct NewUser {
+    name: String,
+}
+
+#[entrait(StoreUser, mockall, forward_impls)]
+fn store_user<D>(_: &D, name: &str) -> Result<u32, String> {
+    if name.is_empty() {
+        Err("empty name".to_string())
+    } else {
+        Ok(1)
+    }
+}
+
+#[entrait(pub CreateUser, tower_service = CreateUserService)]
+async fn create_user(deps: &impl StoreUser, new_user: NewUser) -> Result<u32, String> {
+    deps.store_user(&new_user.name)
+}
+
+#[entrait(pub Echo, tower_service = EchoService)]
+async fn echo<D>(_: &D, message: String) -> String {
+    message
+}
+
+async fn call<S: Service<R>, R>(service: &mut S, request: R) -> Result<S::Response, S::Error> {
+    std::future::poll_fn(|cx| service.poll_ready(cx)).await?;
+    service.call(request).await
+}
+
+fn new_user(name: &str) -> NewUser {
+    NewUser {
+        name: name.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn result_outputs_are_responses_and_errors() {
+    let mut service = CreateUserService(Impl::new(()));
+    assert_eq!(Ok(1), call(&mut service, new_user("name")).await);
+    assert_eq!(
+        Err("empty name".to_string()),
+        call(&mut service, new_user("")).await
+    );
+}
+
+#[tokio::test]
+async fn other_outputs_are_infallible() {
+    let mut service = EchoService(Impl::new(()));
+    let response: Result<String, Infallible> = call(&mut service, "hello".to_string()).await;
+    assert_eq!("hello", response.unwrap());
+}
+
+#[tokio::test]
+async fn services_of_mocks() {
+    let mut mock = MockStoreUser::new();
+    mock.expect_store_user().return_const(Ok(42));
+
+    let mut service = CreateUserService(Arc::new(mock));
+    assert_eq!(Ok(42), call(&mut service, new_user("name")).await);
+}
@@ -0,0 +1,93 @@
+use entrait::*;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata};
+
+/// Records the name and fields of every new span,
+/// and the span that is entered when an event is emitted
+#[derive(Default)]
+struct Recorder {
+    spans: Mutex<Vec<String>>,
+    entered: Mutex<Vec<Id>>,
+    events: Mutex<Vec<String>>,
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl tracing::Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut fields = Fields(attrs.metadata().name().to_string());
+        attrs.record(&mut fields);
+
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(fields.0);
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {
+        let entered = match self.entered.lock().unwrap().last() {
+            Some(id) => self.spans.lock().unwrap()[id.into_u64() as usize - 1].clone(),
+            None => "none".to_string(),
+        };
+        self.events.lock().unwrap().push(entered);
+    }
+
+    fn enter(&self, span: &Id) {
+        self.entered.lock().unwrap().push(span.clone());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.entered.lock().unwrap().pop();
+    }
+}
+
+#[entrait(Greet, instrument = [name])]
+fn greet(_deps: &impl std::any::Any, name: &str, excited: bool) -> String {
+    tracing::info!("greeting");
+    let punctuation = if excited { "!" } else { "" };
+    format!("hello {name}{punctuation}")
+}
+
+#[entrait(pub Fetch, instrument)]
+mod fetch {
+    pub async fn fetch(_deps: &impl std::any::Any, id: u32) -> String {
+        tokio::task::yield_now().await;
+        tracing::info!("fetching");
+        format!("item {id}")
+    }
+}
+
+#[tokio::test]
+async fn test_instrument_spans() {
+    let recorder = Arc::new(Recorder::default());
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let app = Impl::new(());
+    assert_eq!("hello world!", app.greet("world", true));
+    assert_eq!("item 42", app.fetch(42).await);
+    tracing::info!("done");
+
+    assert_eq!(
+        vec!["Greet::greet name=\"world\"", "Fetch::fetch"],
+        *recorder.spans.lock().unwrap()
+    );
+    assert_eq!(
+        vec!["Greet::greet name=\"world\"", "Fetch::fetch", "none"],
+        *recorder.events.lock().unwrap()
+    );
+}
@@ -325,6 +325,7 @@ mod async_no_deps_etc {
         panic!()
     }
 
+    #[allow(clippy::needless_lifetimes)]
     #[entrait(Borrow2)]
     async fn borrow2<'a, 'b>(_: &'a impl Bar, _arg: &'b i32) -> &'a i32 {
         panic!()
@@ -0,0 +1,47 @@
+use entrait::*;
+
+#[entrait(watch)]
+trait Settings: 'static {
+    fn greeting(&self, name: &str) -> String;
+    fn timeout(&self) -> u64;
+}
+
+struct Config {
+    timeout: u64,
+    greeting: &'static str,
+}
+
+impl Settings for Config {
+    fn greeting(&self, name: &str) -> String {
+        format!("{}, {name}", self.greeting)
+    }
+
+    fn timeout(&self) -> u64 {
+        self.timeout
+    }
+}
+
+#[entrait(Greet)]
+fn greet(deps: &impl Settings, name: &str) -> String {
+    deps.greeting(name)
+}
+
+#[test]
+fn reads_latest_value() {
+    let (tx, rx) = tokio::sync::watch::channel(Config {
+        timeout: 1,
+        greeting: "Hello",
+    });
+    let app = Impl::new(rx);
+
+    assert_eq!(1, app.timeout());
+    assert_eq!("Hello, world", app.greet("world"));
+
+    tx.send_replace(Config {
+        timeout: 2,
+        greeting: "Goodbye",
+    });
+
+    assert_eq!(2, app.timeout());
+    assert_eq!("Goodbye, world", app.greet("world"));
+}
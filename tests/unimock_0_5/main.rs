@@ -0,0 +1,77 @@
+//! Tests of the unimock 0.5 integration, selected with `unimock_version = "0.5"`.
+#![cfg(feature = "unimock-0-5")]
+#![allow(dead_code)]
+
+// The macros of unimock refer to the crate as `::unimock`
+extern crate unimock_0_5 as unimock;
+
+mod sync {
+    use entrait::*;
+    use unimock::*;
+
+    #[entrait(Greet, unimock, unimock_version = "0.5", mock_api = GreetMock)]
+    fn greet(deps: &impl FetchName, id: u32) -> String {
+        format!("Hello, {}!", deps.fetch_name(id))
+    }
+
+    #[entrait(FetchName, unimock, unimock_version = "0.5", mock_api = FetchNameMock)]
+    fn fetch_name<T>(_: &T, id: u32) -> String {
+        format!("user{id}")
+    }
+
+    #[test]
+    fn test_mock() {
+        let mock = Unimock::new(
+            FetchNameMock
+                .each_call(matching!(42))
+                .returns("Ann".to_string()),
+        );
+
+        assert_eq!("Hello, Ann!", greet(&mock, 42));
+    }
+
+    #[test]
+    fn test_mock_entrypoint() {
+        let mock = Unimock::new(GreetMock.each_call(matching!(1)).returns("Hi!".to_string()));
+
+        assert_eq!("Hi!", mock.greet(1));
+    }
+
+    #[test]
+    fn test_partial_no_overrides() {
+        assert_eq!("Hello, user1!", greet(&Unimock::new_partial(()), 1));
+    }
+
+    #[test]
+    fn test_impl() {
+        assert_eq!("Hello, user1!", Impl::new(()).greet(1));
+    }
+}
+
+mod async_trait_methods {
+    use entrait::*;
+    use unimock::*;
+
+    // Unimock 0.5 only mocks `async` methods of `#[async_trait]` traits,
+    // and the mock API of a trait is a module with one API per method
+    #[entrait(unimock, unimock_version = "0.5", mock_api = FetchCountMock)]
+    #[async_trait::async_trait]
+    trait FetchCount {
+        async fn fetch_count(&self, key: &str) -> u32;
+    }
+
+    async fn double_count(deps: &impl FetchCount) -> u32 {
+        deps.fetch_count("apples").await * 2
+    }
+
+    #[tokio::test]
+    async fn test_mock() {
+        let mock = Unimock::new(
+            FetchCountMock::fetch_count
+                .each_call(matching!("apples"))
+                .returns(21_u32),
+        );
+
+        assert_eq!(42, double_count(&mock).await);
+    }
+}